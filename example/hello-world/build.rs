@@ -1,13 +1,398 @@
-use g2h::BridgeGenerator;
+use g2h::{
+    AccessLevel, Backend, BridgeGenerator, DuplicateHeaderPolicy, GeneratorHook, HandlerContext,
+    HttpVerb, JsonFieldCase, MethodInfo, RoutePathCase,
+};
+use quote::{format_ident, quote};
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("🚀 Building service with string enum support...");
 
+    let out_dir = std::path::PathBuf::from(std::env::var("OUT_DIR")?);
+    // `g2h.sensitive` and the other g2h options
+    let options_include = g2h::options_proto_include();
+
     // Build service with string enum support (serde is built-in)
+    bridge()
+        .route_manifest_path(out_dir.join("routes.json"))
+        .compile_protos(
+            &["protos/hello-world.proto"],
+            &[std::path::Path::new("protos"), &options_include],
+        )?;
+
+    // Render the same protos without compiling, for the snapshot API tests. protoc writes the
+    // descriptor set itself, as decoding it into prost_types would drop the g2h options
+    let descriptors_path = out_dir.join("hello_world.fds");
+    let status = std::process::Command::new(prost_build::protoc_from_env())
+        .arg("--include_imports")
+        .arg("--include_source_info")
+        .arg("--proto_path=protos")
+        .arg(format!("--proto_path={}", options_include.display()))
+        .arg(format!(
+            "--descriptor_set_out={}",
+            descriptors_path.display()
+        ))
+        .arg("protos/hello-world.proto")
+        .status()?;
+    if !status.success() {
+        return Err(format!("protoc failed with {status}").into());
+    }
+    // Kept for the tests rendering the protos with other configurations
+    let descriptors = std::fs::read(&descriptors_path)?;
+    let sources = bridge().render(descriptors)?;
+    let rendered_dir = out_dir.join("rendered");
+    std::fs::create_dir_all(&rendered_dir)?;
+    for (name, code) in sources.iter() {
+        std::fs::write(rendered_dir.join(name), code)?;
+    }
+
+    // The echo service under other bridge configurations, each in its own directory
+    for (dir, bridge) in [
+        (
+            "keep_first",
+            BridgeGenerator::with_tonic_build()
+                .with_duplicate_header_policy(DuplicateHeaderPolicy::KeepFirst),
+        ),
+        (
+            "join",
+            BridgeGenerator::with_tonic_build()
+                .with_duplicate_header_policy(DuplicateHeaderPolicy::Join),
+        ),
+        (
+            "access",
+            BridgeGenerator::with_tonic_build()
+                .with_access_level("echo.Echo", AccessLevel::Authenticated),
+        ),
+        (
+            "public_access",
+            BridgeGenerator::with_tonic_build()
+                .with_access_level("echo.Echo", AccessLevel::Admin)
+                .with_access_level("echo.Echo/Echo", AccessLevel::Public),
+        ),
+        (
+            "trailers",
+            BridgeGenerator::with_tonic_build()
+                .with_trailers()
+                .with_request_ids(),
+        ),
+        (
+            "envelope",
+            BridgeGenerator::with_tonic_build()
+                .with_response_envelope()
+                .with_not_found_fallback()
+                .with_rate_limiting(),
+        ),
+        (
+            "not_found",
+            BridgeGenerator::with_tonic_build().with_not_found_known_paths(),
+        ),
+        (
+            "single_dispatch",
+            BridgeGenerator::with_tonic_build()
+                .with_single_dispatch()
+                .with_not_found_fallback(),
+        ),
+        (
+            "graphql",
+            BridgeGenerator::with_tonic_build()
+                .with_graphql()
+                .with_max_header_value_len(64),
+        ),
+        (
+            "client_ip",
+            BridgeGenerator::with_tonic_build().with_client_ip_metadata("x-client-ip"),
+        ),
+        (
+            "proxied_client_ip",
+            BridgeGenerator::with_tonic_build()
+                .with_client_ip_metadata("x-client-ip")
+                .with_trusted_proxy_hops(2),
+        ),
+        (
+            "request_ids",
+            BridgeGenerator::with_tonic_build()
+                .with_request_ids()
+                .with_content_type_check(false),
+        ),
+        (
+            "body_limit",
+            BridgeGenerator::with_tonic_build().with_max_body_size(16),
+        ),
+        (
+            "gzip",
+            BridgeGenerator::with_tonic_build()
+                .with_gzip_requests()
+                .with_max_body_size(64),
+        ),
+        (
+            "cache_control",
+            BridgeGenerator::with_tonic_build()
+                .with_cache_control("echo.Echo", "public, max-age=60")
+                .with_cache_control("echo.Echo/Echo", "no-store"),
+        ),
+        (
+            "actix",
+            BridgeGenerator::with_tonic_build().with_backend(Backend::Actix),
+        ),
+        (
+            "cancellation",
+            BridgeGenerator::with_tonic_build().with_cancellation_signal(),
+        ),
+        (
+            "metadata_limits",
+            BridgeGenerator::with_tonic_build()
+                .with_max_metadata_size(256)
+                .with_max_header_value_len(32),
+        ),
+        (
+            "lowercase",
+            BridgeGenerator::with_tonic_build().with_route_path_case(RoutePathCase::Lowercase),
+        ),
+        (
+            "timeout_headers",
+            BridgeGenerator::with_tonic_build().with_timeout_headers(),
+        ),
+        (
+            "default_timeout",
+            BridgeGenerator::with_tonic_build()
+                .with_default_timeout(std::time::Duration::from_millis(100))
+                .with_timeout_headers(),
+        ),
+        (
+            "grpc_code_names",
+            BridgeGenerator::with_tonic_build()
+                .with_grpc_code_names()
+                .with_max_body_size(24),
+        ),
+        (
+            "feature_gate",
+            BridgeGenerator::with_tonic_build().with_feature_gate(),
+        ),
+        (
+            "cfg_gate",
+            BridgeGenerator::with_tonic_build()
+                .with_cfg_gate(r#"any(test, feature = "http-bridge")"#),
+        ),
+        (
+            "hooks",
+            BridgeGenerator::with_tonic_build().with_hook(KeyGuard),
+        ),
+        (
+            "chained",
+            BridgeGenerator::with_tonic_build().chain(Box::new(MethodNames)),
+        ),
+        (
+            "handler_template",
+            BridgeGenerator::with_tonic_build().with_handler_template(echo_template),
+        ),
+    ] {
+        bridge
+            .out_dir(out_dir.join(dir))
+            .compile_protos(&["protos/echo.proto"], &["protos"])?;
+    }
+
+    // The echo service of the same package with an API key option
     BridgeGenerator::with_tonic_build()
-        .with_string_enums() // This enables string serialization for enums!
-        .compile_protos(&["protos/hello-world.proto"], &["protos"])?;
+        .with_route_options()
+        .out_dir(out_dir.join("api_key"))
+        .compile_protos(
+            &["protos/api_key/echo.proto"],
+            &[std::path::Path::new("protos/api_key"), &options_include],
+        )?;
+
+    // The echo service with required request fields
+    BridgeGenerator::with_tonic_build()
+        .with_request_validation()
+        .out_dir(out_dir.join("validation"))
+        .compile_protos(
+            &["protos/validation/echo.proto"],
+            &["protos/validation", "protos"],
+        )?;
+
+    // Services of versioned packages, with the same name in `users.v1`, `users.v2` and
+    // `accounts.v1`
+    let versioned_dir = out_dir.join("versioned");
+    BridgeGenerator::with_tonic_build()
+        .with_version_prefix()
+        .file_descriptor_set_path(versioned_dir.join("versioned.fds"))
+        .out_dir(&versioned_dir)
+        .compile_protos(
+            &[
+                "protos/versioned/users_v1.proto",
+                "protos/versioned/users_v2.proto",
+                "protos/versioned/accounts_v1.proto",
+            ],
+            &["protos/versioned"],
+        )?;
+
+    // The same users services on kebab-case paths
+    BridgeGenerator::with_tonic_build()
+        .with_version_prefix()
+        .with_route_path_case(RoutePathCase::KebabCase)
+        .out_dir(out_dir.join("versioned_kebab"))
+        .compile_protos(
+            &[
+                "protos/versioned/users_v1.proto",
+                "protos/versioned/users_v2.proto",
+            ],
+            &["protos/versioned"],
+        )?;
+
+    // The echo service embedding its descriptor set
+    let descriptor_set_dir = out_dir.join("descriptor_set");
+    BridgeGenerator::with_tonic_build()
+        .file_descriptor_set_path(descriptor_set_dir.join("echo.fds"))
+        .out_dir(&descriptor_set_dir)
+        .compile_protos(&["protos/echo.proto"], &["protos"])?;
+
+    // The echo service with `google.api.routing` parameters
+    BridgeGenerator::with_tonic_build()
+        .with_routing_headers()
+        .out_dir(out_dir.join("routing"))
+        .compile_protos(
+            &["protos/routing/echo.proto"],
+            &["protos/routing", "protos"],
+        )?;
+
+    // The echo service with protovalidate constraints
+    BridgeGenerator::with_tonic_build()
+        .with_protovalidate()
+        .out_dir(out_dir.join("protovalidate"))
+        .compile_protos(
+            &["protos/protovalidate/echo.proto"],
+            &["protos/protovalidate", "protos"],
+        )?;
+
+    // The same protos with a camelCase dialect of the payment methods
+    BridgeGenerator::with_tonic_build()
+        .with_api_version("v2", JsonFieldCase::SnakeCase)
+        .with_method_api_version(
+            "hello_world.PaymentConnector",
+            "v1",
+            JsonFieldCase::CamelCase,
+        )
+        .out_dir(out_dir.join("api_version"))
+        .compile_protos(
+            &["protos/hello-world.proto"],
+            &[std::path::Path::new("protos"), &options_include],
+        )?;
+
+    // A message whose enum field functions share the name of its enum's functions
+    BridgeGenerator::with_tonic_build()
+        .with_string_enums()
+        .out_dir(out_dir.join("enum_alias"))
+        .compile_protos(&["protos/enum_alias.proto"], &["protos"])?;
+
+    // The hello world protos on actix-web, with their `g2h.sensitive` fields
+    BridgeGenerator::with_tonic_build()
+        .with_backend(Backend::Actix)
+        .with_route_options()
+        .out_dir(out_dir.join("actix_sensitive"))
+        .compile_protos(
+            &["protos/hello-world.proto"],
+            &[std::path::Path::new("protos"), &options_include],
+        )?;
+
+    // The same protos for wasm32 targets, only inspected by the tests. example/wasm builds
+    // them for wasm32
+    let wasm_dir = out_dir.join("wasm");
+    std::fs::create_dir_all(&wasm_dir)?;
+    let inner = tonic_prost_build::configure()
+        .build_transport(false)
+        .service_generator();
+    BridgeGenerator::new(inner)
+        .with_wasm_target()
+        .out_dir(&wasm_dir)
+        .compile_protos(
+            &["protos/hello-world.proto"],
+            &[std::path::Path::new("protos"), &options_include],
+        )?;
 
     println!("✅ Build completed - enums will serialize as strings!");
     Ok(())
 }
+
+// The bridge configuration shared by the compiled and the rendered code
+fn bridge() -> BridgeGenerator {
+    BridgeGenerator::with_tonic_build()
+        .with_string_enums() // This enables string serialization for enums!
+        .with_http_transport()
+        .with_access_log()
+        .with_test_clients()
+        .with_mocks()
+        .with_idempotency_keys()
+        .with_rate_limiting()
+        .with_message_hooks()
+        .with_preflight_responses()
+        .with_health_endpoints()
+        .with_method_concurrency_limit("hello_world.PaymentConnector/ProcessPayment", 1)
+        .with_method_slow_request_threshold(
+            "hello_world.Greeter/SayHello",
+            std::time::Duration::ZERO,
+        )
+        .with_trailing_slash()
+        .with_content_type_check(false)
+        .with_method_verb(
+            "hello_world.PaymentConnector/GetPaymentStatus",
+            HttpVerb::Get,
+        )
+}
+
+// Rejects requests for the `forbidden` key before the call, and names the method of every
+// response
+struct KeyGuard;
+
+impl GeneratorHook for KeyGuard {
+    fn before_call(&self, _method: &MethodInfo) -> proc_macro2::TokenStream {
+        quote! {
+            if body.key == "forbidden" {
+                return ::axum::response::IntoResponse::into_response(
+                    ::http::StatusCode::FORBIDDEN,
+                );
+            }
+        }
+    }
+
+    fn after_call(&self, method: &MethodInfo) -> proc_macro2::TokenStream {
+        let name = &method.name;
+        quote! {
+            let mut response = response;
+            response.headers_mut().insert(
+                "x-hooked-method",
+                ::http::HeaderValue::from_static(#name),
+            );
+        }
+    }
+}
+
+// A second service generator, listing the methods of every service
+struct MethodNames;
+
+impl prost_build::ServiceGenerator for MethodNames {
+    fn generate(&mut self, service: prost_build::Service, buf: &mut String) {
+        let name = format_ident!("{}_METHODS", service.name.to_uppercase());
+        let methods = service.methods.iter().map(|method| &method.proto_name);
+        buf.push_str(&quote!(pub const #name: &[&str] = &[#(#methods),*];).to_string());
+    }
+}
+
+// Wraps the built-in echo handler, tagging its responses
+fn echo_template(context: &HandlerContext) -> Option<proc_macro2::TokenStream> {
+    let name = format_ident!("{}", context.handler_name);
+    let builtin = format_ident!("{}", context.builtin_handler_name);
+    let server_bound: proc_macro2::TokenStream = context.server_bound.parse().ok()?;
+    let state_type: proc_macro2::TokenStream = context.state_type.parse().ok()?;
+    Some(quote! {
+        async fn #name<T: #server_bound>(
+            ::axum::extract::State(state): ::axum::extract::State<#state_type>,
+            request: ::axum::extract::Request,
+        ) -> ::axum::response::Response {
+            let mut response =
+                ::axum::handler::Handler::call(#builtin::<T>, request, state).await;
+            response.headers_mut().insert(
+                "x-handler-template",
+                ::http::HeaderValue::from_static("echo"),
+            );
+            response
+        }
+    })
+}
@@ -0,0 +1,147 @@
+/// Tests for the access levels checked by the generated routers
+///
+/// build.rs generates the echo service requiring `Authenticated` callers, and again
+/// with an `Admin` service whose only method is `Public`.
+use tower::ServiceExt;
+
+#[macro_use]
+mod common;
+
+mod access {
+    include!(concat!(env!("OUT_DIR"), "/access/echo.rs"));
+}
+
+mod public_access {
+    include!(concat!(env!("OUT_DIR"), "/public_access/echo.rs"));
+}
+
+echo_server!(AccessServer, access);
+echo_server!(PublicServer, public_access);
+
+// Knows every `x-user` except `mallory`, who is denied access
+struct Gatekeeper;
+
+#[tonic::async_trait]
+impl access::Authorizer for Gatekeeper {
+    async fn authorize(
+        &self,
+        path: &str,
+        level: access::AccessLevel,
+        metadata: &tonic::metadata::MetadataMap,
+    ) -> Result<(), access::AuthorizationError> {
+        assert_eq!(path, "/echo.Echo/Echo");
+        assert_eq!(level, access::AccessLevel::Authenticated);
+        match metadata.get("x-user").map(|user| user.to_str().unwrap()) {
+            None => Err(access::AuthorizationError::Unauthenticated(
+                "Unknown caller".to_string(),
+            )),
+            Some("mallory") => Err(access::AuthorizationError::Forbidden(
+                "Denied caller".to_string(),
+            )),
+            Some(_) => Ok(()),
+        }
+    }
+}
+
+// Rejects every caller, to observe that `Public` methods never consult it
+struct Wall;
+
+#[tonic::async_trait]
+impl public_access::Authorizer for Wall {
+    async fn authorize(
+        &self,
+        _path: &str,
+        _level: public_access::AccessLevel,
+        _metadata: &tonic::metadata::MetadataMap,
+    ) -> Result<(), public_access::AuthorizationError> {
+        Err(public_access::AuthorizationError::Forbidden(
+            "Nobody gets through".to_string(),
+        ))
+    }
+}
+
+fn guarded_router() -> axum::Router {
+    access::echo_handler(AccessServer)
+        .layer(axum::Extension(access::Authorization::new(Gatekeeper)))
+}
+
+#[tokio::test]
+async fn test_authorized_callers_reach_the_service() {
+    let request = common::echo_request()
+        .header("x-user", "alice")
+        .body(common::echo_body("x-user"))
+        .unwrap();
+    let response = guarded_router().oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), http::StatusCode::OK);
+    assert_eq!(
+        common::json_body(response).await["values"],
+        serde_json::json!(["alice"])
+    );
+}
+
+#[tokio::test]
+async fn test_unauthenticated_callers_get_401() {
+    let request = common::echo_request()
+        .body(common::echo_body("x-user"))
+        .unwrap();
+    let response = guarded_router().oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), http::StatusCode::UNAUTHORIZED);
+    let body = common::json_body(response).await;
+    assert_eq!(
+        body["error"]["code"],
+        tonic::Code::Unauthenticated.to_string().as_str()
+    );
+    assert_eq!(body["error"]["message"], "Unknown caller");
+}
+
+#[tokio::test]
+async fn test_forbidden_callers_get_403() {
+    let request = common::echo_request()
+        .header("x-user", "mallory")
+        .body(common::echo_body("x-user"))
+        .unwrap();
+    let response = guarded_router().oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), http::StatusCode::FORBIDDEN);
+    let body = common::json_body(response).await;
+    assert_eq!(
+        body["error"]["code"],
+        tonic::Code::PermissionDenied.to_string().as_str()
+    );
+    assert_eq!(body["error"]["message"], "Denied caller");
+}
+
+#[tokio::test]
+async fn test_missing_authorizer_gets_500() {
+    let request = common::echo_request()
+        .header("x-user", "alice")
+        .body(common::echo_body("x-user"))
+        .unwrap();
+    let response = access::echo_handler(AccessServer)
+        .oneshot(request)
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), http::StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(
+        common::json_body(response).await["error"]["code"],
+        tonic::Code::Internal.to_string().as_str()
+    );
+}
+
+#[tokio::test]
+async fn test_public_methods_skip_the_authorizer() {
+    let guarded = public_access::echo_handler(PublicServer)
+        .layer(axum::Extension(public_access::Authorization::new(Wall)));
+    let unguarded = public_access::echo_handler(PublicServer);
+
+    for router in [guarded, unguarded] {
+        let request = common::echo_request()
+            .body(common::echo_body("x-user"))
+            .unwrap();
+        let response = router.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), http::StatusCode::OK);
+    }
+}
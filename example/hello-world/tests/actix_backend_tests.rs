@@ -0,0 +1,159 @@
+/// Tests for the actix-web backend
+///
+/// build.rs generates the echo service with `Backend::Actix`, configuring its route on an
+/// actix-web `App` instead of an axum router. Headers and metadata go through the helpers
+/// of the axum handlers.
+#[macro_use]
+mod common;
+
+mod actix {
+    include!(concat!(env!("OUT_DIR"), "/actix/echo.rs"));
+}
+
+mod actix_sensitive {
+    include!(concat!(env!("OUT_DIR"), "/actix_sensitive/hello_world.rs"));
+}
+
+echo_server!(ActixServer, actix);
+
+// Replies with the requested key as metadata, along with transport and reserved names
+struct MetadataServer;
+
+#[tonic::async_trait]
+impl actix::echo_server::Echo for MetadataServer {
+    async fn echo(
+        &self,
+        request: tonic::Request<actix::EchoRequest>,
+    ) -> Result<tonic::Response<actix::EchoReply>, tonic::Status> {
+        let key = request.into_inner().key;
+        let mut response = tonic::Response::new(actix::EchoReply { values: vec![] });
+        let metadata = response.metadata_mut();
+        metadata.insert("x_trace_id", key.parse().unwrap());
+        metadata.insert("connection", "close".parse().unwrap());
+        metadata.insert("grpc-status", "0".parse().unwrap());
+        Ok(response)
+    }
+}
+
+// Status and JSON body of an echo of `key` with the extra request `headers`
+async fn echo<S>(server: S, key: &str, headers: &[(&str, &str)]) -> (u16, serde_json::Value)
+where
+    S: actix::echo_server::Echo,
+{
+    let app = actix_web::test::init_service(
+        actix_web::App::new().configure(actix::echo_actix_config(std::sync::Arc::new(server))),
+    )
+    .await;
+
+    let mut request = actix_web::test::TestRequest::post()
+        .uri("/echo.Echo/Echo")
+        .insert_header(("Content-Type", "application/json"));
+    for header in headers {
+        request = request.append_header(*header);
+    }
+    let request = request
+        .set_payload(serde_json::json!({ "key": key }).to_string())
+        .to_request();
+    let response = actix_web::test::call_service(&app, request).await;
+
+    let status = response.status().as_u16();
+    let body = actix_web::test::read_body_json(response).await;
+    (status, body)
+}
+
+#[actix_web::test]
+async fn test_actix_config_serves_unary_methods() {
+    let (status, body) = echo(ActixServer, "x-user", &[("x-user", "alice")]).await;
+
+    assert_eq!(status, 200);
+    assert_eq!(body["values"], serde_json::json!(["alice"]));
+}
+
+#[actix_web::test]
+async fn test_transport_headers_are_not_metadata() {
+    let (status, body) = echo(ActixServer, "host", &[("host", "example.com")]).await;
+
+    assert_eq!(status, 200);
+    assert_eq!(body["values"], serde_json::json!([]));
+}
+
+#[actix_web::test]
+async fn test_invalid_binary_metadata_is_rejected() {
+    let (status, body) = echo(
+        ActixServer,
+        "x-trace-bin",
+        &[("x-trace-bin", "not base64!")],
+    )
+    .await;
+
+    assert_eq!(status, 400);
+    assert_eq!(
+        body["error"]["code"],
+        tonic::Code::InvalidArgument.to_string().as_str()
+    );
+}
+
+#[actix_web::test]
+async fn test_response_metadata_is_filtered() {
+    let app = actix_web::test::init_service(actix_web::App::new().configure(
+        actix::echo_actix_config(std::sync::Arc::new(MetadataServer)),
+    ))
+    .await;
+    let request = actix_web::test::TestRequest::post()
+        .uri("/echo.Echo/Echo")
+        .insert_header(("Content-Type", "application/json"))
+        .set_payload(serde_json::json!({ "key": "abc" }).to_string())
+        .to_request();
+    let response = actix_web::test::call_service(&app, request).await;
+
+    assert_eq!(response.status(), actix_web::http::StatusCode::OK);
+    let headers = response.headers();
+    assert_eq!(headers.get("x-trace-id").unwrap(), "abc");
+    assert!(headers.get("connection").is_none());
+    assert!(headers.get("grpc-status").is_none());
+}
+
+struct Payments;
+
+#[tonic::async_trait]
+impl actix_sensitive::payment_connector_server::PaymentConnector for Payments {
+    async fn process_payment(
+        &self,
+        _request: tonic::Request<actix_sensitive::PaymentRequest>,
+    ) -> Result<tonic::Response<actix_sensitive::PaymentResponse>, tonic::Status> {
+        Err(tonic::Status::unimplemented("Not used by these tests"))
+    }
+
+    async fn get_payment_status(
+        &self,
+        _request: tonic::Request<actix_sensitive::StatusRequest>,
+    ) -> Result<tonic::Response<actix_sensitive::PaymentResponse>, tonic::Status> {
+        Err(tonic::Status::unimplemented("Not used by these tests"))
+    }
+}
+
+#[actix_web::test]
+async fn test_parse_errors_of_sensitive_requests_are_withheld() {
+    let app = actix_web::test::init_service(actix_web::App::new().configure(
+        actix_sensitive::payment_connector_actix_config(std::sync::Arc::new(Payments)),
+    ))
+    .await;
+    let request = actix_web::test::TestRequest::post()
+        .uri("/hello_world.PaymentConnector/ProcessPayment")
+        .insert_header(("Content-Type", "application/json"))
+        .set_payload(r#"{"card_number": 4111111111111111}"#)
+        .to_request();
+    let response = actix_web::test::call_service(&app, request).await;
+
+    assert_eq!(
+        response.status(),
+        actix_web::http::StatusCode::UNPROCESSABLE_ENTITY
+    );
+    let body: serde_json::Value = actix_web::test::read_body_json(response).await;
+    let message = body["error"]["message"].as_str().unwrap();
+    assert!(
+        message.contains("details are withheld"),
+        "unexpected message `{message}`"
+    );
+    assert!(!message.contains("4111"), "unexpected message `{message}`");
+}
@@ -0,0 +1,53 @@
+/// Tests for the API keys required by the `api_key_header` route option
+///
+/// build.rs generates protos/api_key/echo.proto, whose echo service requires a key in
+/// the `x-api-key` header.
+use tower::ServiceExt;
+
+#[macro_use]
+mod common;
+
+mod api_key {
+    include!(concat!(env!("OUT_DIR"), "/api_key/echo.rs"));
+}
+
+echo_server!(KeyedServer, api_key);
+
+#[tokio::test]
+async fn test_api_key_reaches_the_service_as_metadata() {
+    let request = common::echo_request()
+        .header("x-api-key", "secret")
+        .body(common::echo_body("api-key"))
+        .unwrap();
+    let response = api_key::echo_handler(KeyedServer)
+        .oneshot(request)
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), http::StatusCode::OK);
+    assert_eq!(
+        common::json_body(response).await["values"],
+        serde_json::json!(["secret"])
+    );
+}
+
+#[tokio::test]
+async fn test_missing_or_empty_api_key_gets_401() {
+    for key in [None, Some("")] {
+        let mut request = common::echo_request();
+        if let Some(key) = key {
+            request = request.header("x-api-key", key);
+        }
+        let request = request.body(common::echo_body("api-key")).unwrap();
+        let response = api_key::echo_handler(KeyedServer)
+            .oneshot(request)
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), http::StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            common::json_body(response).await["error"]["code"],
+            tonic::Code::Unauthenticated.to_string().as_str()
+        );
+    }
+}
@@ -0,0 +1,144 @@
+/// Tests for the JSON dialects selected by `x-api-version`
+///
+/// build.rs generates the hello world protos with a snake_case `v2` dialect for every
+/// method and a camelCase `v1` dialect for the payment methods only.
+use tower::ServiceExt;
+
+mod common;
+
+mod api_version {
+    include!(concat!(env!("OUT_DIR"), "/api_version/hello_world.rs"));
+}
+
+struct Payments;
+
+#[tonic::async_trait]
+impl api_version::payment_connector_server::PaymentConnector for Payments {
+    // Replies with the saved cards as error detail metadata, keyed by their map keys
+    async fn process_payment(
+        &self,
+        request: tonic::Request<api_version::PaymentRequest>,
+    ) -> Result<tonic::Response<api_version::PaymentResponse>, tonic::Status> {
+        let request = request.into_inner();
+        Ok(tonic::Response::new(api_version::PaymentResponse {
+            transaction_id: request.order_id,
+            error_detail: Some(api_version::ErrorDetail {
+                metadata: request
+                    .saved_cards
+                    .into_iter()
+                    .map(|(name, card)| (name, card.last_four))
+                    .collect(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }))
+    }
+
+    async fn get_payment_status(
+        &self,
+        _request: tonic::Request<api_version::StatusRequest>,
+    ) -> Result<tonic::Response<api_version::PaymentResponse>, tonic::Status> {
+        Err(tonic::Status::unimplemented("Not used by these tests"))
+    }
+}
+
+struct Greeter;
+
+#[tonic::async_trait]
+impl api_version::greeter_server::Greeter for Greeter {
+    async fn say_hello(
+        &self,
+        request: tonic::Request<api_version::HelloRequest>,
+    ) -> Result<tonic::Response<api_version::HelloReply>, tonic::Status> {
+        Ok(tonic::Response::new(api_version::HelloReply {
+            message: format!("Hello {}!", request.into_inner().name),
+            ..Default::default()
+        }))
+    }
+}
+
+fn request(uri: &str, version: &str, body: serde_json::Value) -> http::Request<axum::body::Body> {
+    http::Request::builder()
+        .method("POST")
+        .uri(uri)
+        .header("Content-Type", "application/json")
+        .header("x-api-version", version)
+        .body(axum::body::Body::from(body.to_string()))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_camel_case_dialect_keeps_map_keys() {
+    let body = serde_json::json!({
+        "orderId": "order_1",
+        "receipt": "",
+        "amount": 10.0,
+        "currency": "USD",
+        "customerId": "customer_1",
+        "paymentMethod": "card",
+        "cardNumber": "4111111111111111",
+        "savedCards": { "mainCard": { "lastFour": "4242", "cvv": "123" } },
+    });
+    let response = api_version::payment_connector_handler(Payments)
+        .oneshot(request(
+            "/hello_world.PaymentConnector/ProcessPayment",
+            "v1",
+            body,
+        ))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), http::StatusCode::OK);
+    let body = common::json_body(response).await;
+    assert_eq!(body["transactionId"], "order_1");
+    assert_eq!(
+        body["errorDetail"]["metadata"],
+        serde_json::json!({ "mainCard": "4242" })
+    );
+}
+
+#[tokio::test]
+async fn test_versions_of_every_method_use_their_own_case() {
+    let body = serde_json::json!({
+        "order_id": "order_1",
+        "receipt": "",
+        "amount": 10.0,
+        "currency": "USD",
+        "customer_id": "customer_1",
+        "payment_method": "card",
+        "card_number": "4111111111111111",
+        "saved_cards": {},
+    });
+    let response = api_version::payment_connector_handler(Payments)
+        .oneshot(request(
+            "/hello_world.PaymentConnector/ProcessPayment",
+            "v2",
+            body,
+        ))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), http::StatusCode::OK);
+    assert_eq!(
+        common::json_body(response).await["transaction_id"],
+        "order_1"
+    );
+}
+
+#[tokio::test]
+async fn test_method_versions_are_rejected_by_other_methods() {
+    let response = api_version::greeter_handler(Greeter)
+        .oneshot(request(
+            "/hello_world.Greeter/SayHello",
+            "v1",
+            serde_json::json!({ "name": "Ada" }),
+        ))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), http::StatusCode::BAD_REQUEST);
+    assert_eq!(
+        common::json_body(response).await["error"]["message"],
+        "Unsupported API version `v1`, expected one of: v2"
+    );
+}
@@ -0,0 +1,52 @@
+/// Tests for the request body limit of `with_max_body_size`
+///
+/// build.rs generates the echo service limiting request bodies to 16 bytes.
+use tower::ServiceExt;
+
+#[macro_use]
+mod common;
+
+mod body_limit {
+    include!(concat!(env!("OUT_DIR"), "/body_limit/echo.rs"));
+}
+
+echo_server!(LimitedServer, body_limit);
+
+#[tokio::test]
+async fn test_bodies_within_the_limit_reach_the_service() {
+    let request = common::echo_request()
+        .body(axum::body::Body::from(r#"{"key":"a"}"#))
+        .unwrap();
+    let response = body_limit::echo_handler(LimitedServer)
+        .oneshot(request)
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), http::StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_oversized_bodies_get_a_json_413() {
+    let request = common::echo_request()
+        .body(common::echo_body("x-a-rather-long-metadata-key"))
+        .unwrap();
+    let response = body_limit::echo_handler(LimitedServer)
+        .oneshot(request)
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), http::StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(
+        response.headers()[http::header::CONTENT_TYPE],
+        "application/json"
+    );
+    let body = common::json_body(response).await;
+    assert_eq!(
+        body["error"]["code"],
+        tonic::Code::ResourceExhausted.to_string().as_str()
+    );
+    assert_eq!(
+        body["error"]["message"],
+        "Request body exceeds the limit of 16 bytes"
+    );
+}
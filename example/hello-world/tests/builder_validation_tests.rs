@@ -0,0 +1,482 @@
+/// Tests for the checks `BridgeGenerator` runs while it is configured
+///
+/// Misconfigurations should fail the build script with a clear message instead of
+/// generating code that panics or misbehaves at runtime.
+use std::time::Duration;
+
+use g2h::{AccessLevel, Backend, BridgeGenerator, CorsSpec, MeshPreset};
+
+#[test]
+fn test_cors_accepts_explicit_credentialed_policy() {
+    let bridge = BridgeGenerator::with_tonic_build().with_cors(
+        CorsSpec::new()
+            .allow_origin("https://app.example.com")
+            .allow_header("Content-Type")
+            .allow_credentials(true),
+    );
+    assert!(bridge.render(descriptors()).is_ok());
+}
+
+#[test]
+fn test_cors_rejects_credentials_with_any_origin() {
+    let error = render_error(
+        BridgeGenerator::with_tonic_build().with_cors(
+            CorsSpec::new()
+                .allow_origin("*")
+                .allow_header("content-type")
+                .allow_credentials(true),
+        ),
+    );
+    assert!(error.contains("need explicit origins and headers"));
+}
+
+#[test]
+fn test_cors_rejects_credentials_with_any_header() {
+    let error = render_error(
+        BridgeGenerator::with_tonic_build().with_cors(
+            CorsSpec::new()
+                .allow_origin("https://app.example.com")
+                .allow_header("*")
+                .allow_credentials(true),
+        ),
+    );
+    assert!(error.contains("need explicit origins and headers"));
+}
+
+#[test]
+fn test_cors_rejects_invalid_values() {
+    let error = render_error(
+        BridgeGenerator::with_tonic_build().with_cors(CorsSpec::new().allow_header("x custom")),
+    );
+    assert!(error.contains("is not a valid header name"));
+
+    let error = render_error(
+        BridgeGenerator::with_tonic_build()
+            .with_cors(CorsSpec::new().allow_origin("https://app.example.com ")),
+    );
+    assert!(error.contains("is not a valid header value"));
+}
+
+// Error reported by the protoc plugin run with the parameter `parameter` on no files
+fn plugin_error(parameter: &str) -> Option<String> {
+    use prost::Message;
+
+    let request = prost_types::compiler::CodeGeneratorRequest {
+        parameter: Some(parameter.to_string()),
+        ..Default::default()
+    };
+    let response = g2h::plugin::run(&request.encode_to_vec());
+    prost_types::compiler::CodeGeneratorResponse::decode(response.as_slice())
+        .unwrap()
+        .error
+}
+
+#[test]
+fn test_plugin_rejects_invalid_option_values() {
+    for (parameter, message) in [
+        ("cfg_gate=all(feature", "Invalid cfg predicate"),
+        (
+            "cache_control=shop.Catalog=caf\u{e9}",
+            "Cache-Control `caf\u{e9}` is not a valid header value",
+        ),
+        (
+            "deprecation_sunset=31 D\u{e9}c 2025",
+            "Sunset `31 D\u{e9}c 2025` is not a valid header value",
+        ),
+    ] {
+        let error = plugin_error(parameter).expect("the option should be rejected");
+        assert!(error.contains(message), "unexpected error `{error}`");
+    }
+}
+
+#[test]
+fn test_plugin_keeps_escaped_commas_in_values() {
+    let error =
+        plugin_error(r"cfg_gate=all(unix\, test),cache_control=shop.Catalog=public\, max-age=60");
+    assert!(
+        error
+            .as_deref()
+            .map_or(true, |error| !error.contains("option")),
+        "unexpected error `{error:?}`"
+    );
+
+    let error = plugin_error("cache_control=shop.Catalog=public, max-age=60");
+    assert!(error.unwrap().contains("unknown option `max-age=60`"));
+}
+
+// Descriptors of the example protos, kept by build.rs
+fn descriptors() -> Vec<u8> {
+    let out_dir = std::env::var("OUT_DIR").expect("OUT_DIR not set");
+    std::fs::read(std::path::Path::new(&out_dir).join("hello_world.fds"))
+        .expect("build.rs should keep the descriptors")
+}
+
+// The example protos rendered by `bridge`, without whitespace
+fn render(bridge: BridgeGenerator) -> String {
+    let sources = bridge.render(descriptors()).unwrap();
+    let code = sources
+        .get("hello_world.rs")
+        .expect("hello_world.rs should be rendered");
+    code.split_whitespace().collect()
+}
+
+// Error rendering the example protos with `bridge`
+fn render_error(bridge: BridgeGenerator) -> String {
+    bridge
+        .render(descriptors())
+        .expect_err("the options should be rejected")
+        .to_string()
+}
+
+#[test]
+fn test_profiled_bodies_keep_the_json_content_type_check() {
+    let profiled =
+        BridgeGenerator::with_tonic_build().with_slow_request_profiler(Duration::from_millis(100));
+    assert!(render(profiled).contains("check_profiled_content_type(&headers)"));
+
+    // The content type check layer already runs before the handlers
+    let checked = BridgeGenerator::with_tonic_build()
+        .with_slow_request_profiler(Duration::from_millis(100))
+        .with_content_type_check(false);
+    assert!(!render(checked).contains("check_profiled_content_type(&headers)"));
+}
+
+#[test]
+fn test_required_bearer_tokens_accept_every_selector_form() {
+    let missing_token = "Missing`Authorization:Bearer`header";
+    for selector in [
+        "hello_world.Greeter",
+        "hello_world.Greeter/SayHello",
+        "hello_world.Greeter.SayHello",
+        "/hello_world.Greeter/SayHello",
+    ] {
+        let bridge = BridgeGenerator::with_tonic_build().require_bearer_token([selector]);
+        assert!(
+            render(bridge).contains(missing_token),
+            "`{selector}` should require a bearer token"
+        );
+    }
+
+    let bridge =
+        BridgeGenerator::with_tonic_build().require_bearer_token(["hello_world.Greeter/SayHi"]);
+    assert!(!render(bridge).contains(missing_token));
+}
+
+#[test]
+fn test_max_function_id_len_below_the_hash_length_is_rejected() {
+    let error = render_error(BridgeGenerator::with_tonic_build().with_max_function_id_len(8));
+    assert!(error.contains("the length of the hash suffix"));
+}
+
+#[test]
+fn test_plugin_rejects_max_function_id_len_below_the_hash_length() {
+    let error = plugin_error("max_function_id_len=8").expect("the option should be rejected");
+    assert!(error.contains("the length of the hash suffix"));
+}
+
+#[test]
+fn test_mesh_presets_pass_their_headers_through_the_prefix() {
+    let bridge = BridgeGenerator::with_tonic_build()
+        .with_metadata_header_prefix("x-grpc-")
+        .with_mesh_preset(MeshPreset::Istio);
+    let code = render(bridge);
+    assert!(code.contains("constPASSTHROUGH_HEADERS:&[&str]=&[\"x-request-id\""));
+    assert!(code.contains("\"traceparent\""));
+}
+
+#[test]
+fn test_mesh_preset_without_prefix_is_rejected() {
+    let error =
+        render_error(BridgeGenerator::with_tonic_build().with_mesh_preset(MeshPreset::Linkerd));
+    assert!(error.contains("`with_mesh_preset` needs `with_metadata_header_prefix`"));
+}
+
+#[test]
+fn test_missing_content_type_branch_is_chosen_when_generating() {
+    // Only the branch accepting requests without a content type rebinds the request
+    let accepts_missing = "letmutrequest=request;";
+
+    let allowing = render(BridgeGenerator::with_tonic_build().with_content_type_check(true));
+    assert!(allowing.contains(accepts_missing));
+    assert!(!allowing.contains("iftrue{"));
+
+    let rejecting = render(BridgeGenerator::with_tonic_build().with_content_type_check(false));
+    assert!(!rejecting.contains(accepts_missing));
+    assert!(!rejecting.contains("iffalse{"));
+}
+
+#[test]
+fn test_verb_inference_serves_get_methods_with_get() {
+    let route = |method: &str, verb: &str| {
+        format!(
+            "route_for(\"{method}\",\"/hello_world.PaymentConnector/{method}\",::axum::routing::on(::axum::routing::MethodFilter::{verb},"
+        )
+    };
+
+    let inferred = render(BridgeGenerator::with_tonic_build().with_verb_inference());
+    assert!(inferred.contains(&route("GetPaymentStatus", "GET")));
+    assert!(inferred.contains(&route("ProcessPayment", "POST")));
+
+    let default = render(BridgeGenerator::with_tonic_build());
+    assert!(default.contains(&route("GetPaymentStatus", "POST")));
+}
+
+#[test]
+fn test_plugin_rejects_mesh_presets_without_prefix() {
+    let error = plugin_error("mesh_preset=istio").expect("the option should be rejected");
+    assert!(error.contains("`with_mesh_preset` needs `with_metadata_header_prefix`"));
+
+    let error = plugin_error("mesh_preset=istio,metadata_header_prefix=x-grpc-");
+    assert!(
+        error
+            .as_deref()
+            .map_or(true, |error| !error.contains("mesh_preset")),
+        "unexpected error `{error:?}`"
+    );
+}
+
+#[test]
+fn test_actix_backend_rejects_request_checks() {
+    let out_dir = std::env::var("OUT_DIR").expect("OUT_DIR not set");
+    let descriptors = std::fs::read(std::path::Path::new(&out_dir).join("hello_world.fds"))
+        .expect("build.rs should keep the descriptors");
+    let actix = || BridgeGenerator::with_tonic_build().with_backend(Backend::Actix);
+
+    for (bridge, option) in [
+        (
+            actix().require_bearer_token(["hello_world.Greeter"]),
+            "require_bearer_token",
+        ),
+        (
+            actix().with_access_level("hello_world.Greeter", AccessLevel::Admin),
+            "with_access_level",
+        ),
+        (actix().with_rate_limiting(), "with_rate_limiting"),
+        (actix().with_request_validation(), "with_request_validation"),
+    ] {
+        let error = bridge
+            .render(descriptors.clone())
+            .expect_err("the actix backend should reject the option")
+            .to_string();
+        assert!(error.contains(option), "unexpected error `{error}`");
+    }
+    assert!(actix().render(descriptors).is_ok());
+
+    let error =
+        plugin_error("backend=actix,rate_limiting").expect("the options should be rejected");
+    assert!(
+        error.contains("with_rate_limiting"),
+        "unexpected error `{error}`"
+    );
+}
+
+#[test]
+fn test_actix_backend_rejects_ignored_options() {
+    let out_dir = std::env::var("OUT_DIR").expect("OUT_DIR not set");
+    let descriptors = std::fs::read(std::path::Path::new(&out_dir).join("hello_world.fds"))
+        .expect("build.rs should keep the descriptors");
+    let actix = || BridgeGenerator::with_tonic_build().with_backend(Backend::Actix);
+
+    for (bridge, option) in [
+        (actix().with_max_body_size(1024), "with_max_body_size"),
+        (actix().with_concurrency_limit(8), "with_concurrency_limit"),
+        (
+            actix().with_cors(CorsSpec::new().allow_origin("https://app.example.com")),
+            "with_cors",
+        ),
+        (
+            actix().with_default_timeout(Duration::from_secs(1)),
+            "with_default_timeout",
+        ),
+        (actix().with_idempotency_keys(), "with_idempotency_keys"),
+        (actix().with_access_log(), "with_access_log"),
+        (
+            actix().with_api_version("v1", g2h::JsonFieldCase::CamelCase),
+            "with_api_version",
+        ),
+    ] {
+        let error = bridge
+            .render(descriptors.clone())
+            .expect_err("the actix backend should reject the option")
+            .to_string();
+        assert!(error.contains(option), "unexpected error `{error}`");
+    }
+
+    let error = plugin_error("backend=actix,access_log").expect("the options should be rejected");
+    assert!(
+        error.contains("with_access_log"),
+        "unexpected error `{error}`"
+    );
+}
+
+#[test]
+fn test_graphql_rejects_request_checks() {
+    let out_dir = std::env::var("OUT_DIR").expect("OUT_DIR not set");
+    let descriptors = std::fs::read(std::path::Path::new(&out_dir).join("hello_world.fds"))
+        .expect("build.rs should keep the descriptors");
+    let graphql = || BridgeGenerator::with_tonic_build().with_graphql();
+
+    for (bridge, option) in [
+        (
+            graphql().require_bearer_token(["hello_world.Greeter"]),
+            "require_bearer_token",
+        ),
+        (
+            graphql().with_access_level("hello_world.Greeter", AccessLevel::Admin),
+            "with_access_level",
+        ),
+        (graphql().with_rate_limiting(), "with_rate_limiting"),
+        (
+            graphql().with_request_validation(),
+            "with_request_validation",
+        ),
+        (
+            graphql().with_client_ip_metadata("x-client-ip"),
+            "with_client_ip_metadata",
+        ),
+    ] {
+        let error = bridge
+            .render(descriptors.clone())
+            .expect_err("the GraphQL resolvers should reject the option")
+            .to_string();
+        assert!(error.contains(option), "unexpected error `{error}`");
+    }
+    assert!(graphql()
+        .with_max_header_value_len(64)
+        .render(descriptors)
+        .is_ok());
+}
+
+#[test]
+fn test_client_ip_metadata_rejects_invalid_keys() {
+    let out_dir = std::env::var("OUT_DIR").expect("OUT_DIR not set");
+    let descriptors = std::fs::read(std::path::Path::new(&out_dir).join("hello_world.fds"))
+        .expect("build.rs should keep the descriptors");
+
+    for key in ["x-client-ip-bin", "x client ip", ""] {
+        let error = BridgeGenerator::with_tonic_build()
+            .with_client_ip_metadata(key)
+            .render(descriptors.clone())
+            .expect_err("the key should be rejected")
+            .to_string();
+        assert!(
+            error.contains("not a valid ASCII metadata key"),
+            "unexpected error `{error}`"
+        );
+    }
+}
+
+#[test]
+fn test_wasm_target_rejects_runtime_options() {
+    let error = render_error(
+        BridgeGenerator::with_tonic_build()
+            .with_wasm_target()
+            .with_access_log(),
+    );
+    assert!(
+        error.contains("wasm targets don't support with_access_log"),
+        "unexpected error `{error}`"
+    );
+}
+
+#[test]
+fn test_cli_reports_invalid_option_values() {
+    let out_dir = std::env::var("OUT_DIR").expect("OUT_DIR not set");
+    let descriptors = std::path::Path::new(&out_dir).join("hello_world.fds");
+    let cli_error = |option: &str| {
+        let args = [
+            "generate".to_string(),
+            format!("--descriptor-set={}", descriptors.display()),
+            format!("--out={out_dir}/cli"),
+            option.to_string(),
+        ];
+        g2h::cli::run(args)
+            .expect_err("the option should be rejected")
+            .to_string()
+    };
+
+    assert!(cli_error("--max-function-id-len=8").contains("the length of the hash suffix"));
+    assert!(cli_error("--cfg-gate=all(unix").contains("Invalid cfg predicate"));
+    assert!(cli_error("--mesh-preset=istio")
+        .contains("`with_mesh_preset` needs `with_metadata_header_prefix`"));
+}
+
+#[test]
+fn test_cli_generates_the_bridge_of_a_proto() {
+    let out_dir = format!(
+        "{}/cli-echo",
+        std::env::var("OUT_DIR").expect("OUT_DIR not set")
+    );
+    let args = [
+        "generate",
+        "--proto",
+        "protos/echo.proto",
+        "--include",
+        "protos",
+        "--out",
+        &out_dir,
+        "--string-enums",
+    ];
+    g2h::cli::run(args.map(String::from)).expect("the CLI should generate the bridge");
+
+    let generated = std::fs::read_to_string(format!("{out_dir}/echo.rs"))
+        .expect("the CLI should write echo.rs");
+    assert!(generated.contains("pub fn echo_handler"));
+    assert!(generated.contains("\"/echo.Echo/Echo\""));
+}
+
+// Creates a crate with `dependencies`, resolved from the lockfile of the example
+fn dependency_check_crate(name: &str, dependencies: &str) -> std::path::PathBuf {
+    let dir = std::path::Path::new(&std::env::var("OUT_DIR").expect("OUT_DIR not set")).join(name);
+    std::fs::create_dir_all(dir.join("src")).unwrap();
+    std::fs::write(dir.join("src/lib.rs"), "").unwrap();
+    std::fs::write(
+        dir.join("Cargo.toml"),
+        format!(
+            "[package]\nname = \"{name}\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n\
+             [workspace]\n\n[dependencies]\n{dependencies}"
+        ),
+    )
+    .unwrap();
+    std::fs::copy(
+        concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.lock"),
+        dir.join("Cargo.lock"),
+    )
+    .unwrap();
+    dir
+}
+
+#[test]
+fn test_actix_backend_checks_actix_web_instead_of_axum() {
+    let dir = dependency_check_crate(
+        "actix-only",
+        "actix-web = \"4\"\ntonic = \"0.14\"\nhttp = \"1\"\n\
+         serde = { version = \"1\", features = [\"derive\"] }\nserde_json = \"1\"\n",
+    );
+    let compile = |bridge: BridgeGenerator| {
+        bridge
+            .out_dir(dir.join("out"))
+            .compile_fds(descriptors())
+            .map_err(|err| err.to_string())
+    };
+
+    // The dependencies are those of the crate whose build script runs g2h
+    std::env::set_var("CARGO_MANIFEST_DIR", &dir);
+    std::fs::create_dir_all(dir.join("out")).unwrap();
+    let actix = compile(
+        BridgeGenerator::with_tonic_build()
+            .with_backend(Backend::Actix)
+            .with_strict_validation(),
+    );
+    let strict_axum = compile(BridgeGenerator::with_tonic_build().with_strict_validation());
+    // Without strict validation, the missing dependency is a cargo warning
+    let axum = compile(BridgeGenerator::with_tonic_build());
+    std::env::set_var("CARGO_MANIFEST_DIR", env!("CARGO_MANIFEST_DIR"));
+
+    assert_eq!(actix, Ok(()));
+    assert_eq!(
+        strict_axum,
+        Err("g2h: Dependency `axum` is absent".to_string())
+    );
+    assert_eq!(axum, Ok(()));
+}
@@ -0,0 +1,45 @@
+/// Tests for the `Cache-Control` policies of `with_cache_control`
+///
+/// build.rs generates the echo service with a service policy and a method policy, which
+/// takes precedence.
+use tower::ServiceExt;
+
+#[macro_use]
+mod common;
+
+mod cache_control {
+    include!(concat!(env!("OUT_DIR"), "/cache_control/echo.rs"));
+}
+
+echo_server!(CachedServer, cache_control);
+
+#[tokio::test]
+async fn test_successful_responses_carry_the_method_policy() {
+    let request = common::echo_request()
+        .body(common::echo_body("x-user"))
+        .unwrap();
+    let response = cache_control::echo_handler(CachedServer)
+        .oneshot(request)
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), http::StatusCode::OK);
+    assert_eq!(response.headers()[http::header::CACHE_CONTROL], "no-store");
+}
+
+#[tokio::test]
+async fn test_failed_requests_carry_no_policy() {
+    let request = common::echo_request()
+        .body(axum::body::Body::from("{"))
+        .unwrap();
+    let response = cache_control::echo_handler(CachedServer)
+        .oneshot(request)
+        .await
+        .unwrap();
+
+    assert!(response.status().is_client_error());
+    assert!(response
+        .headers()
+        .get(http::header::CACHE_CONTROL)
+        .is_none());
+}
@@ -0,0 +1,94 @@
+/// Tests for the `HttpCancellation` signal of `with_cancellation_signal`
+///
+/// build.rs generates the echo service with the cancellation signal.
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tokio::sync::oneshot;
+use tower::ServiceExt;
+
+mod common;
+
+mod cancellation {
+    include!(concat!(env!("OUT_DIR"), "/cancellation/echo.rs"));
+}
+
+use cancellation::HttpCancellation;
+
+// Hands the cancellation signal of its request over, then replies or never does
+struct SignalServer {
+    signal: Mutex<Option<oneshot::Sender<Option<HttpCancellation>>>>,
+    pending: bool,
+}
+
+impl SignalServer {
+    fn new(pending: bool) -> (Self, oneshot::Receiver<Option<HttpCancellation>>) {
+        let (sender, receiver) = oneshot::channel();
+        let server = SignalServer {
+            signal: Mutex::new(Some(sender)),
+            pending,
+        };
+        (server, receiver)
+    }
+}
+
+#[tonic::async_trait]
+impl cancellation::echo_server::Echo for SignalServer {
+    async fn echo(
+        &self,
+        request: tonic::Request<cancellation::EchoRequest>,
+    ) -> Result<tonic::Response<cancellation::EchoReply>, tonic::Status> {
+        let cancellation = request.extensions().get::<HttpCancellation>().cloned();
+        if let Some(signal) = self.signal.lock().unwrap().take() {
+            let _ = signal.send(cancellation);
+        }
+        if self.pending {
+            std::future::pending::<()>().await;
+        }
+        Ok(tonic::Response::new(cancellation::EchoReply {
+            values: vec![],
+        }))
+    }
+}
+
+fn echo() -> http::Request<axum::body::Body> {
+    common::echo_request()
+        .body(common::echo_body("x-key"))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_abandoned_requests_signal_the_cancellation() {
+    let (server, signal) = SignalServer::new(true);
+    let call = tokio::spawn(cancellation::echo_handler(server).oneshot(echo()));
+
+    let cancellation = signal
+        .await
+        .unwrap()
+        .expect("the request should carry an HttpCancellation");
+    assert!(!cancellation.is_cancelled());
+
+    // Dropping the handler future, as the server does when the client disconnects
+    call.abort();
+    assert!(call.await.unwrap_err().is_cancelled());
+    tokio::time::timeout(Duration::from_secs(1), cancellation.cancelled())
+        .await
+        .expect("the cancellation should be signalled");
+    assert!(cancellation.is_cancelled());
+}
+
+#[tokio::test]
+async fn test_completed_requests_are_not_cancelled() {
+    let (server, signal) = SignalServer::new(false);
+    let response = cancellation::echo_handler(server)
+        .oneshot(echo())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), http::StatusCode::OK);
+
+    let cancellation = signal
+        .await
+        .unwrap()
+        .expect("the request should carry an HttpCancellation");
+    assert!(!cancellation.is_cancelled());
+}
@@ -0,0 +1,37 @@
+/// Tests for the HTTP bridge gated behind a `cfg` predicate by `with_cfg_gate`
+///
+/// build.rs generates the echo service gated with `any(test, feature = "http-bridge")`,
+/// which test builds satisfy.
+use tower::ServiceExt;
+
+#[macro_use]
+mod common;
+
+mod cfg_gate {
+    include!(concat!(env!("OUT_DIR"), "/cfg_gate/echo.rs"));
+}
+
+echo_server!(EchoServer, cfg_gate);
+
+#[tokio::test]
+async fn test_bridge_is_compiled_when_the_predicate_holds() {
+    let response = cfg_gate::echo_handler(EchoServer)
+        .oneshot(
+            common::echo_request()
+                .header("x-key", "value")
+                .body(common::echo_body("x-key"))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), http::StatusCode::OK);
+    let body = common::json_body(response).await;
+    assert_eq!(body["values"], serde_json::json!(["value"]));
+}
+
+#[test]
+fn test_generated_code_keeps_the_predicate() {
+    let code = std::fs::read_to_string(concat!(env!("OUT_DIR"), "/cfg_gate/echo.rs")).unwrap();
+    assert!(code.contains(r#"#[cfg(any(test, feature = "http-bridge"))]"#));
+}
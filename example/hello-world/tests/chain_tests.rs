@@ -0,0 +1,40 @@
+/// Tests for the code of generators chained with `chain`
+///
+/// build.rs generates the echo service with a second generator listing its methods.
+use tower::ServiceExt;
+
+#[macro_use]
+mod common;
+
+mod chained {
+    include!(concat!(env!("OUT_DIR"), "/chained/echo.rs"));
+}
+
+echo_server!(EchoServer, chained);
+
+#[test]
+fn test_chained_generators_add_their_code() {
+    assert_eq!(chained::ECHO_METHODS, ["Echo"]);
+}
+
+#[tokio::test]
+async fn test_chained_generators_keep_the_bridge() {
+    let response = chained::echo_handler(EchoServer)
+        .oneshot(
+            common::echo_request()
+                .header("x-key", "value")
+                .body(common::echo_body("x-key"))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), http::StatusCode::OK);
+    let body = common::json_body(response).await;
+    assert_eq!(body["values"], serde_json::json!(["value"]));
+}
+
+#[test]
+fn test_chained_generators_keep_the_tonic_code() {
+    let _server = chained::echo_server::EchoServer::new(EchoServer);
+}
@@ -0,0 +1,82 @@
+/// Tests for the client address forwarded by `with_client_ip_metadata`
+///
+/// build.rs generates the echo service forwarding the peer address, and once more taking
+/// it from `X-Forwarded-For` behind two trusted proxies.
+use tower::ServiceExt;
+
+#[macro_use]
+mod common;
+
+mod client_ip {
+    include!(concat!(env!("OUT_DIR"), "/client_ip/echo.rs"));
+}
+
+mod proxied_client_ip {
+    include!(concat!(env!("OUT_DIR"), "/proxied_client_ip/echo.rs"));
+}
+
+echo_server!(PeerServer, client_ip);
+echo_server!(ProxiedServer, proxied_client_ip);
+
+fn peer() -> axum::extract::ConnectInfo<std::net::SocketAddr> {
+    axum::extract::ConnectInfo(std::net::SocketAddr::from(([10, 0, 0, 7], 4711)))
+}
+
+#[tokio::test]
+async fn test_peer_address_replaces_forwarded_and_client_sent_values() {
+    let request = common::echo_request()
+        .header("x-forwarded-for", "203.0.113.9")
+        .header("x-client-ip", "198.51.100.1")
+        .extension(peer())
+        .body(common::echo_body("x-client-ip"))
+        .unwrap();
+    let response = client_ip::echo_handler(PeerServer)
+        .oneshot(request)
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), http::StatusCode::OK);
+    assert_eq!(
+        common::json_body(response).await["values"],
+        serde_json::json!(["10.0.0.7"])
+    );
+}
+
+#[tokio::test]
+async fn test_forwarded_address_skips_the_trusted_proxies() {
+    // The client spoofs the first entry, the two proxies append the next ones
+    let request = common::echo_request()
+        .header("x-forwarded-for", "198.51.100.1, 203.0.113.9")
+        .header("x-forwarded-for", "192.0.2.4")
+        .extension(peer())
+        .body(common::echo_body("x-client-ip"))
+        .unwrap();
+    let response = proxied_client_ip::echo_handler(ProxiedServer)
+        .oneshot(request)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        common::json_body(response).await["values"],
+        serde_json::json!(["203.0.113.9"])
+    );
+}
+
+#[tokio::test]
+async fn test_missing_proxy_entries_give_no_address() {
+    let request = common::echo_request()
+        .header("x-forwarded-for", "203.0.113.9")
+        .header("x-client-ip", "198.51.100.1")
+        .extension(peer())
+        .body(common::echo_body("x-client-ip"))
+        .unwrap();
+    let response = proxied_client_ip::echo_handler(ProxiedServer)
+        .oneshot(request)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        common::json_body(response).await["values"],
+        serde_json::json!([])
+    );
+}
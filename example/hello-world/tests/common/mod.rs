@@ -0,0 +1,61 @@
+/// Helpers shared by the tests of the echo service variants
+///
+/// build.rs generates protos/echo.proto once per bridge configuration under test, each
+/// package in its own directory of `OUT_DIR`.
+
+// Implements the echo service of a generated `$package` for the unit struct `$name`,
+// replying with the request metadata values of the requested key
+#[allow(unused_macros)]
+macro_rules! echo_server {
+    ($name:ident, $package:ident) => {
+        struct $name;
+
+        #[tonic::async_trait]
+        impl $package::echo_server::Echo for $name {
+            async fn echo(
+                &self,
+                request: tonic::Request<$package::EchoRequest>,
+            ) -> Result<tonic::Response<$package::EchoReply>, tonic::Status> {
+                let key = request.get_ref().key.clone();
+                let metadata = request.metadata();
+                let values = if key.ends_with("-bin") {
+                    metadata
+                        .get_all_bin(key.as_str())
+                        .iter()
+                        .map(|value| format!("{:?}", value.to_bytes().unwrap()))
+                        .collect()
+                } else {
+                    metadata
+                        .get_all(key.as_str())
+                        .iter()
+                        .map(|value| value.to_str().unwrap().to_string())
+                        .collect()
+                };
+                Ok(tonic::Response::new($package::EchoReply { values }))
+            }
+        }
+    };
+}
+
+// A JSON request to the echo route, to be completed with headers and an `echo_body`
+#[allow(dead_code)]
+pub fn echo_request() -> http::request::Builder {
+    http::Request::builder()
+        .method("POST")
+        .uri("/echo.Echo/Echo")
+        .header("Content-Type", "application/json")
+}
+
+// Body asking the echo service for the metadata values of `key`
+#[allow(dead_code)]
+pub fn echo_body(key: &str) -> axum::body::Body {
+    axum::body::Body::from(serde_json::json!({ "key": key }).to_string())
+}
+
+#[allow(dead_code)]
+pub async fn json_body(response: axum::response::Response) -> serde_json::Value {
+    let body_bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("Body should be readable");
+    serde_json::from_slice(&body_bytes).expect("Body should be JSON")
+}
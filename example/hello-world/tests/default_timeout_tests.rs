@@ -0,0 +1,72 @@
+/// Tests for the deadline of `with_default_timeout`
+///
+/// build.rs generates the echo service with a default timeout of 100ms, which also honors
+/// the timeout headers.
+use std::time::Duration;
+
+use tower::ServiceExt;
+
+mod common;
+
+mod default_timeout {
+    include!(concat!(env!("OUT_DIR"), "/default_timeout/echo.rs"));
+}
+
+// Replies after the wrapped delay
+struct DelayedServer(Duration);
+
+#[tonic::async_trait]
+impl default_timeout::echo_server::Echo for DelayedServer {
+    async fn echo(
+        &self,
+        _request: tonic::Request<default_timeout::EchoRequest>,
+    ) -> Result<tonic::Response<default_timeout::EchoReply>, tonic::Status> {
+        tokio::time::sleep(self.0).await;
+        Ok(tonic::Response::new(default_timeout::EchoReply {
+            values: vec![],
+        }))
+    }
+}
+
+async fn call(delay: Duration, grpc_timeout: Option<&str>) -> axum::response::Response {
+    let mut request = common::echo_request();
+    if let Some(grpc_timeout) = grpc_timeout {
+        request = request.header("grpc-timeout", grpc_timeout);
+    }
+    default_timeout::echo_handler(DelayedServer(delay))
+        .oneshot(request.body(common::echo_body("x-key")).unwrap())
+        .await
+        .unwrap()
+}
+
+async fn assert_deadline_exceeded(response: axum::response::Response) {
+    assert_eq!(response.status(), http::StatusCode::REQUEST_TIMEOUT);
+    let body = common::json_body(response).await;
+    assert_eq!(
+        body["error"]["code"],
+        tonic::Code::DeadlineExceeded.description()
+    );
+    assert_eq!(body["error"]["message"], "Request deadline exceeded");
+}
+
+#[tokio::test]
+async fn test_fast_calls_complete() {
+    let response = call(Duration::ZERO, None).await;
+
+    assert_eq!(response.status(), http::StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_slow_calls_get_a_json_deadline_exceeded() {
+    assert_deadline_exceeded(call(Duration::from_secs(5), None).await).await;
+}
+
+#[tokio::test]
+async fn test_the_shorter_of_the_default_and_the_client_deadline_applies() {
+    // A longer client deadline doesn't extend the default timeout
+    assert_deadline_exceeded(call(Duration::from_secs(5), Some("10S")).await).await;
+
+    // A shorter one cuts it
+    let response = call(Duration::from_millis(50), Some("10m")).await;
+    assert_deadline_exceeded(response).await;
+}
@@ -0,0 +1,64 @@
+/// Tests for the `FILE_DESCRIPTOR_SET` embedded with `file_descriptor_set_path` and the
+/// reflection server of `with_reflection_service`
+///
+/// build.rs generates the echo service with its descriptor set written to
+/// `OUT_DIR/descriptor_set/echo.fds`.
+use g2h::BridgeGenerator;
+use prost::Message;
+
+mod descriptor_set {
+    include!(concat!(env!("OUT_DIR"), "/descriptor_set/echo.rs"));
+}
+
+#[test]
+fn test_embedded_descriptor_set_is_the_written_one() {
+    let out_dir = std::env::var("OUT_DIR").expect("OUT_DIR not set");
+    let written = std::fs::read(std::path::Path::new(&out_dir).join("descriptor_set/echo.fds"))
+        .expect("build.rs should write the descriptor set");
+
+    assert_eq!(descriptor_set::FILE_DESCRIPTOR_SET, written.as_slice());
+}
+
+#[test]
+fn test_embedded_descriptor_set_describes_the_service() {
+    let descriptors =
+        prost_types::FileDescriptorSet::decode(descriptor_set::FILE_DESCRIPTOR_SET).unwrap();
+
+    let echo = descriptors
+        .file
+        .iter()
+        .find(|file| file.package() == "echo")
+        .expect("the descriptor set should hold echo.proto");
+    assert_eq!(echo.service[0].name(), "Echo");
+    assert_eq!(echo.service[0].method[0].name(), "Echo");
+}
+
+// The example doesn't depend on tonic_reflection, so the reflection server is only rendered
+#[test]
+fn test_reflection_service_registers_the_embedded_descriptor_set() {
+    let out_dir = std::env::var("OUT_DIR").expect("OUT_DIR not set");
+    let out_dir = std::path::Path::new(&out_dir);
+    let descriptors = std::fs::read(out_dir.join("hello_world.fds"))
+        .expect("build.rs should keep the descriptors");
+    let render = |bridge: BridgeGenerator| -> String {
+        let sources = bridge
+            .file_descriptor_set_path(out_dir.join("reflection.fds"))
+            .render(descriptors.clone())
+            .unwrap();
+        sources
+            .get("hello_world.rs")
+            .unwrap()
+            .split_whitespace()
+            .collect()
+    };
+
+    let code = render(BridgeGenerator::with_tonic_build().with_reflection_service());
+    assert!(code.contains("pubconstFILE_DESCRIPTOR_SET:&[u8]=include_bytes!("));
+    assert!(code.contains("pubfnreflection_service()"));
+    assert!(code.contains(".register_encoded_file_descriptor_set(FILE_DESCRIPTOR_SET)"));
+
+    // Without the option, only the descriptor set is embedded
+    let code = render(BridgeGenerator::with_tonic_build());
+    assert!(code.contains("pubconstFILE_DESCRIPTOR_SET"));
+    assert!(!code.contains("reflection_service"));
+}
@@ -0,0 +1,21 @@
+/// Tests for enum fields named like the functions of their enum
+///
+/// build.rs generates `enum_alias.Payment`, whose `status_enum` field of type
+/// `PaymentStatus` gets no field-specific aliases, as they would shadow the enum's own
+/// functions.
+mod enum_alias {
+    include!(concat!(env!("OUT_DIR"), "/enum_alias/enum_alias.rs"));
+}
+
+#[test]
+fn test_enum_field_named_like_its_enum_serializes_as_string() {
+    let payment = enum_alias::Payment {
+        status_enum: enum_alias::PaymentStatus::Settled as i32,
+    };
+
+    let json = serde_json::to_value(&payment).unwrap();
+    assert_eq!(json["status_enum"], "PAYMENT_STATUS_SETTLED");
+
+    let decoded: enum_alias::Payment = serde_json::from_value(json).unwrap();
+    assert_eq!(decoded, payment);
+}
@@ -0,0 +1,41 @@
+/// Tests for the HTTP bridge gated behind the `http-bridge` feature by `with_feature_gate`
+///
+/// build.rs generates the echo service with the gate, and the tests run with the feature
+/// both enabled and disabled.
+#[macro_use]
+mod common;
+
+mod feature_gate {
+    include!(concat!(env!("OUT_DIR"), "/feature_gate/echo.rs"));
+}
+
+echo_server!(EchoServer, feature_gate);
+
+#[test]
+fn test_grpc_code_is_not_gated() {
+    let _server = feature_gate::echo_server::EchoServer::new(EchoServer);
+    let request = feature_gate::EchoRequest {
+        key: "x-key".to_string(),
+    };
+    assert_eq!(request.key, "x-key");
+}
+
+#[cfg(feature = "http-bridge")]
+#[tokio::test]
+async fn test_gated_bridge_serves_the_route_with_the_feature() {
+    use tower::ServiceExt;
+
+    let response = feature_gate::echo_handler(EchoServer)
+        .oneshot(
+            common::echo_request()
+                .header("x-key", "value")
+                .body(common::echo_body("x-key"))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), http::StatusCode::OK);
+    let body = common::json_body(response).await;
+    assert_eq!(body["values"], serde_json::json!(["value"]));
+}
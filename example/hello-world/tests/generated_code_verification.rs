@@ -52,30 +52,30 @@ fn test_generated_code_contains_field_specific_serializers() {
         println!("✅ Found expected function: {}", expected_function);
     }
 
-    // Verify that the functions are type-specific (use the correct enum types)
+    // Verify that the shared per-enum functions are type-specific (use the correct enum types)
     let type_specific_checks = vec![
         (
-            "serialize_conflict_test_request_payment_status_as_string",
+            "serialize_payment_status_enum_as_string",
             "PaymentStatus::try_from",
         ),
         (
-            "serialize_conflict_test_request_auth_status_as_string",
+            "serialize_authentication_status_enum_as_string",
             "AuthenticationStatus::try_from",
         ),
         (
-            "serialize_conflict_test_request_processing_status_as_string",
+            "serialize_processing_status_enum_as_string",
             "ProcessingStatus::try_from",
         ),
         (
-            "deserialize_conflict_test_request_payment_status_from_string",
+            "deserialize_payment_status_enum_from_string",
             "PaymentStatus::from_str_name",
         ),
         (
-            "deserialize_conflict_test_request_auth_status_from_string",
+            "deserialize_authentication_status_enum_from_string",
             "AuthenticationStatus::from_str_name",
         ),
         (
-            "deserialize_conflict_test_request_processing_status_from_string",
+            "deserialize_processing_status_enum_from_string",
             "ProcessingStatus::from_str_name",
         ),
     ];
@@ -191,3 +191,66 @@ fn test_no_generic_enum_serializers() {
 
     println!("✅ No generic enum serializers test passed!");
 }
+
+#[test]
+fn test_route_manifest_lists_routes_and_messages() {
+    let out_dir = std::env::var("OUT_DIR").expect("OUT_DIR not set");
+    let manifest = fs::read_to_string(Path::new(&out_dir).join("routes.json"))
+        .expect("Should be able to read route manifest");
+    let manifest: serde_json::Value =
+        serde_json::from_str(&manifest).expect("Route manifest should be valid JSON");
+
+    let routes = manifest["routes"].as_array().unwrap();
+    let say_hello = routes
+        .iter()
+        .find(|route| route["path"] == "/hello_world.Greeter/SayHello")
+        .expect("SayHello should be in the manifest");
+    assert_eq!(say_hello["http_method"], "POST");
+    assert_eq!(say_hello["service"], "hello_world.Greeter");
+    assert_eq!(say_hello["request_type"], "hello_world.HelloRequest");
+    assert!(routes
+        .iter()
+        .any(|route| route["path"] == "/hello_world.PaymentConnector/ProcessPayment"));
+
+    let fields = manifest["messages"]["hello_world.HelloRequest"]["fields"]
+        .as_array()
+        .unwrap();
+    assert_eq!(fields[0]["name"], "name");
+    assert_eq!(fields[0]["type"], "string");
+    assert_eq!(fields[0]["repeated"], false);
+}
+
+#[test]
+fn test_rendered_code_matches_compiled_bridge() {
+    // build.rs renders the same protos with the same configuration through `BridgeGenerator::render`
+    let out_dir = std::env::var("OUT_DIR").expect("OUT_DIR not set");
+    let rendered_file = Path::new(&out_dir).join("rendered").join("hello_world.rs");
+
+    let rendered_code =
+        fs::read_to_string(&rendered_file).expect("Should be able to read rendered file");
+    let generated_code = fs::read_to_string(Path::new(&out_dir).join("hello_world.rs"))
+        .expect("Should be able to read generated file");
+
+    assert!(rendered_code.contains("pub fn greeter_handler"));
+    pretty_assertions::assert_eq!(rendered_code, generated_code);
+}
+
+#[test]
+fn test_handler_docs_contain_examples() {
+    let out_dir = std::env::var("OUT_DIR").expect("OUT_DIR not set");
+    let generated_code = fs::read_to_string(Path::new(&out_dir).join("hello_world.rs"))
+        .expect("Should be able to read generated file");
+
+    // The `doc` feature documents every handler with example bodies and a curl command
+    for expected in [
+        "Example request:",
+        "Example response:",
+        "curl -X POST 'http://localhost:3000/hello_world.Greeter/SayHello'",
+    ] {
+        assert!(
+            generated_code.contains(expected),
+            "Generated code should contain `{}`",
+            expected
+        );
+    }
+}
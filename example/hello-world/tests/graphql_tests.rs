@@ -0,0 +1,72 @@
+/// Tests for the experimental GraphQL facade
+///
+/// build.rs generates the echo service with `with_graphql` and a 64 byte header value limit,
+/// so the schema compiles against async-graphql and resolves through the tonic trait.
+#[macro_use]
+mod common;
+
+mod graphql {
+    include!(concat!(env!("OUT_DIR"), "/graphql/echo.rs"));
+}
+
+echo_server!(GraphqlServer, graphql);
+
+#[tokio::test]
+async fn test_graphql_schema_resolves_mutations_with_header_metadata() {
+    let schema = graphql::echo_graphql_schema(GraphqlServer);
+    let mut headers = http::HeaderMap::new();
+    headers.insert("x-user", http::HeaderValue::from_static("alice"));
+
+    let request = async_graphql::Request::new(r#"mutation { echo(request: { key: "x-user" }) }"#)
+        .data(headers);
+    let response = schema.execute(request).await;
+
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(
+        response.data.into_json().unwrap(),
+        serde_json::json!({ "echo": { "values": ["alice"] } })
+    );
+}
+
+#[tokio::test]
+async fn test_graphql_schema_rejects_headers_like_the_handlers() {
+    let schema = graphql::echo_graphql_schema(GraphqlServer);
+    let mut headers = http::HeaderMap::new();
+    headers.insert(
+        "x-user",
+        http::HeaderValue::from_str(&"a".repeat(65)).unwrap(),
+    );
+
+    let request = async_graphql::Request::new(r#"mutation { echo(request: { key: "x-user" }) }"#)
+        .data(headers);
+    let response = schema.execute(request).await;
+
+    assert_eq!(response.errors.len(), 1);
+    assert_eq!(
+        response.errors[0].message,
+        "Header `x-user` exceeds the maximum value length"
+    );
+    let code = response.errors[0]
+        .extensions
+        .as_ref()
+        .and_then(|extensions| extensions.get("code"))
+        .cloned();
+    assert_eq!(
+        code,
+        Some(async_graphql::Value::from(
+            tonic::Code::ResourceExhausted.to_string()
+        ))
+    );
+}
+
+#[tokio::test]
+async fn test_graphql_schema_queries_the_service_name() {
+    let schema = graphql::echo_graphql_schema(GraphqlServer);
+    let response = schema.execute("{ service }").await;
+
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(
+        response.data.into_json().unwrap(),
+        serde_json::json!({ "service": "echo.Echo" })
+    );
+}
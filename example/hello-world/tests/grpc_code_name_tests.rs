@@ -0,0 +1,65 @@
+/// Tests for the error codes of `with_grpc_code_names`
+///
+/// build.rs generates the echo service with the gRPC names as error codes and a 24 byte
+/// body limit, and the keep-first variant with the default descriptions.
+use tower::ServiceExt;
+
+#[macro_use]
+mod common;
+
+mod grpc_code_names {
+    include!(concat!(env!("OUT_DIR"), "/grpc_code_names/echo.rs"));
+}
+
+mod keep_first {
+    include!(concat!(env!("OUT_DIR"), "/keep_first/echo.rs"));
+}
+
+echo_server!(NamedServer, grpc_code_names);
+echo_server!(DescribedServer, keep_first);
+
+// Request with binary metadata that isn't base64, rejected by the generated handlers
+fn invalid_request() -> http::Request<axum::body::Body> {
+    common::echo_request()
+        .header("x-data-bin", "not base64!")
+        .body(common::echo_body("x-data-bin"))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_error_codes_are_grpc_names() {
+    let response = grpc_code_names::echo_handler(NamedServer)
+        .oneshot(invalid_request())
+        .await
+        .unwrap();
+    assert_eq!(
+        common::json_body(response).await["error"]["code"],
+        "INVALID_ARGUMENT"
+    );
+
+    let response = grpc_code_names::echo_handler(NamedServer)
+        .oneshot(
+            common::echo_request()
+                .body(common::echo_body("a-key-longer-than-the-limit"))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), http::StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(
+        common::json_body(response).await["error"]["code"],
+        "RESOURCE_EXHAUSTED"
+    );
+}
+
+#[tokio::test]
+async fn test_error_codes_are_descriptions_by_default() {
+    let response = keep_first::echo_handler(DescribedServer)
+        .oneshot(invalid_request())
+        .await
+        .unwrap();
+    assert_eq!(
+        common::json_body(response).await["error"]["code"],
+        "Client specified an invalid argument"
+    );
+}
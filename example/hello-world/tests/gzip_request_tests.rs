@@ -0,0 +1,70 @@
+/// Tests for the gzip request bodies accepted with `with_gzip_requests`
+///
+/// build.rs generates the echo service accepting gzip bodies of up to 64 bytes once
+/// decompressed.
+use std::io::Write;
+
+use tower::ServiceExt;
+
+#[macro_use]
+mod common;
+
+mod gzip {
+    include!(concat!(env!("OUT_DIR"), "/gzip/echo.rs"));
+}
+
+echo_server!(GzipServer, gzip);
+
+fn gzipped(data: &[u8]) -> Vec<u8> {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+async fn send_gzip(body: Vec<u8>) -> axum::response::Response {
+    let request = common::echo_request()
+        .header("Content-Encoding", "gzip")
+        .header("x-user", "alice")
+        .body(axum::body::Body::from(body))
+        .unwrap();
+    gzip::echo_handler(GzipServer)
+        .oneshot(request)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_gzip_bodies_are_decompressed() {
+    let response = send_gzip(gzipped(br#"{"key":"x-user"}"#)).await;
+
+    assert_eq!(response.status(), http::StatusCode::OK);
+    assert_eq!(
+        common::json_body(response).await["values"],
+        serde_json::json!(["alice"])
+    );
+}
+
+#[tokio::test]
+async fn test_corrupt_gzip_bodies_get_400() {
+    let response = send_gzip(b"not gzip at all".to_vec()).await;
+
+    assert_eq!(response.status(), http::StatusCode::BAD_REQUEST);
+    assert_eq!(
+        common::json_body(response).await["error"]["code"],
+        tonic::Code::InvalidArgument.to_string().as_str()
+    );
+}
+
+#[tokio::test]
+async fn test_decompressed_size_is_bounded_by_the_body_limit() {
+    let key = "x".repeat(200);
+    let body = gzipped(serde_json::json!({ "key": key }).to_string().as_bytes());
+    assert!(body.len() < 64, "the compressed body should fit the limit");
+    let response = send_gzip(body).await;
+
+    assert_eq!(response.status(), http::StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(
+        common::json_body(response).await["error"]["code"],
+        tonic::Code::ResourceExhausted.to_string().as_str()
+    );
+}
@@ -0,0 +1,47 @@
+/// Tests for the handlers replaced with `with_handler_template`
+///
+/// build.rs generates the echo service with a template wrapping the built-in handler and
+/// tagging its responses.
+use tower::ServiceExt;
+
+#[macro_use]
+mod common;
+
+mod handler_template {
+    include!(concat!(env!("OUT_DIR"), "/handler_template/echo.rs"));
+}
+
+echo_server!(EchoServer, handler_template);
+
+#[tokio::test]
+async fn test_templated_handlers_serve_the_route() {
+    let response = handler_template::echo_handler(EchoServer)
+        .oneshot(
+            common::echo_request()
+                .header("x-key", "value")
+                .body(common::echo_body("x-key"))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), http::StatusCode::OK);
+    assert_eq!(response.headers()["x-handler-template"], "echo");
+    let body = common::json_body(response).await;
+    assert_eq!(body["values"], serde_json::json!(["value"]));
+}
+
+#[tokio::test]
+async fn test_templated_handlers_keep_the_builtin_errors() {
+    let response = handler_template::echo_handler(EchoServer)
+        .oneshot(
+            common::echo_request()
+                .body(axum::body::Body::from("not json"))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), http::StatusCode::BAD_REQUEST);
+    assert_eq!(response.headers()["x-handler-template"], "echo");
+}
@@ -0,0 +1,56 @@
+/// Tests for the duplicate header policies of the generated routers
+///
+/// build.rs generates the echo service once per policy, each echoing the request
+/// metadata values of a key back in its reply.
+use tower::ServiceExt;
+
+#[macro_use]
+mod common;
+
+mod keep_first {
+    include!(concat!(env!("OUT_DIR"), "/keep_first/echo.rs"));
+}
+
+mod join {
+    include!(concat!(env!("OUT_DIR"), "/join/echo.rs"));
+}
+
+echo_server!(KeepFirstServer, keep_first);
+echo_server!(JoinServer, join);
+
+// Values of the metadata `key` seen by the service, for a request repeating `x-tag` and
+// `x-trace-bin`
+async fn echoed(router: axum::Router, key: &str) -> serde_json::Value {
+    let request = common::echo_request()
+        .header("x-tag", "first")
+        .header("x-tag", "second")
+        .header("x-trace-bin", "AQI=")
+        .header("x-trace-bin", "AwQ=")
+        .body(common::echo_body(key))
+        .unwrap();
+
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), http::StatusCode::OK);
+    let body = common::json_body(response).await;
+    body["values"].clone()
+}
+
+#[tokio::test]
+async fn test_keep_first_policy_drops_later_values() {
+    let router = keep_first::echo_handler(KeepFirstServer);
+
+    assert_eq!(echoed(router, "x-tag").await, serde_json::json!(["first"]));
+}
+
+#[tokio::test]
+async fn test_join_policy_joins_values_except_binary_ones() {
+    let router = join::echo_handler(JoinServer);
+
+    assert_eq!(
+        echoed(router.clone(), "x-tag").await,
+        serde_json::json!(["first, second"])
+    );
+    // Binary values can't be joined, so every one of them is kept
+    let binary = echoed(router, "x-trace-bin").await;
+    assert_eq!(binary.as_array().unwrap().len(), 2);
+}
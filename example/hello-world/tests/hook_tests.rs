@@ -0,0 +1,44 @@
+/// Tests for the code `with_hook` injects into the generated handlers
+///
+/// build.rs generates the echo service with a hook rejecting the `forbidden` key before the
+/// call and naming the method in a response header after it.
+use tower::ServiceExt;
+
+#[macro_use]
+mod common;
+
+mod hooks {
+    include!(concat!(env!("OUT_DIR"), "/hooks/echo.rs"));
+}
+
+echo_server!(EchoServer, hooks);
+
+async fn call(key: &str) -> axum::response::Response {
+    hooks::echo_handler(EchoServer)
+        .oneshot(
+            common::echo_request()
+                .header("x-key", "value")
+                .body(common::echo_body(key))
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_after_call_hooks_edit_the_response() {
+    let response = call("x-key").await;
+
+    assert_eq!(response.status(), http::StatusCode::OK);
+    assert_eq!(response.headers()["x-hooked-method"], "Echo");
+    let body = common::json_body(response).await;
+    assert_eq!(body["values"], serde_json::json!(["value"]));
+}
+
+#[tokio::test]
+async fn test_before_call_hooks_answer_without_calling_the_service() {
+    let response = call("forbidden").await;
+
+    assert_eq!(response.status(), http::StatusCode::FORBIDDEN);
+    assert!(response.headers().get("x-hooked-method").is_none());
+}
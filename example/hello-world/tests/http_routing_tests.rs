@@ -0,0 +1,1257 @@
+/// Tests for the HTTP behaviour of the generated Axum routers
+///
+/// These tests drive the generated router directly with `oneshot` requests to
+/// verify routing-level responses that never reach the service implementation.
+use tower::ServiceExt;
+
+mod common;
+
+mod hello_world {
+    tonic::include_proto!("hello_world");
+}
+
+struct Server;
+
+#[tonic::async_trait]
+impl hello_world::greeter_server::Greeter for Server {
+    async fn say_hello(
+        &self,
+        request: tonic::Request<hello_world::HelloRequest>,
+    ) -> Result<tonic::Response<hello_world::HelloReply>, tonic::Status> {
+        // Echo `x-echo` metadata back so header conversion can be observed
+        let echoed = request
+            .metadata()
+            .get_all("x-echo")
+            .iter()
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let reply = hello_world::HelloReply {
+            message: format!("Hello {}!", request.into_inner().name),
+            status: 0,
+        };
+        let mut response = tonic::Response::new(reply);
+        for value in echoed {
+            response.metadata_mut().append("x-echo", value);
+        }
+        Ok(response)
+    }
+}
+
+#[tokio::test]
+async fn test_wrong_method_returns_405_with_allow_header() {
+    let router = hello_world::greeter_handler(Server);
+
+    let request = http::Request::builder()
+        .method("GET")
+        .uri("/hello_world.Greeter/SayHello")
+        .body(axum::body::Body::empty())
+        .unwrap();
+
+    let response = router.oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), http::StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(response.headers()[http::header::ALLOW], "POST");
+
+    let json = common::json_body(response).await;
+    assert_eq!(json["error"]["code"], "METHOD_NOT_ALLOWED");
+}
+
+#[tokio::test]
+async fn test_post_still_reaches_service() {
+    let router = hello_world::greeter_handler(Server);
+
+    let request = http::Request::builder()
+        .method("POST")
+        .uri("/hello_world.Greeter/SayHello")
+        .header("Content-Type", "application/json")
+        .body(axum::body::Body::from(r#"{"name":"World"}"#))
+        .unwrap();
+
+    let response = router.oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), http::StatusCode::OK);
+    assert_eq!(common::json_body(response).await["message"], "Hello World!");
+}
+
+#[tokio::test]
+async fn test_invalid_binary_metadata_is_rejected() {
+    let router = hello_world::greeter_handler(Server);
+
+    let request = http::Request::builder()
+        .method("POST")
+        .uri("/hello_world.Greeter/SayHello")
+        .header("Content-Type", "application/json")
+        .header("x-trace-bin", "not base64!")
+        .body(axum::body::Body::from(r#"{"name":"World"}"#))
+        .unwrap();
+
+    let response = router.oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), http::StatusCode::BAD_REQUEST);
+    assert_eq!(
+        common::json_body(response).await["error"]["code"],
+        tonic::Code::InvalidArgument.to_string().as_str()
+    );
+}
+
+#[tokio::test]
+async fn test_duplicate_headers_are_kept_in_both_directions() {
+    let router = hello_world::greeter_handler(Server);
+
+    let request = http::Request::builder()
+        .method("POST")
+        .uri("/hello_world.Greeter/SayHello")
+        .header("Content-Type", "application/json")
+        .header("x-echo", "first")
+        .header("x-echo", "second")
+        .body(axum::body::Body::from(r#"{"name":"World"}"#))
+        .unwrap();
+
+    let response = router.oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), http::StatusCode::OK);
+    let echoed = response
+        .headers()
+        .get_all("x-echo")
+        .iter()
+        .map(|value| value.to_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(echoed, vec!["first", "second"]);
+}
+
+#[tokio::test]
+async fn test_http_transport_calls_bridge_from_tonic_client() {
+    let router = hello_world::greeter_handler(Server);
+    let transport =
+        hello_world::GreeterHttpTransport::new(router, "http://localhost".parse().unwrap());
+    let mut client = hello_world::greeter_client::GreeterClient::new(transport);
+
+    let mut request = tonic::Request::new(hello_world::HelloRequest {
+        name: "World".to_string(),
+        ..Default::default()
+    });
+    request
+        .metadata_mut()
+        .insert("x-echo", "over-http".parse().unwrap());
+
+    let response = client
+        .say_hello(request)
+        .await
+        .expect("Call through the HTTP transport should succeed");
+
+    assert_eq!(response.metadata().get("x-echo").unwrap(), "over-http");
+    assert_eq!(response.into_inner().message, "Hello World!");
+}
+
+#[tokio::test]
+async fn test_handler_with_attaches_layers_per_method() {
+    let router = hello_world::greeter_handler_with(Server, |method_name, route| {
+        assert_eq!(method_name, "SayHello");
+        route.layer(axum::middleware::map_response(
+            |mut response: axum::response::Response| async move {
+                response
+                    .headers_mut()
+                    .insert("x-layered", http::HeaderValue::from_static("yes"));
+                response
+            },
+        ))
+    });
+
+    let request = http::Request::builder()
+        .method("POST")
+        .uri("/hello_world.Greeter/SayHello")
+        .header("Content-Type", "application/json")
+        .body(axum::body::Body::from(r#"{"name":"World"}"#))
+        .unwrap();
+
+    let response = router.oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), http::StatusCode::OK);
+    assert_eq!(response.headers()["x-layered"], "yes");
+}
+
+#[tokio::test]
+async fn test_router_builder_overrides_paths() {
+    let router = hello_world::GreeterRouterBuilder::new(Server)
+        .path("SayHello", "/v1/hello")
+        .build();
+
+    let request = http::Request::builder()
+        .method("POST")
+        .uri("/v1/hello")
+        .header("Content-Type", "application/json")
+        .body(axum::body::Body::from(r#"{"name":"World"}"#))
+        .unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), http::StatusCode::OK);
+
+    let request = http::Request::builder()
+        .method("POST")
+        .uri("/hello_world.Greeter/SayHello")
+        .header("Content-Type", "application/json")
+        .body(axum::body::Body::from(r#"{"name":"World"}"#))
+        .unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), http::StatusCode::NOT_FOUND);
+}
+
+#[test]
+fn test_routes_describe_greeter_methods() {
+    let routes = hello_world::greeter_routes();
+    assert_eq!(routes.len(), 1);
+    assert_eq!(routes[0].path, "/hello_world.Greeter/SayHello");
+    assert_eq!(routes[0].http_method, "POST");
+    assert_eq!(routes[0].method_name, "SayHello");
+    assert_eq!(routes[0].request_type, "hello_world.HelloRequest");
+    assert_eq!(routes[0].response_type, "hello_world.HelloReply");
+    assert_eq!(routes[0].streaming, hello_world::RouteStreaming::Unary);
+}
+
+#[test]
+fn test_path_constants_match_routes() {
+    assert_eq!(
+        hello_world::greeter_paths::SERVICE_NAME,
+        "hello_world.Greeter"
+    );
+    assert_eq!(
+        hello_world::greeter_paths::SAY_HELLO,
+        "/hello_world.Greeter/SayHello"
+    );
+    assert_eq!(
+        hello_world::greeter_paths::SAY_HELLO_ROUTE,
+        hello_world::greeter_routes()[0].path
+    );
+}
+
+#[tokio::test]
+async fn test_access_logger_receives_redacted_bodies() {
+    let entries = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let logger = {
+        let entries = entries.clone();
+        hello_world::AccessLogger::new(move |entry| entries.lock().unwrap().push(entry.clone()))
+            .with_bodies()
+            .redact(|_, body| {
+                if let Some(name) = body.get_mut("name") {
+                    *name = "****".into();
+                }
+            })
+    };
+    let router = hello_world::greeter_handler(Server).layer(axum::Extension(logger));
+
+    let request = http::Request::builder()
+        .method("POST")
+        .uri("/hello_world.Greeter/SayHello")
+        .header("Content-Type", "application/json")
+        .body(axum::body::Body::from(r#"{"name":"Secret"}"#))
+        .unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), http::StatusCode::OK);
+
+    let entries = entries.lock().unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].path, "/hello_world.Greeter/SayHello");
+    assert_eq!(entries[0].method, "SayHello");
+    assert_eq!(entries[0].status, http::StatusCode::OK);
+    assert_eq!(entries[0].request.as_ref().unwrap()["name"], "****");
+    assert_eq!(
+        entries[0].response.as_ref().unwrap()["message"],
+        "Hello Secret!"
+    );
+}
+
+#[tokio::test]
+async fn test_access_logger_masks_sensitive_fields() {
+    let entries = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let logger = {
+        let entries = entries.clone();
+        hello_world::AccessLogger::new(move |entry| entries.lock().unwrap().push(entry.clone()))
+            .with_bodies()
+    };
+    let router = hello_world::payment_connector_handler(Payments).layer(axum::Extension(logger));
+
+    let request = http::Request::builder()
+        .method("POST")
+        .uri("/hello_world.PaymentConnector/ProcessPayment")
+        .header("Content-Type", "application/json")
+        .body(axum::body::Body::from(
+            serde_json::json!({
+                "order_id": "order_1",
+                "receipt": "",
+                "amount": 10.0,
+                "currency": "USD",
+                "customer_id": "customer_1",
+                "payment_method": "card",
+                "card_number": "4111111111111111",
+                "saved_cards": {"primary": {"last_four": "1111", "cvv": "123"}}
+            })
+            .to_string(),
+        ))
+        .unwrap();
+    router.oneshot(request).await.unwrap();
+
+    let entries = entries.lock().unwrap();
+    let logged = entries[0].request.as_ref().unwrap();
+    assert_eq!(logged["order_id"], "order_1");
+    assert_eq!(logged["card_number"], "****");
+    // Values of map fields are masked like nested messages
+    assert_eq!(logged["saved_cards"]["primary"]["last_four"], "1111");
+    assert_eq!(logged["saved_cards"]["primary"]["cvv"], "****");
+}
+
+#[tokio::test]
+async fn test_parse_errors_of_sensitive_requests_are_withheld() {
+    let router = hello_world::payment_connector_handler(Payments);
+
+    let request = http::Request::builder()
+        .method("POST")
+        .uri("/hello_world.PaymentConnector/ProcessPayment")
+        .header("Content-Type", "application/json")
+        .body(axum::body::Body::from(
+            r#"{"card_number":4111111111111111}"#,
+        ))
+        .unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), http::StatusCode::UNPROCESSABLE_ENTITY);
+
+    let body = common::json_body(response).await;
+    assert!(!body.to_string().contains("4111111111111111"));
+    assert!(body["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("details are withheld"));
+}
+
+#[test]
+fn test_metrics_count_rejected_requests() {
+    let recorder = metrics_util::debugging::DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+
+    let request = http::Request::builder()
+        .method("POST")
+        .uri("/hello_world.Greeter/SayHello")
+        .header("Content-Type", "application/json")
+        .body(axum::body::Body::from(r#"{"name":"#))
+        .unwrap();
+    // The recorder is local to this thread, which runs the whole request
+    let response = metrics::with_local_recorder(&recorder, || {
+        runtime.block_on(hello_world::greeter_handler(Server).oneshot(request))
+    })
+    .unwrap();
+    assert_eq!(response.status(), http::StatusCode::BAD_REQUEST);
+
+    let counted = snapshotter
+        .snapshot()
+        .into_vec()
+        .into_iter()
+        .any(|(key, _, _, value)| {
+            key.key().name() == "g2h_http_requests_total"
+                && key
+                    .key()
+                    .labels()
+                    .any(|label| label.key() == "status" && label.value() == "400")
+                && matches!(value, metrics_util::debugging::DebugValue::Counter(1))
+        });
+    assert!(counted, "The malformed request should be counted");
+}
+
+// Subscriber keeping the fields of every span, in creation order
+#[derive(Clone, Default)]
+struct SpanRecorder {
+    spans: std::sync::Arc<std::sync::Mutex<Vec<std::collections::BTreeMap<String, String>>>>,
+}
+
+struct SpanFields<'a>(&'a mut std::collections::BTreeMap<String, String>);
+
+impl tracing::field::Visit for SpanFields<'_> {
+    fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), format!("{value:?}"));
+    }
+}
+
+impl tracing::Subscriber for SpanRecorder {
+    fn enabled(&self, _metadata: &tracing::Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, attributes: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+        let mut fields = std::collections::BTreeMap::new();
+        fields.insert("name".to_string(), attributes.metadata().name().to_string());
+        attributes.record(&mut SpanFields(&mut fields));
+        let mut spans = self.spans.lock().unwrap();
+        spans.push(fields);
+        tracing::span::Id::from_u64(spans.len() as u64)
+    }
+
+    fn record(&self, span: &tracing::span::Id, values: &tracing::span::Record<'_>) {
+        let mut spans = self.spans.lock().unwrap();
+        let index = span.into_u64() as usize - 1;
+        values.record(&mut SpanFields(&mut spans[index]));
+    }
+
+    fn record_follows_from(&self, _span: &tracing::span::Id, _follows: &tracing::span::Id) {}
+
+    fn event(&self, _event: &tracing::Event<'_>) {}
+
+    fn enter(&self, _span: &tracing::span::Id) {}
+
+    fn exit(&self, _span: &tracing::span::Id) {}
+}
+
+// The `g2h.handler` span of a request to `SayHello` with the JSON `body`
+fn handler_span(body: &'static str) -> std::collections::BTreeMap<String, String> {
+    let recorder = SpanRecorder::default();
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    let request = http::Request::builder()
+        .method("POST")
+        .uri("/hello_world.Greeter/SayHello")
+        .header("Content-Type", "application/json")
+        .body(axum::body::Body::from(body))
+        .unwrap();
+
+    // The subscriber is local to this thread, which runs the whole request
+    tracing::subscriber::with_default(recorder.clone(), || {
+        runtime.block_on(hello_world::greeter_handler(Server).oneshot(request))
+    })
+    .unwrap();
+
+    let spans = recorder.spans.lock().unwrap();
+    let handler_spans = spans
+        .iter()
+        .filter(|span| span["name"] == "g2h.handler")
+        .collect::<Vec<_>>();
+    assert_eq!(handler_spans.len(), 1, "unexpected spans {spans:?}");
+    handler_spans[0].clone()
+}
+
+#[test]
+fn test_tracing_spans_record_the_call() {
+    let span = handler_span(r#"{"name":"Tonic"}"#);
+
+    assert_eq!(span["service"], "hello_world.Greeter");
+    assert_eq!(span["method"], "SayHello");
+    assert_eq!(span["grpc.code"], "0");
+    assert!(span["latency_ms"].parse::<f64>().unwrap() >= 0.0);
+}
+
+#[test]
+fn test_tracing_spans_record_rejected_requests() {
+    let span = handler_span(r#"{"name":"#);
+
+    assert_eq!(span["method"], "SayHello");
+    // `INVALID_ARGUMENT`, the code of the `400` answering the malformed body
+    assert_eq!(span["grpc.code"], "3");
+    assert!(span.contains_key("latency_ms"));
+}
+
+#[tokio::test]
+async fn test_access_logger_receives_rejected_requests() {
+    let entries = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let logger = {
+        let entries = entries.clone();
+        hello_world::AccessLogger::new(move |entry| entries.lock().unwrap().push(entry.clone()))
+    };
+    let router = hello_world::greeter_handler(Server).layer(axum::Extension(logger));
+
+    let request = http::Request::builder()
+        .method("POST")
+        .uri("/hello_world.Greeter/SayHello")
+        .header("Content-Type", "application/json")
+        .body(axum::body::Body::from(r#"{"name":"#))
+        .unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), http::StatusCode::BAD_REQUEST);
+
+    let entries = entries.lock().unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].method, "SayHello");
+    assert_eq!(entries[0].status, http::StatusCode::BAD_REQUEST);
+}
+
+struct PanickingServer;
+
+#[tonic::async_trait]
+impl hello_world::greeter_server::Greeter for PanickingServer {
+    async fn say_hello(
+        &self,
+        _request: tonic::Request<hello_world::HelloRequest>,
+    ) -> Result<tonic::Response<hello_world::HelloReply>, tonic::Status> {
+        panic!("boom")
+    }
+}
+
+#[tokio::test]
+async fn test_panicking_service_returns_internal_error() {
+    let router = hello_world::greeter_handler(PanickingServer);
+
+    let request = http::Request::builder()
+        .method("POST")
+        .uri("/hello_world.Greeter/SayHello")
+        .header("Content-Type", "application/json")
+        .body(axum::body::Body::from(r#"{"name":"World"}"#))
+        .unwrap();
+    let response = router.oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), http::StatusCode::INTERNAL_SERVER_ERROR);
+    let body = common::json_body(response).await;
+    assert_eq!(
+        body["error"]["code"],
+        tonic::Code::Internal.to_string().as_str()
+    );
+}
+
+#[tokio::test]
+async fn test_handler_arc_shares_service_instance() {
+    let server = std::sync::Arc::new(Server);
+    let _grpc = hello_world::greeter_server::GreeterServer::from_arc(server.clone());
+    let router = hello_world::greeter_handler_arc(server);
+
+    let request = http::Request::builder()
+        .method("POST")
+        .uri("/hello_world.Greeter/SayHello")
+        .header("Content-Type", "application/json")
+        .body(axum::body::Body::from(r#"{"name":"World"}"#))
+        .unwrap();
+    let response = router.oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), http::StatusCode::OK);
+    assert_eq!(common::json_body(response).await["message"], "Hello World!");
+}
+
+#[tokio::test]
+async fn test_handler_dyn_serves_trait_object() {
+    let server: std::sync::Arc<dyn hello_world::greeter_server::Greeter> =
+        std::sync::Arc::new(Server);
+    let router = hello_world::greeter_handler_dyn(server);
+
+    let request = http::Request::builder()
+        .method("POST")
+        .uri("/hello_world.Greeter/SayHello")
+        .header("Content-Type", "application/json")
+        .body(axum::body::Body::from(r#"{"name":"World"}"#))
+        .unwrap();
+    let response = router.oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), http::StatusCode::OK);
+    assert_eq!(common::json_body(response).await["message"], "Hello World!");
+}
+
+#[tokio::test]
+async fn test_named_handler_can_be_mounted_directly() {
+    let router = axum::Router::new()
+        .route(
+            "/hello",
+            axum::routing::post(hello_world::greeter_say_hello_http_handler::<Server>),
+        )
+        .with_state(std::sync::Arc::new(Server));
+
+    let request = http::Request::builder()
+        .method("POST")
+        .uri("/hello")
+        .header("Content-Type", "application/json")
+        .body(axum::body::Body::from(r#"{"name":"World"}"#))
+        .unwrap();
+    let response = router.oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), http::StatusCode::OK);
+    assert_eq!(common::json_body(response).await["message"], "Hello World!");
+}
+
+#[tokio::test]
+async fn test_generated_test_client_calls_router() {
+    let client = hello_world::greeter_test_client(hello_world::greeter_handler(Server)).header(
+        http::header::HeaderName::from_static("x-echo"),
+        http::HeaderValue::from_static("ping"),
+    );
+
+    let reply = client
+        .say_hello(hello_world::HelloRequest {
+            name: "World".to_string(),
+            ..Default::default()
+        })
+        .await
+        .expect("The call should succeed");
+
+    assert_eq!(reply.message, "Hello World!");
+}
+
+#[tokio::test]
+async fn test_generated_test_client_returns_error_response() {
+    let router = axum::Router::new();
+    let client = hello_world::greeter_test_client(router);
+
+    let err = client
+        .say_hello(hello_world::HelloRequest::default())
+        .await
+        .expect_err("An unrouted call should fail");
+
+    assert_eq!(err.error.code, "404");
+}
+
+#[tokio::test]
+async fn test_repeated_idempotency_key_replays_response() {
+    let storage =
+        hello_world::IdempotencyStorage::new(hello_world::InMemoryIdempotencyStore::default());
+    let router = hello_world::greeter_handler(Server).layer(axum::Extension(storage));
+
+    let request = |name: &str| {
+        http::Request::builder()
+            .method("POST")
+            .uri("/hello_world.Greeter/SayHello")
+            .header("Content-Type", "application/json")
+            .header("Idempotency-Key", "key-1")
+            .body(axum::body::Body::from(format!(r#"{{"name":"{}"}}"#, name)))
+            .unwrap()
+    };
+
+    let first = router.clone().oneshot(request("World")).await.unwrap();
+    assert_eq!(first.status(), http::StatusCode::OK);
+    assert!(first.headers().get("idempotent-replayed").is_none());
+    assert_eq!(common::json_body(first).await["message"], "Hello World!");
+
+    let second = router.clone().oneshot(request("World")).await.unwrap();
+    assert_eq!(second.status(), http::StatusCode::OK);
+    assert_eq!(second.headers()["idempotent-replayed"], "true");
+    assert_eq!(common::json_body(second).await["message"], "Hello World!");
+
+    // The key was used with another request message
+    let conflict = router.oneshot(request("Again")).await.unwrap();
+    assert_eq!(conflict.status(), http::StatusCode::UNPROCESSABLE_ENTITY);
+    assert!(conflict.headers().get("idempotent-replayed").is_none());
+    assert_eq!(
+        common::json_body(conflict).await["error"]["code"],
+        tonic::Code::InvalidArgument.to_string().as_str()
+    );
+}
+
+#[tokio::test]
+async fn test_idempotency_key_in_progress_gets_409() {
+    use hello_world::IdempotencyStore;
+
+    let store = std::sync::Arc::new(hello_world::InMemoryIdempotencyStore::default());
+    let storage = hello_world::IdempotencyStorage(store.clone());
+    let router = hello_world::greeter_handler(Server).layer(axum::Extension(storage));
+    let request = || {
+        http::Request::builder()
+            .method("POST")
+            .uri("/hello_world.Greeter/SayHello")
+            .header("Content-Type", "application/json")
+            .header("Idempotency-Key", "key-1")
+            .body(axum::body::Body::from(r#"{"name":"World"}"#))
+            .unwrap()
+    };
+
+    // Another request holds the key
+    let metadata = tonic::metadata::MetadataMap::new();
+    let path = "/hello_world.Greeter/SayHello";
+    assert!(matches!(
+        store.reserve(path, "key-1", &metadata).await,
+        hello_world::IdempotencyReservation::Reserved
+    ));
+
+    let pending = router.clone().oneshot(request()).await.unwrap();
+    assert_eq!(pending.status(), http::StatusCode::CONFLICT);
+    assert_eq!(
+        common::json_body(pending).await["error"]["code"],
+        tonic::Code::Aborted.to_string().as_str()
+    );
+
+    // Once released, the key is free again
+    store.release(path, "key-1", &metadata);
+    let response = router.oneshot(request()).await.unwrap();
+    assert_eq!(response.status(), http::StatusCode::OK);
+    assert!(response.headers().get("idempotent-replayed").is_none());
+}
+
+/// Scopes idempotency keys to the `x-tenant` of the caller
+#[derive(Default)]
+struct TenantIdempotencyStore(hello_world::InMemoryIdempotencyStore);
+
+#[tonic::async_trait]
+impl hello_world::IdempotencyStore for TenantIdempotencyStore {
+    async fn reserve(
+        &self,
+        path: &str,
+        key: &str,
+        metadata: &tonic::metadata::MetadataMap,
+    ) -> hello_world::IdempotencyReservation {
+        let key = tenant_key(key, metadata);
+        hello_world::IdempotencyStore::reserve(&self.0, path, &key, metadata).await
+    }
+
+    async fn put(
+        &self,
+        path: &str,
+        key: &str,
+        metadata: &tonic::metadata::MetadataMap,
+        response: hello_world::StoredResponse,
+    ) {
+        let key = tenant_key(key, metadata);
+        hello_world::IdempotencyStore::put(&self.0, path, &key, metadata, response).await
+    }
+
+    fn release(&self, path: &str, key: &str, metadata: &tonic::metadata::MetadataMap) {
+        let key = tenant_key(key, metadata);
+        hello_world::IdempotencyStore::release(&self.0, path, &key, metadata)
+    }
+}
+
+fn tenant_key(key: &str, metadata: &tonic::metadata::MetadataMap) -> String {
+    let tenant = metadata
+        .get("x-tenant")
+        .and_then(|tenant| tenant.to_str().ok());
+    format!("{}/{}", tenant.unwrap_or_default(), key)
+}
+
+#[tokio::test]
+async fn test_idempotency_store_sees_caller_metadata() {
+    let storage = hello_world::IdempotencyStorage::new(TenantIdempotencyStore::default());
+    let router = hello_world::greeter_handler(Server).layer(axum::Extension(storage));
+
+    let request = |tenant: &str, name: &str| {
+        http::Request::builder()
+            .method("POST")
+            .uri("/hello_world.Greeter/SayHello")
+            .header("Content-Type", "application/json")
+            .header("Idempotency-Key", "key-1")
+            .header("x-tenant", tenant)
+            .body(axum::body::Body::from(format!(r#"{{"name":"{}"}}"#, name)))
+            .unwrap()
+    };
+
+    let first = router
+        .clone()
+        .oneshot(request("acme", "World"))
+        .await
+        .unwrap();
+    assert_eq!(first.status(), http::StatusCode::OK);
+
+    // Another tenant reuses the key for its own request
+    let other = router.oneshot(request("globex", "Again")).await.unwrap();
+    assert_eq!(other.status(), http::StatusCode::OK);
+    assert!(other.headers().get("idempotent-replayed").is_none());
+    assert_eq!(common::json_body(other).await["message"], "Hello Again!");
+}
+
+struct ExhaustedQuota;
+
+#[tonic::async_trait]
+impl hello_world::RateLimiter for ExhaustedQuota {
+    async fn check(
+        &self,
+        path: &str,
+        _metadata: &tonic::metadata::MetadataMap,
+    ) -> Result<(), hello_world::RateLimited> {
+        assert_eq!(path, "/hello_world.Greeter/SayHello");
+        Err(hello_world::RateLimited::retry_after(
+            std::time::Duration::from_millis(1500),
+        ))
+    }
+}
+
+#[tokio::test]
+async fn test_rate_limited_request_gets_retry_after() {
+    let limiting = hello_world::RateLimiting::new(ExhaustedQuota);
+    let router = hello_world::greeter_handler(Server).layer(axum::Extension(limiting));
+
+    let request = http::Request::builder()
+        .method("POST")
+        .uri("/hello_world.Greeter/SayHello")
+        .header("Content-Type", "application/json")
+        .body(axum::body::Body::from(r#"{"name":"World"}"#))
+        .unwrap();
+
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), http::StatusCode::TOO_MANY_REQUESTS);
+    // Retry delays are rounded up to whole seconds
+    assert_eq!(response.headers()["retry-after"], "2");
+    assert_eq!(
+        common::json_body(response).await["error"]["code"],
+        tonic::Code::ResourceExhausted.to_string().as_str()
+    );
+}
+
+#[tokio::test]
+async fn test_interceptor_runs_on_http_requests() {
+    let interceptor = |mut request: tonic::Request<()>| {
+        if request.metadata().get("x-tenant").is_none() {
+            return Err(tonic::Status::unauthenticated("Missing tenant"));
+        }
+        request
+            .metadata_mut()
+            .insert("x-echo", "intercepted".parse().unwrap());
+        Ok(request)
+    };
+    let router = hello_world::greeter_handler_with_interceptor(Server, interceptor);
+
+    let request = |tenant: Option<&str>| {
+        let builder = http::Request::builder()
+            .method("POST")
+            .uri("/hello_world.Greeter/SayHello")
+            .header("Content-Type", "application/json");
+        let builder = match tenant {
+            Some(tenant) => builder.header("x-tenant", tenant),
+            None => builder,
+        };
+        builder
+            .body(axum::body::Body::from(r#"{"name":"World"}"#))
+            .unwrap()
+    };
+
+    let rejected = router.clone().oneshot(request(None)).await.unwrap();
+    assert_eq!(rejected.status(), http::StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        common::json_body(rejected).await["error"]["code"],
+        tonic::Code::Unauthenticated.to_string().as_str()
+    );
+
+    // Metadata added by the interceptor reaches the service
+    let accepted = router.oneshot(request(Some("acme"))).await.unwrap();
+    assert_eq!(accepted.status(), http::StatusCode::OK);
+    assert_eq!(accepted.headers()["x-echo"], "intercepted");
+}
+
+#[tokio::test]
+async fn test_interceptor_rejection_blocks_idempotent_replay() {
+    let interceptor = |request: tonic::Request<()>| match request.metadata().get("x-tenant") {
+        Some(_) => Ok(request),
+        None => Err(tonic::Status::unauthenticated("Missing tenant")),
+    };
+    let storage =
+        hello_world::IdempotencyStorage::new(hello_world::InMemoryIdempotencyStore::default());
+    let router = hello_world::greeter_handler_with_interceptor(Server, interceptor)
+        .layer(axum::Extension(storage));
+
+    let request = |tenant: Option<&str>| {
+        let builder = http::Request::builder()
+            .method("POST")
+            .uri("/hello_world.Greeter/SayHello")
+            .header("Content-Type", "application/json")
+            .header("Idempotency-Key", "key-1");
+        let builder = match tenant {
+            Some(tenant) => builder.header("x-tenant", tenant),
+            None => builder,
+        };
+        builder
+            .body(axum::body::Body::from(r#"{"name":"World"}"#))
+            .unwrap()
+    };
+
+    let stored = router.clone().oneshot(request(Some("acme"))).await.unwrap();
+    assert_eq!(stored.status(), http::StatusCode::OK);
+
+    // The interceptor runs before the stored response would be replayed
+    let rejected = router.oneshot(request(None)).await.unwrap();
+    assert_eq!(rejected.status(), http::StatusCode::UNAUTHORIZED);
+    assert!(rejected.headers().get("idempotent-replayed").is_none());
+}
+
+struct Shout;
+
+#[tonic::async_trait]
+impl hello_world::MessageHooks for Shout {
+    async fn before_request(&self, _path: &str, request: &mut serde_json::Value) {
+        let name = request["name"].as_str().unwrap_or_default().to_uppercase();
+        request["name"] = name.into();
+    }
+
+    async fn after_response(&self, path: &str, response: &mut serde_json::Value) {
+        assert_eq!(path, "/hello_world.Greeter/SayHello");
+        let message = format!("{} :)", response["message"].as_str().unwrap_or_default());
+        response["message"] = message.into();
+        response["served_by"] = path.into();
+    }
+}
+
+#[tokio::test]
+async fn test_message_hooks_transform_request_and_response() {
+    let router = hello_world::GreeterRouterBuilder::new(Server)
+        .message_hooks(Shout)
+        .build();
+
+    let request = http::Request::builder()
+        .method("POST")
+        .uri("/hello_world.Greeter/SayHello")
+        .header("Content-Type", "application/json")
+        .body(axum::body::Body::from(r#"{"name":"World"}"#))
+        .unwrap();
+
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), http::StatusCode::OK);
+    let body = common::json_body(response).await;
+    assert_eq!(body["message"], "Hello WORLD! :)");
+    // Fields added by response hooks are kept
+    assert_eq!(body["served_by"], "/hello_world.Greeter/SayHello");
+}
+
+#[tokio::test]
+async fn test_options_request_lists_allowed_methods() {
+    let router = hello_world::greeter_handler(Server);
+
+    let request = http::Request::builder()
+        .method("OPTIONS")
+        .uri("/hello_world.Greeter/SayHello")
+        .header("Origin", "https://app.example.com")
+        .header("Access-Control-Request-Method", "POST")
+        .body(axum::body::Body::empty())
+        .unwrap();
+
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), http::StatusCode::NO_CONTENT);
+    assert_eq!(response.headers()[http::header::ALLOW], "POST, OPTIONS");
+    // Without a CORS policy, no origin is allowed
+    assert!(response
+        .headers()
+        .get(http::header::ACCESS_CONTROL_ALLOW_ORIGIN)
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_trailing_slash_reaches_service() {
+    let router = hello_world::greeter_handler(Server);
+
+    let request = http::Request::builder()
+        .method("POST")
+        .uri("/hello_world.Greeter/SayHello/")
+        .header("Content-Type", "application/json")
+        .body(axum::body::Body::from(r#"{"name":"World"}"#))
+        .unwrap();
+
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), http::StatusCode::OK);
+    assert_eq!(common::json_body(response).await["message"], "Hello World!");
+}
+
+#[tokio::test]
+async fn test_content_type_charset_is_checked() {
+    let router = hello_world::greeter_handler(Server);
+
+    let request = |content_type: &str| {
+        http::Request::builder()
+            .method("POST")
+            .uri("/hello_world.Greeter/SayHello")
+            .header("Content-Type", content_type)
+            .body(axum::body::Body::from(r#"{"name":"World"}"#))
+            .unwrap()
+    };
+
+    let accepted = router
+        .clone()
+        .oneshot(request("application/json; charset=UTF-8"))
+        .await
+        .unwrap();
+    assert_eq!(accepted.status(), http::StatusCode::OK);
+
+    let rejected = router
+        .oneshot(request("application/json; charset=iso-8859-1"))
+        .await
+        .unwrap();
+    assert_eq!(rejected.status(), http::StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    assert_eq!(
+        common::json_body(rejected).await["error"]["code"],
+        tonic::Code::InvalidArgument.to_string().as_str()
+    );
+}
+
+struct Payments;
+
+#[tonic::async_trait]
+impl hello_world::payment_connector_server::PaymentConnector for Payments {
+    async fn process_payment(
+        &self,
+        _request: tonic::Request<hello_world::PaymentRequest>,
+    ) -> Result<tonic::Response<hello_world::PaymentResponse>, tonic::Status> {
+        Err(tonic::Status::unimplemented("Not used by these tests"))
+    }
+
+    async fn get_payment_status(
+        &self,
+        request: tonic::Request<hello_world::StatusRequest>,
+    ) -> Result<tonic::Response<hello_world::PaymentResponse>, tonic::Status> {
+        let request = request.into_inner();
+        Ok(tonic::Response::new(hello_world::PaymentResponse {
+            transaction_id: request.transaction_id,
+            network_txn_id: request.order_id,
+            ..Default::default()
+        }))
+    }
+}
+
+#[tokio::test]
+async fn test_method_verb_override_serves_get_with_etag() {
+    let router = hello_world::payment_connector_handler(Payments);
+
+    let request = |if_none_match: Option<&str>| {
+        let builder = http::Request::builder().method("GET").uri(
+            "/hello_world.PaymentConnector/GetPaymentStatus?transaction_id=txn_1&order_id=order_1",
+        );
+        let builder = match if_none_match {
+            Some(etag) => builder.header(http::header::IF_NONE_MATCH, etag),
+            None => builder,
+        };
+        builder.body(axum::body::Body::empty()).unwrap()
+    };
+
+    // Request fields are decoded from the query string
+    let response = router.clone().oneshot(request(None)).await.unwrap();
+    assert_eq!(response.status(), http::StatusCode::OK);
+    let etag = response.headers()[http::header::ETAG]
+        .to_str()
+        .unwrap()
+        .to_string();
+    let body = common::json_body(response).await;
+    assert_eq!(body["transaction_id"], "txn_1");
+    assert_eq!(body["network_txn_id"], "order_1");
+
+    let response = router.clone().oneshot(request(Some(&etag))).await.unwrap();
+    assert_eq!(response.status(), http::StatusCode::NOT_MODIFIED);
+
+    // The method no longer answers POST
+    let request = http::Request::builder()
+        .method("POST")
+        .uri("/hello_world.PaymentConnector/GetPaymentStatus")
+        .header("Content-Type", "application/json")
+        .body(axum::body::Body::from("{}"))
+        .unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), http::StatusCode::METHOD_NOT_ALLOWED);
+}
+
+struct NotReady;
+
+#[tonic::async_trait]
+impl hello_world::HttpReadiness for NotReady {
+    async fn is_ready(&self) -> bool {
+        false
+    }
+}
+
+#[tokio::test]
+async fn test_health_router_reports_liveness_and_readiness() {
+    let probe = |router: axum::Router, path: &'static str| async move {
+        let request = http::Request::builder()
+            .method("GET")
+            .uri(path)
+            .body(axum::body::Body::empty())
+            .unwrap();
+        let response = router.oneshot(request).await.unwrap();
+        (response.status(), common::json_body(response).await)
+    };
+
+    let app = hello_world::greeter_handler(Server).merge(hello_world::health_router(()));
+    let (status, body) = probe(app.clone(), "/healthz").await;
+    assert_eq!(status, http::StatusCode::OK);
+    assert_eq!(body["status"], "ok");
+    let (status, body) = probe(app, "/readyz").await;
+    assert_eq!(status, http::StatusCode::OK);
+    assert_eq!(body["status"], "ready");
+
+    let app = hello_world::health_router(NotReady);
+    let (status, _) = probe(app.clone(), "/healthz").await;
+    assert_eq!(status, http::StatusCode::OK);
+    let (status, body) = probe(app, "/readyz").await;
+    assert_eq!(status, http::StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(body["status"], "not_ready");
+}
+
+// Holds `ProcessPayment` calls until released
+struct GatedPayments {
+    entered: std::sync::Arc<tokio::sync::Notify>,
+    release: std::sync::Arc<tokio::sync::Notify>,
+}
+
+#[tonic::async_trait]
+impl hello_world::payment_connector_server::PaymentConnector for GatedPayments {
+    async fn process_payment(
+        &self,
+        _request: tonic::Request<hello_world::PaymentRequest>,
+    ) -> Result<tonic::Response<hello_world::PaymentResponse>, tonic::Status> {
+        self.entered.notify_one();
+        self.release.notified().await;
+        Ok(tonic::Response::new(hello_world::PaymentResponse::default()))
+    }
+
+    async fn get_payment_status(
+        &self,
+        request: tonic::Request<hello_world::StatusRequest>,
+    ) -> Result<tonic::Response<hello_world::PaymentResponse>, tonic::Status> {
+        hello_world::payment_connector_server::PaymentConnector::get_payment_status(
+            &Payments, request,
+        )
+        .await
+    }
+}
+
+#[tokio::test]
+async fn test_method_concurrency_limit_sheds_only_its_method() {
+    let entered = std::sync::Arc::new(tokio::sync::Notify::new());
+    let release = std::sync::Arc::new(tokio::sync::Notify::new());
+    let router = hello_world::payment_connector_handler(GatedPayments {
+        entered: entered.clone(),
+        release: release.clone(),
+    });
+    let process_payment = || {
+        http::Request::builder()
+            .method("POST")
+            .uri("/hello_world.PaymentConnector/ProcessPayment")
+            .header("Content-Type", "application/json")
+            .body(axum::body::Body::from(
+                serde_json::json!({
+                    "order_id": "order_1",
+                    "receipt": "",
+                    "amount": 10.0,
+                    "currency": "USD",
+                    "customer_id": "customer_1",
+                    "payment_method": "card",
+                    "card_number": "",
+                    "saved_cards": {}
+                })
+                .to_string(),
+            ))
+            .unwrap()
+    };
+
+    // The first call holds the single permit of `ProcessPayment`
+    let first = tokio::spawn(router.clone().oneshot(process_payment()));
+    entered.notified().await;
+
+    let response = router.clone().oneshot(process_payment()).await.unwrap();
+    assert_eq!(response.status(), http::StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(
+        common::json_body(response).await["error"]["code"],
+        tonic::Code::Unavailable.to_string().as_str()
+    );
+
+    // Other methods of the service have no limit
+    let request = http::Request::builder()
+        .method("GET")
+        .uri("/hello_world.PaymentConnector/GetPaymentStatus?transaction_id=txn_1&order_id=order_1")
+        .body(axum::body::Body::empty())
+        .unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), http::StatusCode::OK);
+
+    release.notify_one();
+    assert_eq!(first.await.unwrap().unwrap().status(), http::StatusCode::OK);
+
+    // The permit is free again, and the next call is released right away
+    release.notify_one();
+    let response = router.oneshot(process_payment()).await.unwrap();
+    assert_eq!(response.status(), http::StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_slow_request_profiles_follow_method_thresholds() {
+    let profiles = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let observer = {
+        let profiles = profiles.clone();
+        hello_world::SlowRequestObserver::new(move |profile| {
+            profiles.lock().unwrap().push(profile.method)
+        })
+    };
+
+    // Only `SayHello` has a threshold, of zero
+    let request = http::Request::builder()
+        .method("POST")
+        .uri("/hello_world.Greeter/SayHello")
+        .header("Content-Type", "application/json")
+        .body(axum::body::Body::from(r#"{"name":"World"}"#))
+        .unwrap();
+    let response = hello_world::greeter_handler(Server)
+        .layer(axum::Extension(observer.clone()))
+        .oneshot(request)
+        .await
+        .unwrap();
+    assert_eq!(response.status(), http::StatusCode::OK);
+
+    let request = http::Request::builder()
+        .method("GET")
+        .uri("/hello_world.PaymentConnector/GetPaymentStatus?transaction_id=txn_1&order_id=order_1")
+        .body(axum::body::Body::empty())
+        .unwrap();
+    let response = hello_world::payment_connector_handler(Payments)
+        .layer(axum::Extension(observer))
+        .oneshot(request)
+        .await
+        .unwrap();
+    assert_eq!(response.status(), http::StatusCode::OK);
+
+    assert_eq!(*profiles.lock().unwrap(), ["/hello_world.Greeter/SayHello"]);
+}
+
+#[test]
+#[should_panic(expected = "`ProcessPayment` and `GetPaymentStatus` are both served on `/payments`")]
+fn test_router_builder_rejects_paths_shared_by_two_methods() {
+    let _ = hello_world::PaymentConnectorRouterBuilder::new(Payments)
+        .path("GetPaymentStatus", "/payments")
+        .path("ProcessPayment", "/payments")
+        .build();
+}
+
+#[tokio::test]
+async fn test_mock_handler_serves_programmed_responses() {
+    let say_hello = || {
+        http::Request::builder()
+            .method("POST")
+            .uri("/hello_world.Greeter/SayHello")
+            .header("Content-Type", "application/json")
+            .body(axum::body::Body::from(r#"{"name":"World"}"#))
+            .unwrap()
+    };
+
+    let mock = hello_world::GreeterMock::new().on_say_hello(|request| {
+        Ok(hello_world::HelloReply {
+            message: format!("Mocked {}", request.name),
+            status: 0,
+        })
+    });
+    let response = hello_world::greeter_mock_handler(mock)
+        .oneshot(say_hello())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), http::StatusCode::OK);
+    assert_eq!(common::json_body(response).await["message"], "Mocked World");
+
+    let mock = hello_world::GreeterMock::new()
+        .on_say_hello_json(r#"{"message":"From a fixture","status":"ERROR"}"#);
+    let response = hello_world::greeter_mock_handler(mock)
+        .oneshot(say_hello())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), http::StatusCode::OK);
+    let body = common::json_body(response).await;
+    assert_eq!(body["message"], "From a fixture");
+    assert_eq!(body["status"], "ERROR");
+}
+
+#[tokio::test]
+async fn test_mock_handler_answers_unprogrammed_methods_with_unimplemented() {
+    let request = http::Request::builder()
+        .method("POST")
+        .uri("/hello_world.Greeter/SayHello")
+        .header("Content-Type", "application/json")
+        .body(axum::body::Body::from(r#"{"name":"World"}"#))
+        .unwrap();
+    let response = hello_world::greeter_mock_handler(hello_world::GreeterMock::new())
+        .oneshot(request)
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), http::StatusCode::NOT_IMPLEMENTED);
+    assert_eq!(
+        common::json_body(response).await["error"]["code"],
+        tonic::Code::Unimplemented.to_string().as_str()
+    );
+}
@@ -0,0 +1,62 @@
+/// Tests for the lowercase routes of `RoutePathCase::Lowercase` and the generated
+/// `lowercase_request_path` shim
+///
+/// build.rs generates the echo service with lowercase route paths.
+use tower::ServiceExt;
+
+#[macro_use]
+mod common;
+
+mod lowercase {
+    include!(concat!(env!("OUT_DIR"), "/lowercase/echo.rs"));
+}
+
+echo_server!(LowercaseServer, lowercase);
+
+fn echo(path: &str) -> http::Request<axum::body::Body> {
+    common::echo_request()
+        .uri(path)
+        .body(common::echo_body("x-key"))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_routes_are_served_on_lowercase_paths() {
+    let router = lowercase::echo_handler(LowercaseServer);
+
+    let response = router
+        .clone()
+        .oneshot(echo("/echo.echo/echo"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), http::StatusCode::OK);
+
+    let response = router.oneshot(echo("/echo.Echo/Echo")).await.unwrap();
+    assert_eq!(response.status(), http::StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_shim_routes_mixed_case_requests() {
+    let service = tower::ServiceBuilder::new()
+        .map_request(lowercase::lowercase_request_path)
+        .service(lowercase::echo_handler(LowercaseServer));
+
+    for path in ["/echo.Echo/Echo", "/ECHO.ECHO/ECHO", "/echo.echo/echo"] {
+        let response = service.clone().oneshot(echo(path)).await.unwrap();
+        assert_eq!(response.status(), http::StatusCode::OK, "`{path}`");
+    }
+}
+
+#[test]
+fn test_shim_keeps_the_query() {
+    let request = http::Request::builder()
+        .uri("https://api.example.com/Echo.Echo/Echo?Key=Value")
+        .body(())
+        .unwrap();
+
+    let request = lowercase::lowercase_request_path(request);
+    assert_eq!(
+        request.uri(),
+        "https://api.example.com/echo.echo/echo?Key=Value"
+    );
+}
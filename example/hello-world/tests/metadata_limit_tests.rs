@@ -0,0 +1,81 @@
+/// Tests for the metadata limits of `with_max_metadata_size` and `with_max_header_value_len`
+///
+/// build.rs generates the echo service limiting the metadata to 256 bytes in total and
+/// header values to 32 bytes.
+use tower::ServiceExt;
+
+#[macro_use]
+mod common;
+
+mod metadata_limits {
+    include!(concat!(env!("OUT_DIR"), "/metadata_limits/echo.rs"));
+}
+
+echo_server!(LimitedServer, metadata_limits);
+
+async fn call(request: http::Request<axum::body::Body>) -> axum::response::Response {
+    metadata_limits::echo_handler(LimitedServer)
+        .oneshot(request)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_metadata_within_the_limits_is_forwarded() {
+    let request = common::echo_request()
+        .header("x-tenant", "acme")
+        .body(common::echo_body("x-tenant"))
+        .unwrap();
+    let response = call(request).await;
+
+    assert_eq!(response.status(), http::StatusCode::OK);
+    let body = common::json_body(response).await;
+    assert_eq!(body["values"], serde_json::json!(["acme"]));
+}
+
+#[tokio::test]
+async fn test_long_header_values_get_a_json_431() {
+    let request = common::echo_request()
+        .header("x-tenant", "a".repeat(33))
+        .body(common::echo_body("x-tenant"))
+        .unwrap();
+    let response = call(request).await;
+
+    assert_eq!(
+        response.status(),
+        http::StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE
+    );
+    let body = common::json_body(response).await;
+    assert_eq!(
+        body["error"]["code"],
+        tonic::Code::ResourceExhausted.description()
+    );
+    assert_eq!(
+        body["error"]["message"],
+        "Header `x-tenant` exceeds the maximum value length"
+    );
+}
+
+#[tokio::test]
+async fn test_oversized_metadata_gets_a_json_431() {
+    // Every value is within the limit, but not their total size
+    let mut request = common::echo_request();
+    for i in 0..10 {
+        request = request.header(format!("x-tenant-{i}"), "a".repeat(32));
+    }
+    let response = call(request.body(common::echo_body("x-tenant-0")).unwrap()).await;
+
+    assert_eq!(
+        response.status(),
+        http::StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE
+    );
+    let body = common::json_body(response).await;
+    assert_eq!(
+        body["error"]["code"],
+        tonic::Code::ResourceExhausted.description()
+    );
+    assert_eq!(
+        body["error"]["message"],
+        "Request metadata exceeds the maximum total size"
+    );
+}
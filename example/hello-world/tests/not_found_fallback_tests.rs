@@ -0,0 +1,86 @@
+/// Tests for the JSON 404 of `with_not_found_fallback`
+///
+/// build.rs generates the echo service with the fallback listing the known paths, which
+/// the tests attach to the router themselves.
+use tower::ServiceExt;
+
+#[macro_use]
+mod common;
+
+mod not_found {
+    include!(concat!(env!("OUT_DIR"), "/not_found/echo.rs"));
+}
+
+echo_server!(FallbackServer, not_found);
+
+#[tokio::test]
+async fn test_unknown_paths_get_a_json_404_with_known_paths() {
+    let request = http::Request::builder()
+        .method("POST")
+        .uri("/echo.Echo/Ech")
+        .header("Content-Type", "application/json")
+        .body(common::echo_body("x-user"))
+        .unwrap();
+    let response = not_found::echo_handler(FallbackServer)
+        .fallback_service(not_found::not_found_fallback())
+        .oneshot(request)
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), http::StatusCode::NOT_FOUND);
+    let body = common::json_body(response).await;
+    assert_eq!(body["error"]["code"], "NOT_FOUND");
+    assert_eq!(
+        body["error"]["message"],
+        "No method is mapped to `/echo.Echo/Ech`"
+    );
+    assert_eq!(
+        body["error"]["known_paths"],
+        serde_json::json!(["/echo.Echo/Echo"])
+    );
+}
+
+#[tokio::test]
+async fn test_known_paths_are_served() {
+    let request = common::echo_request()
+        .header("x-user", "alice")
+        .body(common::echo_body("x-user"))
+        .unwrap();
+    let response = not_found::echo_handler(FallbackServer)
+        .fallback_service(not_found::not_found_fallback())
+        .oneshot(request)
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), http::StatusCode::OK);
+    assert_eq!(
+        common::json_body(response).await["values"],
+        serde_json::json!(["alice"])
+    );
+}
+
+#[tokio::test]
+async fn test_generated_routers_merge_before_the_fallback() {
+    let health = axum::Router::new().route("/health", axum::routing::get(|| async { "ok" }));
+    let router = not_found::echo_handler(FallbackServer)
+        .merge(health)
+        .fallback_service(not_found::not_found_fallback());
+
+    let request = http::Request::builder()
+        .uri("/missing")
+        .body(axum::body::Body::empty())
+        .unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), http::StatusCode::NOT_FOUND);
+    assert_eq!(
+        common::json_body(response).await["error"]["message"],
+        "No method is mapped to `/missing`"
+    );
+
+    let request = http::Request::builder()
+        .uri("/health")
+        .body(axum::body::Body::empty())
+        .unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), http::StatusCode::OK);
+}
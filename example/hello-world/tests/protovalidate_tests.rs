@@ -0,0 +1,77 @@
+/// Tests for the `buf.validate` constraints checked with `with_protovalidate`
+///
+/// build.rs generates protos/protovalidate/echo.proto, whose request `key` needs at least
+/// one character. The tests stand in for a protovalidate runtime with a closure.
+use prost::Message;
+use tower::ServiceExt;
+
+#[macro_use]
+mod common;
+
+mod protovalidate {
+    include!(concat!(env!("OUT_DIR"), "/protovalidate/echo.rs"));
+}
+
+echo_server!(ValidatedServer, protovalidate);
+
+// Applies the `min_len` constraint of `echo.EchoRequest.key`
+fn validator() -> protovalidate::RequestValidator {
+    protovalidate::RequestValidator::new(|message_type, encoded| {
+        assert_eq!(message_type, "echo.EchoRequest");
+        let request = protovalidate::EchoRequest::decode(encoded).unwrap();
+        if request.key.is_empty() {
+            vec![protovalidate::FieldViolation {
+                field: "key".to_string(),
+                constraint: "string.min_len".to_string(),
+                description: "value length must be at least 1 characters".to_string(),
+            }]
+        } else {
+            Vec::new()
+        }
+    })
+}
+
+async fn validated(router: axum::Router, key: &str) -> axum::response::Response {
+    let request = common::echo_request().body(common::echo_body(key)).unwrap();
+    router.oneshot(request).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_valid_requests_reach_the_service() {
+    let router = protovalidate::echo_handler(ValidatedServer).layer(axum::Extension(validator()));
+    let response = validated(router, "x-user").await;
+
+    assert_eq!(response.status(), http::StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_violations_get_400_with_field_violations() {
+    let router = protovalidate::echo_handler(ValidatedServer).layer(axum::Extension(validator()));
+    let response = validated(router, "").await;
+
+    assert_eq!(response.status(), http::StatusCode::BAD_REQUEST);
+    let body = common::json_body(response).await;
+    assert_eq!(
+        body["error"]["code"],
+        tonic::Code::InvalidArgument.to_string().as_str()
+    );
+    assert_eq!(
+        body["error"]["field_violations"],
+        serde_json::json!([{
+            "field": "key",
+            "constraint": "string.min_len",
+            "description": "value length must be at least 1 characters"
+        }])
+    );
+}
+
+#[tokio::test]
+async fn test_missing_validator_gets_500() {
+    let response = validated(protovalidate::echo_handler(ValidatedServer), "x-user").await;
+
+    assert_eq!(response.status(), http::StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(
+        common::json_body(response).await["error"]["code"],
+        tonic::Code::Internal.to_string().as_str()
+    );
+}
@@ -0,0 +1,78 @@
+/// Tests for the request IDs of `with_request_ids`
+///
+/// build.rs generates the echo service with request IDs, so the service can echo the
+/// `x-request-id` metadata it receives, and with the content type check answering other
+/// content types with a JSON error.
+use tower::ServiceExt;
+
+#[macro_use]
+mod common;
+
+mod request_ids {
+    include!(concat!(env!("OUT_DIR"), "/request_ids/echo.rs"));
+}
+
+echo_server!(RequestIdServer, request_ids);
+
+#[tokio::test]
+async fn test_missing_request_id_is_generated_and_echoed() {
+    let request = common::echo_request()
+        .body(common::echo_body("x-request-id"))
+        .unwrap();
+    let response = request_ids::echo_handler(RequestIdServer)
+        .oneshot(request)
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), http::StatusCode::OK);
+    let request_id = response.headers()["x-request-id"]
+        .to_str()
+        .unwrap()
+        .to_string();
+    assert!(uuid::Uuid::parse_str(&request_id).is_ok(), "{request_id}");
+    // The service saw the same ID as metadata
+    assert_eq!(
+        common::json_body(response).await["values"],
+        serde_json::json!([request_id])
+    );
+}
+
+#[tokio::test]
+async fn test_client_request_id_is_kept() {
+    let request = common::echo_request()
+        .header("x-request-id", "req-42")
+        .body(common::echo_body("x-request-id"))
+        .unwrap();
+    let response = request_ids::echo_handler(RequestIdServer)
+        .oneshot(request)
+        .await
+        .unwrap();
+
+    assert_eq!(response.headers()["x-request-id"], "req-42");
+    assert_eq!(
+        common::json_body(response).await["values"],
+        serde_json::json!(["req-42"])
+    );
+}
+
+#[tokio::test]
+async fn test_error_bodies_carry_the_request_id() {
+    let request = http::Request::builder()
+        .method("POST")
+        .uri("/echo.Echo/Echo")
+        .header("Content-Type", "text/plain")
+        .header("x-request-id", "req-42")
+        .body(common::echo_body("x-request-id"))
+        .unwrap();
+    let response = request_ids::echo_handler(RequestIdServer)
+        .oneshot(request)
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), http::StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    assert_eq!(response.headers()["x-request-id"], "req-42");
+    assert_eq!(
+        common::json_body(response).await["error"]["request_id"],
+        "req-42"
+    );
+}
@@ -0,0 +1,55 @@
+/// Tests for the required fields checked by `with_request_validation`
+///
+/// build.rs generates protos/validation/echo.proto, whose request requires `key` and the
+/// `region` of its optional `origin`.
+use tower::ServiceExt;
+
+#[macro_use]
+mod common;
+
+mod validation {
+    include!(concat!(env!("OUT_DIR"), "/validation/echo.rs"));
+}
+
+echo_server!(ValidatedServer, validation);
+
+async fn validated(body: serde_json::Value) -> axum::response::Response {
+    let request = common::echo_request()
+        .body(axum::body::Body::from(body.to_string()))
+        .unwrap();
+    validation::echo_handler(ValidatedServer)
+        .oneshot(request)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_valid_requests_reach_the_service() {
+    let response = validated(serde_json::json!({
+        "key": "x-user",
+        "origin": { "region": "eu" }
+    }))
+    .await;
+
+    assert_eq!(response.status(), http::StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_missing_required_fields_get_400_with_field_violations() {
+    let response = validated(serde_json::json!({ "origin": { "region": "" } })).await;
+
+    assert_eq!(response.status(), http::StatusCode::BAD_REQUEST);
+    let body = common::json_body(response).await;
+    assert_eq!(
+        body["error"]["code"],
+        tonic::Code::InvalidArgument.to_string().as_str()
+    );
+    assert_eq!(body["error"]["message"], "Request validation failed");
+    assert_eq!(
+        body["error"]["field_violations"],
+        serde_json::json!([
+            { "field": "key", "constraint": "required", "description": "Field is required" },
+            { "field": "origin.region", "constraint": "required", "description": "Field is required" }
+        ])
+    );
+}
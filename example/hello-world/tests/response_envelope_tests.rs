@@ -0,0 +1,122 @@
+/// Tests for the envelopes of `with_response_envelope`
+///
+/// build.rs generates the echo service with response envelopes, the JSON not-found
+/// fallback and rate limiting.
+use tower::ServiceExt;
+
+mod common;
+
+mod envelope {
+    include!(concat!(env!("OUT_DIR"), "/envelope/echo.rs"));
+}
+
+// Echoes the key back, or fails for the `fail` key
+struct EnvelopedServer;
+
+#[tonic::async_trait]
+impl envelope::echo_server::Echo for EnvelopedServer {
+    async fn echo(
+        &self,
+        request: tonic::Request<envelope::EchoRequest>,
+    ) -> Result<tonic::Response<envelope::EchoReply>, tonic::Status> {
+        let key = request.into_inner().key;
+        if key == "fail" {
+            return Err(tonic::Status::not_found("No such key"));
+        }
+        Ok(tonic::Response::new(envelope::EchoReply {
+            values: vec![key],
+        }))
+    }
+}
+
+// Rejects every request, asking to retry after 30 seconds
+struct Exhausted;
+
+#[tonic::async_trait]
+impl envelope::RateLimiter for Exhausted {
+    async fn check(
+        &self,
+        _path: &str,
+        _metadata: &tonic::metadata::MetadataMap,
+    ) -> Result<(), envelope::RateLimited> {
+        Err(envelope::RateLimited::retry_after(
+            std::time::Duration::from_secs(30),
+        ))
+    }
+}
+
+// Response parts and JSON body of `request`
+async fn enveloped(
+    router: axum::Router,
+    request: http::Request<axum::body::Body>,
+) -> (http::response::Parts, serde_json::Value) {
+    let response = router.oneshot(request).await.unwrap();
+    let (parts, body) = response.into_parts();
+    let body_bytes = axum::body::to_bytes(body, usize::MAX)
+        .await
+        .expect("Body should be readable");
+    let body = serde_json::from_slice(&body_bytes).expect("Body should be JSON");
+    (parts, body)
+}
+
+#[tokio::test]
+async fn test_success_is_wrapped_in_data() {
+    let request = common::echo_request()
+        .body(common::echo_body("x-user"))
+        .unwrap();
+    let (parts, body) = enveloped(envelope::echo_handler(EnvelopedServer), request).await;
+
+    assert_eq!(parts.status, http::StatusCode::OK);
+    assert_eq!(
+        body,
+        serde_json::json!({ "data": { "values": ["x-user"] }, "error": null })
+    );
+}
+
+#[tokio::test]
+async fn test_service_errors_are_wrapped_in_error_with_200() {
+    let request = common::echo_request()
+        .body(common::echo_body("fail"))
+        .unwrap();
+    let (parts, body) = enveloped(envelope::echo_handler(EnvelopedServer), request).await;
+
+    assert_eq!(parts.status, http::StatusCode::OK);
+    assert!(body["data"].is_null());
+    assert_eq!(
+        body["error"]["code"],
+        tonic::Code::NotFound.to_string().as_str()
+    );
+    assert_eq!(body["error"]["message"], "No such key");
+}
+
+#[tokio::test]
+async fn test_routing_404_is_wrapped_in_error() {
+    let request = http::Request::builder()
+        .uri("/echo.Echo/Unknown")
+        .body(axum::body::Body::empty())
+        .unwrap();
+    let router =
+        envelope::echo_handler(EnvelopedServer).fallback_service(envelope::not_found_fallback());
+    let (parts, body) = enveloped(router, request).await;
+
+    assert_eq!(parts.status, http::StatusCode::OK);
+    assert!(body["data"].is_null());
+    assert_eq!(body["error"]["code"], "NOT_FOUND");
+}
+
+#[tokio::test]
+async fn test_rate_limited_requests_keep_retry_after() {
+    let router = envelope::echo_handler(EnvelopedServer)
+        .layer(axum::Extension(envelope::RateLimiting::new(Exhausted)));
+    let request = common::echo_request()
+        .body(common::echo_body("x-user"))
+        .unwrap();
+    let (parts, body) = enveloped(router, request).await;
+
+    assert_eq!(parts.status, http::StatusCode::OK);
+    assert_eq!(parts.headers["retry-after"], "30");
+    assert_eq!(
+        body["error"]["code"],
+        tonic::Code::ResourceExhausted.to_string().as_str()
+    );
+}
@@ -0,0 +1,63 @@
+/// Tests for the `x-goog-request-params` metadata of `with_routing_headers`
+///
+/// build.rs generates protos/routing/echo.proto, whose method takes routing parameters
+/// from the `key` and `table` request fields.
+use tower::ServiceExt;
+
+#[macro_use]
+mod common;
+
+mod routing {
+    include!(concat!(env!("OUT_DIR"), "/routing/echo.rs"));
+}
+
+echo_server!(RoutingServer, routing);
+
+// The `x-goog-request-params` values the service receives for the request `body`
+async fn request_params(body: serde_json::Value) -> serde_json::Value {
+    let request = common::echo_request()
+        .body(axum::body::Body::from(body.to_string()))
+        .unwrap();
+    let response = routing::echo_handler(RoutingServer)
+        .oneshot(request)
+        .await
+        .unwrap();
+    assert_eq!(response.status(), http::StatusCode::OK);
+    common::json_body(response).await["values"].clone()
+}
+
+#[tokio::test]
+async fn test_whole_fields_are_keyed_by_their_path() {
+    let params = request_params(serde_json::json!({
+        "key": "x-goog-request-params",
+        "table": "",
+    }))
+    .await;
+
+    assert_eq!(params, serde_json::json!(["key=x-goog-request-params"]));
+}
+
+#[tokio::test]
+async fn test_path_templates_extract_their_named_segments() {
+    let params = request_params(serde_json::json!({
+        "key": "x-goog-request-params",
+        "table": "projects/p1/tables/t1",
+    }))
+    .await;
+
+    assert_eq!(
+        params,
+        serde_json::json!(["key=x-goog-request-params&project=projects%2Fp1&table=tables%2Ft1"])
+    );
+}
+
+#[tokio::test]
+async fn test_fields_not_matching_a_template_are_skipped() {
+    let params = request_params(serde_json::json!({
+        "key": "x-goog-request-params",
+        "table": "tables/t1",
+    }))
+    .await;
+
+    assert_eq!(params, serde_json::json!(["key=x-goog-request-params"]));
+}
@@ -0,0 +1,84 @@
+/// Tests for the one route per service of `with_single_dispatch`
+///
+/// build.rs generates the echo service with single dispatch and the JSON 404 fallback.
+use tower::ServiceExt;
+
+#[macro_use]
+mod common;
+
+mod single_dispatch {
+    include!(concat!(env!("OUT_DIR"), "/single_dispatch/echo.rs"));
+}
+
+echo_server!(DispatchedServer, single_dispatch);
+
+#[tokio::test]
+async fn test_methods_are_dispatched() {
+    let request = common::echo_request()
+        .header("x-user", "alice")
+        .body(common::echo_body("x-user"))
+        .unwrap();
+    let response = single_dispatch::echo_handler(DispatchedServer)
+        .oneshot(request)
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), http::StatusCode::OK);
+    assert_eq!(
+        common::json_body(response).await["values"],
+        serde_json::json!(["alice"])
+    );
+}
+
+#[tokio::test]
+async fn test_unknown_methods_get_the_json_404() {
+    let request = http::Request::builder()
+        .method("POST")
+        .uri("/echo.Echo/Unknown")
+        .header("Content-Type", "application/json")
+        .body(common::echo_body("x-user"))
+        .unwrap();
+    let response = single_dispatch::echo_handler(DispatchedServer)
+        .oneshot(request)
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), http::StatusCode::NOT_FOUND);
+    assert_eq!(
+        common::json_body(response).await["error"]["code"],
+        "NOT_FOUND"
+    );
+}
+
+#[tokio::test]
+async fn test_wrong_verbs_get_405_from_the_method_route() {
+    let request = http::Request::builder()
+        .method("GET")
+        .uri("/echo.Echo/Echo")
+        .body(axum::body::Body::empty())
+        .unwrap();
+    let response = single_dispatch::echo_handler(DispatchedServer)
+        .oneshot(request)
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), http::StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(response.headers()[http::header::ALLOW], "POST");
+}
+
+#[tokio::test]
+async fn test_relocated_methods_keep_their_own_route() {
+    let router = single_dispatch::EchoRouterBuilder::new(DispatchedServer)
+        .path("Echo", "/echo")
+        .build();
+    let request = http::Request::builder()
+        .method("POST")
+        .uri("/echo")
+        .header("Content-Type", "application/json")
+        .header("x-user", "alice")
+        .body(common::echo_body("x-user"))
+        .unwrap();
+    let response = router.oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), http::StatusCode::OK);
+}
@@ -0,0 +1,95 @@
+/// Tests for the `grpc-timeout` and `X-Request-Timeout` deadlines of `with_timeout_headers`
+///
+/// build.rs generates the echo service honoring the timeout headers.
+use std::time::Duration;
+
+use tower::ServiceExt;
+
+mod common;
+
+mod timeout_headers {
+    include!(concat!(env!("OUT_DIR"), "/timeout_headers/echo.rs"));
+}
+
+// Replies after 200ms
+struct SlowServer;
+
+#[tonic::async_trait]
+impl timeout_headers::echo_server::Echo for SlowServer {
+    async fn echo(
+        &self,
+        _request: tonic::Request<timeout_headers::EchoRequest>,
+    ) -> Result<tonic::Response<timeout_headers::EchoReply>, tonic::Status> {
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        Ok(tonic::Response::new(timeout_headers::EchoReply {
+            values: vec![],
+        }))
+    }
+}
+
+async fn call(headers: &[(&str, &str)]) -> axum::response::Response {
+    let mut request = common::echo_request();
+    for (name, value) in headers {
+        request = request.header(*name, *value);
+    }
+    timeout_headers::echo_handler(SlowServer)
+        .oneshot(request.body(common::echo_body("x-key")).unwrap())
+        .await
+        .unwrap()
+}
+
+async fn assert_deadline_exceeded(response: axum::response::Response) {
+    assert_eq!(response.status(), http::StatusCode::REQUEST_TIMEOUT);
+    let body = common::json_body(response).await;
+    assert_eq!(
+        body["error"]["code"],
+        tonic::Code::DeadlineExceeded.description()
+    );
+    assert_eq!(body["error"]["message"], "Request deadline exceeded");
+}
+
+#[tokio::test]
+async fn test_calls_without_timeout_headers_run_to_completion() {
+    let response = call(&[]).await;
+
+    assert_eq!(response.status(), http::StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_expired_grpc_timeouts_get_deadline_exceeded() {
+    assert_deadline_exceeded(call(&[("grpc-timeout", "10m")]).await).await;
+    assert_deadline_exceeded(call(&[("grpc-timeout", "10000u")]).await).await;
+}
+
+#[tokio::test]
+async fn test_calls_within_the_grpc_timeout_complete() {
+    let response = call(&[("grpc-timeout", "5S")]).await;
+
+    assert_eq!(response.status(), http::StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_request_timeouts_accept_milliseconds_and_the_grpc_format() {
+    assert_deadline_exceeded(call(&[("x-request-timeout", "10")]).await).await;
+    assert_deadline_exceeded(call(&[("x-request-timeout", "10m")]).await).await;
+
+    let response = call(&[("x-request-timeout", "5000")]).await;
+    assert_eq!(response.status(), http::StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_grpc_timeout_wins_over_request_timeout() {
+    let response = call(&[("grpc-timeout", "5S"), ("x-request-timeout", "10")]).await;
+    assert_eq!(response.status(), http::StatusCode::OK);
+
+    let response = call(&[("grpc-timeout", "10m"), ("x-request-timeout", "5000")]).await;
+    assert_deadline_exceeded(response).await;
+}
+
+#[tokio::test]
+async fn test_malformed_timeouts_are_ignored() {
+    for value in ["10", "10x", "m", "-5S"] {
+        let response = call(&[("grpc-timeout", value)]).await;
+        assert_eq!(response.status(), http::StatusCode::OK, "`{value}`");
+    }
+}
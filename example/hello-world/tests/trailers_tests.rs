@@ -0,0 +1,108 @@
+/// Tests for the trailing metadata sent by `with_trailers`
+///
+/// build.rs generates the echo service with trailers and request IDs, whose middleware
+/// rewrites error bodies after the trailers are attached.
+use http_body_util::BodyExt;
+use tower::ServiceExt;
+
+mod common;
+
+mod trailers {
+    include!(concat!(env!("OUT_DIR"), "/trailers/echo.rs"));
+}
+
+// Replies with an `x-checksum` trailer, or fails with it for the `fail` key
+struct TrailingServer;
+
+#[tonic::async_trait]
+impl trailers::echo_server::Echo for TrailingServer {
+    async fn echo(
+        &self,
+        request: tonic::Request<trailers::EchoRequest>,
+    ) -> Result<tonic::Response<trailers::EchoReply>, tonic::Status> {
+        let key = request.into_inner().key;
+        let mut metadata = tonic::metadata::MetadataMap::new();
+        metadata.insert("x-checksum", "abc123".parse().unwrap());
+        if key == "fail" {
+            return Err(tonic::Status::with_metadata(
+                tonic::Code::NotFound,
+                "No such key",
+                metadata,
+            ));
+        }
+
+        let mut response = tonic::Response::new(trailers::EchoReply { values: vec![key] });
+        response
+            .extensions_mut()
+            .insert(trailers::TrailingMetadata(metadata));
+        Ok(response)
+    }
+}
+
+// Response parts, JSON body and trailers of an echo of `key`
+async fn echo(
+    key: &str,
+    te: Option<&str>,
+) -> (
+    http::response::Parts,
+    serde_json::Value,
+    Option<http::HeaderMap>,
+) {
+    let mut request = common::echo_request();
+    if let Some(te) = te {
+        request = request.header("TE", te);
+    }
+    let request = request.body(common::echo_body(key)).unwrap();
+    let response = trailers::echo_handler(TrailingServer)
+        .oneshot(request)
+        .await
+        .unwrap();
+
+    let (parts, body) = response.into_parts();
+    let collected = body.collect().await.expect("Body should be readable");
+    let trailers = collected.trailers().cloned();
+    let body = serde_json::from_slice(&collected.to_bytes()).expect("Body should be JSON");
+    (parts, body, trailers)
+}
+
+#[tokio::test]
+async fn test_trailing_metadata_is_sent_as_trailers_when_accepted() {
+    let (parts, body, trailers) = echo("x-user", Some("trailers")).await;
+
+    assert_eq!(parts.status, http::StatusCode::OK);
+    assert_eq!(body["values"], serde_json::json!(["x-user"]));
+    assert_eq!(parts.headers["trailer"], "x-checksum");
+    assert!(parts.headers.get("x-checksum").is_none());
+    assert!(parts.headers.get("content-length").is_none());
+    assert_eq!(
+        trailers.expect("Trailers should be sent")["x-checksum"],
+        "abc123"
+    );
+}
+
+#[tokio::test]
+async fn test_trailing_metadata_is_merged_into_headers_otherwise() {
+    let (parts, body, trailers) = echo("x-user", None).await;
+
+    assert_eq!(parts.status, http::StatusCode::OK);
+    assert_eq!(body["values"], serde_json::json!(["x-user"]));
+    assert_eq!(parts.headers["x-checksum"], "abc123");
+    assert!(parts.headers.get("trailer").is_none());
+    assert!(trailers.is_none());
+}
+
+#[tokio::test]
+async fn test_error_trailers_survive_the_request_id_middleware() {
+    let (parts, body, trailers) = echo("fail", Some("trailers")).await;
+
+    assert_eq!(parts.status, http::StatusCode::NOT_FOUND);
+    assert_eq!(body["error"]["message"], "No such key");
+    assert_eq!(
+        body["error"]["request_id"],
+        parts.headers["x-request-id"].to_str().unwrap()
+    );
+    assert_eq!(
+        trailers.expect("Trailers should be sent")["x-checksum"],
+        "abc123"
+    );
+}
@@ -0,0 +1,146 @@
+/// Tests for the `/vN` route prefixes of `with_version_prefix`
+///
+/// build.rs generates the `Users` services of the `users.v1`, `users.v2` and `accounts.v1`
+/// packages under `OUT_DIR/versioned`, and the ones of `users.v1` and `users.v2` on
+/// kebab-case paths under `OUT_DIR/versioned_kebab`.
+use g2h::{BridgeGenerator, RoutePathCase};
+use tower::ServiceExt;
+
+mod common;
+
+mod versioned {
+    pub mod users_v1 {
+        include!(concat!(env!("OUT_DIR"), "/versioned/users.v1.rs"));
+    }
+    pub mod users_v2 {
+        include!(concat!(env!("OUT_DIR"), "/versioned/users.v2.rs"));
+    }
+    pub mod accounts_v1 {
+        include!(concat!(env!("OUT_DIR"), "/versioned/accounts.v1.rs"));
+    }
+}
+
+mod versioned_kebab {
+    pub mod users_v1 {
+        include!(concat!(env!("OUT_DIR"), "/versioned_kebab/users.v1.rs"));
+    }
+    pub mod users_v2 {
+        include!(concat!(env!("OUT_DIR"), "/versioned_kebab/users.v2.rs"));
+    }
+}
+
+// Implements the `Users` service of `$package` for the unit struct `$name`, answering with
+// the package name
+macro_rules! users_server {
+    ($name:ident, $($package:ident)::+, $package_name:literal) => {
+        struct $name;
+
+        #[tonic::async_trait]
+        impl $($package)::+::users_server::Users for $name {
+            async fn get_user(
+                &self,
+                request: tonic::Request<$($package)::+::GetUserRequest>,
+            ) -> Result<tonic::Response<$($package)::+::User>, tonic::Status> {
+                Ok(tonic::Response::new($($package)::+::User {
+                    id: request.into_inner().id,
+                    package: $package_name.to_string(),
+                }))
+            }
+        }
+    };
+}
+
+users_server!(UsersV1, versioned::users_v1, "users.v1");
+users_server!(UsersV2, versioned::users_v2, "users.v2");
+users_server!(AccountsV1, versioned::accounts_v1, "accounts.v1");
+users_server!(KebabUsersV1, versioned_kebab::users_v1, "users.v1");
+users_server!(KebabUsersV2, versioned_kebab::users_v2, "users.v2");
+
+// The package of the service answering a `GetUser` request on `path`, if any
+async fn answering_package(router: axum::Router, path: &str) -> Option<String> {
+    let request = http::Request::builder()
+        .method("POST")
+        .uri(path)
+        .header("Content-Type", "application/json")
+        .body(axum::body::Body::from(r#"{"id":"u1"}"#))
+        .unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    if response.status() == http::StatusCode::NOT_FOUND {
+        return None;
+    }
+    assert_eq!(response.status(), http::StatusCode::OK);
+    let body = common::json_body(response).await;
+    assert_eq!(body["id"], "u1");
+    body["package"].as_str().map(str::to_string)
+}
+
+#[tokio::test]
+async fn test_versions_move_to_the_front_of_the_path() {
+    let router = versioned::users_v1::users_handler(UsersV1)
+        .merge(versioned::users_v2::users_handler(UsersV2))
+        .merge(versioned::accounts_v1::users_handler(AccountsV1));
+
+    for (path, package) in [
+        ("/v1/users.Users/GetUser", "users.v1"),
+        ("/v2/users.Users/GetUser", "users.v2"),
+        ("/v1/accounts.Users/GetUser", "accounts.v1"),
+    ] {
+        assert_eq!(
+            answering_package(router.clone(), path).await.as_deref(),
+            Some(package),
+            "`{path}`"
+        );
+    }
+
+    // The versioned gRPC paths are not served
+    assert_eq!(
+        answering_package(router, "/users.v1.Users/GetUser").await,
+        None
+    );
+}
+
+#[tokio::test]
+async fn test_version_prefixes_apply_to_cased_paths() {
+    let router = versioned_kebab::users_v1::users_handler(KebabUsersV1)
+        .merge(versioned_kebab::users_v2::users_handler(KebabUsersV2));
+
+    for (path, package) in [
+        ("/v1/users/get-user", "users.v1"),
+        ("/v2/users/get-user", "users.v2"),
+    ] {
+        assert_eq!(
+            answering_package(router.clone(), path).await.as_deref(),
+            Some(package),
+            "`{path}`"
+        );
+    }
+}
+
+#[test]
+fn test_unversioned_packages_keep_their_paths() {
+    let out_dir = std::env::var("OUT_DIR").expect("OUT_DIR not set");
+    let descriptors = std::fs::read(std::path::Path::new(&out_dir).join("hello_world.fds"))
+        .expect("build.rs should keep the descriptors");
+    let sources = BridgeGenerator::with_tonic_build()
+        .with_version_prefix()
+        .render(descriptors)
+        .unwrap();
+
+    let code = sources.get("hello_world.rs").unwrap();
+    assert!(code.contains("\"/hello_world.Greeter/SayHello\""));
+}
+
+#[test]
+#[should_panic(
+    expected = "`/users.v1.Users/GetUser` and `/accounts.v1.Users/GetUser` are both served on `/v1/users/get-user`"
+)]
+fn test_paths_shared_across_packages_are_rejected() {
+    // The kebab-case paths leave the package out, so `users.v1` and `accounts.v1` collide
+    let out_dir = std::env::var("OUT_DIR").expect("OUT_DIR not set");
+    let descriptors = std::fs::read(std::path::Path::new(&out_dir).join("versioned/versioned.fds"))
+        .expect("build.rs should write the descriptor set");
+    let _ = BridgeGenerator::with_tonic_build()
+        .with_version_prefix()
+        .with_route_path_case(RoutePathCase::KebabCase)
+        .render(descriptors);
+}
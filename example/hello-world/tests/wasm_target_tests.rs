@@ -0,0 +1,29 @@
+/// Tests for the code generated with `with_wasm_target`
+///
+/// build.rs generates the hello-world protos for wasm32 into `OUT_DIR/wasm`.
+use std::path::Path;
+
+fn wasm_code() -> String {
+    let out_dir = std::env::var("OUT_DIR").expect("OUT_DIR not set");
+    std::fs::read_to_string(Path::new(&out_dir).join("wasm/hello_world.rs"))
+        .expect("Should be able to read the wasm code")
+}
+
+#[test]
+fn test_wasm_code_serves_the_services() {
+    let code = wasm_code();
+
+    assert!(code.contains("pub fn greeter_handler"));
+}
+
+#[test]
+fn test_wasm_code_needs_no_runtime_or_sockets() {
+    let code = wasm_code();
+
+    for unsupported in ["tokio", "Instant", "catch_unwind", "tonic::transport"] {
+        assert!(
+            !code.contains(unsupported),
+            "The wasm code should not use `{unsupported}`"
+        );
+    }
+}
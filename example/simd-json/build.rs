@@ -0,0 +1,15 @@
+use g2h::BridgeGenerator;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let protos = std::path::Path::new("../hello-world/protos");
+    BridgeGenerator::with_tonic_build()
+        .with_string_enums()
+        // Reads the `g2h.sensitive` fields, whose parse errors are withheld
+        .with_route_options()
+        .compile_protos(
+            &[protos.join("hello-world.proto")],
+            &[protos, &g2h::options_proto_include()],
+        )?;
+
+    Ok(())
+}
@@ -0,0 +1,5 @@
+//! The hello world protos, with handlers parsing request bodies with simd-json.
+
+pub mod hello_world {
+    include!(concat!(env!("OUT_DIR"), "/hello_world.rs"));
+}
@@ -0,0 +1,144 @@
+/// Tests for the handlers generated with the `simd-json` feature
+///
+/// build.rs generates the hello world protos with the feature, so request bodies are
+/// parsed with simd-json.
+use simd_json_example::hello_world;
+use tower::ServiceExt;
+
+struct Server;
+
+#[tonic::async_trait]
+impl hello_world::greeter_server::Greeter for Server {
+    async fn say_hello(
+        &self,
+        request: tonic::Request<hello_world::HelloRequest>,
+    ) -> Result<tonic::Response<hello_world::HelloReply>, tonic::Status> {
+        let request = request.into_inner();
+        Ok(tonic::Response::new(hello_world::HelloReply {
+            message: format!("Hello {} ({:?})!", request.name, request.greeting_type()),
+            status: 0,
+        }))
+    }
+}
+
+struct Payments;
+
+#[tonic::async_trait]
+impl hello_world::payment_connector_server::PaymentConnector for Payments {
+    async fn process_payment(
+        &self,
+        request: tonic::Request<hello_world::PaymentRequest>,
+    ) -> Result<tonic::Response<hello_world::PaymentResponse>, tonic::Status> {
+        Ok(tonic::Response::new(hello_world::PaymentResponse {
+            transaction_id: request.into_inner().order_id,
+            ..Default::default()
+        }))
+    }
+
+    async fn get_payment_status(
+        &self,
+        _request: tonic::Request<hello_world::StatusRequest>,
+    ) -> Result<tonic::Response<hello_world::PaymentResponse>, tonic::Status> {
+        Err(tonic::Status::unimplemented("not served"))
+    }
+}
+
+fn request(path: &str, body: &'static str) -> http::Request<axum::body::Body> {
+    http::Request::builder()
+        .method("POST")
+        .uri(path)
+        .header("Content-Type", "application/json")
+        .body(axum::body::Body::from(body))
+        .unwrap()
+}
+
+async fn body_text(response: axum::response::Response) -> String {
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("Body should be readable");
+    String::from_utf8(bytes.to_vec()).expect("Body should be UTF-8")
+}
+
+#[tokio::test]
+async fn test_valid_bodies_are_parsed() {
+    let response = hello_world::greeter_handler(Server)
+        .oneshot(request(
+            "/hello_world.Greeter/SayHello",
+            r#"{"name":"World","greeting_type":"CASUAL"}"#,
+        ))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), http::StatusCode::OK);
+    let body: serde_json::Value = serde_json::from_str(&body_text(response).await).unwrap();
+    assert_eq!(body["message"], "Hello World (Casual)!");
+}
+
+#[tokio::test]
+async fn test_malformed_bodies_are_bad_requests() {
+    let response = hello_world::greeter_handler(Server)
+        .oneshot(request("/hello_world.Greeter/SayHello", r#"{"name":"#))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), http::StatusCode::BAD_REQUEST);
+    assert!(body_text(response)
+        .await
+        .starts_with("Failed to parse the request body as JSON: "));
+}
+
+#[tokio::test]
+async fn test_values_serde_rejects_are_unprocessable() {
+    let response = hello_world::greeter_handler(Server)
+        .oneshot(request(
+            "/hello_world.Greeter/SayHello",
+            r#"{"name":"World","greeting_type":"RUDE"}"#,
+        ))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), http::StatusCode::UNPROCESSABLE_ENTITY);
+    assert!(body_text(response)
+        .await
+        .starts_with("Failed to deserialize the JSON body into the target type: "));
+}
+
+#[tokio::test]
+async fn test_parse_errors_of_sensitive_requests_are_withheld() {
+    let response = hello_world::payment_connector_handler(Payments)
+        .oneshot(request(
+            "/hello_world.PaymentConnector/ProcessPayment",
+            r#"{"card_number":"4111111111111111""#,
+        ))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), http::StatusCode::BAD_REQUEST);
+    let body = body_text(response).await;
+    assert!(!body.contains("4111111111111111"));
+    assert!(body.contains("details are withheld"));
+}
+
+#[tokio::test]
+async fn test_sensitive_requests_are_parsed() {
+    let response = hello_world::payment_connector_handler(Payments)
+        .oneshot(request(
+            "/hello_world.PaymentConnector/ProcessPayment",
+            r#"{
+                "order_id": "order_1",
+                "receipt": "",
+                "amount": 10.0,
+                "currency": "USD",
+                "customer_id": "customer_1",
+                "payment_method": "card",
+                "card_number": "4111111111111111",
+                "saved_cards": {}
+            }"#,
+        ))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), http::StatusCode::OK);
+    let body: serde_json::Value = serde_json::from_str(&body_text(response).await).unwrap();
+    assert_eq!(body["transaction_id"], "order_1");
+}
@@ -0,0 +1,18 @@
+use g2h::BridgeGenerator;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let protos = std::path::Path::new("../hello-world/protos");
+    // Without tonic's transport, which needs tokio and sockets
+    let inner = tonic_prost_build::configure()
+        .build_transport(false)
+        .service_generator();
+    BridgeGenerator::new(inner)
+        .with_string_enums()
+        .with_wasm_target()
+        .compile_protos(
+            &[protos.join("hello-world.proto")],
+            &[protos, &g2h::options_proto_include()],
+        )?;
+
+    Ok(())
+}
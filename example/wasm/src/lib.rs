@@ -0,0 +1,5 @@
+//! The hello world protos generated for wasm32 targets, as served by a Cloudflare Worker.
+
+pub mod hello_world {
+    include!(concat!(env!("OUT_DIR"), "/hello_world.rs"));
+}
@@ -0,0 +1,204 @@
+//!
+//! actix-web backend: per-service configuration functions and handlers with the JSON,
+//! metadata and error semantics of the axum routers, for unary methods.
+//!
+use heck::ToSnakeCase;
+use quote::quote;
+
+use crate::rust_type_tokens;
+
+/// A method served by the actix backend, with its route.
+pub(crate) struct ActixRoute<'a> {
+    pub(crate) method: &'a prost_build::Method,
+    pub(crate) path: &'a str,
+    pub(crate) verb: &'a str,
+    pub(crate) success_status: u16,
+    /// Whether the request has `g2h.sensitive` fields, whose parse errors are withheld
+    pub(crate) masked: bool,
+}
+
+/// Generates the `{service}_actix_config` function of `service` and its handlers.
+pub(crate) fn service_code(
+    service: &prost_build::Service,
+    routes: &[ActixRoute<'_>],
+) -> proc_macro2::TokenStream {
+    let snake_case_name = service.name.to_snake_case();
+    let trait_name = quote::format_ident!("{}", service.name);
+    let server_module = quote::format_ident!("{}_server", snake_case_name);
+    let config_fn = quote::format_ident!("{}_actix_config", snake_case_name);
+    let config_doc = format!(
+        "Configures the HTTP routes of `{}.{}` on an actix-web `App` or scope, e.g. \
+         `App::new().configure({config_fn}(server.clone()))`. Streaming methods are not served.",
+        service.package, service.proto_name
+    );
+
+    // Streaming methods have no JSON equivalent on this backend
+    let routes = routes
+        .iter()
+        .filter(|route| !route.method.client_streaming && !route.method.server_streaming)
+        .collect::<Vec<_>>();
+    let handler_fns = routes
+        .iter()
+        .map(|route| {
+            quote::format_ident!("{}_{}_actix_handler", snake_case_name, route.method.name)
+        })
+        .collect::<Vec<_>>();
+    let paths = routes.iter().map(|route| route.path);
+    let verbs = routes
+        .iter()
+        .map(|route| quote::format_ident!("{}", route.verb));
+    let method_names = routes
+        .iter()
+        .map(|route| quote::format_ident!("{}", route.method.name));
+    let success_statuses = routes.iter().map(|route| route.success_status);
+    let decode_bodies = routes.iter().map(|route| {
+        let request_type = rust_type_tokens(&route.method.input_type);
+        if route.verb == "GET" {
+            quote! {
+                let body = match ::actix_web::web::Query::<#request_type>::from_query(request.query_string()) {
+                    Ok(query) => query.into_inner(),
+                    Err(err) => return actix_error_response(::actix_web::http::StatusCode::BAD_REQUEST, ::tonic::Code::InvalidArgument, err.to_string()),
+                };
+            }
+        } else {
+            let masked = route.masked;
+            quote! {
+                let body = match actix_json_body::<#request_type>(&request, &body, #masked) {
+                    Ok(body) => body,
+                    Err(response) => return response,
+                };
+            }
+        }
+    });
+
+    quote! {
+        #[allow(dead_code)]
+        #[doc = #config_doc]
+        pub fn #config_fn<T: #server_module::#trait_name>(server: ::std::sync::Arc<T>) -> impl FnOnce(&mut ::actix_web::web::ServiceConfig) {
+            move |config| {
+                config.app_data(::actix_web::web::Data::from(server));
+                #(
+                    config.route(#paths, ::actix_web::web::method(::actix_web::http::Method::#verbs).to(#handler_fns::<T>));
+                )*
+            }
+        }
+
+        #(
+            #[allow(dead_code)]
+            async fn #handler_fns<T: #server_module::#trait_name>(
+                state: ::actix_web::web::Data<T>,
+                request: ::actix_web::HttpRequest,
+                body: ::actix_web::web::Bytes,
+            ) -> ::actix_web::HttpResponse {
+                #decode_bodies
+                let metadata_map = match actix_request_metadata(&request) {
+                    Ok(metadata_map) => metadata_map,
+                    Err(response) => return response,
+                };
+                let request = ::tonic::Request::from_parts(metadata_map, ::tonic::Extensions::new(), body);
+
+                let call = CatchPanic(Box::pin(<T as #server_module::#trait_name>::#method_names(state.get_ref(), request)));
+                match call.await {
+                    Ok(response) => actix_success_response(#success_statuses, response),
+                    Err(status) => actix_status_response(&status),
+                }
+            }
+        )*
+    }
+}
+
+/// Generates the helpers shared by the actix handlers of a package, on top of the metadata
+/// helpers they share with the axum handlers.
+pub(crate) fn package_helpers() -> proc_macro2::TokenStream {
+    quote! {
+        // actix-web has its own `http` types, which are converted through their raw values
+        #[allow(dead_code)]
+        fn actix_status(status: ::http::StatusCode) -> ::actix_web::http::StatusCode {
+            ::actix_web::http::StatusCode::from_u16(status.as_u16())
+                .unwrap_or(::actix_web::http::StatusCode::INTERNAL_SERVER_ERROR)
+        }
+
+        // Answers with the JSON error shape of the generated handlers
+        #[allow(dead_code)]
+        fn actix_error_response(status: ::actix_web::http::StatusCode, code: ::tonic::Code, message: String) -> ::actix_web::HttpResponse {
+            let error_body = ErrorResponse {
+                error: ErrorDetails::new(error_code(code), message)
+            };
+
+            ::actix_web::HttpResponse::build(status).json(error_body)
+        }
+
+        // Parses a JSON request body, rejecting it like axum's `Json` extractor does. Parse
+        // errors of requests with `g2h.sensitive` fields could echo their values, so `masked`
+        // withholds serde's details
+        #[allow(dead_code)]
+        fn actix_json_body<B: serde::de::DeserializeOwned>(request: &::actix_web::HttpRequest, body: &[u8], masked: bool) -> Result<B, ::actix_web::HttpResponse> {
+            let is_json = request
+                .headers()
+                .get(::actix_web::http::header::CONTENT_TYPE)
+                .and_then(|content_type| content_type.to_str().ok())
+                .is_some_and(|content_type| content_type.starts_with("application/json"));
+            if !is_json {
+                return Err(actix_error_response(
+                    ::actix_web::http::StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                    ::tonic::Code::InvalidArgument,
+                    "Expected request with `Content-Type: application/json`".to_string(),
+                ));
+            }
+
+            ::serde_json::from_slice(body).map_err(|err| {
+                let status = if err.classify() == ::serde_json::error::Category::Data {
+                    ::actix_web::http::StatusCode::UNPROCESSABLE_ENTITY
+                } else {
+                    ::actix_web::http::StatusCode::BAD_REQUEST
+                };
+                let message = if masked {
+                    "Failed to deserialize the JSON body, details are withheld as the message has sensitive fields".to_string()
+                } else {
+                    format!("Failed to parse the request body: {}", err)
+                };
+                actix_error_response(status, ::tonic::Code::InvalidArgument, message)
+            })
+        }
+
+        // Turns the request headers into gRPC metadata like the axum handlers do, answering
+        // rejected headers with their JSON error
+        #[allow(dead_code)]
+        fn actix_request_metadata(request: &::actix_web::HttpRequest) -> Result<::tonic::metadata::MetadataMap, ::actix_web::HttpResponse> {
+            let mut headers = ::http::header::HeaderMap::with_capacity(request.headers().len());
+            for (name, value) in request.headers() {
+                let name = ::http::header::HeaderName::from_bytes(name.as_str().as_bytes());
+                let value = ::http::header::HeaderValue::from_bytes(value.as_bytes());
+                if let (Ok(name), Ok(value)) = (name, value) {
+                    headers.append(name, value);
+                }
+            }
+
+            let peer_ip = request.peer_addr().map(|peer_addr| peer_addr.ip());
+            metadata_from_headers(headers, peer_ip).map_err(|(status, error_body)| {
+                ::actix_web::HttpResponse::build(actix_status(status)).json(error_body)
+            })
+        }
+
+        // Answers with the JSON message and its metadata as headers
+        #[allow(dead_code)]
+        fn actix_success_response<M: serde::Serialize>(status: u16, response: ::tonic::Response<M>) -> ::actix_web::HttpResponse {
+            let (metadata_map, body, _) = response.into_parts();
+            let status = ::actix_web::http::StatusCode::from_u16(status).unwrap_or(::actix_web::http::StatusCode::OK);
+
+            let mut builder = ::actix_web::HttpResponse::build(status);
+            for (name, value) in response_headers_from_metadata(metadata_map).iter() {
+                if let Ok(value) = ::actix_web::http::header::HeaderValue::from_bytes(value.as_bytes()) {
+                    builder.append_header((name.as_str(), value));
+                }
+            }
+            builder.json(body)
+        }
+
+        // Answers with the HTTP status and JSON error matching a gRPC status
+        #[allow(dead_code)]
+        fn actix_status_response(status: &::tonic::Status) -> ::actix_web::HttpResponse {
+            actix_error_response(actix_status(http_status_of(status.code())), status.code(), status.message().to_string())
+        }
+    }
+}
@@ -0,0 +1,419 @@
+//!
+//! Reading of proto annotations: the `google.api.routing` and `g2h.route` method options,
+//! the `g2h.service` service option, the `g2h.sensitive` and `google.api.field_behavior`
+//! field options, and the presence of `buf.validate` constraints.
+//!
+//! `prost_types` drops unknown fields while decoding, which includes custom options. The
+//! annotations are therefore read from protoc's raw output through minimal descriptor
+//! messages which declare the extensions as regular fields.
+//!
+use std::collections::{HashMap, HashSet};
+
+use prost::Message;
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct RawFileDescriptorSet {
+    #[prost(message, repeated, tag = "1")]
+    file: Vec<RawFileDescriptor>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct RawFileDescriptor {
+    #[prost(string, optional, tag = "2")]
+    package: Option<String>,
+    #[prost(message, repeated, tag = "4")]
+    message_type: Vec<RawMessageDescriptor>,
+    #[prost(message, repeated, tag = "6")]
+    service: Vec<RawServiceDescriptor>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct RawMessageDescriptor {
+    #[prost(string, optional, tag = "1")]
+    name: Option<String>,
+    #[prost(message, repeated, tag = "2")]
+    field: Vec<RawFieldDescriptor>,
+    #[prost(message, repeated, tag = "3")]
+    nested_type: Vec<RawMessageDescriptor>,
+    #[prost(message, optional, tag = "7")]
+    options: Option<RawMessageOptions>,
+    #[prost(message, repeated, tag = "8")]
+    oneof_decl: Vec<RawOneofDescriptor>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct RawMessageOptions {
+    #[prost(bool, optional, tag = "7")]
+    map_entry: Option<bool>,
+    /// `buf.validate.message` extension of `google.protobuf.MessageOptions`, left encoded
+    #[prost(bytes = "vec", optional, tag = "1159")]
+    protovalidate: Option<Vec<u8>>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct RawOneofDescriptor {
+    #[prost(message, optional, tag = "2")]
+    options: Option<RawOneofOptions>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct RawOneofOptions {
+    /// `buf.validate.oneof` extension of `google.protobuf.OneofOptions`, left encoded
+    #[prost(bytes = "vec", optional, tag = "1159")]
+    protovalidate: Option<Vec<u8>>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct RawFieldDescriptor {
+    #[prost(string, optional, tag = "1")]
+    name: Option<String>,
+    #[prost(string, optional, tag = "6")]
+    type_name: Option<String>,
+    #[prost(message, optional, tag = "8")]
+    options: Option<RawFieldOptions>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct RawFieldOptions {
+    /// `google.api.field_behavior` extension of `google.protobuf.FieldOptions`
+    #[prost(int32, repeated, tag = "1052")]
+    field_behavior: Vec<i32>,
+    /// `g2h.sensitive` extension of `google.protobuf.FieldOptions`, see `proto/g2h/options.proto`
+    #[prost(bool, optional, tag = "50743")]
+    sensitive: Option<bool>,
+    /// `buf.validate.field` extension of `google.protobuf.FieldOptions`, left encoded
+    #[prost(bytes = "vec", optional, tag = "1159")]
+    protovalidate: Option<Vec<u8>>,
+}
+
+/// `REQUIRED` value of the `google.api.FieldBehavior` enum
+const FIELD_BEHAVIOR_REQUIRED: i32 = 2;
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct RawServiceDescriptor {
+    #[prost(string, optional, tag = "1")]
+    name: Option<String>,
+    #[prost(message, repeated, tag = "2")]
+    method: Vec<RawMethodDescriptor>,
+    #[prost(message, optional, tag = "3")]
+    options: Option<RawServiceOptions>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct RawServiceOptions {
+    /// `g2h.service` extension of `google.protobuf.ServiceOptions`, see `proto/g2h/options.proto`
+    #[prost(message, optional, tag = "50744")]
+    service: Option<ServiceOptions>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct RawMethodDescriptor {
+    #[prost(string, optional, tag = "1")]
+    name: Option<String>,
+    #[prost(message, optional, tag = "4")]
+    options: Option<RawMethodOptions>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct RawMethodOptions {
+    /// `google.api.routing` extension of `google.protobuf.MethodOptions`
+    #[prost(message, optional, tag = "72295729")]
+    routing: Option<RawRoutingRule>,
+    /// `g2h.route` extension of `google.protobuf.MethodOptions`, see `proto/g2h/options.proto`
+    #[prost(message, optional, tag = "50742")]
+    route: Option<RouteOptions>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct RawRoutingRule {
+    #[prost(message, repeated, tag = "2")]
+    routing_parameters: Vec<RoutingParameter>,
+}
+
+///
+/// A single `routing_parameters` entry of a `google.api.routing` annotation.
+///
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct RoutingParameter {
+    /// Request field the value is read from, as a dotted path
+    #[prost(string, tag = "1")]
+    pub field: String,
+    /// Template extracting the routing key and value from the field, empty to use the whole field
+    #[prost(string, tag = "2")]
+    pub path_template: String,
+}
+
+///
+/// Per-method route configuration of the `g2h.route` option.
+///
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct RouteOptions {
+    /// HTTP path replacing `/package.Service/Method`
+    #[prost(string, tag = "1")]
+    pub path: String,
+    /// HTTP method replacing `POST`
+    #[prost(string, tag = "2")]
+    pub verb: String,
+    /// Whether requests without a bearer token are rejected
+    #[prost(bool, tag = "3")]
+    pub auth_required: bool,
+    /// Whether responses carry a `Deprecation` header
+    #[prost(bool, tag = "4")]
+    pub deprecated: bool,
+    /// HTTP status of successful responses, `0` for `200`
+    #[prost(uint32, tag = "5")]
+    pub success_status: u32,
+    /// `Cache-Control` header of successful responses, empty for none
+    #[prost(string, tag = "6")]
+    pub cache_control: String,
+    /// Header carrying the required API key, empty for the service's
+    #[prost(string, tag = "7")]
+    pub api_key_header: String,
+    /// Access level checked by the `Authorizer`, empty for the service's
+    #[prost(string, tag = "8")]
+    pub access: String,
+}
+
+///
+/// Per-service configuration of the `g2h.service` option.
+///
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct ServiceOptions {
+    /// Header carrying the required API key, empty for none
+    #[prost(string, tag = "1")]
+    pub api_key_header: String,
+    /// Access level checked by the `Authorizer`, empty for none
+    #[prost(string, tag = "2")]
+    pub access: String,
+}
+
+///
+/// The annotations of one method.
+///
+#[derive(Clone, Debug, Default)]
+pub(crate) struct MethodAnnotations {
+    pub routing_parameters: Vec<RoutingParameter>,
+    pub route: Option<RouteOptions>,
+}
+
+///
+/// A field of a message, as far as masking sensitive data and validation are concerned.
+///
+#[derive(Clone, Debug, Default)]
+pub(crate) struct FieldAnnotations {
+    pub name: String,
+    /// Fully qualified type of message and enum fields, without the leading dot. For map
+    /// fields, the type of the map values
+    pub type_name: Option<String>,
+    /// Whether the field is a map, a JSON object of values keyed by the map keys
+    pub map: bool,
+    pub sensitive: bool,
+    /// Whether the field has `google.api.field_behavior = REQUIRED`
+    pub required: bool,
+    /// Whether the field has `buf.validate.field` constraints
+    pub constrained: bool,
+}
+
+///
+/// The annotations of all compiled protos.
+///
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Annotations {
+    /// Annotated methods, keyed by their gRPC path (`/package.Service/Method`)
+    pub methods: HashMap<String, MethodAnnotations>,
+    /// Fields of every message, keyed by the fully qualified message name
+    pub messages: HashMap<String, Vec<FieldAnnotations>>,
+    /// Messages with `buf.validate.message` or `buf.validate.oneof` constraints
+    pub constrained_messages: HashSet<String>,
+    /// Annotated services, keyed by their fully qualified name (`package.Service`)
+    pub services: HashMap<String, ServiceOptions>,
+}
+
+///
+/// Runs protoc over `protos`, with the extra `protoc_args`, and collects the annotations
+/// of every annotated method and the fields of every message.
+///
+pub(crate) fn load_annotations(
+    protos: &[impl AsRef<std::path::Path>],
+    includes: &[impl AsRef<std::path::Path>],
+    protoc_args: &[String],
+) -> Result<Annotations, Box<dyn std::error::Error>> {
+    let out_dir = std::env::var_os("OUT_DIR")
+        .map(std::path::PathBuf::from)
+        .unwrap_or_else(std::env::temp_dir);
+    let descriptor_path = out_dir.join("g2h-annotation-descriptors.bin");
+
+    let mut command = std::process::Command::new(prost_build::protoc_from_env());
+    command
+        .arg("--include_imports")
+        .arg("-o")
+        .arg(&descriptor_path);
+    for include in includes {
+        command.arg("-I").arg(include.as_ref());
+    }
+    if let Some(include) = prost_build::protoc_include_from_env() {
+        command.arg("-I").arg(include);
+    }
+    command.args(protoc_args);
+    for proto in protos {
+        command.arg(proto.as_ref());
+    }
+
+    let output = command.output()?;
+    if !output.status.success() {
+        return Err(format!(
+            "protoc failed while reading method annotations: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )
+        .into());
+    }
+
+    let bytes = std::fs::read(&descriptor_path)?;
+    decode_annotations(&bytes)
+}
+
+///
+/// Collects the annotations of every annotated method and the fields of every message
+/// from an encoded `FileDescriptorSet`.
+///
+pub(crate) fn decode_annotations(bytes: &[u8]) -> Result<Annotations, Box<dyn std::error::Error>> {
+    let descriptor_set = RawFileDescriptorSet::decode(bytes)?;
+
+    let mut annotations = Annotations::default();
+    for file in &descriptor_set.file {
+        let package = file.package.as_deref().unwrap_or_default();
+        for message in &file.message_type {
+            collect_message_fields(package, message, &mut annotations);
+        }
+        for service in &file.service {
+            let service_name = service.name.as_deref().unwrap_or_default();
+            if let Some(options) = service
+                .options
+                .as_ref()
+                .and_then(|options| options.service.as_ref())
+            {
+                let service_path = if package.is_empty() {
+                    service_name.to_string()
+                } else {
+                    format!("{package}.{service_name}")
+                };
+                annotations.services.insert(service_path, options.clone());
+            }
+            for method in &service.method {
+                let Some(options) = method.options.as_ref() else {
+                    continue;
+                };
+                if options.routing.is_none() && options.route.is_none() {
+                    continue;
+                }
+                let method_name = method.name.as_deref().unwrap_or_default();
+                annotations.methods.insert(
+                    format!("/{package}.{service_name}/{method_name}"),
+                    MethodAnnotations {
+                        routing_parameters: options
+                            .routing
+                            .as_ref()
+                            .map(|rule| rule.routing_parameters.clone())
+                            .unwrap_or_default(),
+                        route: options.route.clone(),
+                    },
+                );
+            }
+        }
+    }
+
+    Ok(annotations)
+}
+
+fn is_map_entry(message: &RawMessageDescriptor) -> bool {
+    message
+        .options
+        .as_ref()
+        .and_then(|options| options.map_entry)
+        .unwrap_or(false)
+}
+
+fn collect_message_fields(
+    scope: &str,
+    message: &RawMessageDescriptor,
+    annotations: &mut Annotations,
+) {
+    let name = message.name.as_deref().unwrap_or_default();
+    let name = if scope.is_empty() {
+        name.to_string()
+    } else {
+        format!("{scope}.{name}")
+    };
+    for nested in &message.nested_type {
+        collect_message_fields(&name, nested, annotations);
+    }
+    // Map entries are JSON objects keyed by the map keys, not messages
+    if is_map_entry(message) {
+        return;
+    }
+    // Types of the values of the map entries declared in this message
+    let map_values = message
+        .nested_type
+        .iter()
+        .filter(|nested| is_map_entry(nested))
+        .map(|entry| {
+            let entry_name = format!("{name}.{}", entry.name.as_deref().unwrap_or_default());
+            let value_type = entry
+                .field
+                .iter()
+                .find(|field| field.name.as_deref() == Some("value"))
+                .and_then(|field| field.type_name.as_deref())
+                .map(|type_name| type_name.trim_start_matches('.').to_string());
+            (entry_name, value_type)
+        })
+        .collect::<HashMap<_, _>>();
+    let fields = message
+        .field
+        .iter()
+        .map(|field| {
+            let type_name = field
+                .type_name
+                .as_deref()
+                .map(|type_name| type_name.trim_start_matches('.').to_string());
+            let map_value = type_name
+                .as_ref()
+                .and_then(|type_name| map_values.get(type_name));
+            (field, type_name, map_value)
+        })
+        .map(|(field, type_name, map_value)| FieldAnnotations {
+            name: field.name.clone().unwrap_or_default(),
+            map: map_value.is_some(),
+            type_name: match map_value {
+                Some(value_type) => value_type.clone(),
+                None => type_name,
+            },
+            sensitive: field
+                .options
+                .as_ref()
+                .and_then(|options| options.sensitive)
+                .unwrap_or(false),
+            required: field
+                .options
+                .as_ref()
+                .is_some_and(|options| options.field_behavior.contains(&FIELD_BEHAVIOR_REQUIRED)),
+            constrained: field
+                .options
+                .as_ref()
+                .is_some_and(|options| options.protovalidate.is_some()),
+        })
+        .collect();
+    let constrained = message
+        .options
+        .as_ref()
+        .is_some_and(|options| options.protovalidate.is_some())
+        || message.oneof_decl.iter().any(|oneof| {
+            oneof
+                .options
+                .as_ref()
+                .is_some_and(|options| options.protovalidate.is_some())
+        });
+    if constrained {
+        annotations.constrained_messages.insert(name.clone());
+    }
+    annotations.messages.insert(name, fields);
+}
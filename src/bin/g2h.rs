@@ -0,0 +1,11 @@
+//!
+//! `g2h`: generates the bridged Rust code outside of a build script.
+//!
+//! See [`g2h::cli`] for usage.
+//!
+fn main() {
+    if let Err(err) = g2h::cli::run(std::env::args().skip(1)) {
+        eprintln!("g2h: {err}");
+        std::process::exit(1);
+    }
+}
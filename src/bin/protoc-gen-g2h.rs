@@ -0,0 +1,12 @@
+//!
+//! `protoc-gen-g2h`: runs g2h as a protoc plugin, e.g. from `buf generate`.
+//!
+//! See [`g2h::plugin`] for the supported options.
+//!
+use std::io::{Read, Write};
+
+fn main() -> std::io::Result<()> {
+    let mut request = Vec::new();
+    std::io::stdin().read_to_end(&mut request)?;
+    std::io::stdout().write_all(&g2h::plugin::run(&request))
+}
@@ -0,0 +1,104 @@
+//!
+//! The `g2h` command line generator, running the builder as a separate pipeline step
+//! instead of from a build script.
+//!
+//! ```text
+//! g2h generate --proto proto/service.proto --include proto --out generated/src \
+//!     --string-enums --max-body-size=1048576
+//! ```
+//!
+//! Builder options are the names of the `BridgeGenerator` methods, with dashes, and take
+//! their value after `=`.
+//!
+use crate::{options, BridgeGenerator};
+
+const USAGE: &str = "\
+usage: g2h generate --proto <file>... [--include <dir>...] --out <dir> [options]
+       g2h generate --descriptor-set <file> --out <dir> [options]";
+
+///
+/// Runs the CLI with `args`, without the program name, and returns a message on failure.
+///
+pub fn run(args: impl IntoIterator<Item = String>) -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = args.into_iter();
+    match args.next().as_deref() {
+        Some("generate") => generate(args),
+        Some("help" | "--help" | "-h") | None => {
+            println!("{}", help());
+            Ok(())
+        }
+        Some(command) => Err(format!("unknown command `{command}`\n{USAGE}").into()),
+    }
+}
+
+fn generate(mut args: impl Iterator<Item = String>) -> Result<(), Box<dyn std::error::Error>> {
+    let mut generator = BridgeGenerator::with_tonic_build();
+    let mut protos = Vec::new();
+    let mut includes = Vec::new();
+    let mut descriptor_set = None;
+    let mut out = None;
+
+    while let Some(arg) = args.next() {
+        let Some(flag) = arg.strip_prefix("--") else {
+            return Err(format!("unexpected argument `{arg}`\n{USAGE}").into());
+        };
+        let (name, value) = match flag.split_once('=') {
+            Some((name, value)) => (name, Some(value.to_string())),
+            None => (flag, None),
+        };
+        match name {
+            "help" => {
+                println!("{}", help());
+                return Ok(());
+            }
+            "proto" => protos.push(value_of(name, value, &mut args)?),
+            "include" => includes.push(value_of(name, value, &mut args)?),
+            "descriptor-set" => descriptor_set = Some(value_of(name, value, &mut args)?),
+            "out" => out = Some(value_of(name, value, &mut args)?),
+            _ => generator = options::apply(generator, &name.replace('-', "_"), value.as_deref())?,
+        }
+    }
+
+    let Some(out) = out else {
+        return Err(format!("missing `--out`\n{USAGE}").into());
+    };
+    // The CLI runs outside of any crate, so there are no dependencies to check
+    generator.dependencies_checked = true;
+    let generator = generator.out_dir(out);
+    match descriptor_set {
+        Some(path) => generator.compile_fds(std::fs::read(path)?),
+        None if protos.is_empty() => {
+            Err(format!("missing `--proto` or `--descriptor-set`\n{USAGE}").into())
+        }
+        None => {
+            if includes.is_empty() {
+                includes.push(".".to_string());
+            }
+            generator.compile_protos(&protos, &includes)
+        }
+    }
+}
+
+fn value_of(
+    name: &str,
+    value: Option<String>,
+    args: &mut impl Iterator<Item = String>,
+) -> Result<String, String> {
+    value
+        .or_else(|| args.next())
+        .ok_or_else(|| format!("missing value for `--{name}`"))
+}
+
+fn help() -> String {
+    let mut help = format!("{USAGE}\n\noptions:\n");
+    for (option, description) in options::OPTIONS {
+        let split = option.find(['=', '[']).unwrap_or(option.len());
+        let flag = format!(
+            "--{}{}",
+            option[..split].replace('_', "-"),
+            &option[split..]
+        );
+        help.push_str(&format!("  {flag:<52} {description}\n"));
+    }
+    help
+}
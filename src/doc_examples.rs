@@ -0,0 +1,228 @@
+//!
+//! Handler documentation examples: JSON request/response bodies derived from message
+//! descriptors, and a curl command calling the route.
+//!
+use std::collections::BTreeMap;
+
+use heck::ToSnakeCase;
+use prost_types::field_descriptor_proto::{Label, Type};
+use prost_types::{DescriptorProto, EnumDescriptorProto, FileDescriptorSet};
+
+use crate::manifest::json_string;
+
+/// Message and enum descriptors of a descriptor set, by fully qualified name.
+pub(crate) struct Descriptors<'a> {
+    messages: BTreeMap<String, &'a DescriptorProto>,
+    enums: BTreeMap<String, &'a EnumDescriptorProto>,
+}
+
+impl<'a> Descriptors<'a> {
+    pub(crate) fn new(file_descriptor_set: &'a FileDescriptorSet) -> Self {
+        let mut descriptors = Self {
+            messages: BTreeMap::new(),
+            enums: BTreeMap::new(),
+        };
+        for file in &file_descriptor_set.file {
+            let package = file.package();
+            for message in &file.message_type {
+                descriptors.collect(package, message);
+            }
+            for enum_type in &file.enum_type {
+                descriptors
+                    .enums
+                    .insert(qualified_name(package, enum_type.name()), enum_type);
+            }
+        }
+        descriptors
+    }
+
+    fn collect(&mut self, scope: &str, message: &'a DescriptorProto) {
+        let name = qualified_name(scope, message.name());
+        for nested in &message.nested_type {
+            self.collect(&name, nested);
+        }
+        for enum_type in &message.enum_type {
+            self.enums
+                .insert(qualified_name(&name, enum_type.name()), enum_type);
+        }
+        self.messages.insert(name, message);
+    }
+
+    /// Renders an example JSON body of the message `type_name` (e.g. `.hello_world.HelloRequest`),
+    /// with enums as their first variant, by name when `string_enums` is set
+    pub(crate) fn example_json(&self, type_name: &str, string_enums: bool) -> String {
+        let mut out = String::new();
+        self.write_message(
+            &mut out,
+            type_name.trim_start_matches('.'),
+            string_enums,
+            &mut Vec::new(),
+            0,
+        );
+        out
+    }
+
+    /// Query string of the scalar and enum fields of `type_name`, as sent to GET routes
+    pub(crate) fn example_query(&self, type_name: &str, string_enums: bool) -> String {
+        let Some(message) = self.messages.get(type_name.trim_start_matches('.')) else {
+            return String::new();
+        };
+        example_fields(message)
+            .filter(|field| field.label() != Label::Repeated && field.r#type() != Type::Message)
+            .map(|field| {
+                let value = self.scalar_value(field, string_enums);
+                format!(
+                    "{}={}",
+                    field.name().to_snake_case(),
+                    value.trim_matches('"')
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("&")
+    }
+
+    fn write_message(
+        &self,
+        out: &mut String,
+        name: &str,
+        string_enums: bool,
+        stack: &mut Vec<String>,
+        depth: usize,
+    ) {
+        // Unknown (e.g. well-known) and recursive messages are shown empty
+        let Some(message) = self
+            .messages
+            .get(name)
+            .filter(|_| !stack.iter().any(|seen| seen == name))
+        else {
+            out.push_str("{}");
+            return;
+        };
+        let fields = example_fields(message).collect::<Vec<_>>();
+        if fields.is_empty() {
+            out.push_str("{}");
+            return;
+        }
+
+        stack.push(name.to_string());
+        let indent = "  ".repeat(depth + 1);
+        out.push_str("{\n");
+        for (index, field) in fields.iter().enumerate() {
+            if index > 0 {
+                out.push_str(",\n");
+            }
+            out.push_str(&indent);
+            out.push_str(&json_string(&field.name().to_snake_case()));
+            out.push_str(": ");
+
+            let map_entry = self
+                .messages
+                .get(field.type_name().trim_start_matches('.'))
+                .filter(|entry| {
+                    entry
+                        .options
+                        .as_ref()
+                        .is_some_and(|options| options.map_entry())
+                });
+            if let Some(entry) = map_entry {
+                out.push_str("{ \"key\": ");
+                match entry.field.get(1) {
+                    Some(value) => self.write_value(out, value, string_enums, stack, depth + 1),
+                    None => out.push_str("null"),
+                }
+                out.push_str(" }");
+            } else if field.label() == Label::Repeated {
+                out.push('[');
+                self.write_value(out, field, string_enums, stack, depth + 1);
+                out.push(']');
+            } else {
+                self.write_value(out, field, string_enums, stack, depth + 1);
+            }
+        }
+        out.push('\n');
+        out.push_str(&"  ".repeat(depth));
+        out.push('}');
+        stack.pop();
+    }
+
+    fn write_value(
+        &self,
+        out: &mut String,
+        field: &prost_types::FieldDescriptorProto,
+        string_enums: bool,
+        stack: &mut Vec<String>,
+        depth: usize,
+    ) {
+        if field.r#type() == Type::Message {
+            let name = field.type_name().trim_start_matches('.');
+            self.write_message(out, name, string_enums, stack, depth);
+        } else {
+            out.push_str(&self.scalar_value(field, string_enums));
+        }
+    }
+
+    fn scalar_value(
+        &self,
+        field: &prost_types::FieldDescriptorProto,
+        string_enums: bool,
+    ) -> String {
+        match field.r#type() {
+            Type::String => json_string(field.name()),
+            Type::Bytes => json_string(""),
+            Type::Bool => "false".to_string(),
+            Type::Double | Type::Float => "0.0".to_string(),
+            Type::Enum => {
+                let first = self
+                    .enums
+                    .get(field.type_name().trim_start_matches('.'))
+                    .and_then(|enum_type| enum_type.value.first());
+                match first {
+                    Some(value) if string_enums => json_string(value.name()),
+                    Some(value) => value.number().to_string(),
+                    None => "0".to_string(),
+                }
+            }
+            _ => "0".to_string(),
+        }
+    }
+}
+
+/// The curl command calling `verb path` with an example `body`, or `query` for GET routes.
+pub(crate) fn curl_command(verb: &str, path: &str, body: &str, query: &str) -> String {
+    if verb == "GET" {
+        let query = if query.is_empty() {
+            String::new()
+        } else {
+            format!("?{query}")
+        };
+        format!("curl 'http://localhost:3000{path}{query}'")
+    } else {
+        let body = body
+            .split('\n')
+            .map(str::trim)
+            .collect::<String>()
+            .replace('\'', "'\\''");
+        format!(
+            "curl -X {verb} 'http://localhost:3000{path}' \\\n  -H 'Content-Type: application/json' \\\n  -d '{body}'"
+        )
+    }
+}
+
+// Fields of a message shown in examples; oneof members are left out, since only one of
+// them can be set, except for proto3 `optional` fields
+fn example_fields(
+    message: &DescriptorProto,
+) -> impl Iterator<Item = &prost_types::FieldDescriptorProto> {
+    message
+        .field
+        .iter()
+        .filter(|field| field.oneof_index.is_none() || field.proto3_optional())
+}
+
+fn qualified_name(scope: &str, name: &str) -> String {
+    if scope.is_empty() {
+        name.to_string()
+    } else {
+        format!("{scope}.{name}")
+    }
+}
@@ -0,0 +1,149 @@
+//!
+//! Experimental GraphQL facade: async-graphql query and mutation roots resolving unary
+//! methods through the tonic service trait, with messages as JSON scalars.
+//!
+use heck::ToSnakeCase;
+use quote::quote;
+
+use crate::rust_type_tokens;
+
+/// Generates the GraphQL roots and schema of `service`, whose methods are served with the
+/// HTTP `verbs`: GET methods become queries, the others mutations.
+pub(crate) fn service_code(
+    service: &prost_build::Service,
+    verbs: &[String],
+) -> proc_macro2::TokenStream {
+    let snake_case_name = service.name.to_snake_case();
+    let trait_name = quote::format_ident!("{}", service.name);
+    let server_module = quote::format_ident!("{}_server", snake_case_name);
+    let query_name = quote::format_ident!("{}GraphqlQuery", service.name);
+    let mutation_name = quote::format_ident!("{}GraphqlMutation", service.name);
+    let schema_fn = quote::format_ident!("{}_graphql_schema", snake_case_name);
+    let service_path = if service.package.is_empty() {
+        service.proto_name.clone()
+    } else {
+        format!("{}.{}", service.package, service.proto_name)
+    };
+    let query_doc = format!("GraphQL queries of `{service_path}`, one per unary GET method.");
+    let mutation_doc =
+        format!("GraphQL mutations of `{service_path}`, one per unary non-GET method.");
+    let schema_doc = format!(
+        "Builds the GraphQL schema of `{service_path}`, resolving through `server`. \
+         Requests and responses are JSON scalars with the shape of the HTTP bodies, and an \
+         `http::HeaderMap` in the request data is passed on as gRPC metadata, as the HTTP handlers \
+         convert headers."
+    );
+
+    // Streaming methods have no single result to resolve to
+    let (queries, mutations): (Vec<_>, Vec<_>) = service
+        .methods
+        .iter()
+        .zip(verbs)
+        .filter(|(method, _)| !method.client_streaming && !method.server_streaming)
+        .partition(|(_, verb)| verb.as_str() == "GET");
+    let queries = queries
+        .into_iter()
+        .map(|(method, _)| resolver(method, &server_module, &trait_name));
+    let mutations = mutations
+        .into_iter()
+        .map(|(method, _)| resolver(method, &server_module, &trait_name))
+        .collect::<Vec<_>>();
+
+    // GraphQL objects need at least one field, so a service without mutations uses
+    // async-graphql's empty mutation root
+    let (mutation_root, mutation_type, mutation_value) = if mutations.is_empty() {
+        (
+            quote! {},
+            quote! { ::async_graphql::EmptyMutation },
+            quote! { ::async_graphql::EmptyMutation },
+        )
+    } else {
+        (
+            quote! {
+                #[allow(dead_code)]
+                #[doc = #mutation_doc]
+                pub struct #mutation_name<T> {
+                    server: ::std::sync::Arc<T>,
+                }
+
+                #[::async_graphql::Object]
+                impl<T: #server_module::#trait_name> #mutation_name<T> {
+                    #(#mutations)*
+                }
+            },
+            quote! { #mutation_name<T> },
+            quote! { #mutation_name { server: server.clone() } },
+        )
+    };
+
+    quote! {
+        #[allow(dead_code)]
+        #[doc = #query_doc]
+        pub struct #query_name<T> {
+            server: ::std::sync::Arc<T>,
+        }
+
+        #[::async_graphql::Object]
+        impl<T: #server_module::#trait_name> #query_name<T> {
+            /// Fully qualified name of the gRPC service
+            async fn service(&self) -> &'static str {
+                #service_path
+            }
+
+            #(#queries)*
+        }
+
+        #mutation_root
+
+        #[allow(dead_code)]
+        #[doc = #schema_doc]
+        pub fn #schema_fn<T: #server_module::#trait_name>(server: T) -> ::async_graphql::Schema<#query_name<T>, #mutation_type, ::async_graphql::EmptySubscription> {
+            let server = ::std::sync::Arc::new(server);
+            ::async_graphql::Schema::build(
+                #query_name { server: server.clone() },
+                #mutation_value,
+                ::async_graphql::EmptySubscription,
+            )
+            .finish()
+        }
+    }
+}
+
+// Resolver calling `method` on the server, with rejected headers and gRPC errors as GraphQL
+// errors carrying the status code in their `code` extension
+fn resolver(
+    method: &prost_build::Method,
+    server_module: &proc_macro2::Ident,
+    trait_name: &proc_macro2::Ident,
+) -> proc_macro2::TokenStream {
+    let method_name = quote::format_ident!("{}", method.name);
+    let request_type = rust_type_tokens(&method.input_type);
+    let response_type = rust_type_tokens(&method.output_type);
+    let doc = format!("Calls `{}`.", method.proto_name);
+
+    quote! {
+        #[doc = #doc]
+        async fn #method_name(
+            &self,
+            ctx: &::async_graphql::Context<'_>,
+            request: ::async_graphql::Json<#request_type>,
+        ) -> ::async_graphql::Result<::async_graphql::Json<#response_type>> {
+            let headers = ctx.data_opt::<::http::header::HeaderMap>().cloned().unwrap_or_default();
+            let metadata = metadata_from_headers(headers, None).map_err(|(_, error_body)| {
+                ::async_graphql::ErrorExtensions::extend_with(
+                    ::async_graphql::Error::new(error_body.error.message),
+                    |_, extensions| extensions.set("code", error_body.error.code),
+                )
+            })?;
+            let request = ::tonic::Request::from_parts(metadata, ::tonic::Extensions::new(), request.0);
+
+            match <T as #server_module::#trait_name>::#method_name(&self.server, request).await {
+                Ok(response) => Ok(::async_graphql::Json(response.into_inner())),
+                Err(status) => Err(::async_graphql::ErrorExtensions::extend_with(
+                    ::async_graphql::Error::new(status.message()),
+                    |_, extensions| extensions.set("code", error_code(status.code())),
+                )),
+            }
+        }
+    }
+}
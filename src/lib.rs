@@ -80,24 +80,41 @@
 
 #[cfg(feature = "validate")]
 mod ver {
-    pub const AXUM_VERSION: &str = "0.8.3";
-    pub const TONIC_VERSION: &str = "0.13.0";
-    pub const HTTP_VERSION: &str = "1.3.1";
+    // Ranges of versions the generated code is known to work with
+    pub const AXUM_VERSION_REQ: &str = ">=0.8.3, <0.9";
+    pub const TONIC_VERSION_REQ: &str = ">=0.14.0, <0.15";
+    pub const HTTP_VERSION_REQ: &str = ">=1.3.1, <2";
+    pub const ACTIX_WEB_VERSION_REQ: &str = ">=4.0.0, <5";
 }
 
-use heck::ToSnakeCase;
+use heck::{ToKebabCase, ToShoutySnakeCase, ToSnakeCase};
 use prost_build::ServiceGenerator;
 use quote::quote;
 
 use prost::Message;
 use prost_types::{
     field_descriptor_proto::{Label, Type},
-    DescriptorProto, FieldDescriptorProto, FileDescriptorProto, FileDescriptorSet,
+    DescriptorProto, EnumDescriptorProto, FieldDescriptorProto, FileDescriptorProto,
+    FileDescriptorSet,
 };
 
+mod actix;
+mod annotations;
+pub mod cli;
+#[cfg(feature = "doc")]
+mod doc_examples;
+mod graphql;
+mod manifest;
+mod mock;
+mod options;
+pub mod plugin;
+mod test_client;
 #[cfg(feature = "validate")]
 pub(crate) mod vercheck;
 
+// Callback set by `BridgeGenerator::with_handler_template`
+type HandlerTemplate = Box<dyn Fn(&HandlerContext) -> Option<proc_macro2::TokenStream>>;
+
 /// A service generator that creates web endpoints for gRPC services using Axum.
 ///
 /// The `WebGenerator` wraps another service generator and extends its functionality
@@ -156,11 +173,285 @@ pub struct BridgeGenerator {
     /// Whether to enable automatic string enum deserialization
     enable_string_enums: bool,
 
+    /// Whether field-specific enum functions delegate to shared per-enum lookup tables
+    enable_enum_tables: bool,
+
+    /// Maximum length of the field/enum identifiers embedded in generated enum function
+    /// names; longer identifiers are abbreviated with a stable hash suffix
+    max_function_id_len: Option<usize>,
+
+    /// Whether handlers expose an `HttpCancellation` signal that fires when the HTTP
+    /// request is abandoned
+    enable_cancellation: bool,
+
+    /// Prefix that request headers must carry to be forwarded as metadata
+    metadata_header_prefix: Option<String>,
+
+    /// Whether `Authorization: Bearer` tokens are extracted into `token` metadata
+    enable_bearer_token: bool,
+
+    /// Methods (`package.Service.Method`) or services (`package.Service`) that reject
+    /// requests without a bearer token
+    bearer_token_required: Vec<String>,
+
+    /// How headers/metadata entries sharing one name are combined
+    duplicate_header_policy: DuplicateHeaderPolicy,
+
+    /// Metadata key that receives the address of the HTTP client
+    client_ip_metadata_key: Option<String>,
+
+    /// Number of proxies in front of the bridge appending to `X-Forwarded-For`
+    trusted_proxy_hops: usize,
+
+    /// Maximum combined size of the request metadata (names and values, in bytes)
+    max_metadata_size: Option<usize>,
+
+    /// Maximum length of a single request header value forwarded as metadata
+    max_header_value_len: Option<usize>,
+
+    /// Whether `grpc-timeout` / `x-request-timeout` headers bound the service call
+    enable_timeout_headers: bool,
+
+    /// JSON dialects selectable through the `x-api-version` request header
+    api_versions: Vec<(String, JsonFieldCase)>,
+
+    /// JSON dialects of some services or methods only, keyed by method selector
+    method_api_versions: Vec<(String, (String, JsonFieldCase))>,
+
+    /// Whether `google.api.routing` annotations are forwarded as `x-goog-request-params`
+    enable_routing_headers: bool,
+
+    /// Whether `g2h.route` method options configure the generated routes
+    enable_route_options: bool,
+
+    /// Annotations of annotated methods, keyed by gRPC method path
+    method_annotations: std::collections::HashMap<String, annotations::MethodAnnotations>,
+
+    /// Messages containing `g2h.sensitive` fields, directly or nested, with the fields to mask
+    masked_messages: std::collections::BTreeMap<String, Vec<annotations::FieldAnnotations>>,
+
+    /// Messages containing map fields, directly or nested, with the fields leading to them
+    map_messages: std::collections::BTreeMap<String, Vec<annotations::FieldAnnotations>>,
+
+    /// Whether requests are validated before reaching the service
+    enable_request_validation: bool,
+
+    /// Messages containing required fields, directly or nested, with the fields to check
+    validated_messages: std::collections::BTreeMap<String, Vec<annotations::FieldAnnotations>>,
+
+    /// Whether requests are checked against their `buf.validate` constraints
+    enable_protovalidate: bool,
+
+    /// Messages with `buf.validate` constraints, directly or nested
+    protovalidate_messages: std::collections::BTreeSet<String>,
+
+    /// Upper bound on the duration of every service call
+    default_timeout: Option<std::time::Duration>,
+
+    /// Largest request body accepted by every route, in bytes
+    max_body_size: Option<usize>,
+
+    /// Whether gzip-encoded request bodies are decompressed before parsing
+    enable_gzip_requests: bool,
+
+    /// Number of requests every route handles at once before shedding load
+    concurrency_limit: Option<usize>,
+
+    /// Concurrency limits of specific services or methods, overriding `concurrency_limit`
+    method_concurrency_limits: Vec<(String, usize)>,
+
+    /// Whether a tonic client transport speaking the bridge's HTTP/JSON protocol is generated
+    enable_http_transport: bool,
+
+    /// Whether routers hold the service as `State<T>` by cloning it, instead of in an `Arc`
+    enable_clone_state: bool,
+
+    /// Headers always propagated unprefixed between HTTP and metadata
+    passthrough_headers: Vec<&'static str>,
+
+    /// Methods kept out of the generated routers (`package.Service/Method`)
+    excluded_methods: Vec<String>,
+
+    /// Latency above which requests are profiled for the `SlowRequestObserver`
+    slow_request_threshold: Option<std::time::Duration>,
+
+    /// Profiling thresholds of services and methods, overriding the global one
+    method_slow_request_thresholds: Vec<(String, std::time::Duration)>,
+
+    /// Whether handlers report every request to an `AccessLogger`
+    enable_access_log: bool,
+
+    /// When non-empty, the only services and methods given HTTP routes
+    included_methods: Vec<String>,
+
+    /// Naming convention of the default route paths
+    route_path_case: RoutePathCase,
+
+    /// Whether a trailing `.vN` package segment becomes a `/vN` path prefix
+    enable_version_prefix: bool,
+
+    /// Versioned route paths generated so far, with the gRPC path they serve
+    versioned_paths: std::collections::HashMap<String, String>,
+
+    /// Callback computing the default route paths
+    route_naming: Option<fn(&ServiceInfo, &MethodInfo) -> String>,
+
+    /// Whether HTTP verbs are inferred from method name prefixes
+    enable_verb_inference: bool,
+
+    /// Whether methods taking `google.protobuf.Empty` are served with `GET`
+    enable_empty_request_get: bool,
+
+    /// `Sunset` header value of deprecated methods
+    deprecation_sunset: Option<String>,
+
+    /// `Cache-Control` policies of services or methods
+    cache_policies: Vec<(String, String)>,
+
+    /// CORS policies, the first one applying to a service is used
+    cors_specs: Vec<CorsSpec>,
+
+    /// Whether generated routers serve `/healthz` and `/readyz`
+    enable_health_endpoints: bool,
+
+    /// Whether generated routers answer unknown paths with a JSON `NOT_FOUND` error
+    enable_not_found_fallback: bool,
+
+    /// Whether the `NOT_FOUND` fallback lists the method paths served by the router
+    list_known_paths: bool,
+
     /// File descriptor set for enum processing (only set when string enums are enabled)
     file_descriptor_set: Option<FileDescriptorSet>,
 
+    /// Whether a `tonic_reflection` server helper is generated
+    enable_reflection_service: bool,
+
     /// Path where file descriptor set should be written (for tonic_reflection support)
     descriptor_set_path: Option<std::path::PathBuf>,
+
+    /// Path where the JSON route manifest is written
+    route_manifest_path: Option<std::path::PathBuf>,
+
+    /// Directory for the generated code, and for relative artifact paths
+    out_dir: Option<std::path::PathBuf>,
+
+    /// Routes generated so far, recorded for the route manifest
+    manifest_routes: Vec<manifest::ManifestRoute>,
+
+    /// `cfg` predicate the generated HTTP code is gated behind, e.g. `feature = "http-bridge"`
+    cfg_gate: Option<String>,
+
+    /// Extra arguments passed to every protoc invocation
+    protoc_args: Vec<String>,
+
+    /// Proto paths of map fields generated as `BTreeMap`
+    btree_map_paths: Vec<String>,
+
+    /// Proto paths of bytes fields generated as `bytes::Bytes`
+    bytes_paths: Vec<String>,
+
+    /// Proto paths of message fields generated as `Box`
+    boxed_paths: Vec<String>,
+
+    /// Proto paths mapped to Rust types of other crates, with their Rust paths
+    extern_paths: Vec<(String, String)>,
+
+    /// Whether the well-known types are generated instead of taken from `prost_types`
+    compile_well_known_types: bool,
+
+    /// Whether incompatible dependency versions fail the build instead of warning
+    strict_validation: bool,
+
+    /// Whether the dependency versions were already checked
+    dependencies_checked: bool,
+
+    /// Version requirements replacing the built-in ones, by dependency name
+    #[cfg_attr(not(feature = "validate"), allow(dead_code))]
+    expected_versions: Vec<(&'static str, String)>,
+
+    /// Axum release the generated code is written for
+
+    /// Tonic release the generated code is written for
+
+    /// Whether the generated code has to run on wasm32 targets such as Cloudflare Workers
+    wasm_target: bool,
+
+    /// HTTP framework the generated handlers are written for
+    backend: Backend,
+
+    /// Hooks injecting code into every generated handler, in registration order
+    hooks: Vec<Box<dyn GeneratorHook>>,
+
+    /// Generators run after `inner`, in registration order
+    chained: Vec<Box<dyn ServiceGenerator>>,
+
+    /// Callback replacing built-in handlers with custom code
+    handler_template: Option<HandlerTemplate>,
+
+    /// Whether mock services and their routers are generated
+    enable_mocks: bool,
+
+    /// Whether in-process test clients are generated
+    enable_test_clients: bool,
+
+    /// Whether GraphQL schemas are generated
+    enable_graphql: bool,
+
+    /// Whether trailing metadata is sent as HTTP trailers
+    enable_trailers: bool,
+
+    /// Whether handlers replay responses for repeated `Idempotency-Key`s
+    enable_idempotency: bool,
+
+    /// Whether routers ensure and echo an `X-Request-Id`
+    enable_request_ids: bool,
+    enable_grpc_code_names: bool,
+
+    /// `g2h.service` options, keyed by `package.Service`
+    service_annotations: std::collections::HashMap<String, annotations::ServiceOptions>,
+
+    /// Access levels of services and methods, as `(service or method, level)`
+    access_levels: Vec<(String, AccessLevel)>,
+
+    /// Whether handlers call an `Authorizer`, with access levels from the builder or options
+    enable_authorizer: bool,
+
+    /// Whether handlers consult a `RateLimiter`
+    enable_rate_limiting: bool,
+
+    /// Whether handlers run the installed `MessageHooks` on request and response messages
+    enable_message_hooks: bool,
+
+    /// Whether responses are wrapped in a `{"data": ..., "error": ...}` envelope
+    enable_response_envelope: bool,
+
+    /// Whether every route answers `OPTIONS` requests itself
+    enable_preflight_responses: bool,
+
+    /// Whether routes are also served with a trailing slash
+    enable_trailing_slash: bool,
+
+    /// Whether the methods on the gRPC paths of a service share one dispatching route
+    enable_single_dispatch: bool,
+
+    /// Whether routes with a JSON body check its `Content-Type` themselves
+    enable_content_type_check: bool,
+
+    /// Whether the content type check reads bodies without `Content-Type` as JSON
+    allow_missing_content_type: bool,
+
+    /// Error code of JSON responses to requests with the wrong HTTP method
+    method_not_allowed_code: String,
+
+    /// HTTP verbs configured for methods, overriding the inferred ones
+    method_verbs: Vec<(String, HttpVerb)>,
+
+    /// Whether a service of the current package has a GET route, reset per package
+    package_has_get_routes: bool,
+
+    /// Route paths of the services of the current package, listed by the `NOT_FOUND`
+    /// fallback, reset per package
+    package_route_paths: Vec<String>,
 }
 
 impl BridgeGenerator {
@@ -174,25 +465,104 @@ impl BridgeGenerator {
     ///
     /// ```rust
     /// use g2h::BridgeGenerator;
-    /// let service_generator = BridgeGenerator::new(tonic_build::configure().service_generator());
+    /// let service_generator = BridgeGenerator::new(tonic_prost_build::configure().service_generator());
     /// ```
     ///
     pub fn new(inner: Box<dyn ServiceGenerator>) -> Self {
-        #[cfg(feature = "validate")]
-        {
-            let output =
-                vercheck::Deps::new(ver::AXUM_VERSION, ver::TONIC_VERSION, ver::HTTP_VERSION)
-                    .and_then(vercheck::Deps::validate);
-            if let Err(err) = output {
-                eprintln!("g2h: {err}");
-            }
-        }
-
         Self {
             inner,
             enable_string_enums: false,
+            enable_enum_tables: false,
+            max_function_id_len: None,
+            enable_cancellation: false,
+            metadata_header_prefix: None,
+            enable_bearer_token: false,
+            bearer_token_required: Vec::new(),
+            duplicate_header_policy: DuplicateHeaderPolicy::KeepAll,
+            client_ip_metadata_key: None,
+            trusted_proxy_hops: 0,
+            max_metadata_size: None,
+            max_header_value_len: None,
+            enable_timeout_headers: false,
+            api_versions: Vec::new(),
+            method_api_versions: Vec::new(),
+            enable_routing_headers: false,
+            enable_route_options: false,
+            method_annotations: std::collections::HashMap::new(),
+            masked_messages: std::collections::BTreeMap::new(),
+            map_messages: std::collections::BTreeMap::new(),
+            enable_request_validation: false,
+            validated_messages: std::collections::BTreeMap::new(),
+            enable_protovalidate: false,
+            protovalidate_messages: std::collections::BTreeSet::new(),
+            default_timeout: None,
+            max_body_size: None,
+            enable_gzip_requests: false,
+            concurrency_limit: None,
+            method_concurrency_limits: Vec::new(),
+            enable_http_transport: false,
+            enable_clone_state: false,
+            passthrough_headers: Vec::new(),
+            excluded_methods: Vec::new(),
+            slow_request_threshold: None,
+            method_slow_request_thresholds: Vec::new(),
+            enable_access_log: false,
+            included_methods: Vec::new(),
+            route_path_case: RoutePathCase::Dotted,
+            enable_version_prefix: false,
+            versioned_paths: std::collections::HashMap::new(),
+            route_naming: None,
+            enable_verb_inference: false,
+            enable_empty_request_get: false,
+            deprecation_sunset: None,
+            cache_policies: Vec::new(),
+            cors_specs: Vec::new(),
+            enable_health_endpoints: false,
+            enable_not_found_fallback: false,
+            list_known_paths: false,
             file_descriptor_set: None,
+            enable_reflection_service: false,
             descriptor_set_path: None,
+            route_manifest_path: None,
+            out_dir: None,
+            manifest_routes: Vec::new(),
+            cfg_gate: None,
+            protoc_args: Vec::new(),
+            btree_map_paths: Vec::new(),
+            bytes_paths: Vec::new(),
+            boxed_paths: Vec::new(),
+            extern_paths: Vec::new(),
+            compile_well_known_types: false,
+            strict_validation: false,
+            dependencies_checked: false,
+            expected_versions: Vec::new(),
+            wasm_target: false,
+            backend: Backend::Axum,
+            hooks: Vec::new(),
+            chained: Vec::new(),
+            handler_template: None,
+            enable_mocks: false,
+            enable_test_clients: false,
+            enable_graphql: false,
+            enable_trailers: false,
+            enable_idempotency: false,
+            enable_request_ids: false,
+            enable_grpc_code_names: false,
+            service_annotations: std::collections::HashMap::new(),
+            access_levels: Vec::new(),
+            enable_authorizer: false,
+            enable_rate_limiting: false,
+            enable_message_hooks: false,
+            enable_response_envelope: false,
+            enable_preflight_responses: false,
+            enable_trailing_slash: false,
+            enable_single_dispatch: false,
+            enable_content_type_check: false,
+            allow_missing_content_type: false,
+            method_not_allowed_code: "METHOD_NOT_ALLOWED".to_string(),
+            method_verbs: Vec::new(),
+            package_has_get_routes: false,
+            package_route_paths: Vec::new(),
         }
     }
 
@@ -213,11 +583,51 @@ impl BridgeGenerator {
     ///    .compile_protos(&["path/to/your.proto"], &["path/to/your/include"]);
     /// ```
     ///
-    pub fn build_prost_config(self) -> prost_build::Config {
-        let mut config = prost_build::Config::new();
+    pub fn build_prost_config(mut self) -> prost_build::Config {
+        let mut config = self.protoc_config();
+        if !self.btree_map_paths.is_empty() {
+            config.btree_map(std::mem::take(&mut self.btree_map_paths));
+        }
+        if !self.bytes_paths.is_empty() {
+            config.bytes(std::mem::take(&mut self.bytes_paths));
+        }
+        for path in std::mem::take(&mut self.boxed_paths) {
+            config.boxed(path);
+        }
+        for (proto_path, rust_path) in &self.extern_paths {
+            config.extern_path(proto_path, rust_path);
+        }
+        if self.compile_well_known_types {
+            config.compile_well_known_types();
+        }
+        let serde_default_paths = self.serde_default_paths();
         config
             .service_generator(Box::new(self))
             .type_attribute(".", "#[derive(serde::Serialize, serde::Deserialize)]");
+        for path in serde_default_paths {
+            config.type_attribute(path, "#[serde(default)]");
+        }
+        config
+    }
+
+    // Messages whose missing required fields are reported by the request validation rather
+    // than rejected by the JSON extractor
+    fn serde_default_paths(&self) -> Vec<String> {
+        if !self.enable_request_validation {
+            return Vec::new();
+        }
+        self.validated_messages
+            .keys()
+            .map(|message| format!(".{message}"))
+            .collect()
+    }
+
+    // A `prost_build::Config` passing the configured protoc arguments
+    fn protoc_config(&self) -> prost_build::Config {
+        let mut config = prost_build::Config::new();
+        for arg in &self.protoc_args {
+            config.protoc_arg(arg);
+        }
         config
     }
 
@@ -236,13 +646,19 @@ impl BridgeGenerator {
     /// ```
     ///
     pub fn compile_protos(
-        self,
+        mut self,
         protos: &[impl AsRef<std::path::Path>],
         includes: &[impl AsRef<std::path::Path>],
     ) -> Result<(), Box<dyn std::error::Error>> {
-        let file_descriptor_set = if self.enable_string_enums || self.descriptor_set_path.is_some()
+        self.check_dependencies()?;
+        self.resolve_artifact_paths()?;
+
+        let file_descriptor_set = if self.enable_string_enums
+            || self.descriptor_set_path.is_some()
+            || self.route_manifest_path.is_some()
+            || cfg!(feature = "doc")
         {
-            Some(prost_build::Config::new().load_fds(protos, includes)?)
+            Some(self.protoc_config().load_fds(protos, includes)?)
         } else {
             None
         };
@@ -253,13 +669,26 @@ impl BridgeGenerator {
             std::fs::write(path, bytes)?;
         }
 
+        if self.needs_annotations() {
+            self.load_annotations(protos, includes)?;
+        }
+        self.check_options()?;
+
+        if self.route_manifest_path.is_some() || cfg!(feature = "doc") {
+            self.file_descriptor_set = file_descriptor_set.clone();
+        }
+
         if !self.enable_string_enums {
             let descriptor_path = self.descriptor_set_path.clone();
+            let out_dir = self.out_dir.clone();
             let mut config = self.build_prost_config();
             // Add descriptor set path to config if provided
             if let Some(path) = descriptor_path {
                 config.file_descriptor_set_path(path);
             }
+            if let Some(out_dir) = out_dir {
+                config.out_dir(out_dir);
+            }
             return Ok(config.compile_protos(protos, includes)?);
         }
 
@@ -267,9 +696,13 @@ impl BridgeGenerator {
         let file_descriptor_set = file_descriptor_set.unwrap(); // Safe because enable_string_enums is true
         let mut generator = self;
         generator.file_descriptor_set = Some(file_descriptor_set.clone());
+        let out_dir = generator.out_dir.clone();
         let mut final_config = generator
             .build_enum_config()
             .build_prost_config_with_descriptors(&file_descriptor_set);
+        if let Some(out_dir) = out_dir {
+            final_config.out_dir(out_dir);
+        }
 
         final_config.compile_protos(protos, includes)?;
 
@@ -388,10 +821,16 @@ impl BridgeGenerator {
         protos: &[impl AsRef<std::path::Path>],
         includes: &[impl AsRef<std::path::Path>],
     ) -> Result<(), Box<dyn std::error::Error>> {
+        self.check_dependencies()?;
+        self.resolve_artifact_paths()?;
+
         // Load file descriptor set if needed for string enums or descriptor set writing
-        let file_descriptor_set = if self.enable_string_enums || self.descriptor_set_path.is_some()
+        let file_descriptor_set = if self.enable_string_enums
+            || self.descriptor_set_path.is_some()
+            || self.route_manifest_path.is_some()
+            || cfg!(feature = "doc")
         {
-            Some(prost_build::Config::new().load_fds(protos, includes)?)
+            Some(self.protoc_config().load_fds(protos, includes)?)
         } else {
             None
         };
@@ -402,13 +841,30 @@ impl BridgeGenerator {
             std::fs::write(path, bytes)?;
         }
 
+        // Read method annotations, which prost_types does not retain
+        if self.needs_annotations() {
+            self.load_annotations(protos, includes)?;
+        }
+        self.check_options()?;
+
+        // The route manifest and doc examples need message descriptors even without string enums
+        if self.route_manifest_path.is_some() || cfg!(feature = "doc") {
+            self.file_descriptor_set = file_descriptor_set.clone();
+        }
+
         // Add default serde derives if not already present
         config.type_attribute(".", "#[derive(serde::Serialize, serde::Deserialize)]");
+        for path in self.serde_default_paths() {
+            config.type_attribute(path, "#[serde(default)]");
+        }
 
         // Add descriptor set path to config if provided
         if let Some(ref path) = self.descriptor_set_path {
             config.file_descriptor_set_path(path);
         }
+        if let Some(ref out_dir) = self.out_dir {
+            config.out_dir(out_dir);
+        }
 
         // If string enums are not enabled, set the service generator and compile directly
         if !self.enable_string_enums {
@@ -423,10 +879,15 @@ impl BridgeGenerator {
         self.file_descriptor_set = Some(file_descriptor_set.clone());
 
         // Apply enum string support by detecting enum fields automatically
-        config = EnumConfig::add_enum_string_support_static(config, &file_descriptor_set);
+        let local_descriptors = self.local_descriptors(&file_descriptor_set);
+        config = EnumConfig::add_enum_string_support_static(
+            config,
+            &local_descriptors,
+            self.max_function_id_len,
+        );
 
         // Add skip nulls support by default
-        config = EnumConfig::add_skip_nulls_support_static(config, &file_descriptor_set);
+        config = EnumConfig::add_skip_nulls_support_static(config, &local_descriptors);
 
         // Set the service generator with the file descriptor set at the end
         config.service_generator(Box::new(self));
@@ -437,6 +898,134 @@ impl BridgeGenerator {
         Ok(())
     }
 
+    ///
+    /// Compile an encoded `FileDescriptorSet` produced by buf or another build system,
+    /// without running protoc.
+    ///
+    /// The descriptor set goes through the same pipeline as in
+    /// [`compile_protos`](Self::compile_protos): string enums and skip nulls when enabled,
+    /// proto annotations and the HTTP bridge. It has to include imports (`--include_imports`
+    /// with protoc, the default for `buf build`), and is passed encoded so that the custom
+    /// options `prost_types` would drop are still available.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use g2h::BridgeGenerator;
+    ///
+    /// // buf build -o descriptors.bin
+    /// BridgeGenerator::with_tonic_build()
+    ///     .with_string_enums()
+    ///     .compile_fds(std::fs::read("descriptors.bin")?)?;
+    /// ```
+    ///
+    pub fn compile_fds(
+        self,
+        file_descriptor_set: impl AsRef<[u8]>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let (mut config, file_descriptor_set) = self.prepare_fds(file_descriptor_set.as_ref())?;
+        config.compile_fds(file_descriptor_set)?;
+
+        Ok(())
+    }
+
+    ///
+    /// Render the code generated for an encoded `FileDescriptorSet` without writing it,
+    /// e.g. to snapshot-test that a g2h upgrade doesn't change the generated code.
+    ///
+    /// The descriptor set goes through the pipeline of [`compile_fds`](Self::compile_fds),
+    /// and every file it contains is rendered, by output file name. Dependency versions
+    /// aren't checked, since rendering happens outside of build scripts.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use g2h::BridgeGenerator;
+    ///
+    /// let sources = BridgeGenerator::with_tonic_build()
+    ///     .with_string_enums()
+    ///     .render(std::fs::read("descriptors.bin")?)?;
+    /// insta::assert_snapshot!(sources.get("hello_world.rs").unwrap());
+    /// ```
+    ///
+    pub fn render(
+        mut self,
+        file_descriptor_set: impl AsRef<[u8]>,
+    ) -> Result<GeneratedSources, Box<dyn std::error::Error>> {
+        self.dependencies_checked = true;
+        let files = self.generate_files(file_descriptor_set.as_ref(), None)?;
+
+        Ok(GeneratedSources {
+            files: files.into_iter().collect(),
+        })
+    }
+
+    /// Generates the code of `files_to_generate` (all files when `None`) from an encoded
+    /// `FileDescriptorSet`, returning the contents of every output file by file name
+    pub(crate) fn generate_files(
+        mut self,
+        file_descriptor_set: &[u8],
+        files_to_generate: Option<&[String]>,
+    ) -> Result<Vec<(String, String)>, Box<dyn std::error::Error>> {
+        // Plugins run outside of any crate, and their stdout carries the generated files
+        self.dependencies_checked = true;
+        let (mut config, file_descriptor_set) = self.prepare_fds(file_descriptor_set)?;
+        let requests = file_descriptor_set
+            .file
+            .into_iter()
+            .filter(|file| {
+                files_to_generate.map_or(true, |names| names.iter().any(|name| name == file.name()))
+            })
+            .map(|file| {
+                (
+                    prost_build::Module::from_protobuf_package_name(file.package()),
+                    file,
+                )
+            })
+            .collect();
+        let mut files = config
+            .generate(requests)?
+            .into_iter()
+            .map(|(module, contents)| (module.to_file_name_or("_"), contents))
+            .collect::<Vec<_>>();
+        files.sort();
+        Ok(files)
+    }
+
+    // Runs the compile_protos pipeline up to code generation on an encoded descriptor set
+    fn prepare_fds(
+        mut self,
+        bytes: &[u8],
+    ) -> Result<(prost_build::Config, FileDescriptorSet), Box<dyn std::error::Error>> {
+        self.check_dependencies()?;
+        self.resolve_artifact_paths()?;
+
+        let file_descriptor_set = FileDescriptorSet::decode(bytes)?;
+
+        if let Some(ref path) = self.descriptor_set_path {
+            std::fs::write(path, bytes)?;
+        }
+
+        if self.needs_annotations() {
+            self.apply_annotations(annotations::decode_annotations(bytes)?);
+        }
+        self.check_options()?;
+
+        self.file_descriptor_set = Some(file_descriptor_set.clone());
+        let out_dir = self.out_dir.clone();
+        let mut config = if self.enable_string_enums {
+            self.build_enum_config()
+                .build_prost_config_with_descriptors(&file_descriptor_set)
+        } else {
+            self.build_prost_config()
+        };
+        if let Some(out_dir) = out_dir {
+            config.out_dir(out_dir);
+        }
+
+        Ok((config, file_descriptor_set))
+    }
+
     ///
     /// Creates an EnumConfig instance for advanced enum configuration.
     ///
@@ -499,1109 +1088,9269 @@ impl BridgeGenerator {
     }
 
     ///
-    /// Set the path where the file descriptor set should be written.
-    /// This is useful for tonic_reflection support which requires access to the
-    /// file descriptor set at runtime.
+    /// Generate string enum support in table-driven mode.
+    ///
+    /// By default every enum field gets its own serializer/deserializer bodies, which
+    /// becomes very expensive to compile for packages with many or very large enums.
+    /// In table-driven mode a static `(value, name)` table is emitted once per enum
+    /// type, and the field-specific functions become one-line wrappers around a small
+    /// set of shared lookup helpers.
+    ///
+    /// The generated function names and JSON behaviour are identical in both modes.
+    /// Implies [`with_string_enums`](Self::with_string_enums).
     ///
     /// # Example
     ///
     /// ```rust,ignore
     /// use g2h::BridgeGenerator;
-    /// use std::env;
-    /// use std::path::PathBuf;
     ///
-    /// let out_dir = PathBuf::from(env::var("OUT_DIR")?);
     /// BridgeGenerator::with_tonic_build()
-    ///     .with_string_enums()
-    ///     .file_descriptor_set_path(out_dir.join("service_descriptor.bin"))
+    ///     .with_enum_tables()
     ///     .compile_protos(&["proto/service.proto"], &["proto"])?;
     /// ```
     ///
-    pub fn file_descriptor_set_path(mut self, path: impl AsRef<std::path::Path>) -> Self {
-        self.descriptor_set_path = Some(path.as_ref().to_path_buf());
+    pub fn with_enum_tables(mut self) -> Self {
+        self.enable_string_enums = true;
+        self.enable_enum_tables = true;
         self
     }
 
-    /// Generate enum deserializer code for a specific package with field-specific serializers
     ///
-    /// This method creates type-safe enum serialization functions that prevent conflicts
-    /// between different enums that might have the same integer values. Each enum field
-    /// gets its own dedicated serializer/deserializer functions.
+    /// Limit the length of identifiers embedded in generated enum function names.
     ///
-    /// # Arguments
-    /// * `file_descriptor_set` - The protobuf file descriptor set containing enum definitions
-    /// * `target_package` - The specific package to generate serializers for
+    /// Field-specific enum functions are named after the full message path of the field
+    /// (e.g. `deserialize_option_outer_inner_deeply_nested_field_from_string`), which gets
+    /// unwieldy for deeply nested messages. Identifiers longer than `max_len` are cut down
+    /// and suffixed with a stable hash of the full identifier, so names do not change
+    /// between builds. The mapping from abbreviated to full identifiers is emitted as doc
+    /// comments on the generated `enum_deserializer` module.
     ///
-    /// # Returns
-    /// A string containing the generated Rust code with field-specific enum functions
-    fn generate_package_specific_enum_deserializer_code(
-        file_descriptor_set: &FileDescriptorSet,
-        target_package: &str,
-    ) -> String {
-        let package_enum_fields =
-            Self::extract_package_enum_fields_static(file_descriptor_set, target_package);
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use g2h::BridgeGenerator;
+    ///
+    /// BridgeGenerator::with_tonic_build()
+    ///     .with_string_enums()
+    ///     .with_max_function_id_len(24)
+    ///     .compile_protos(&["proto/service.proto"], &["proto"])?;
+    /// ```
+    ///
+    /// A `max_len` below 9, the length of the hash suffix alone, fails the build.
+    ///
+    pub fn with_max_function_id_len(mut self, max_len: usize) -> Self {
+        self.max_function_id_len = Some(max_len);
+        self
+    }
 
-        if package_enum_fields.is_empty() {
-            return String::new();
-        }
+    ///
+    /// Generate a structured `404 Not Found` fallback for the routers of every package.
+    ///
+    /// Instead of axum's empty-body 404, requests for unknown paths receive the standard
+    /// `ErrorResponse` JSON with the code `NOT_FOUND`. Every package gains a
+    /// `not_found_fallback()` returning the fallback route, with the request ID and envelope
+    /// layers of the generated routers when those are enabled. As axum panics when merging
+    /// two routers which both have a fallback, the generated routers don't set it; attach
+    /// it once to the router combining them.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use g2h::BridgeGenerator;
+    ///
+    /// BridgeGenerator::with_tonic_build()
+    ///     .with_not_found_fallback()
+    ///     .compile_protos(&["proto/service.proto"], &["proto"])?;
+    /// ```
+    ///
+    /// ```rust,ignore
+    /// let app = user_service_handler(users)
+    ///     .merge(billing_service_handler(billing))
+    ///     .fallback_service(not_found_fallback());
+    /// ```
+    ///
+    /// A request to `POST /user.v1.UserService/CreateUsr` then yields:
+    /// ```json
+    /// {
+    ///   "error": {
+    ///     "code": "NOT_FOUND",
+    ///     "message": "No method is mapped to `/user.v1.UserService/CreateUsr`"
+    ///   }
+    /// }
+    /// ```
+    ///
+    pub fn with_not_found_fallback(mut self) -> Self {
+        self.enable_not_found_fallback = true;
+        self
+    }
 
-        let field_specific_functions =
-            Self::generate_field_specific_enum_functions_static(&package_enum_fields);
+    ///
+    /// List the method paths served by the routers of the package as `known_paths` in the
+    /// 404 of [`with_not_found_fallback`](Self::with_not_found_fallback), which this
+    /// enables, so clients hitting a mistyped path get actionable output.
+    ///
+    /// ```json
+    /// {
+    ///   "error": {
+    ///     "code": "NOT_FOUND",
+    ///     "message": "No method is mapped to `/user.v1.UserService/CreateUsr`",
+    ///     "known_paths": ["/user.v1.UserService/CreateUser", "/user.v1.UserService/GetUser"]
+    ///   }
+    /// }
+    /// ```
+    ///
+    pub fn with_not_found_known_paths(mut self) -> Self {
+        self.enable_not_found_fallback = true;
+        self.list_known_paths = true;
+        self
+    }
 
-        // Parse the generated string as token stream for quote
-        let field_functions_tokens: proc_macro2::TokenStream = field_specific_functions
+    ///
+    /// Use `code` (e.g. `UNIMPLEMENTED`) as the error code of the JSON responses to requests
+    /// with the wrong HTTP method, instead of `METHOD_NOT_ALLOWED`.
+    ///
+    /// Such requests are answered with `405 Method Not Allowed`, an `Allow` header listing
+    /// the method of the route and the usual error body, whatever the code.
+    ///
+    pub fn with_method_not_allowed_code(mut self, code: impl Into<String>) -> Self {
+        self.method_not_allowed_code = code.into();
+        self
+    }
+
+    ///
+    /// Use the names of the gRPC spec (e.g. `NOT_FOUND`) as the error codes of the JSON
+    /// responses, instead of the descriptions of `tonic::Code` (e.g. `Some requested entity
+    /// was not found`).
+    ///
+    /// This applies to the errors of the services and to the requests the generated
+    /// handlers reject themselves, with every backend and in GraphQL error extensions.
+    /// Clients matching on the descriptions have to be updated before enabling it.
+    ///
+    pub fn with_grpc_code_names(mut self) -> Self {
+        self.enable_grpc_code_names = true;
+        self
+    }
+
+    ///
+    /// Propagate HTTP request cancellation into the service.
+    ///
+    /// When the HTTP client disconnects, the server drops the handler future and with it
+    /// the in-flight service call. Work the service has handed off to other tasks is not
+    /// affected by that, so with this option every request additionally carries an
+    /// `HttpCancellation` in its extensions. It is signalled when the handler is dropped
+    /// before the call completes:
+    ///
+    /// ```rust,ignore
+    /// async fn get_report(&self, request: Request<GetReportRequest>) -> Result<Response<Report>, Status> {
+    ///     let cancellation = request.extensions().get::<HttpCancellation>().cloned();
+    ///     tokio::spawn(async move {
+    ///         if let Some(cancellation) = cancellation {
+    ///             cancellation.cancelled().await;
+    ///             // stop background work
+    ///         }
+    ///     });
+    ///     // ...
+    /// }
+    /// ```
+    ///
+    /// The generated code uses `tokio::sync::Notify`, so `tokio` must be a dependency of
+    /// the crate including the generated code.
+    ///
+    pub fn with_cancellation_signal(mut self) -> Self {
+        self.enable_cancellation = true;
+        self
+    }
+
+    ///
+    /// Generate route paths in the given casing instead of the gRPC path.
+    ///
+    /// With [`RoutePathCase::KebabCase`], `user.v1.UserService/CreateUser` is served on
+    /// `/user-service/create-user`. The cased paths leave the package out, so services with
+    /// the same name in different packages must not share a router.
+    ///
+    /// With [`RoutePathCase::Lowercase`], for proxies lowercasing paths, the package is kept
+    /// and every generated package gains a `lowercase_request_path` function. Wrapped around
+    /// the router, it lowercases request paths before routing, so mixed-case requests match
+    /// too.
+    ///
+    pub fn with_route_path_case(mut self, case: RoutePathCase) -> Self {
+        self.route_path_case = case;
+        self
+    }
+
+    ///
+    /// Serve methods of versioned packages under a version prefix.
+    ///
+    /// When the package ends in a version segment (`.v1`, `.v2`, `.v1beta1`, ...), the
+    /// segment is moved to the front of the path: `user.v1.UserService/CreateUser` is served
+    /// on `/v1/user.UserService/CreateUser` (or `/v1/user-service/create-user` with
+    /// [`RoutePathCase::KebabCase`]). Code generation panics when two methods of different
+    /// versions end up on the same path.
+    ///
+    pub fn with_version_prefix(mut self) -> Self {
+        self.enable_version_prefix = true;
+        self
+    }
+
+    // Splits a trailing version segment (`v1`, `v2beta1`, ...) off `package`
+    fn split_package_version(package: &str) -> Option<(&str, &str)> {
+        let (rest, version) = match package.rsplit_once('.') {
+            Some((rest, version)) => (rest, version),
+            None => ("", package),
+        };
+        let number = version.strip_prefix('v')?;
+        let digits = number.len()
+            - number
+                .trim_start_matches(|c: char| c.is_ascii_digit())
+                .len();
+        let suffix = &number[digits..];
+        let is_version = digits > 0
+            && (suffix.is_empty()
+                || ["alpha", "beta"].iter().any(|stage| {
+                    suffix
+                        .strip_prefix(stage)
+                        .is_some_and(|n| n.chars().all(|c| c.is_ascii_digit()))
+                }));
+        is_version.then_some((rest, version))
+    }
+
+    ///
+    /// Compute route paths with `naming` instead of the built-in conventions.
+    ///
+    /// The callback runs during code generation for every method and must return a path
+    /// starting with `/`. It replaces [`with_route_path_case`](Self::with_route_path_case)
+    /// and [`with_version_prefix`](Self::with_version_prefix) paths, while `g2h.route`
+    /// options still take precedence.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use g2h::{BridgeGenerator, MethodInfo, ServiceInfo};
+    ///
+    /// fn route_naming(service: &ServiceInfo, method: &MethodInfo) -> String {
+    ///     format!("/api/{}/{}", service.name.to_lowercase(), method.name.to_lowercase())
+    /// }
+    ///
+    /// BridgeGenerator::with_tonic_build()
+    ///     .with_route_naming(route_naming)
+    ///     .compile_protos(&["proto/service.proto"], &["proto"])?;
+    /// ```
+    ///
+    pub fn with_route_naming(mut self, naming: fn(&ServiceInfo, &MethodInfo) -> String) -> Self {
+        self.route_naming = Some(naming);
+        self
+    }
+
+    ///
+    /// Infer HTTP verbs from method name prefixes.
+    ///
+    /// `Get*` and `List*` methods are served with `GET`, taking their request fields from
+    /// the query string, `Delete*` with `DELETE`, `Update*` with `PATCH` and `Create*` with
+    /// `POST`. Other methods keep `POST`, and `g2h.route` verbs take precedence. Query string
+    /// requests only support scalar fields.
+    ///
+    pub fn with_verb_inference(mut self) -> Self {
+        self.enable_verb_inference = true;
+        self
+    }
+
+    ///
+    /// Serve methods taking `google.protobuf.Empty` with a bodyless `GET`.
+    ///
+    /// This suits status or configuration endpoints, which become cacheable and can be
+    /// fetched with a plain `curl`. `g2h.route` verbs take precedence.
+    ///
+    pub fn with_empty_request_get(mut self) -> Self {
+        self.enable_empty_request_get = true;
+        self
+    }
+
+    ///
+    /// Serve the method `method` with the HTTP verb `verb`, e.g. for protos shared with
+    /// other teams that cannot carry `g2h.route` options.
+    ///
+    /// `method` is written as `payments.v1.Payments/GetRefund` or
+    /// `payments.v1.Payments.GetRefund`. The verb takes precedence over inferred verbs, and
+    /// `g2h.route` verbs, when enabled, take precedence over it. `GET` methods take their
+    /// request fields from the query string.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use g2h::{BridgeGenerator, HttpVerb};
+    ///
+    /// BridgeGenerator::with_tonic_build()
+    ///     .with_method_verb("payments.v1.Payments/GetRefund", HttpVerb::Get)
+    ///     .compile_protos(&["proto/payments.proto"], &["proto"])?;
+    /// ```
+    ///
+    pub fn with_method_verb(mut self, method: impl AsRef<str>, verb: HttpVerb) -> Self {
+        self.method_verbs.push((
+            method.as_ref().trim_start_matches(['.', '/']).to_string(),
+            verb,
+        ));
+        self
+    }
+
+    // HTTP verb inferred from `method`, when enabled
+    fn inferred_verb(&self, method: &prost_build::Method) -> Option<&'static str> {
+        if self.enable_empty_request_get && is_empty_request(method) {
+            return Some("GET");
+        }
+        if !self.enable_verb_inference {
+            return None;
+        }
+        const PREFIXES: &[(&str, &str)] = &[
+            ("Get", "GET"),
+            ("List", "GET"),
+            ("Delete", "DELETE"),
+            ("Update", "PATCH"),
+            ("Create", "POST"),
+        ];
+        PREFIXES.iter().find_map(|(prefix, verb)| {
+            let rest = method.proto_name.strip_prefix(prefix)?;
+            // `Getaway` is not a `Get` method
+            (rest.is_empty() || rest.starts_with(|c: char| c.is_ascii_uppercase())).then_some(*verb)
+        })
+    }
+
+    // Default HTTP path of `method`, before `g2h.route` overrides
+    fn route_path(&self, service: &prost_build::Service, method: &prost_build::Method) -> String {
+        if let Some(naming) = self.route_naming {
+            let service_info = ServiceInfo {
+                package: service.package.clone(),
+                name: service.proto_name.clone(),
+            };
+            let method_info = MethodInfo {
+                name: method.proto_name.clone(),
+                input_type: method.input_proto_type.trim_start_matches('.').to_string(),
+                output_type: method.output_proto_type.trim_start_matches('.').to_string(),
+            };
+            let path = naming(&service_info, &method_info);
+            if !path.starts_with('/') {
+                panic!(
+                    "route path `{path}` of `{}.{}/{}` must start with `/`",
+                    service.package, service.proto_name, method.proto_name
+                );
+            }
+            return path;
+        }
+
+        let (version_prefix, package) = match Self::split_package_version(&service.package) {
+            Some((package, version)) if self.enable_version_prefix => {
+                (format!("/{version}"), package)
+            }
+            _ => (String::new(), service.package.as_str()),
+        };
+        let path = match self.route_path_case {
+            RoutePathCase::Dotted | RoutePathCase::Lowercase
+                if package.is_empty() && !version_prefix.is_empty() =>
+            {
+                format!("/{}/{}", service.proto_name, method.proto_name)
+            }
+            RoutePathCase::Dotted | RoutePathCase::Lowercase => {
+                format!("/{}.{}/{}", package, service.proto_name, method.proto_name)
+            }
+            RoutePathCase::KebabCase => format!(
+                "/{}/{}",
+                service.proto_name.to_kebab_case(),
+                method.proto_name.to_kebab_case()
+            ),
+            RoutePathCase::SnakeCase => format!(
+                "/{}/{}",
+                service.proto_name.to_snake_case(),
+                method.proto_name.to_snake_case()
+            ),
+        };
+        let path = format!("{version_prefix}{path}");
+        if self.route_path_case == RoutePathCase::Lowercase {
+            path.to_ascii_lowercase()
+        } else {
+            path
+        }
+    }
+
+    ///
+    /// Only forward request headers starting with `prefix` as gRPC metadata.
+    ///
+    /// By default every request header becomes metadata, which lets arbitrary client
+    /// headers reach interceptors that make decisions based on metadata. With a prefix
+    /// configured, other headers are ignored and the prefix is stripped from the ones
+    /// that are forwarded. Response metadata is re-prefixed on the way out.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use g2h::BridgeGenerator;
+    ///
+    /// // `x-grpc-tenant-id: acme` arrives in the service as `tenant-id: acme`
+    /// BridgeGenerator::with_tonic_build()
+    ///     .with_metadata_header_prefix("x-grpc-")
+    ///     .compile_protos(&["proto/service.proto"], &["proto"])?;
+    /// ```
+    ///
+    pub fn with_metadata_header_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.metadata_header_prefix = Some(prefix.into().to_ascii_lowercase());
+        self
+    }
+
+    ///
+    /// Always propagate the tracing and routing headers of a service mesh.
+    ///
+    /// The headers of the preset are forwarded between HTTP and gRPC metadata under their
+    /// own names in both directions, even when
+    /// [`with_metadata_header_prefix`](Self::with_metadata_header_prefix) restricts the other
+    /// headers. This keeps mesh features such as distributed tracing and retries working
+    /// across the protocol hop. Presets can be combined.
+    ///
+    /// Without a prefix every header already crosses the hop, so presets are only meaningful
+    /// together with one.
+    ///
+    /// # Panics
+    ///
+    /// Generating panics if a preset is set without a
+    /// [metadata header prefix](Self::with_metadata_header_prefix).
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use g2h::{BridgeGenerator, MeshPreset};
+    ///
+    /// BridgeGenerator::with_tonic_build()
+    ///     .with_metadata_header_prefix("x-grpc-")
+    ///     .with_mesh_preset(MeshPreset::Istio)
+    ///     .compile_protos(&["proto/service.proto"], &["proto"])?;
+    /// ```
+    ///
+    pub fn with_mesh_preset(mut self, preset: MeshPreset) -> Self {
+        for header in preset.headers() {
+            if !self.passthrough_headers.contains(header) {
+                self.passthrough_headers.push(header);
+            }
+        }
+        self
+    }
+
+    ///
+    /// Extract `Authorization: Bearer <token>` headers into metadata.
+    ///
+    /// The raw `authorization` header is forwarded as metadata (even when a
+    /// [metadata header prefix](Self::with_metadata_header_prefix) is configured) and the
+    /// bare token is inserted under the `token` key. A client-supplied `token` header is
+    /// never forwarded, so services can trust that entry to come from `Authorization`.
+    ///
+    pub fn with_bearer_token_extraction(mut self) -> Self {
+        self.enable_bearer_token = true;
+        self
+    }
+
+    ///
+    /// Reject requests without a bearer token for the given methods or services.
+    ///
+    /// Entries name a whole service (`user.v1.UserService`) or a single method, as
+    /// `user.v1.UserService/DeleteUser` or `user.v1.UserService.DeleteUser`. Matching
+    /// requests without an `Authorization: Bearer` header are answered with a
+    /// `401 Unauthorized` JSON error before the service is called. Implies
+    /// [`with_bearer_token_extraction`](Self::with_bearer_token_extraction).
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use g2h::BridgeGenerator;
+    ///
+    /// BridgeGenerator::with_tonic_build()
+    ///     .require_bearer_token(["user.v1.UserService.DeleteUser", "admin.v1.AdminService"])
+    ///     .compile_protos(&["proto/service.proto"], &["proto"])?;
+    /// ```
+    ///
+    pub fn require_bearer_token<I, S>(mut self, methods: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.enable_bearer_token = true;
+        self.bearer_token_required.extend(
+            methods
+                .into_iter()
+                .map(|method| method.into().trim_start_matches(['.', '/']).to_string()),
+        );
+        self
+    }
+
+    ///
+    /// Keep the given methods out of the generated HTTP routers.
+    ///
+    /// Entries name a method as `user.v1.UserService/AdminWipe` (the gRPC path) or
+    /// `user.v1.UserService.AdminWipe`. Excluded methods get no HTTP route, so sensitive
+    /// RPCs stay reachable over gRPC only.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use g2h::BridgeGenerator;
+    ///
+    /// BridgeGenerator::with_tonic_build()
+    ///     .exclude_methods(&["user.v1.UserService/AdminWipe"])
+    ///     .compile_protos(&["proto/service.proto"], &["proto"])?;
+    /// ```
+    ///
+    pub fn exclude_methods<I, S>(mut self, methods: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.excluded_methods.extend(
+            methods
+                .into_iter()
+                .map(|method| method.as_ref().trim_start_matches(['.', '/']).to_string()),
+        );
+        self
+    }
+
+    ///
+    /// Only give HTTP routes to the given services and methods.
+    ///
+    /// Entries name a whole service (`user.v1.UserService`) or a single method, as
+    /// `user.v1.UserService/GetUser` or `user.v1.UserService.GetUser`. Everything else stays
+    /// reachable over gRPC only, which suits exposing a small public surface from a large
+    /// internal API. [`exclude_methods`](Self::exclude_methods) still applies on top.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use g2h::BridgeGenerator;
+    ///
+    /// BridgeGenerator::with_tonic_build()
+    ///     .include_only(&["catalog.v1.CatalogService", "user.v1.UserService/GetUser"])
+    ///     .compile_protos(&["proto/service.proto"], &["proto"])?;
+    /// ```
+    ///
+    pub fn include_only<I, S>(mut self, entries: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.included_methods.extend(
+            entries
+                .into_iter()
+                .map(|entry| entry.as_ref().trim_start_matches(['.', '/']).to_string()),
+        );
+        self
+    }
+
+    // Concurrency limit of a method, the most specific configured one
+    fn concurrency_limit_of(&self, service_path: &str, method: &str) -> Option<usize> {
+        method_entry(&self.method_concurrency_limits, service_path, method)
+            .or_else(|| service_entry(&self.method_concurrency_limits, service_path))
+            .copied()
+            .or(self.concurrency_limit)
+    }
+
+    // Whether `service_path`'s method gets an HTTP route
+    fn is_method_exposed(&self, service_path: &str, method: &str) -> bool {
+        let included = self.included_methods.is_empty()
+            || self
+                .included_methods
+                .iter()
+                .any(|entry| selects_method(entry, service_path, method));
+        included
+            && !self
+                .excluded_methods
+                .iter()
+                .any(|entry| names_method(entry, service_path, method))
+    }
+
+    ///
+    /// Choose how multiple values for one header or metadata key are combined.
+    ///
+    /// The policy applies to request headers before they become metadata and to
+    /// response metadata before it becomes headers. See [`DuplicateHeaderPolicy`].
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use g2h::{BridgeGenerator, DuplicateHeaderPolicy};
+    ///
+    /// BridgeGenerator::with_tonic_build()
+    ///     .with_duplicate_header_policy(DuplicateHeaderPolicy::Join)
+    ///     .compile_protos(&["proto/service.proto"], &["proto"])?;
+    /// ```
+    ///
+    pub fn with_duplicate_header_policy(mut self, policy: DuplicateHeaderPolicy) -> Self {
+        self.duplicate_header_policy = policy;
+        self
+    }
+
+    ///
+    /// Forward the address of the HTTP client into the metadata entry `key`.
+    ///
+    /// The address is the peer of the connection, from axum's `ConnectInfo<SocketAddr>`
+    /// (which requires serving the app with
+    /// `into_make_service_with_connect_info::<SocketAddr>()`). Behind proxies, set their
+    /// number with [`with_trusted_proxy_hops`](Self::with_trusted_proxy_hops) to take it
+    /// from `X-Forwarded-For` instead. A client-sent header named `key` is always discarded.
+    ///
+    /// Compiling fails if `key` is not a valid ASCII metadata key, e.g. one ending in
+    /// `-bin`.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use g2h::BridgeGenerator;
+    ///
+    /// BridgeGenerator::with_tonic_build()
+    ///     .with_client_ip_metadata("x-client-ip")
+    ///     .compile_protos(&["proto/service.proto"], &["proto"])?;
+    /// ```
+    ///
+    pub fn with_client_ip_metadata(mut self, key: impl Into<String>) -> Self {
+        self.client_ip_metadata_key = Some(key.into().to_ascii_lowercase());
+        self
+    }
+
+    ///
+    /// Take the client address of [`with_client_ip_metadata`](Self::with_client_ip_metadata)
+    /// from `X-Forwarded-For`, as appended by `hops` trusted proxies in front of the bridge.
+    ///
+    /// Every proxy appends the address it received the request from, so the client address
+    /// is the `hops`-th entry from the right, and entries further left, which the client
+    /// may have sent itself, are ignored. Requests with fewer entries get no client address.
+    /// `0`, the default, uses the peer of the connection and ignores the header.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use g2h::BridgeGenerator;
+    ///
+    /// // Behind a single load balancer
+    /// BridgeGenerator::with_tonic_build()
+    ///     .with_client_ip_metadata("x-client-ip")
+    ///     .with_trusted_proxy_hops(1)
+    ///     .compile_protos(&["proto/service.proto"], &["proto"])?;
+    /// ```
+    ///
+    pub fn with_trusted_proxy_hops(mut self, hops: usize) -> Self {
+        self.trusted_proxy_hops = hops;
+        self
+    }
+
+    ///
+    /// Reject requests whose metadata exceeds `max_size` bytes in total.
+    ///
+    /// The size is the sum of the lengths of all names and values forwarded as metadata.
+    /// Oversized requests are answered with `431 Request Header Fields Too Large` and a
+    /// JSON error, instead of the opaque failure tonic backends produce when their own
+    /// metadata limits are hit.
+    ///
+    pub fn with_max_metadata_size(mut self, max_size: usize) -> Self {
+        self.max_metadata_size = Some(max_size);
+        self
+    }
+
+    ///
+    /// Reject requests with a header value longer than `max_len` bytes.
+    ///
+    /// Only headers that are forwarded as metadata are checked. Oversized requests are
+    /// answered with `431 Request Header Fields Too Large` and a JSON error.
+    ///
+    pub fn with_max_header_value_len(mut self, max_len: usize) -> Self {
+        self.max_header_value_len = Some(max_len);
+        self
+    }
+
+    ///
+    /// Honor `grpc-timeout` and `X-Request-Timeout` request headers as call deadlines.
+    ///
+    /// `grpc-timeout` uses the gRPC wire format (an integer followed by one of the units
+    /// `H`, `M`, `S`, `m`, `u`, `n`, e.g. `250m`). `X-Request-Timeout` accepts the same
+    /// format, or a plain integer number of milliseconds. When both are present,
+    /// `grpc-timeout` wins. A call that runs past its deadline is abandoned and answered
+    /// like a `DEADLINE_EXCEEDED` status, just as it would be for a gRPC client.
+    ///
+    /// The generated code uses `tokio::time::timeout`, so `tokio` must be a dependency of
+    /// the crate including the generated code.
+    ///
+    pub fn with_timeout_headers(mut self) -> Self {
+        self.enable_timeout_headers = true;
+        self
+    }
+
+    ///
+    /// Bound every service call of the generated routers by `timeout`.
+    ///
+    /// A call that runs longer is abandoned and answered with the standard JSON error for
+    /// `DEADLINE_EXCEEDED`, so routers no longer need to be wrapped in a timeout layer by hand.
+    /// Combined with [`with_timeout_headers`](Self::with_timeout_headers), the shorter of the
+    /// client deadline and `timeout` applies.
+    ///
+    /// The generated code uses `tokio::time::timeout`, so `tokio` must be a dependency of
+    /// the crate including the generated code.
+    ///
+    pub fn with_default_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.default_timeout = Some(timeout);
+        self
+    }
+
+    ///
+    /// Reject request bodies larger than `bytes` on every generated route.
+    ///
+    /// Oversized requests are answered with `413 Payload Too Large` and the standard JSON
+    /// error body, instead of axum's default limit of 2 MB and its plain text rejection.
+    ///
+    pub fn with_max_body_size(mut self, bytes: usize) -> Self {
+        self.max_body_size = Some(bytes);
+        self
+    }
+
+    ///
+    /// Accept request bodies sent with `Content-Encoding: gzip`.
+    ///
+    /// Such bodies are decompressed before JSON deserialization; corrupt ones are answered
+    /// with `400 Bad Request`. The decompressed body is bounded by
+    /// [`with_max_body_size`](Self::with_max_body_size), or 2 MB by default, and larger ones
+    /// get `413 Payload Too Large`.
+    ///
+    /// The generated code uses `flate2`, which must be a dependency of the crate including
+    /// the generated code.
+    ///
+    pub fn with_gzip_requests(mut self) -> Self {
+        self.enable_gzip_requests = true;
+        self
+    }
+
+    ///
+    /// Let every generated route handle at most `max` requests at once.
+    ///
+    /// Requests beyond the limit are shed right away with `503 Service Unavailable` and the
+    /// standard JSON error body, rather than queueing up behind a saturated service.
+    /// [`with_method_concurrency_limit`](Self::with_method_concurrency_limit) overrides the
+    /// limit of individual services or methods.
+    ///
+    /// The generated code uses `tokio::sync::Semaphore`, so `tokio` must be a dependency of
+    /// the crate including the generated code.
+    ///
+    pub fn with_concurrency_limit(mut self, max: usize) -> Self {
+        self.concurrency_limit = Some(max);
+        self
+    }
+
+    ///
+    /// Limit the concurrent requests of a service or method, see
+    /// [`with_concurrency_limit`](Self::with_concurrency_limit).
+    ///
+    /// `method` names a service (`user.v1.UserService`) or a method as
+    /// `user.v1.UserService/GetUser` or `user.v1.UserService.GetUser`. Method entries take
+    /// precedence over service entries, and both over the global limit.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use g2h::BridgeGenerator;
+    ///
+    /// BridgeGenerator::with_tonic_build()
+    ///     .with_concurrency_limit(256)
+    ///     .with_method_concurrency_limit("payments.v1.Payments/Refund", 16)
+    ///     .compile_protos(&["proto/service.proto"], &["proto"])?;
+    /// ```
+    ///
+    pub fn with_method_concurrency_limit(mut self, method: impl AsRef<str>, max: usize) -> Self {
+        self.method_concurrency_limits.push((
+            method.as_ref().trim_start_matches(['.', '/']).to_string(),
+            max,
+        ));
+        self
+    }
+
+    ///
+    /// Attach a CORS layer to the generated routers.
+    ///
+    /// Browser clients need CORS to call the JSON bridge. The layer answers preflight
+    /// requests and allows the HTTP verbs of the router's routes. Policies restricted with
+    /// [`CorsSpec::for_service`] apply to that service only; when several policies apply to
+    /// a service, the first one configured is used.
+    ///
+    /// Policies allowing credentials together with the `*` origin or header, which browsers
+    /// refuse for credentialed requests, fail the build.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use g2h::{BridgeGenerator, CorsSpec};
+    ///
+    /// BridgeGenerator::with_tonic_build()
+    ///     .with_cors(
+    ///         CorsSpec::new()
+    ///             .allow_origin("https://app.example.com")
+    ///             .allow_header("content-type")
+    ///             .allow_header("authorization"),
+    ///     )
+    ///     .compile_protos(&["proto/service.proto"], &["proto"])?;
+    /// ```
+    ///
+    pub fn with_cors(mut self, spec: CorsSpec) -> Self {
+        self.cors_specs.push(spec);
+        self
+    }
+
+    ///
+    /// Answer `OPTIONS` requests on every generated route with `204 No Content` and the
+    /// allowed methods, instead of `405 Method Not Allowed`.
+    ///
+    /// The handlers are part of the routes, so they stay in place when routes are mounted
+    /// with `{service}_handler_with()` or the generated router builders. Preflight
+    /// requests get the CORS headers of the policy configured with
+    /// [`BridgeGenerator::with_cors`] for the service, which keeps them working where its
+    /// layer is not applied. Without a policy, preflights get no
+    /// `Access-Control-Allow-Origin` header, so browsers still refuse cross-origin calls.
+    ///
+    pub fn with_preflight_responses(mut self) -> Self {
+        self.enable_preflight_responses = true;
+        self
+    }
+
+    ///
+    /// Serve every route on its path with a trailing slash too, e.g.
+    /// `/hello_world.Greeter/SayHello/`, for clients and proxies that append one.
+    ///
+    /// The route is registered a second time rather than rewritten by a middleware, since
+    /// layers of an axum router only run after routing. Paths ending with a slash or a
+    /// wildcard are registered once.
+    ///
+    pub fn with_trailing_slash(mut self) -> Self {
+        self.enable_trailing_slash = true;
+        self
+    }
+
+    ///
+    /// Register one route per service, e.g. `/hello_world.Greeter/{method}`, which looks up
+    /// the method in a `HashMap` keyed by path, instead of one route per method.
+    ///
+    /// Routes on other paths, e.g. relocated with `g2h.route` or a router builder, or
+    /// carrying path parameters, are still registered on their own. Requests under the
+    /// service path which match no method get the JSON 404 of
+    /// [`BridgeGenerator::with_not_found_fallback`] when enabled, and an empty 404
+    /// otherwise, so they don't reach a fallback set on the router.
+    ///
+    pub fn with_single_dispatch(mut self) -> Self {
+        self.enable_single_dispatch = true;
+        self
+    }
+
+    ///
+    /// Check the `Content-Type` of JSON request bodies in the generated routes instead of
+    /// relying on the checks of axum's `Json` extractor.
+    ///
+    /// `application/json` and `application/*+json` are accepted with any parameters, and
+    /// a `charset` parameter, when present, must be `utf-8`. Other content types and
+    /// charsets are answered with `415 Unsupported Media Type` and an
+    /// `InvalidArgument` JSON error. With `allow_missing`, bodies sent without a
+    /// `Content-Type` header are read as JSON instead of being rejected, for clients that
+    /// omit it.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use g2h::BridgeGenerator;
+    ///
+    /// BridgeGenerator::with_tonic_build()
+    ///     .with_content_type_check(false)
+    ///     .compile_protos(&["proto/service.proto"], &["proto"])?;
+    /// ```
+    ///
+    /// `Content-Type: application/json; charset=iso-8859-1` then yields a 415 with:
+    /// ```json
+    /// {
+    ///   "error": {
+    ///     "code": "Client specified an invalid argument",
+    ///     "message": "Unsupported charset `iso-8859-1`, request bodies must be UTF-8"
+    ///   }
+    /// }
+    /// ```
+    ///
+    pub fn with_content_type_check(mut self, allow_missing: bool) -> Self {
+        self.enable_content_type_check = true;
+        self.allow_missing_content_type = allow_missing;
+        self
+    }
+
+    ///
+    /// Generate a `health_router()` serving Kubernetes-style `/healthz` and `/readyz` probes
+    /// in every package.
+    ///
+    /// `/healthz` always answers `200 {"status":"ok"}`. `/readyz` asks the value given to
+    /// `health_router` through the `HttpReadiness` trait generated alongside, and answers
+    /// `200 {"status":"ready"}` or `503 {"status":"not_ready"}`. `()` is always ready. The
+    /// service routers are left as they are, so merge the health router once.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// #[tonic::async_trait]
+    /// impl HttpReadiness for Database {
+    ///     async fn is_ready(&self) -> bool {
+    ///         self.is_connected()
+    ///     }
+    /// }
+    ///
+    /// let app = greeter_handler(greeter).merge(health_router(database));
+    /// ```
+    ///
+    pub fn with_health_endpoints(mut self) -> Self {
+        self.enable_health_endpoints = true;
+        self
+    }
+
+    ///
+    /// Profile requests slower than `threshold` and report them to a `SlowRequestObserver`.
+    ///
+    /// Every generated package gains a `SlowRequestProfile` type with the time spent parsing
+    /// the JSON request, in the service and building the response, along with the request
+    /// and response body sizes, and a `SlowRequestObserver` receiving the profiles. Install
+    /// the observer as a request extension; requests are only profiled when it is present.
+    ///
+    /// [`with_method_slow_request_threshold`](Self::with_method_slow_request_threshold)
+    /// overrides the threshold of individual services or methods.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let observer = SlowRequestObserver::new(|profile| tracing::warn!(?profile, "slow request"));
+    /// let app = greeter_handler(server).layer(axum::Extension(observer));
+    /// ```
+    ///
+    pub fn with_slow_request_profiler(mut self, threshold: std::time::Duration) -> Self {
+        self.slow_request_threshold = Some(threshold);
+        self
+    }
+
+    ///
+    /// Profile the requests of a service or method slower than `threshold`, see
+    /// [`with_slow_request_profiler`](Self::with_slow_request_profiler).
+    ///
+    /// `method` names a service (`user.v1.UserService`) or a method as
+    /// `user.v1.UserService/GetUser` or `user.v1.UserService.GetUser`. Method entries take
+    /// precedence over service entries, and both over the global threshold. Without a
+    /// global threshold, only the configured services and methods are reported.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use std::time::Duration;
+    ///
+    /// BridgeGenerator::with_tonic_build()
+    ///     .with_slow_request_profiler(Duration::from_millis(200))
+    ///     .with_method_slow_request_threshold("search.v1.Search/Query", Duration::from_secs(1))
+    ///     .compile_protos(&["proto/service.proto"], &["proto"])?;
+    /// ```
+    ///
+    pub fn with_method_slow_request_threshold(
+        mut self,
+        method: impl AsRef<str>,
+        threshold: std::time::Duration,
+    ) -> Self {
+        self.method_slow_request_thresholds.push((
+            method.as_ref().trim_start_matches(['.', '/']).to_string(),
+            threshold,
+        ));
+        self
+    }
+
+    // Whether handlers profile requests for the `SlowRequestObserver`
+    fn is_profiling_slow_requests(&self) -> bool {
+        self.slow_request_threshold.is_some() || !self.method_slow_request_thresholds.is_empty()
+    }
+
+    // Profiling threshold of a method, the most specific configured one
+    fn slow_request_threshold_of(
+        &self,
+        service_path: &str,
+        method: &str,
+    ) -> Option<std::time::Duration> {
+        method_entry(&self.method_slow_request_thresholds, service_path, method)
+            .or_else(|| service_entry(&self.method_slow_request_thresholds, service_path))
+            .copied()
+            .or(self.slow_request_threshold)
+    }
+
+    ///
+    /// Report every request to an `AccessLogger` installed as a request extension.
+    ///
+    /// Every generated package gains an `AccessLogEntry` type with the method's HTTP path
+    /// and proto name, response status and latency, and an `AccessLogger` receiving the
+    /// entries, for requests rejected before the service is called too. Loggers created
+    /// with `with_bodies()` also receive the request and response as JSON, after running
+    /// the redaction closures registered with `redact()`. Fields marked with the
+    /// `g2h.sensitive` option are always replaced with `"****"`. The generated code uses
+    /// `serde_json`, which must be a dependency of the crate including the generated code.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let logger = AccessLogger::new(|entry| tracing::info!(?entry, "access"))
+    ///     .with_bodies()
+    ///     .redact(|_, body| {
+    ///         if let Some(card) = body.get_mut("card_number") {
+    ///             *card = "****".into();
+    ///         }
+    ///     });
+    /// let app = payment_connector_handler(server).layer(axum::Extension(logger));
+    /// ```
+    ///
+    pub fn with_access_log(mut self) -> Self {
+        self.enable_access_log = true;
+        self
+    }
+
+    ///
+    /// Validate requests before they reach the service, answering invalid ones with a
+    /// `400 Bad Request` listing every violation.
+    ///
+    /// Fields marked with `(google.api.field_behavior) = REQUIRED` must be set to a
+    /// non-default value, in nested messages as well. The error mirrors
+    /// `google.rpc.BadRequest`: its `field_violations` give the path of each failing field,
+    /// the violated constraint and a description. The generated code uses `serde_json`,
+    /// which must be a dependency of the crate including the generated code.
+    ///
+    /// # Example
+    ///
+    /// ```json
+    /// {
+    ///   "error": {
+    ///     "code": "Client specified an invalid argument",
+    ///     "message": "Request validation failed",
+    ///     "field_violations": [
+    ///       { "field": "address.city", "constraint": "required", "description": "Field is required" }
+    ///     ]
+    ///   }
+    /// }
+    /// ```
+    ///
+    pub fn with_request_validation(mut self) -> Self {
+        self.enable_request_validation = true;
+        self
+    }
+
+    ///
+    /// Check requests against their `buf.validate` (protovalidate) constraints before they
+    /// reach the service.
+    ///
+    /// Requests of messages with constraints, directly or in nested messages, are passed
+    /// to a `RequestValidator` installed as a request extension, typically wrapping a
+    /// protovalidate runtime working on the encoded message and `FILE_DESCRIPTOR_SET`.
+    /// Violations are answered like those of
+    /// [`with_request_validation`](Self::with_request_validation), with a `400 Bad Request`
+    /// listing them. Without a validator, these requests fail with a `500` rather than
+    /// skipping validation.
+    ///
+    /// `RequestValidator::check()` reports the same violations as a `tonic::Status` with
+    /// `INVALID_ARGUMENT`, so gRPC services can apply the constraints consistently.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let validator = RequestValidator::new(move |message_type, encoded| {
+    ///     runtime.validate(message_type, encoded)
+    /// });
+    /// let app = user_service_handler(service).layer(axum::Extension(validator));
+    /// ```
+    ///
+    pub fn with_protovalidate(mut self) -> Self {
+        self.enable_protovalidate = true;
+        self
+    }
+
+    ///
+    /// Fail the build when the dependencies of the crate are incompatible with the generated
+    /// code, instead of emitting a `cargo:warning`.
+    ///
+    /// With the `validate` feature, the versions of `axum` (`actix-web` with
+    /// [`Backend::Actix`]), `tonic` and `http` are checked once, before compiling. In strict mode, [`compile_protos`](Self::compile_protos) and the
+    /// other compile methods return the incompatibility as an error, and generators used
+    /// through a custom `prost_build::Config` panic when finalizing the generated code.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use g2h::BridgeGenerator;
+    ///
+    /// BridgeGenerator::with_tonic_build()
+    ///     .with_strict_validation()
+    ///     .compile_protos(&["proto/service.proto"], &["proto"])?;
+    /// ```
+    ///
+    pub fn with_strict_validation(mut self) -> Self {
+        self.strict_validation = true;
+        self
+    }
+
+    ///
+    /// Expect an `axum` version matching `version_req` (e.g. `">=0.8"`) instead of the
+    /// range the generated code was tested with.
+    ///
+    /// This keeps dependency validation useful when pinning a patched fork or a release
+    /// g2h doesn't know of yet. See [`with_strict_validation`](Self::with_strict_validation).
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use g2h::BridgeGenerator;
+    ///
+    /// BridgeGenerator::with_tonic_build()
+    ///     .expect_axum(">=0.8, <0.10")
+    ///     .expect_tonic("=0.14.2")
+    ///     .compile_protos(&["proto/service.proto"], &["proto"])?;
+    /// ```
+    ///
+    pub fn expect_axum(self, version_req: impl Into<String>) -> Self {
+        self.expect_version("axum", version_req)
+    }
+
+    ///
+    /// Expect a `tonic` version matching `version_req`, like
+    /// [`expect_axum`](Self::expect_axum).
+    ///
+    pub fn expect_tonic(self, version_req: impl Into<String>) -> Self {
+        self.expect_version("tonic", version_req)
+    }
+
+    ///
+    /// Expect an `http` version matching `version_req`, like
+    /// [`expect_axum`](Self::expect_axum).
+    ///
+    pub fn expect_http(self, version_req: impl Into<String>) -> Self {
+        self.expect_version("http", version_req)
+    }
+
+    ///
+    /// Choose the HTTP framework of the generated handlers.
+    ///
+    /// With [`Backend::Actix`], every service gets a `{service}_actix_config` function
+    /// configuring its routes on an actix-web `App`, instead of an axum router. Requests and
+    /// responses have the same JSON bodies, metadata headers, route paths and verbs, and
+    /// error responses as with axum. Only unary methods are served. Options checking
+    /// requests before the call (bearer tokens, API keys, access levels, rate limiting and
+    /// validation) and options tailoring the axum handlers (body and concurrency limits,
+    /// CORS, timeouts, idempotency keys, access logs, API versions, hooks, ...) fail the
+    /// build, rather than being ignored.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use g2h::{Backend, BridgeGenerator};
+    ///
+    /// BridgeGenerator::with_tonic_build()
+    ///     .with_backend(Backend::Actix)
+    ///     .compile_protos(&["proto/service.proto"], &["proto"])?;
+    /// ```
+    ///
+    pub fn with_backend(mut self, backend: Backend) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    // Enabled options checking requests before the call, which only the axum handlers do
+    fn request_check_options(&self) -> Vec<&'static str> {
+        let mut routes = self
+            .method_annotations
+            .values()
+            .filter_map(|annotations| annotations.route.as_ref())
+            .filter(|_| self.enable_route_options);
+        let declares_api_keys = routes.clone().any(|route| !route.api_key_header.is_empty())
+            || (self.enable_route_options
+                && self
+                    .service_annotations
+                    .values()
+                    .any(|options| !options.api_key_header.is_empty()));
+        let requires_bearer_token =
+            !self.bearer_token_required.is_empty() || routes.any(|route| route.auth_required);
+        [
+            (requires_bearer_token, "require_bearer_token"),
+            (declares_api_keys, "api_key_header options"),
+            (self.enable_authorizer, "with_access_level"),
+            (self.enable_rate_limiting, "with_rate_limiting"),
+            (self.enable_request_validation, "with_request_validation"),
+            (self.enable_protovalidate, "with_protovalidate"),
+        ]
+        .into_iter()
+        .filter_map(|(enabled, option)| enabled.then_some(option))
+        .collect()
+    }
+
+    // Enabled options tailoring the axum handlers, which the actix handlers would ignore
+    fn actix_ignored_options(&self) -> Vec<&'static str> {
+        [
+            (self.max_body_size.is_some(), "with_max_body_size"),
+            (self.enable_gzip_requests, "with_gzip_requests"),
+            (
+                self.concurrency_limit.is_some() || !self.method_concurrency_limits.is_empty(),
+                "with_concurrency_limit",
+            ),
+            (!self.cors_specs.is_empty(), "with_cors"),
+            (self.default_timeout.is_some(), "with_default_timeout"),
+            (self.enable_timeout_headers, "with_timeout_headers"),
+            (self.enable_cancellation, "with_cancellation_signal"),
+            (self.enable_idempotency, "with_idempotency_keys"),
+            (self.enable_access_log, "with_access_log"),
+            (
+                self.is_profiling_slow_requests(),
+                "with_slow_request_profiler",
+            ),
+            (self.is_versioned(), "with_api_version"),
+            (self.enable_response_envelope, "with_response_envelope"),
+            (self.enable_request_ids, "with_request_ids"),
+            (self.enable_trailers, "with_trailers"),
+            (self.enable_message_hooks, "with_message_hooks"),
+            (!self.hooks.is_empty(), "with_hook"),
+        ]
+        .into_iter()
+        .filter_map(|(enabled, option)| enabled.then_some(option))
+        .collect()
+    }
+
+    // Option combinations the generated code can't honor, checked once the proto
+    // annotations are known
+    fn check_options(&self) -> Result<(), String> {
+        if let Some(max_len) = self.max_function_id_len {
+            if max_len < MIN_FUNCTION_ID_LEN {
+                return Err(format!(
+                    "max function id length {max_len} is below {MIN_FUNCTION_ID_LEN}, the length of the hash suffix"
+                ));
+            }
+        }
+        // Headers built with `HeaderValue::from_static`, which panics on invalid values
+        if let Some(ref sunset) = self.deprecation_sunset {
+            if !is_header_value(sunset) {
+                return Err(format!("Sunset `{sunset}` is not a valid header value"));
+            }
+        }
+        if let Some((_, policy)) = self
+            .cache_policies
+            .iter()
+            .find(|(_, policy)| !is_header_value(policy))
+        {
+            return Err(format!(
+                "Cache-Control `{policy}` is not a valid header value"
+            ));
+        }
+        if let Some(ref predicate) = self.cfg_gate {
+            if let Err(e) = predicate.parse::<proc_macro2::TokenStream>() {
+                return Err(format!("Invalid cfg predicate '{predicate}': {e}"));
+            }
+        }
+        for spec in &self.cors_specs {
+            spec.check()?;
+        }
+        if !self.passthrough_headers.is_empty() && self.metadata_header_prefix.is_none() {
+            return Err(
+                "`with_mesh_preset` needs `with_metadata_header_prefix`, every header is forwarded without one"
+                    .to_string(),
+            );
+        }
+        if self.enable_route_options {
+            self.check_annotations()?;
+        }
+        // The key of `MetadataMap::insert`, which panics on binary or invalid keys
+        if let Some(ref key) = self.client_ip_metadata_key {
+            if !is_header_name(key) || key.ends_with("-bin") {
+                return Err(format!(
+                    "client IP metadata key `{key}` is not a valid ASCII metadata key"
+                ));
+            }
+        }
+        if self.backend == Backend::Actix {
+            let unsupported = self.request_check_options();
+            if !unsupported.is_empty() {
+                return Err(format!(
+                    "the actix backend doesn't check requests for {}, its routes would be unprotected",
+                    unsupported.join(", ")
+                ));
+            }
+            let ignored = self.actix_ignored_options();
+            if !ignored.is_empty() {
+                return Err(format!(
+                    "the actix backend doesn't support {}, the option would be silently ignored",
+                    ignored.join(", ")
+                ));
+            }
+        }
+        if self.enable_graphql {
+            // GraphQL requests carry no client address to take the IP from
+            let mut unsupported = self.request_check_options();
+            if self.client_ip_metadata_key.is_some() {
+                unsupported.push("with_client_ip_metadata");
+            }
+            if !unsupported.is_empty() {
+                return Err(format!(
+                    "the GraphQL resolvers don't check requests for {}, they would be unprotected",
+                    unsupported.join(", ")
+                ));
+            }
+        }
+        if self.wasm_target {
+            let incompatible = self.wasm_incompatible_options();
+            if !incompatible.is_empty() {
+                return Err(format!(
+                    "wasm targets don't support {}, used with `with_wasm_target`",
+                    incompatible.join(", ")
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    // Values of the `g2h.route` and `g2h.service` options the generated code can't use
+    fn check_annotations(&self) -> Result<(), String> {
+        let mut routes = self
+            .method_annotations
+            .iter()
+            .filter_map(|(branch_name, annotations)| {
+                Some((branch_name, annotations.route.as_ref()?))
+            })
+            .collect::<Vec<_>>();
+        routes.sort_by_key(|(branch_name, _)| *branch_name);
+        for (branch_name, route) in &routes {
+            if !route.path.is_empty() && !route.path.starts_with('/') {
+                return Err(format!(
+                    "g2h.route path `{}` of `{branch_name}` must start with `/`",
+                    route.path
+                ));
+            }
+            let verb = route.verb.to_ascii_uppercase();
+            if !verb.is_empty()
+                && !["GET", "POST", "PUT", "PATCH", "DELETE"].contains(&verb.as_str())
+            {
+                return Err(format!(
+                    "g2h.route verb `{verb}` of `{branch_name}` is not one of GET, POST, PUT, PATCH, DELETE"
+                ));
+            }
+            if route.success_status != 0 && !(200..300).contains(&route.success_status) {
+                return Err(format!(
+                    "g2h.route success_status `{}` of `{branch_name}` is not a 2xx status",
+                    route.success_status
+                ));
+            }
+            if !route.cache_control.is_empty() && !is_header_value(&route.cache_control) {
+                return Err(format!(
+                    "g2h.route cache_control `{}` of `{branch_name}` is not a valid header value",
+                    route.cache_control
+                ));
+            }
+        }
+
+        let mut services = self.service_annotations.iter().collect::<Vec<_>>();
+        services.sort_by_key(|(service_path, _)| *service_path);
+        let options = routes
+            .iter()
+            .map(|(branch_name, route)| (*branch_name, &route.api_key_header, &route.access))
+            .chain(services.iter().map(|(service_path, options)| {
+                (*service_path, &options.api_key_header, &options.access)
+            }));
+        for (name, api_key_header, access) in options {
+            let header = api_key_header.to_ascii_lowercase();
+            if !header.is_empty() && !is_header_name(&header) {
+                return Err(format!(
+                    "g2h api_key_header `{header}` of `{name}` is not a valid header name"
+                ));
+            }
+            if !access.is_empty() && AccessLevel::from_option(access).is_none() {
+                return Err(format!(
+                    "g2h access `{access}` of `{name}` is not one of public, authenticated, admin"
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    ///
+    /// Generate HTTP code that runs on wasm32 targets such as Cloudflare Workers, where
+    /// there is no tokio runtime, no sockets and no `std::time::Instant`.
+    ///
+    /// The generated handlers and routers then only use what axum offers without its
+    /// `tokio` feature. Options relying on the runtime or on sockets (timeouts, concurrency
+    /// limits, cancellation, client IP metadata, access logs, the slow request profiler and
+    /// the actix backend) fail the build rather than the Worker, and the `tracing` and
+    /// `metrics` features add nothing to the handlers. As wasm32 aborts on panics, service
+    /// calls are not wrapped to turn panics into JSON 500s.
+    ///
+    /// The gRPC code of the inner generator has to avoid tonic's transport as well, e.g.
+    /// with `tonic_prost_build::configure().build_transport(false)`.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use g2h::BridgeGenerator;
+    ///
+    /// let inner = tonic_prost_build::configure()
+    ///     .build_transport(false)
+    ///     .service_generator();
+    /// BridgeGenerator::new(inner)
+    ///     .with_wasm_target()
+    ///     .compile_protos(&["proto/service.proto"], &["proto"])?;
+    /// ```
+    ///
+    pub fn with_wasm_target(mut self) -> Self {
+        self.wasm_target = true;
+        self
+    }
+
+    // Enabled options whose generated code needs tokio, sockets or a monotonic clock
+    fn wasm_incompatible_options(&self) -> Vec<&'static str> {
+        [
+            (self.default_timeout.is_some(), "with_default_timeout"),
+            (self.enable_timeout_headers, "with_timeout_headers"),
+            (
+                self.concurrency_limit.is_some() || !self.method_concurrency_limits.is_empty(),
+                "with_concurrency_limit",
+            ),
+            (self.enable_cancellation, "with_cancellation_signal"),
+            (
+                self.client_ip_metadata_key.is_some(),
+                "with_client_ip_metadata",
+            ),
+            (self.enable_access_log, "with_access_log"),
+            (
+                self.is_profiling_slow_requests(),
+                "with_slow_request_profiler",
+            ),
+            (self.backend == Backend::Actix, "the actix backend"),
+        ]
+        .into_iter()
+        .filter_map(|(enabled, option)| enabled.then_some(option))
+        .collect()
+    }
+
+    ///
+    /// Inject the code of `hook` into every generated axum handler, e.g. for audit events,
+    /// feature flags or custom authorization checks.
+    ///
+    /// Hooks run in registration order. See [`GeneratorHook`] for the bindings available to
+    /// the injected code.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use g2h::{BridgeGenerator, GeneratorHook, MethodInfo};
+    /// use quote::quote;
+    ///
+    /// struct Audit;
+    ///
+    /// impl GeneratorHook for Audit {
+    ///     fn after_call(&self, method: &MethodInfo) -> proc_macro2::TokenStream {
+    ///         let name = &method.name;
+    ///         quote! { crate::audit::record(#name, response.status()); }
+    ///     }
+    /// }
+    ///
+    /// BridgeGenerator::with_tonic_build()
+    ///     .with_hook(Audit)
+    ///     .compile_protos(&["proto/service.proto"], &["proto"])?;
+    /// ```
+    ///
+    pub fn with_hook(mut self, hook: impl GeneratorHook + 'static) -> Self {
+        self.hooks.push(Box::new(hook));
+        self
+    }
+
+    ///
+    /// Run another `ServiceGenerator` alongside the inner one, e.g. a metrics or client
+    /// generator, to combine them in a single `prost_build` pass.
+    ///
+    /// For every service, the inner generator runs first, then the chained generators in
+    /// the order they were added, then the HTTP bridge. `finalize` and `finalize_package`
+    /// are called in the same order, so each generator's output follows the previous one.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use g2h::BridgeGenerator;
+    ///
+    /// BridgeGenerator::with_tonic_build()
+    ///     .chain(Box::new(MetricsGenerator::default()))
+    ///     .compile_protos(&["proto/service.proto"], &["proto"])?;
+    /// ```
+    ///
+    pub fn chain(mut self, generator: Box<dyn ServiceGenerator>) -> Self {
+        self.chained.push(generator);
+        self
+    }
+
+    ///
+    /// Replace the generated axum handlers of some methods with the code returned by
+    /// `template`.
+    ///
+    /// The template, a function or a closure capturing its configuration, runs during code
+    /// generation for every method. Returning `None` keeps the built-in handler; returning
+    /// code replaces it, and the code must define an `async fn` named
+    /// [`HandlerContext::handler_name`], generic over the service type `T` and usable as an
+    /// axum handler with the router state [`HandlerContext::state_type`].
+    /// The built-in handler is still generated as
+    /// [`HandlerContext::builtin_handler_name`], so custom handlers can wrap it.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use g2h::{BridgeGenerator, HandlerContext};
+    /// use quote::{format_ident, quote};
+    ///
+    /// fn handler_template(context: &HandlerContext) -> Option<proc_macro2::TokenStream> {
+    ///     if context.verb != "GET" {
+    ///         return None;
+    ///     }
+    ///     let name = format_ident!("{}", context.handler_name);
+    ///     let builtin = format_ident!("{}", context.builtin_handler_name);
+    ///     let server_bound: proc_macro2::TokenStream = context.server_bound.parse().unwrap();
+    ///     let state_type: proc_macro2::TokenStream = context.state_type.parse().unwrap();
+    ///     Some(quote! {
+    ///         async fn #name<T: #server_bound>(
+    ///             ::axum::extract::State(state): ::axum::extract::State<#state_type>,
+    ///             request: ::axum::extract::Request,
+    ///         ) -> ::axum::response::Response {
+    ///             // Custom conventions, then the built-in behavior
+    ///             ::axum::handler::Handler::call(#builtin::<T>, request, state).await
+    ///         }
+    ///     })
+    /// }
+    ///
+    /// BridgeGenerator::with_tonic_build()
+    ///     .with_handler_template(handler_template)
+    ///     .compile_protos(&["proto/service.proto"], &["proto"])?;
+    /// ```
+    ///
+    pub fn with_handler_template(
+        mut self,
+        template: impl Fn(&HandlerContext) -> Option<proc_macro2::TokenStream> + 'static,
+    ) -> Self {
+        self.handler_template = Some(Box::new(template));
+        self
+    }
+
+    ///
+    /// Generate a `{Service}Mock` for every service whose methods are all unary, with a
+    /// `{service}_mock_handler()` router serving it.
+    ///
+    /// Mocks implement the tonic service trait, answering each method with the response
+    /// programmed with `on_{method}` (a closure) or `on_{method}_json` (a JSON fixture),
+    /// and `UNIMPLEMENTED` otherwise. Served through the generated router, they give
+    /// frontend teams a faithful fake of the bridge without the real backend. The
+    /// generated code uses `serde_json`, which must be a dependency of the crate.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let mock = GreeterMock::new()
+    ///     .on_say_hello(|request| Ok(HelloReply { message: format!("Hello {}!", request.name) }))
+    ///     .on_say_goodbye_json(r#"{ "message": "Goodbye!" }"#);
+    /// let app = greeter_mock_handler(mock);
+    /// ```
+    ///
+    pub fn with_mocks(mut self) -> Self {
+        self.enable_mocks = true;
+        self
+    }
+
+    ///
+    /// Generate a `{Service}TestClient` for every service, created with
+    /// `{service}_test_client(router)`, calling the routes of a router in-process.
+    ///
+    /// Each unary method gets a typed method sending the request with
+    /// `tower::ServiceExt::oneshot`, as JSON or as a query string for GET routes, and
+    /// returning the decoded response or the `ErrorResponse` of the bridge. Headers set
+    /// with `header()` are sent with every request. Only the axum backend gets test
+    /// clients. The generated code uses `tower` with its `util` feature and `serde_json`,
+    /// which must be dependencies of the crate, usually as dev-dependencies of tests
+    /// including the generated code.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let client = greeter_test_client(greeter_handler(MyGreeter::default()));
+    /// let reply = client
+    ///     .say_hello(HelloRequest { name: "Tonic".into() })
+    ///     .await?;
+    /// assert_eq!(reply.message, "Hello Tonic!");
+    /// ```
+    ///
+    pub fn with_test_clients(mut self) -> Self {
+        self.enable_test_clients = true;
+        self
+    }
+
+    ///
+    /// Generate an [async-graphql](https://docs.rs/async-graphql) schema for every service
+    /// (experimental), built with `{service}_graphql_schema(server)`.
+    ///
+    /// Unary methods routed with GET become query fields, and the other unary methods
+    /// mutation fields, resolving through the same tonic service trait as the HTTP
+    /// handlers. Requests and responses are `JSON` scalars shaped like the HTTP bodies, gRPC
+    /// errors carry their code in the `code` extension, and an `http::HeaderMap` added to
+    /// the request data becomes the gRPC metadata, with the header conversions and limits of
+    /// the HTTP handlers. The resolvers don't run the handlers' request checks, so the build
+    /// fails with bearer token or API key requirements, access levels, rate limiting,
+    /// request validation or client IP metadata. The generated code uses
+    /// `async-graphql`, which must be a dependency of the crate; serving the schema, e.g.
+    /// with `async-graphql-axum`, is up to the application.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let schema = greeter_graphql_schema(MyGreeter::default());
+    /// let app = axum::Router::new().route_service("/graphql", async_graphql_axum::GraphQL::new(schema));
+    /// ```
+    ///
+    pub fn with_graphql(mut self) -> Self {
+        self.enable_graphql = true;
+        self
+    }
+
+    ///
+    /// Send the trailing metadata of unary calls as HTTP trailers.
+    ///
+    /// Trailing metadata is the metadata of an error `Status`, which gRPC sends as
+    /// trailers, and the `TrailingMetadata` a service or interceptor inserts into the
+    /// extensions of a successful response. It is sent as trailers of a chunked response
+    /// when the request accepts them (`TE: trailers`), and merged into the response
+    /// headers otherwise, with the same name mapping as the initial metadata. The generated
+    /// code uses `http-body`, which must be a dependency of the crate.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let mut response = tonic::Response::new(reply);
+    /// let mut trailers = tonic::metadata::MetadataMap::new();
+    /// trailers.insert("x-checksum", "abc123".parse().unwrap());
+    /// response.extensions_mut().insert(TrailingMetadata(trailers));
+    /// ```
+    ///
+    pub fn with_trailers(mut self) -> Self {
+        self.enable_trailers = true;
+        self
+    }
+
+    ///
+    /// Replay the stored response of requests repeating an `Idempotency-Key` header, using
+    /// an `IdempotencyStorage` installed as a request extension.
+    ///
+    /// Every generated package gains an `IdempotencyStore` trait to implement over any
+    /// storage, and an `InMemoryIdempotencyStore` for tests and single instances. Unary
+    /// handlers look the key up per method after authentication and validation, and answer
+    /// repeated requests with the stored status, headers and body, marked with
+    /// `Idempotent-Replayed: true`, or with a 422 error when the key was used with another
+    /// request message. Responses other than 5xx errors are stored. Handlers reserve the
+    /// key before calling the service, and requests with a key reserved by another one in
+    /// progress get a `409 Conflict`. The key also reaches the service as
+    /// `idempotency-key` metadata, like any other header.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let storage = IdempotencyStorage::new(InMemoryIdempotencyStore::default());
+    /// let app = payment_connector_handler(server).layer(axum::Extension(storage));
+    /// ```
+    ///
+    pub fn with_idempotency_keys(mut self) -> Self {
+        self.enable_idempotency = true;
+        self
+    }
+
+    ///
+    /// Give every request an `X-Request-Id`, generating a UUID v4 when the client sends
+    /// none, for correlation across the HTTP/gRPC hop.
+    ///
+    /// The generated routers run a middleware that sets the header before the request
+    /// reaches the handler, so the service reads it as `x-request-id` metadata. The ID is
+    /// echoed on the response, unless the service already set the header, and added as
+    /// `request_id` to JSON error bodies, including routing errors. The generated code uses
+    /// `uuid` with its `v4` feature and `serde_json`, which must be dependencies of the
+    /// crate.
+    ///
+    pub fn with_request_ids(mut self) -> Self {
+        self.enable_request_ids = true;
+        self
+    }
+
+    ///
+    /// Wrap JSON responses in an envelope, as `{"data": ..., "error": null}` on success and
+    /// `{"data": null, "error": {...}}` with HTTP 200 on errors, for clients that expect
+    /// every response in that shape.
+    ///
+    /// The generated routers run a middleware wrapping every JSON response, including
+    /// routing errors and request IDs added by [`BridgeGenerator::with_request_ids`]. Other
+    /// responses, e.g. streams or empty bodies, pass unchanged. The generated test clients and
+    /// HTTP transport expect the default format, and don't work with enveloped routers.
+    ///
+    pub fn with_response_envelope(mut self) -> Self {
+        self.enable_response_envelope = true;
+        self
+    }
+
+    fn expect_version(mut self, name: &'static str, version_req: impl Into<String>) -> Self {
+        self.expected_versions.push((name, version_req.into()));
+        self
+    }
+
+    /// Check the dependency versions once, returning incompatibilities in strict mode and
+    /// reporting them as cargo warnings otherwise
+    fn check_dependencies(&mut self) -> Result<(), String> {
+        if std::mem::replace(&mut self.dependencies_checked, true) {
+            return Ok(());
+        }
+        let Err(err) = self.validate_dependencies() else {
+            return Ok(());
+        };
+        if self.strict_validation {
+            return Err(format!("g2h: {err}"));
+        }
+        println!("cargo:warning=g2h: {err}");
+        Ok(())
+    }
+
+    #[cfg(feature = "validate")]
+    fn validate_dependencies(&self) -> Result<(), vercheck::DepError> {
+        // The last requirement set for a dependency wins over the built-in one
+        let expected = |name: &str, default: &'static str| {
+            self.expected_versions
+                .iter()
+                .rev()
+                .find(|(dependency, _)| *dependency == name)
+                .map_or(default, |(_, version_req)| version_req.as_str())
+        };
+        // The actix backend serves with actix-web, and doesn't need axum
+        let framework = match self.backend {
+            Backend::Axum => ("axum", expected("axum", ver::AXUM_VERSION_REQ)),
+            Backend::Actix => (
+                "actix-web",
+                expected("actix-web", ver::ACTIX_WEB_VERSION_REQ),
+            ),
+        };
+        vercheck::Deps::new(&[
+            framework,
+            ("tonic", expected("tonic", ver::TONIC_VERSION_REQ)),
+            ("http", expected("http", ver::HTTP_VERSION_REQ)),
+        ])?
+        .validate()
+    }
+
+    #[cfg(not(feature = "validate"))]
+    fn validate_dependencies(&self) -> Result<(), std::convert::Infallible> {
+        Ok(())
+    }
+
+    ///
+    /// Serve an additional JSON dialect selected by the `x-api-version` request header.
+    ///
+    /// Requests carrying `x-api-version: <version>` have their JSON object keys
+    /// converted from `field_case` before deserialization, and the keys of successful
+    /// responses converted to `field_case`. Requests without the header use the native
+    /// (snake_case) field names, and unknown versions are rejected with a `400` JSON error.
+    /// Invalid fields get the status of unversioned requests, `422` in JSON bodies and `400`
+    /// in query strings. This allows serving two JSON dialects from one service during a
+    /// migration.
+    ///
+    /// Keys are converted recursively following the message descriptors, so the keys of
+    /// protobuf `map` fields are kept as sent. Versions apply to every method; see
+    /// [`with_method_api_version`](Self::with_method_api_version) for the versions of some
+    /// methods only. The generated code uses `serde_json`, which must be a dependency of the
+    /// crate including the generated code.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use g2h::{BridgeGenerator, JsonFieldCase};
+    ///
+    /// BridgeGenerator::with_tonic_build()
+    ///     .with_api_version("v1", JsonFieldCase::CamelCase)
+    ///     .with_api_version("v2", JsonFieldCase::SnakeCase)
+    ///     .compile_protos(&["proto/service.proto"], &["proto"])?;
+    /// ```
+    ///
+    pub fn with_api_version(
+        mut self,
+        version: impl Into<String>,
+        field_case: JsonFieldCase,
+    ) -> Self {
+        self.api_versions.push((version.into(), field_case));
+        self
+    }
+
+    ///
+    /// Serve an additional JSON dialect selected by the `x-api-version` request header for
+    /// a service or method only, as [`with_api_version`](Self::with_api_version) does for
+    /// every method.
+    ///
+    /// `method` names a service (`payments.v1.Payments`) or a method as
+    /// `payments.v1.Payments/Refund` or `payments.v1.Payments.Refund`. Other methods reject
+    /// the version with a `400` JSON error, and a method version replaces a
+    /// [`with_api_version`](Self::with_api_version) version of the same name.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use g2h::{BridgeGenerator, JsonFieldCase};
+    ///
+    /// BridgeGenerator::with_tonic_build()
+    ///     .with_api_version("v2", JsonFieldCase::SnakeCase)
+    ///     .with_method_api_version("payments.v1.Payments/Refund", "v1", JsonFieldCase::CamelCase)
+    ///     .compile_protos(&["proto/service.proto"], &["proto"])?;
+    /// ```
+    ///
+    pub fn with_method_api_version(
+        mut self,
+        method: impl AsRef<str>,
+        version: impl Into<String>,
+        field_case: JsonFieldCase,
+    ) -> Self {
+        self.method_api_versions.push((
+            method.as_ref().trim_start_matches(['.', '/']).to_string(),
+            (version.into(), field_case),
+        ));
+        self
+    }
+
+    // Whether any method serves `x-api-version` dialects
+    fn is_versioned(&self) -> bool {
+        !self.api_versions.is_empty() || !self.method_api_versions.is_empty()
+    }
+
+    // The `x-api-version` dialects of `method` of `service_path`, method versions replacing
+    // the versions of every method
+    fn api_versions_of(&self, service_path: &str, method: &str) -> Vec<(&str, JsonFieldCase)> {
+        let mut versions: Vec<(&str, JsonFieldCase)> = Vec::new();
+        let method_versions = self
+            .method_api_versions
+            .iter()
+            .filter(|(entry, _)| selects_method(entry, service_path, method))
+            .map(|(_, version)| version);
+        for (version, field_case) in self.api_versions.iter().chain(method_versions) {
+            versions.retain(|(known, _)| known != version);
+            versions.push((version, *field_case));
+        }
+        versions
+    }
+
+    ///
+    /// Forward `google.api.routing` annotations as the `x-goog-request-params` metadata entry.
+    ///
+    /// For every method annotated with `option (google.api.routing)`, the generated handler
+    /// extracts the configured routing parameters from the request body and sends them to the
+    /// service as `key1=value1&key2=value2`, the same way Google client libraries do. This keeps
+    /// bridged requests compatible with backends that shard on routing headers.
+    ///
+    /// Both an empty `path_template` (the whole field is used, keyed by the field path) and
+    /// `{key=pattern}` templates with `*` and `**` segments are supported. When several
+    /// parameters produce the same key, the last matching one wins.
+    ///
+    /// `compile_protos()` and `compile_protos_with_config()` run protoc an additional time to
+    /// read the annotations, so `google/api/routing.proto` must be reachable from the include
+    /// paths. `compile_fds()`, `render()` and the protoc plugin read them from the encoded
+    /// descriptor set, which has to come from protoc as is: decoding it into `prost_types`
+    /// drops the extensions. The generated code uses `serde_json`, which must be a dependency
+    /// of the crate including the generated code.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use g2h::BridgeGenerator;
+    ///
+    /// BridgeGenerator::with_tonic_build()
+    ///     .with_routing_headers()
+    ///     .compile_protos(&["proto/service.proto"], &["proto", "googleapis"])?;
+    /// ```
+    ///
+    pub fn with_routing_headers(mut self) -> Self {
+        self.enable_routing_headers = true;
+        self
+    }
+
+    ///
+    /// Configure routes with the `g2h.route` method option of `g2h/options.proto`.
+    ///
+    /// Annotated methods can be moved to another `path`, served with another `verb` (`POST`,
+    /// `GET`, `PUT`, `PATCH` or `DELETE`), require a bearer token (`auth_required`), be marked
+    /// `deprecated` (responses carry `Deprecation: true`) and answer successful calls with
+    /// another 2xx `success_status` and `Cache-Control` policy (`cache_control`). This
+    /// keeps routing configuration next to the RPC definitions.
+    ///
+    /// An `api_key_header`, set for a method or for all methods of a service with the
+    /// `g2h.service` option, makes handlers reject requests without a non-empty key in that
+    /// header with `401 Unauthorized`. The key reaches the service as `api-key` metadata;
+    /// checking its value is up to the service. An `access` level (`public`,
+    /// `authenticated` or `admin`) is checked by the installed `Authorizer`; see
+    /// [`with_access_level`](Self::with_access_level).
+    ///
+    /// Fields marked with the `g2h.sensitive` field option are masked in access logs and
+    /// withhold the details of JSON parse errors; see [`with_access_log`](Self::with_access_log).
+    ///
+    /// `compile_protos()` and `compile_protos_with_config()` run protoc an additional time to
+    /// read the options, while `compile_fds()`, `render()` and the protoc plugin read them
+    /// from the encoded descriptor set. Add [`options_proto_include`] to the include paths to
+    /// import `g2h/options.proto`.
+    ///
+    /// # Example
+    ///
+    /// ```protobuf
+    /// import "g2h/options.proto";
+    ///
+    /// service UserService {
+    ///   option (g2h.service) = { api_key_header: "x-api-key" };
+    ///
+    ///   rpc CreateUser(CreateUserRequest) returns (User) {
+    ///     option (g2h.route) = { path: "/v1/users" success_status: 201 auth_required: true };
+    ///   }
+    /// }
+    /// ```
+    ///
+    /// ```rust,ignore
+    /// use g2h::BridgeGenerator;
+    ///
+    /// let includes = [std::path::PathBuf::from("proto"), g2h::options_proto_include()];
+    /// BridgeGenerator::with_tonic_build()
+    ///     .with_route_options()
+    ///     .compile_protos(&["proto/user.proto"], &includes)?;
+    /// ```
+    ///
+    pub fn with_route_options(mut self) -> Self {
+        self.enable_route_options = true;
+        self
+    }
+
+    ///
+    /// Announce the removal date of deprecated methods with a `Sunset` header.
+    ///
+    /// Responses of methods or services marked `deprecated = true` in the proto (or with the
+    /// `deprecated` route option) always carry `Deprecation: true`. With a sunset configured,
+    /// they also carry `Sunset: <sunset>`, which must be an HTTP date such as
+    /// `Wed, 31 Dec 2025 23:59:59 GMT`.
+    ///
+    pub fn with_deprecation_sunset(mut self, sunset: impl Into<String>) -> Self {
+        self.deprecation_sunset = Some(sunset.into());
+        self
+    }
+
+    ///
+    /// Send `Cache-Control: <policy>` with the successful responses of a service or method.
+    ///
+    /// `method` names a service (`catalog.v1.Catalog`) or a method as
+    /// `catalog.v1.Catalog/GetProduct` or `catalog.v1.Catalog.GetProduct`; method entries
+    /// take precedence. The `cache_control` route option, when enabled, overrides both.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use g2h::BridgeGenerator;
+    ///
+    /// BridgeGenerator::with_tonic_build()
+    ///     .with_verb_inference()
+    ///     .with_cache_control("catalog.v1.Catalog/GetProduct", "public, max-age=60")
+    ///     .compile_protos(&["proto/catalog.proto"], &["proto"])?;
+    /// ```
+    ///
+    pub fn with_cache_control(
+        mut self,
+        method: impl AsRef<str>,
+        policy: impl Into<String>,
+    ) -> Self {
+        self.cache_policies.push((
+            method.as_ref().trim_start_matches(['.', '/']).to_string(),
+            policy.into(),
+        ));
+        self
+    }
+
+    // Masks the `g2h.sensitive` fields of `value`, a JSON `proto_type` message
+    fn mask_tokens(&self, proto_type: &str) -> proc_macro2::TokenStream {
+        let proto_type = proto_type.trim_start_matches('.');
+        if self.masked_messages.contains_key(proto_type) {
+            quote! { mask_sensitive_fields(#proto_type, &mut value); }
+        } else {
+            quote! {}
+        }
+    }
+
+    // Tokens reading a whole response `body` into `body`, and rebuilding a body from it,
+    // which keep the trailers of the response when trailers are enabled
+    fn response_body_tokens(&self) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
+        if self.enable_trailers {
+            (
+                quote! { let (body, trailers) = collect_body(body).await; },
+                quote! { body_with_trailers(body, trailers) },
+            )
+        } else {
+            (
+                quote! { let body = ::axum::body::to_bytes(body, usize::MAX).await.unwrap_or_default(); },
+                quote! { ::axum::body::Body::from(body) },
+            )
+        }
+    }
+
+    // Whether any enabled option is driven by proto annotations
+    fn needs_annotations(&self) -> bool {
+        self.enable_routing_headers
+            || self.enable_route_options
+            || self.enable_access_log
+            || self.enable_request_validation
+            || self.enable_protovalidate
+            || self.is_versioned()
+    }
+
+    // Reads the proto annotations enabled on this generator
+    fn load_annotations(
+        &mut self,
+        protos: &[impl AsRef<std::path::Path>],
+        includes: &[impl AsRef<std::path::Path>],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let annotations = annotations::load_annotations(protos, includes, &self.protoc_args)?;
+        self.apply_annotations(annotations);
+        Ok(())
+    }
+
+    fn apply_annotations(&mut self, annotations: annotations::Annotations) {
+        self.protovalidate_messages = protovalidate_messages(&annotations);
+        self.masked_messages = annotated_messages(&annotations.messages, |field| field.sensitive);
+        self.map_messages = annotated_messages(&annotations.messages, |field| field.map);
+        self.validated_messages = annotated_messages(&annotations.messages, |field| field.required);
+        self.method_annotations = annotations.methods;
+        self.service_annotations = annotations.services;
+        if self.enable_route_options {
+            let requires_auth = self.method_annotations.values().any(|annotations| {
+                annotations
+                    .route
+                    .as_ref()
+                    .is_some_and(|route| route.auth_required)
+            });
+            self.enable_bearer_token |= requires_auth;
+
+            let declares_access = self
+                .method_annotations
+                .values()
+                .filter_map(|annotations| annotations.route.as_ref())
+                .map(|route| &route.access)
+                .chain(
+                    self.service_annotations
+                        .values()
+                        .map(|options| &options.access),
+                )
+                .any(|access| !access.is_empty());
+            self.enable_authorizer |= declares_access;
+        }
+    }
+
+    ///
+    /// Require an access level for a service or method, checked by the `Authorizer`
+    /// installed as a request extension before the service is called.
+    ///
+    /// `method` names a service (`payments.v1.Payments`) or a method as
+    /// `payments.v1.Payments/Refund` or `payments.v1.Payments.Refund`; method entries take
+    /// precedence, and the `access` route and service options, when enabled, override both.
+    ///
+    /// Every generated package gains an `AccessLevel` enum and an `Authorizer` trait. Its
+    /// `authorize()` receives the gRPC path, the required level and the request metadata,
+    /// and rejects requests with `AuthorizationError::Unauthenticated` (`401`) or
+    /// `AuthorizationError::Forbidden` (`403`), answered with the JSON error shape.
+    /// `Public` methods skip the authorizer, and non-public methods fail with `500` when
+    /// none is installed.
+    ///
+    /// Only the Axum handlers check access levels: the [mock](Self::with_mocks) routers call
+    /// services without consulting the authorizer, and the [GraphQL facade](Self::with_graphql)
+    /// and the [actix-web](Backend::Actix) backend fail the build.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use g2h::{AccessLevel, BridgeGenerator};
+    ///
+    /// BridgeGenerator::with_tonic_build()
+    ///     .with_access_level("payments.v1.Payments", AccessLevel::Authenticated)
+    ///     .with_access_level("payments.v1.Payments/Refund", AccessLevel::Admin)
+    ///     .compile_protos(&["proto/payments.proto"], &["proto"])?;
+    /// ```
+    ///
+    /// ```rust,ignore
+    /// let authorization = Authorization::new(MyAuthorizer::new(keys));
+    /// let app = payments_handler(server).layer(axum::Extension(authorization));
+    /// ```
+    ///
+    pub fn with_access_level(mut self, method: impl AsRef<str>, level: AccessLevel) -> Self {
+        self.access_levels.push((
+            method.as_ref().trim_start_matches(['.', '/']).to_string(),
+            level,
+        ));
+        self.enable_authorizer = true;
+        self
+    }
+
+    ///
+    /// Consult a `RateLimiter` installed as a request extension before calling the service,
+    /// answering rejected requests with `429 Too Many Requests` and a `Retry-After` header.
+    ///
+    /// Every generated package gains a `RateLimiter` trait, whose `check()` receives the
+    /// gRPC path of the method and the request metadata (after authentication, so quotas
+    /// can be kept per caller or merchant), and returns a `RateLimited` rejection with an
+    /// optional retry delay. Requests pass when no limiter is installed.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// #[tonic::async_trait]
+    /// impl RateLimiter for MerchantQuotas {
+    ///     async fn check(&self, path: &str, metadata: &MetadataMap) -> Result<(), RateLimited> {
+    ///         let merchant = metadata.get("x-merchant-id").and_then(|id| id.to_str().ok());
+    ///         self.take(path, merchant).map_err(RateLimited::retry_after)
+    ///     }
+    /// }
+    ///
+    /// let app = payments_handler(server).layer(axum::Extension(RateLimiting::new(quotas)));
+    /// ```
+    ///
+    pub fn with_rate_limiting(mut self) -> Self {
+        self.enable_rate_limiting = true;
+        self
+    }
+
+    ///
+    /// Run the `MessageHooks` installed on the router on the JSON of every request message
+    /// before the service is called, and of every response message before it is encoded.
+    ///
+    /// Every generated package gains a `MessageHooks` trait with async `before_request()`
+    /// and `after_response()` methods, both defaulting to no-ops, which receive the gRPC
+    /// path of the method and the message as a `serde_json::Value` to modify in place. Hooks
+    /// are installed with the `message_hooks()` method of the generated router builders, or
+    /// as a `MessageHooking` request extension, and messages pass unchanged without them.
+    /// Request hooks run after authentication and before validation. The JSON returned by
+    /// response hooks is sent as is, so they may add fields the message does not have.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// #[tonic::async_trait]
+    /// impl MessageHooks for Normalize {
+    ///     async fn before_request(&self, _path: &str, request: &mut serde_json::Value) {
+    ///         if let Some(email) = request.get_mut("email") {
+    ///             *email = email.as_str().unwrap_or_default().to_lowercase().into();
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let app = PaymentsRouterBuilder::new(server).message_hooks(Normalize).build();
+    /// ```
+    ///
+    pub fn with_message_hooks(mut self) -> Self {
+        self.enable_message_hooks = true;
+        self
+    }
+
+    ///
+    /// Generate a `{Service}HttpTransport` for every service, letting the generated tonic
+    /// clients call a remote g2h bridge over plain HTTP/JSON.
+    ///
+    /// The transport implements the `GrpcService` contract expected by tonic clients. Each
+    /// unary call is decoded from its gRPC frame, sent as a JSON `POST` through an inner HTTP
+    /// client service (any `tower::Service<http::Request<axum::body::Body>>`, e.g. a
+    /// `hyper_util` client), and the JSON answer is turned back into a gRPC response. Metadata
+    /// travels as HTTP headers, and bridge JSON errors become the matching `tonic::Status`.
+    /// Compressed and streaming calls are not supported.
+    ///
+    /// The generated code uses `serde_json`, which must be a dependency of the crate
+    /// including the generated code.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let transport = GreeterHttpTransport::new(http_client, "http://bridge:3000".parse()?);
+    /// let mut client = GreeterClient::new(transport);
+    /// let reply = client.say_hello(HelloRequest { name: "World".into() }).await?;
+    /// ```
+    ///
+    pub fn with_http_transport(mut self) -> Self {
+        self.enable_http_transport = true;
+        self
+    }
+
+    ///
+    /// Hold services as `State<T>` in the generated routers, cloning them per request,
+    /// instead of wrapping them in an `Arc`.
+    ///
+    /// Services then have to implement `Clone`, as tonic servers already require. This
+    /// saves an allocation and an indirection for services that are cheap handles, e.g.
+    /// around a connection pool. Middleware passed to `{service}_handler_with()` and the
+    /// router builders then operates on `MethodRouter<T>`.
+    ///
+    pub fn with_clone_state(mut self) -> Self {
+        self.enable_clone_state = true;
+        self
+    }
+
+    ///
+    /// Gate the generated HTTP bridge behind the `http-bridge` feature of the crate
+    /// including the generated code.
+    ///
+    /// Every generated router, handler and helper is wrapped in
+    /// `#[cfg(feature = "http-bridge")]`, while the prost messages, tonic clients and
+    /// servers and the string enum serializers stay available. Crates that only need the
+    /// gRPC side then don't pull in `axum` at all.
+    ///
+    pub fn with_feature_gate(self) -> Self {
+        self.with_feature_gate_named("http-bridge")
+    }
+
+    ///
+    /// Gate the generated HTTP bridge behind a feature with a custom name, see
+    /// [`with_feature_gate`](Self::with_feature_gate).
+    ///
+    pub fn with_feature_gate_named(self, feature: impl AsRef<str>) -> Self {
+        let feature = feature.as_ref();
+        self.with_cfg_gate(quote!(feature = #feature).to_string())
+    }
+
+    ///
+    /// Gate the generated HTTP bridge behind an arbitrary `cfg` predicate, for workspaces
+    /// whose feature naming doesn't fit [`with_feature_gate`](Self::with_feature_gate).
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// BridgeGenerator::with_tonic_build()
+    ///     .with_cfg_gate(r#"all(feature = "server", not(target_arch = "wasm32"))"#)
+    ///     .compile_protos(&["proto/service.proto"], &["proto"])?;
+    /// ```
+    ///
+    /// A `predicate` that is not valid Rust syntax fails the build.
+    ///
+    pub fn with_cfg_gate(mut self, predicate: impl Into<String>) -> Self {
+        self.cfg_gate = Some(predicate.into());
+        self
+    }
+
+    /// Wrap every item written to `buf` since `start` in the configured `cfg` gate
+    fn gate_http_code(&self, buf: &mut String, start: usize) {
+        if let Some(ref predicate) = self.cfg_gate {
+            let code = buf.split_off(start);
+            buf.push_str(&gate_items(&code, predicate));
+        }
+    }
+
+    ///
+    /// Set the path where the file descriptor set should be written.
+    /// This is useful for tonic_reflection support which requires access to the
+    /// file descriptor set at runtime.
+    ///
+    /// Every generated package also embeds the descriptor set as
+    /// `pub const FILE_DESCRIPTOR_SET: &[u8]`, so it can be used without file I/O.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use g2h::BridgeGenerator;
+    /// use std::env;
+    /// use std::path::PathBuf;
+    ///
+    /// let out_dir = PathBuf::from(env::var("OUT_DIR")?);
+    /// BridgeGenerator::with_tonic_build()
+    ///     .with_string_enums()
+    ///     .file_descriptor_set_path(out_dir.join("service_descriptor.bin"))
+    ///     .compile_protos(&["proto/service.proto"], &["proto"])?;
+    /// ```
+    ///
+    pub fn file_descriptor_set_path(mut self, path: impl AsRef<std::path::Path>) -> Self {
+        self.descriptor_set_path = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    ///
+    /// Generate a `reflection_service()` function in every package, building a
+    /// `tonic_reflection` server from the embedded `FILE_DESCRIPTOR_SET`.
+    ///
+    /// Requires [`file_descriptor_set_path`](Self::file_descriptor_set_path). The generated
+    /// code uses `tonic_reflection`, which must be a dependency of the crate including the
+    /// generated code.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// tonic::transport::Server::builder()
+    ///     .add_service(hello_world::reflection_service()?)
+    ///     .add_service(GreeterServer::new(greeter))
+    ///     .serve(addr)
+    ///     .await?;
+    /// ```
+    ///
+    pub fn with_reflection_service(mut self) -> Self {
+        self.enable_reflection_service = true;
+        self
+    }
+
+    ///
+    /// Write a JSON manifest of all generated routes to `path` during compilation.
+    ///
+    /// The manifest lists every route with its path, HTTP method, RPC and message types,
+    /// along with the fields of each message reachable from a request or response, for
+    /// generating API gateway configuration (Kong, Envoy, ...) in CI.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// BridgeGenerator::with_tonic_build()
+    ///     .route_manifest_path("routes.json")
+    ///     .compile_protos(&["proto/service.proto"], &["proto"])?;
+    /// ```
+    ///
+    pub fn route_manifest_path(mut self, path: impl AsRef<std::path::Path>) -> Self {
+        self.route_manifest_path = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    ///
+    /// Write the generated code to `path` instead of `OUT_DIR`.
+    ///
+    /// Relative paths given to [`file_descriptor_set_path`](Self::file_descriptor_set_path)
+    /// and [`route_manifest_path`](Self::route_manifest_path) are resolved against it as
+    /// well, so all artifacts can be generated into a dedicated crate and checked in. The
+    /// directory is created if missing. The generated code embeds the descriptor set
+    /// through a path relative to itself, so a descriptor set outside the directory is
+    /// rejected.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// BridgeGenerator::with_tonic_build()
+    ///     .out_dir("generated/src")
+    ///     .route_manifest_path("routes.json") // generated/src/routes.json
+    ///     .compile_protos(&["proto/service.proto"], &["proto"])?;
+    /// ```
+    ///
+    pub fn out_dir(mut self, path: impl AsRef<std::path::Path>) -> Self {
+        self.out_dir = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    ///
+    /// Pass an extra argument to protoc, e.g. `--experimental_allow_proto3_optional`.
+    ///
+    /// Like the other prost settings below, this applies to the configuration created by
+    /// [`compile_protos`](Self::compile_protos); with
+    /// [`compile_protos_with_config`](Self::compile_protos_with_config), set it on the
+    /// given `prost_build::Config` instead.
+    ///
+    pub fn protoc_arg(mut self, arg: impl Into<String>) -> Self {
+        self.protoc_args.push(arg.into());
+        self
+    }
+
+    ///
+    /// Generate the map fields matching `paths` as `BTreeMap` instead of `HashMap`, see
+    /// `prost_build::Config::btree_map`. Use `["."]` for all map fields.
+    ///
+    pub fn btree_map<I, S>(mut self, paths: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.btree_map_paths
+            .extend(paths.into_iter().map(|path| path.as_ref().to_string()));
+        self
+    }
+
+    ///
+    /// Generate the bytes fields matching `paths` as `bytes::Bytes` instead of `Vec<u8>`,
+    /// see `prost_build::Config::bytes`. Use `["."]` for all bytes fields.
+    ///
+    pub fn bytes<I, S>(mut self, paths: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.bytes_paths
+            .extend(paths.into_iter().map(|path| path.as_ref().to_string()));
+        self
+    }
+
+    ///
+    /// Wrap the message field matching `path` in a `Box`, see `prost_build::Config::boxed`.
+    ///
+    pub fn boxed(mut self, path: impl AsRef<str>) -> Self {
+        self.boxed_paths.push(path.as_ref().to_string());
+        self
+    }
+
+    ///
+    /// Use the Rust type at `rust_path` for the proto types under `proto_path`, e.g. types
+    /// already generated in another crate; see `prost_build::Config::extern_path`.
+    ///
+    /// No string enum serializers or skip-null attributes are generated for external types.
+    /// Fields of external enum types are serialized as numbers.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// BridgeGenerator::with_tonic_build()
+    ///     .with_string_enums()
+    ///     .extern_path(".common.v1", "::common_protos::common::v1")
+    ///     .compile_protos(&["proto/service.proto"], &["proto"])?;
+    /// ```
+    ///
+    pub fn extern_path(
+        mut self,
+        proto_path: impl AsRef<str>,
+        rust_path: impl Into<String>,
+    ) -> Self {
+        let proto_path = proto_path.as_ref();
+        let proto_path = if proto_path.starts_with('.') {
+            proto_path.to_string()
+        } else {
+            format!(".{proto_path}")
+        };
+        self.extern_paths.push((proto_path, rust_path.into()));
+        self
+    }
+
+    ///
+    /// Generate the `google.protobuf` well-known types instead of using `prost_types`, see
+    /// `prost_build::Config::compile_well_known_types`.
+    ///
+    /// The generated types then derive serde like all other messages, so messages holding a
+    /// `Timestamp` or `Duration` can be served over HTTP.
+    ///
+    pub fn compile_well_known_types(mut self) -> Self {
+        self.compile_well_known_types = true;
+        self
+    }
+
+    /// Whether the fully qualified proto name `name` maps to a type of another crate
+    fn is_extern(&self, name: &str) -> bool {
+        let is_under = |prefix: &str| {
+            name == prefix
+                || name
+                    .strip_prefix(prefix)
+                    .is_some_and(|rest| rest.starts_with('.'))
+        };
+        (!self.compile_well_known_types && is_under(".google.protobuf"))
+            || self
+                .extern_paths
+                .iter()
+                .any(|(proto_path, _)| is_under(proto_path))
+    }
+
+    /// `file_descriptor_set` without the messages of other crates, whose generated code
+    /// can't carry serde attributes, and with their enums in local fields left as numbers
+    fn local_descriptors(&self, file_descriptor_set: &FileDescriptorSet) -> FileDescriptorSet {
+        let mut local = file_descriptor_set.clone();
+        local.file.retain(|file| {
+            file.package().is_empty() || !self.is_extern(&format!(".{}", file.package()))
+        });
+        for file in &mut local.file {
+            let scope = if file.package().is_empty() {
+                String::new()
+            } else {
+                format!(".{}", file.package())
+            };
+            self.retain_local_messages(&scope, &mut file.message_type);
+        }
+        local
+    }
+
+    fn retain_local_messages(&self, scope: &str, messages: &mut Vec<DescriptorProto>) {
+        messages.retain(|message| !self.is_extern(&format!("{scope}.{}", message.name())));
+        for message in messages {
+            let name = format!("{scope}.{}", message.name());
+            for field in &mut message.field {
+                if field.r#type() == Type::Enum && self.is_extern(field.type_name()) {
+                    field.set_type(Type::Int32);
+                }
+            }
+            self.retain_local_messages(&name, &mut message.nested_type);
+        }
+    }
+
+    /// Resolve relative artifact paths against the output directory, creating it if needed
+    fn resolve_artifact_paths(&mut self) -> std::io::Result<()> {
+        let Some(ref out_dir) = self.out_dir else {
+            return Ok(());
+        };
+        std::fs::create_dir_all(out_dir)?;
+        for path in [&mut self.descriptor_set_path, &mut self.route_manifest_path]
+            .into_iter()
+            .flatten()
+        {
+            if path.is_relative() {
+                *path = out_dir.join(&*path);
+            }
+        }
+        // The generated code embeds the descriptor set relative to itself
+        if let Some(ref path) = self.descriptor_set_path {
+            if path_within(path, out_dir).is_none() {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!(
+                        "descriptor set `{}` is outside the output directory `{}`",
+                        path.display(),
+                        out_dir.display()
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Generate enum deserializer code for a specific package with field-specific serializers
+    ///
+    /// This method creates type-safe enum serialization functions that prevent conflicts
+    /// between different enums that might have the same integer values. Each enum field
+    /// gets its own dedicated serializer/deserializer functions.
+    ///
+    /// # Arguments
+    /// * `file_descriptor_set` - The protobuf file descriptor set containing enum definitions
+    /// * `target_package` - The specific package to generate serializers for
+    /// * `enum_tables` - Whether to delegate to shared per-enum lookup tables
+    /// * `max_function_id_len` - Length above which identifiers in function names are abbreviated
+    ///
+    /// # Returns
+    /// A string containing the generated Rust code with field-specific enum functions
+    fn generate_package_specific_enum_deserializer_code(
+        file_descriptor_set: &FileDescriptorSet,
+        target_package: &str,
+        enum_tables: bool,
+        max_function_id_len: Option<usize>,
+    ) -> String {
+        let package_enum_fields = Self::extract_package_enum_fields_static(
+            file_descriptor_set,
+            target_package,
+            max_function_id_len,
+        );
+
+        if package_enum_fields.is_empty() {
+            return String::new();
+        }
+
+        let field_specific_functions = if enum_tables {
+            Self::generate_table_driven_enum_functions_static(
+                file_descriptor_set,
+                &package_enum_fields,
+            )
+        } else {
+            Self::generate_field_specific_enum_functions_static(&package_enum_fields)
+        };
+
+        // Parse the generated string as token stream for quote
+        let field_functions_tokens: proc_macro2::TokenStream = field_specific_functions
             .parse()
             .expect("Generated field-specific enum functions should be valid Rust syntax");
 
+        // Document every abbreviated identifier so generated names can be traced back
+        let mut abbreviations = std::collections::BTreeMap::new();
+        for enum_field in &package_enum_fields {
+            if enum_field.field_id != enum_field.full_field_id {
+                abbreviations.insert(
+                    enum_field.field_id.clone(),
+                    enum_field.full_field_id.clone(),
+                );
+            }
+            if enum_field.enum_fn_id != Self::enum_function_id(&enum_field.enum_path, None) {
+                abbreviations.insert(enum_field.enum_fn_id.clone(), enum_field.enum_type.clone());
+            }
+        }
+        let abbreviation_docs = if abbreviations.is_empty() {
+            quote! {}
+        } else {
+            let lines = abbreviations
+                .iter()
+                .map(|(short, full)| format!("- `{short}`: `{full}`"));
+            quote! {
+                #[doc = "Abbreviated identifiers used in the function names of this module:"]
+                #[doc = ""]
+                #(#[doc = #lines])*
+            }
+        };
+
+        quote! {
+            // Auto-generated enum deserializer module for package: #target_package
+            // This file contains field-specific utilities for serializing and deserializing protobuf enums from string values in JSON
+
+            #abbreviation_docs
+            pub mod enum_deserializer {
+                use super::*;
+
+                #field_functions_tokens
+            }
+        }
+        .to_string()
+    }
+
+    /// Extract enum fields with their types from a specific package
+    fn extract_package_enum_fields_static(
+        file_descriptor_set: &FileDescriptorSet,
+        target_package: &str,
+        max_function_id_len: Option<usize>,
+    ) -> Vec<EnumField> {
+        let mut enum_fields = Vec::new();
+
+        for file in &file_descriptor_set.file {
+            let package = file.package();
+
+            // Only process files that match the target package
+            if package != target_package {
+                continue;
+            }
+
+            // Process all message types in the file
+            for message in &file.message_type {
+                Self::extract_enum_fields_from_message_static(
+                    message,
+                    &mut enum_fields,
+                    max_function_id_len,
+                );
+            }
+        }
+
+        // Descriptor order follows the order protoc was handed the files, so sort to keep
+        // the generated module identical across builds and platforms
+        enum_fields.sort_by(|a, b| a.full_field_id.cmp(&b.full_field_id));
+        enum_fields
+    }
+
+    /// Recursively extract enum fields from a message
+    fn extract_enum_fields_from_message_static(
+        message: &DescriptorProto,
+        enum_fields: &mut Vec<EnumField>,
+        max_function_id_len: Option<usize>,
+    ) {
+        Self::extract_enum_fields_from_message_with_path_static(
+            message,
+            enum_fields,
+            "",
+            max_function_id_len,
+        );
+    }
+
+    /// Helper function to extract enum fields with full message path tracking
+    fn extract_enum_fields_from_message_with_path_static(
+        message: &DescriptorProto,
+        enum_fields: &mut Vec<EnumField>,
+        message_path: &str,
+        max_function_id_len: Option<usize>,
+    ) {
+        let message_name = message.name();
+        let current_path = if message_path.is_empty() {
+            message_name.to_snake_case()
+        } else {
+            format!("{}_{}", message_path, message_name.to_snake_case())
+        };
+
+        // Process all fields in the message
+        for field in &message.field {
+            if field.r#type() == Type::Enum {
+                let full_field_id = format!("{}_{}", current_path, field.name().to_snake_case());
+                let enum_type = field.type_name().trim_start_matches('.');
+
+                let enum_path = Self::resolve_enum_path(enum_type);
+
+                let field_label = match field.label() {
+                    Label::Optional => {
+                        if field.proto3_optional() {
+                            "Option"
+                        } else {
+                            "Single"
+                        }
+                    }
+                    Label::Required => "Single",
+                    Label::Repeated => "Repeated",
+                };
+
+                enum_fields.push(EnumField {
+                    field_id: abbreviate_function_id(&full_field_id, max_function_id_len),
+                    full_field_id,
+                    enum_fn_id: Self::enum_function_id(&enum_path, max_function_id_len),
+                    enum_path,
+                    enum_type: enum_type.to_string(),
+                    field_label,
+                });
+            }
+        }
+
+        // Recursively process nested message types
+        for nested_message in &message.nested_type {
+            Self::extract_enum_fields_from_message_with_path_static(
+                nested_message,
+                enum_fields,
+                &current_path,
+                max_function_id_len,
+            );
+        }
+    }
+
+    /// Resolve the correct Rust path for an enum type from its protobuf type name
+    fn resolve_enum_path(enum_type: &str) -> String {
+        if !enum_type.contains('.') {
+            return enum_type.to_string();
+        }
+
+        let parts: Vec<&str> = enum_type.split('.').collect();
+
+        match parts.len() {
+            0 | 1 => parts.last().unwrap_or(&"UnknownEnum").to_string(),
+            2 => {
+                // Package-level enum like "package.EnumName"
+                // Use just the enum name since it's in the same crate
+                parts[1].to_string()
+            }
+            _ => {
+                // Three or more parts - need to determine the structure
+                let enum_name = parts[parts.len() - 1];
+
+                // Look for message parts (PascalCase) vs package parts (lowercase/version)
+                let mut message_parts = Vec::new();
+                let start_idx = 1; // Skip the package name
+
+                for &part in &parts[start_idx..parts.len() - 1] {
+                    if Self::is_message_name(part) {
+                        message_parts.push(part.to_snake_case());
+                    }
+                }
+
+                if message_parts.is_empty() {
+                    // No message parts found, treat as package-level enum
+                    enum_name.to_string()
+                } else {
+                    // Build the nested module path
+                    format!("{}::{}", message_parts.join("::"), enum_name)
+                }
+            }
+        }
+    }
+
+    /// Check if a name looks like a protobuf message name (PascalCase)
+    fn is_message_name(name: &str) -> bool {
+        name.chars().next().is_some_and(|c| c.is_uppercase())
+    }
+
+    /// Generate field-specific enum serialization/deserialization functions
+    ///
+    /// Serializer bodies are emitted once per enum type and field cardinality; every
+    /// field then gets thin re-exports under its field-specific names.
+    fn generate_field_specific_enum_functions_static(enum_fields: &[EnumField]) -> String {
+        let mut functions = String::new();
+        let mut emitted_functions = std::collections::HashSet::new();
+
+        for enum_field in enum_fields {
+            let enum_name = &enum_field.enum_path;
+            let enum_fn_id = &enum_field.enum_fn_id;
+
+            if emitted_functions.insert((enum_fn_id.clone(), enum_field.field_label)) {
+                let enum_ident: proc_macro2::TokenStream = enum_name
+                    .parse()
+                    .unwrap_or_else(|e| panic!("Invalid enum type path '{enum_name}': {e}"));
+
+                let function_code = match enum_field.field_label {
+                    "Single" => Self::generate_single_enum_functions(enum_fn_id, &enum_ident),
+                    "Option" => Self::generate_option_enum_functions(enum_fn_id, &enum_ident),
+                    "Repeated" => Self::generate_repeated_enum_functions(enum_fn_id, &enum_ident),
+                    _ => String::new(),
+                };
+
+                functions.push_str(&function_code);
+            }
+
+            functions.push_str(&Self::generate_field_enum_aliases(enum_field, enum_fn_id));
+        }
+
+        functions
+    }
+
+    /// Identifier used to name the shared functions of an enum type
+    ///
+    /// `hello_reply::ResponseStatus` becomes `hello_reply_response_status_enum`.
+    fn enum_function_id(enum_path: &str, max_function_id_len: Option<usize>) -> String {
+        let enum_id = enum_path.replace("::", "_").to_snake_case();
+        format!(
+            "{}_enum",
+            abbreviate_function_id(&enum_id, max_function_id_len)
+        )
+    }
+
+    /// Generate re-exports that expose the shared enum functions under field-specific names
+    fn generate_field_enum_aliases(enum_field: &EnumField, enum_fn_id: &str) -> String {
+        let prefix = match enum_field.field_label {
+            "Option" => "option_",
+            "Repeated" => "repeated_",
+            _ => "",
+        };
+        let field_id = &enum_field.field_id;
+        // `Payment.status_enum` of type `PaymentStatus` already has the functions' name
+        if field_id == enum_fn_id {
+            return String::new();
+        }
+
+        let serialize_fn = quote::format_ident!("serialize_{}{}_as_string", prefix, enum_fn_id);
+        let deserialize_fn =
+            quote::format_ident!("deserialize_{}{}_from_string", prefix, enum_fn_id);
+        let field_serialize_fn = quote::format_ident!("serialize_{}{}_as_string", prefix, field_id);
+        let field_deserialize_fn =
+            quote::format_ident!("deserialize_{}{}_from_string", prefix, field_id);
+
+        quote! {
+            #[allow(unused_imports)]
+            pub use self::#serialize_fn as #field_serialize_fn;
+            #[allow(unused_imports)]
+            pub use self::#deserialize_fn as #field_deserialize_fn;
+        }
+        .to_string()
+    }
+
+    /// Find an enum definition by its fully-qualified protobuf name (without the leading dot)
+    fn find_enum_descriptor<'a>(
+        file_descriptor_set: &'a FileDescriptorSet,
+        enum_type: &str,
+    ) -> Option<&'a EnumDescriptorProto> {
+        fn find_in_message<'a>(
+            message: &'a DescriptorProto,
+            prefix: &str,
+            enum_type: &str,
+        ) -> Option<&'a EnumDescriptorProto> {
+            let message_prefix = format!("{prefix}{}.", message.name());
+            message
+                .enum_type
+                .iter()
+                .find(|e| format!("{message_prefix}{}", e.name()) == enum_type)
+                .or_else(|| {
+                    message
+                        .nested_type
+                        .iter()
+                        .find_map(|nested| find_in_message(nested, &message_prefix, enum_type))
+                })
+        }
+
+        file_descriptor_set.file.iter().find_map(|file| {
+            let prefix = if file.package().is_empty() {
+                String::new()
+            } else {
+                format!("{}.", file.package())
+            };
+            file.enum_type
+                .iter()
+                .find(|e| format!("{prefix}{}", e.name()) == enum_type)
+                .or_else(|| {
+                    file.message_type
+                        .iter()
+                        .find_map(|message| find_in_message(message, &prefix, enum_type))
+                })
+        })
+    }
+
+    /// Generate table-driven enum serialization/deserialization functions
+    ///
+    /// Emits one static `(value, name)` table per distinct enum type plus a single set of
+    /// shared lookup helpers. The per-enum functions keep their usual signatures but only
+    /// forward to the helpers with the right table.
+    fn generate_table_driven_enum_functions_static(
+        file_descriptor_set: &FileDescriptorSet,
+        enum_fields: &[EnumField],
+    ) -> String {
+        let mut functions = Self::generate_enum_table_helpers().to_string();
+        let mut emitted_tables = std::collections::HashSet::new();
+        let mut emitted_functions = std::collections::HashSet::new();
+
+        for enum_field in enum_fields {
+            let table_ident = quote::format_ident!(
+                "{}_TABLE",
+                enum_field
+                    .enum_type
+                    .replace('.', "_")
+                    .to_shouty_snake_case()
+            );
+
+            if emitted_tables.insert(table_ident.to_string()) {
+                let Some(enum_descriptor) =
+                    Self::find_enum_descriptor(file_descriptor_set, &enum_field.enum_type)
+                else {
+                    panic!(
+                        "Enum type '{}' not found in descriptors",
+                        enum_field.enum_type
+                    );
+                };
+
+                // prost drops aliased values, so keep the first name for every number and
+                // sort by number so value lookups can binary search.
+                let mut entries: Vec<(i32, &str)> = Vec::new();
+                for value in &enum_descriptor.value {
+                    if !entries.iter().any(|(number, _)| *number == value.number()) {
+                        entries.push((value.number(), value.name()));
+                    }
+                }
+                entries.sort_by_key(|(number, _)| *number);
+
+                let numbers = entries.iter().map(|(number, _)| number);
+                let names = entries.iter().map(|(_, name)| name);
+
+                functions.push_str(
+                    &quote! {
+                        #[allow(dead_code)]
+                        pub const #table_ident: EnumTable = &[#((#numbers, #names)),*];
+                    }
+                    .to_string(),
+                );
+            }
+
+            let enum_name = enum_field.enum_path.as_str();
+            let enum_fn_id = &enum_field.enum_fn_id;
+
+            if emitted_functions.insert((enum_fn_id.clone(), enum_field.field_label)) {
+                let function_code = match enum_field.field_label {
+                    "Single" => {
+                        let serialize_fn =
+                            quote::format_ident!("serialize_{}_as_string", enum_fn_id);
+                        let deserialize_fn =
+                            quote::format_ident!("deserialize_{}_from_string", enum_fn_id);
+                        quote! {
+                            #[allow(dead_code)]
+                            pub fn #serialize_fn<S: serde::Serializer>(value: &i32, serializer: S) -> Result<S::Ok, S::Error> {
+                                serialize_single_with_table(#table_ident, value, serializer)
+                            }
+
+                            #[allow(dead_code)]
+                            pub fn #deserialize_fn<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<i32, D::Error> {
+                                deserialize_single_with_table(#table_ident, #enum_name, deserializer)
+                            }
+                        }
+                    }
+                    "Option" => {
+                        let serialize_fn =
+                            quote::format_ident!("serialize_option_{}_as_string", enum_fn_id);
+                        let deserialize_fn =
+                            quote::format_ident!("deserialize_option_{}_from_string", enum_fn_id);
+                        quote! {
+                            #[allow(dead_code)]
+                            pub fn #serialize_fn<S: serde::Serializer>(value: &Option<i32>, serializer: S) -> Result<S::Ok, S::Error> {
+                                serialize_option_with_table(#table_ident, value, serializer)
+                            }
+
+                            #[allow(dead_code)]
+                            pub fn #deserialize_fn<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Option<i32>, D::Error> {
+                                deserialize_option_with_table(#table_ident, #enum_name, deserializer)
+                            }
+                        }
+                    }
+                    "Repeated" => {
+                        let serialize_fn =
+                            quote::format_ident!("serialize_repeated_{}_as_string", enum_fn_id);
+                        let deserialize_fn =
+                            quote::format_ident!("deserialize_repeated_{}_from_string", enum_fn_id);
+                        quote! {
+                            #[allow(dead_code)]
+                            pub fn #serialize_fn<S: serde::Serializer>(values: &[i32], serializer: S) -> Result<S::Ok, S::Error> {
+                                serialize_repeated_with_table(#table_ident, values, serializer)
+                            }
+
+                            #[allow(dead_code)]
+                            pub fn #deserialize_fn<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Vec<i32>, D::Error> {
+                                deserialize_repeated_with_table(#table_ident, #enum_name, deserializer)
+                            }
+                        }
+                    }
+                    _ => quote! {},
+                };
+
+                functions.push_str(&function_code.to_string());
+            }
+
+            functions.push_str(&Self::generate_field_enum_aliases(enum_field, enum_fn_id));
+        }
+
+        functions
+    }
+
+    /// Generate the shared lookup helpers used by table-driven enum functions
+    fn generate_enum_table_helpers() -> proc_macro2::TokenStream {
+        quote! {
+            /// `(value, name)` pairs of a protobuf enum, sorted by value
+            pub type EnumTable = &'static [(i32, &'static str)];
+
+            #[derive(serde::Deserialize)]
+            #[serde(untagged)]
+            #[allow(dead_code)]
+            enum EnumOrString {
+                String(String),
+                Int(i32),
+            }
+
+            #[allow(dead_code)]
+            fn table_name(table: EnumTable, value: i32) -> Option<&'static str> {
+                let index = table.partition_point(|(number, _)| *number < value);
+                table.get(index).filter(|(number, _)| *number == value).map(|(_, name)| *name)
+            }
+
+            #[allow(dead_code)]
+            fn table_value<E: serde::de::Error>(table: EnumTable, enum_name: &str, item: EnumOrString) -> Result<i32, E> {
+                match item {
+                    EnumOrString::String(s) => table
+                        .iter()
+                        .find(|(_, name)| *name == s)
+                        .map(|(number, _)| *number)
+                        .ok_or_else(|| E::custom(format!("Unknown enum value for {}: {}", enum_name, s))),
+                    EnumOrString::Int(i) => Ok(i),
+                }
+            }
+
+            #[allow(dead_code)]
+            fn serialize_single_with_table<S: serde::Serializer>(table: EnumTable, value: &i32, serializer: S) -> Result<S::Ok, S::Error> {
+                use serde::Serialize;
+                match table_name(table, *value) {
+                    Some(name) => name.serialize(serializer),
+                    None => value.serialize(serializer),
+                }
+            }
+
+            #[allow(dead_code)]
+            fn serialize_option_with_table<S: serde::Serializer>(table: EnumTable, value: &Option<i32>, serializer: S) -> Result<S::Ok, S::Error> {
+                match value {
+                    Some(val) => serializer.serialize_some(&EnumValue(table, *val)),
+                    None => serializer.serialize_none(),
+                }
+            }
+
+            #[allow(dead_code)]
+            fn serialize_repeated_with_table<S: serde::Serializer>(table: EnumTable, values: &[i32], serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_seq(values.iter().map(|val| {
+                    table_name(table, *val).map(str::to_string).unwrap_or_else(|| val.to_string())
+                }))
+            }
+
+            #[allow(dead_code)]
+            fn deserialize_single_with_table<'de, D: serde::Deserializer<'de>>(table: EnumTable, enum_name: &str, deserializer: D) -> Result<i32, D::Error> {
+                use serde::Deserialize;
+                table_value(table, enum_name, EnumOrString::deserialize(deserializer)?)
+            }
+
+            #[allow(dead_code)]
+            fn deserialize_option_with_table<'de, D: serde::Deserializer<'de>>(table: EnumTable, enum_name: &str, deserializer: D) -> Result<Option<i32>, D::Error> {
+                use serde::Deserialize;
+                Option::<EnumOrString>::deserialize(deserializer)?
+                    .map(|item| table_value(table, enum_name, item))
+                    .transpose()
+            }
+
+            #[allow(dead_code)]
+            fn deserialize_repeated_with_table<'de, D: serde::Deserializer<'de>>(table: EnumTable, enum_name: &str, deserializer: D) -> Result<Vec<i32>, D::Error> {
+                use serde::Deserialize;
+                Vec::<EnumOrString>::deserialize(deserializer)?
+                    .into_iter()
+                    .map(|item| table_value(table, enum_name, item))
+                    .collect()
+            }
+
+            #[allow(dead_code)]
+            struct EnumValue(EnumTable, i32);
+
+            impl serde::Serialize for EnumValue {
+                fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    serialize_single_with_table(self.0, &self.1, serializer)
+                }
+            }
+        }
+    }
+
+    /// Generate serializer/deserializer functions for a single enum value
+    fn generate_single_enum_functions(
+        enum_fn_id: &str,
+        enum_ident: &proc_macro2::TokenStream,
+    ) -> String {
+        let serialize_fn = quote::format_ident!("serialize_{}_as_string", enum_fn_id);
+        let deserialize_fn = quote::format_ident!("deserialize_{}_from_string", enum_fn_id);
+
+        quote! {
+            #[allow(dead_code)]
+            pub fn #serialize_fn<S>(value: &i32, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: serde::Serializer,
+            {
+                use serde::Serialize;
+                if let Ok(enum_val) = #enum_ident::try_from(*value) {
+                    enum_val.as_str_name().serialize(serializer)
+                } else {
+                    value.serialize(serializer)
+                }
+            }
+
+            #[allow(dead_code)]
+            pub fn #deserialize_fn<'de, D>(deserializer: D) -> Result<i32, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                use serde::Deserialize;
+
+                #[derive(Deserialize)]
+                #[serde(untagged)]
+                #[allow(dead_code)]
+                enum EnumOrString {
+                    String(String),
+                    Int(i32),
+                }
+
+                match EnumOrString::deserialize(deserializer)? {
+                    EnumOrString::String(s) => {
+                        if let Some(enum_val) = #enum_ident::from_str_name(&s) {
+                            Ok(enum_val as i32)
+                        } else {
+                            Err(serde::de::Error::custom(format!("Unknown enum value for {}: {}", stringify!(#enum_ident), s)))
+                        }
+                    }
+                    EnumOrString::Int(i) => Ok(i),
+                }
+            }
+        }.to_string()
+    }
+
+    /// Generate serializer/deserializer functions for an optional enum value
+    fn generate_option_enum_functions(
+        enum_fn_id: &str,
+        enum_ident: &proc_macro2::TokenStream,
+    ) -> String {
+        let serialize_fn = quote::format_ident!("serialize_option_{}_as_string", enum_fn_id);
+        let deserialize_fn = quote::format_ident!("deserialize_option_{}_from_string", enum_fn_id);
+
+        quote! {
+            #[allow(dead_code)]
+            pub fn #serialize_fn<S>(value: &Option<i32>, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: serde::Serializer,
+            {
+                use serde::Serialize;
+                match value {
+                    Some(val) => {
+                        if let Ok(enum_val) = #enum_ident::try_from(*val) {
+                            Some(enum_val.as_str_name()).serialize(serializer)
+                        } else {
+                            Some(*val).serialize(serializer)
+                        }
+                    }
+                    None => None::<&str>.serialize(serializer),
+                }
+            }
+
+            #[allow(dead_code)]
+            pub fn #deserialize_fn<'de, D>(deserializer: D) -> Result<Option<i32>, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                use serde::Deserialize;
+                #[derive(Deserialize)]
+                #[serde(untagged)]
+                #[allow(dead_code)]
+                enum OptionalEnumOrString {
+                    String(String),
+                    Int(i32),
+                    None,
+                }
+                match Option::<OptionalEnumOrString>::deserialize(deserializer)? {
+                    Some(OptionalEnumOrString::String(s)) => {
+                        if let Some(enum_val) = #enum_ident::from_str_name(&s) {
+                            Ok(Some(enum_val as i32))
+                        } else {
+                            Err(serde::de::Error::custom(format!("Unknown enum value for {}: {}", stringify!(#enum_ident), s)))
+                        }
+                    }
+                    Some(OptionalEnumOrString::Int(i)) => Ok(Some(i)),
+                    Some(OptionalEnumOrString::None) | None => Ok(None),
+                }
+            }
+        }.to_string()
+    }
+
+    /// Generate serializer/deserializer functions for a repeated enum value
+    fn generate_repeated_enum_functions(
+        enum_fn_id: &str,
+        enum_ident: &proc_macro2::TokenStream,
+    ) -> String {
+        let serialize_fn = quote::format_ident!("serialize_repeated_{}_as_string", enum_fn_id);
+        let deserialize_fn =
+            quote::format_ident!("deserialize_repeated_{}_from_string", enum_fn_id);
+
+        quote! {
+            #[allow(dead_code)]
+            pub fn #serialize_fn<S>(values: &[i32], serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: serde::Serializer,
+            {
+                use serde::Serialize;
+                let string_values: Vec<_> = values.iter().map(|val| {
+                    if let Ok(enum_val) = #enum_ident::try_from(*val) {
+                        enum_val.as_str_name().to_string()
+                    } else {
+                        val.to_string()
+                    }
+                }).collect();
+                string_values.serialize(serializer)
+            }
+
+            #[allow(dead_code)]
+            pub fn #deserialize_fn<'de, D>(deserializer: D) -> Result<Vec<i32>, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                use serde::Deserialize;
+                #[derive(Deserialize)]
+                #[serde(untagged)]
+                #[allow(dead_code)]
+                enum EnumOrStringItem {
+                    String(String),
+                    Int(i32),
+                }
+                let items: Vec<EnumOrStringItem> = Vec::deserialize(deserializer)?;
+                let mut result = Vec::with_capacity(items.len());
+
+                for item in items {
+                    match item {
+                        EnumOrStringItem::String(s) => {
+                            if let Some(enum_val) = #enum_ident::from_str_name(&s) {
+                                result.push(enum_val as i32);
+                            } else {
+                                return Err(serde::de::Error::custom(format!("Unknown enum value for {}: {}", stringify!(#enum_ident), s)));
+                            }
+                        }
+                        EnumOrStringItem::Int(i) => {
+                            result.push(i);
+                        }
+                    }
+                }
+
+                Ok(result)
+            }
+        }.to_string()
+    }
+}
+
+/// Access level required by a method, see [`BridgeGenerator::with_access_level`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessLevel {
+    /// Callable without authorization
+    Public,
+    /// Callable by authenticated callers
+    Authenticated,
+    /// Callable by administrators
+    Admin,
+}
+
+impl AccessLevel {
+    /// Parses the `access` value of the route and service options
+    fn from_option(value: &str) -> Option<Self> {
+        match value {
+            "public" => Some(AccessLevel::Public),
+            "authenticated" => Some(AccessLevel::Authenticated),
+            "admin" => Some(AccessLevel::Admin),
+            _ => None,
+        }
+    }
+}
+
+/// HTTP verb serving a method, see [`BridgeGenerator::with_method_verb`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HttpVerb {
+    /// `GET`, with the request fields in the query string
+    Get,
+    /// `POST`
+    Post,
+    /// `PUT`
+    Put,
+    /// `PATCH`
+    Patch,
+    /// `DELETE`
+    Delete,
+}
+
+impl HttpVerb {
+    fn as_str(self) -> &'static str {
+        match self {
+            HttpVerb::Get => "GET",
+            HttpVerb::Post => "POST",
+            HttpVerb::Put => "PUT",
+            HttpVerb::Patch => "PATCH",
+            HttpVerb::Delete => "DELETE",
+        }
+    }
+}
+
+/// How the generated code combines several values for the same header or metadata key.
+///
+/// The same policy is applied to request headers on their way into metadata and to
+/// response metadata on its way out as headers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicateHeaderPolicy {
+    /// Forward every value as a separate entry (the default)
+    #[default]
+    KeepAll,
+    /// Forward only the first value
+    KeepFirst,
+    /// Join all values into one entry separated by `", "`, as permitted by RFC 9110.
+    /// Binary (`-bin`) entries are always kept separate since their values are base64.
+    Join,
+}
+
+/// HTTP framework targeted by the generated handlers.
+///
+/// See [`BridgeGenerator::with_backend`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Backend {
+    /// axum routers (the default)
+    #[default]
+    Axum,
+    /// actix-web service configurations, for unary methods
+    Actix,
+}
+
+/// Naming convention of the route paths generated for methods.
+///
+/// See [`BridgeGenerator::with_route_path_case`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RoutePathCase {
+    /// `/user.v1.UserService/CreateUser`, the gRPC path (the default)
+    #[default]
+    Dotted,
+    /// `/user-service/create-user`
+    KebabCase,
+    /// `/user_service/create_user`
+    SnakeCase,
+    /// `/user.v1.userservice/createuser`, the gRPC path in lowercase
+    Lowercase,
+}
+
+/// Code rendered by [`BridgeGenerator::render`], by output file name.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GeneratedSources {
+    files: std::collections::BTreeMap<String, String>,
+}
+
+impl GeneratedSources {
+    /// Returns the code of the output file `name`, e.g. `hello_world.rs`
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.files.get(name).map(String::as_str)
+    }
+
+    /// Iterates over the output files and their code, sorted by file name
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.files
+            .iter()
+            .map(|(name, code)| (name.as_str(), code.as_str()))
+    }
+
+    /// Returns the number of output files
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Returns whether no file was rendered
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+}
+
+/// Service described to a [`BridgeGenerator::with_route_naming`] callback.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ServiceInfo {
+    /// Protobuf package, e.g. `user.v1`
+    pub package: String,
+    /// Protobuf service name, e.g. `UserService`
+    pub name: String,
+}
+
+/// Method described to a [`BridgeGenerator::with_route_naming`] callback.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct MethodInfo {
+    /// Protobuf method name, e.g. `CreateUser`
+    pub name: String,
+    /// Fully-qualified protobuf request type, e.g. `user.v1.CreateUserRequest`
+    pub input_type: String,
+    /// Fully-qualified protobuf response type, e.g. `user.v1.User`
+    pub output_type: String,
+}
+
+/// Method described to a [`BridgeGenerator::with_handler_template`] callback.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct HandlerContext {
+    /// Service of the method
+    pub service: ServiceInfo,
+    /// Method of the handler
+    pub method: MethodInfo,
+    /// HTTP route path, e.g. `/user.v1.UserService/CreateUser`
+    pub path: String,
+    /// HTTP method of the route, e.g. `POST`
+    pub verb: String,
+    /// Name the handler must have to be routed, e.g. `user_service_create_user_http_handler`
+    pub handler_name: String,
+    /// Name of the built-in handler, kept for custom handlers to delegate to
+    pub builtin_handler_name: String,
+    /// Rust path of the request message, e.g. `super::CreateUserRequest`
+    pub request_type: String,
+    /// Rust path of the response message
+    pub response_type: String,
+    /// Bounds of the service type `T` in the generated routers, e.g.
+    /// `?Sized + user_service_server::UserService`
+    pub server_bound: String,
+    /// Rust type of the router state, given the service type `T`
+    pub state_type: String,
+}
+
+/// Code injected into every generated HTTP handler.
+///
+/// See [`BridgeGenerator::with_hook`]. Both methods return nothing by default.
+pub trait GeneratorHook {
+    /// Code run before the service is called, after the request is decoded and checked.
+    ///
+    /// `body` (the request message), `metadata_map` (its `tonic::metadata::MetadataMap`)
+    /// and `extension` (the request `http::Extensions`) are in scope and can be shadowed.
+    /// The code can `return` an `axum::response::Response` to answer without calling
+    /// the service.
+    fn before_call(&self, method: &MethodInfo) -> proc_macro2::TokenStream {
+        let _ = method;
+        proc_macro2::TokenStream::new()
+    }
+
+    /// Code run after the service call, once its result became the HTTP response.
+    ///
+    /// `response` (an `axum::response::Response`) is in scope and can be shadowed.
+    fn after_call(&self, method: &MethodInfo) -> proc_macro2::TokenStream {
+        let _ = method;
+        proc_macro2::TokenStream::new()
+    }
+}
+
+/// CORS policy attached to generated routers.
+///
+/// See [`BridgeGenerator::with_cors`]. The generated code uses `tower_http::cors`, so
+/// `tower-http` (with the `cors` feature) must be a dependency of the crate including the
+/// generated code.
+#[derive(Debug, Clone, Default)]
+pub struct CorsSpec {
+    origins: Vec<String>,
+    headers: Vec<String>,
+    allow_credentials: bool,
+    max_age: Option<std::time::Duration>,
+    services: Vec<String>,
+}
+
+impl CorsSpec {
+    /// Creates a policy allowing no origins
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allows requests from `origin` (e.g. `https://app.example.com`), or any origin with `*`
+    pub fn allow_origin(mut self, origin: impl Into<String>) -> Self {
+        self.origins.push(origin.into());
+        self
+    }
+
+    /// Allows the request header `header`, or any header with `*`
+    pub fn allow_header(mut self, header: impl Into<String>) -> Self {
+        self.headers.push(header.into().to_ascii_lowercase());
+        self
+    }
+
+    /// Allows credentialed requests, which requires explicit origins and headers
+    pub fn allow_credentials(mut self, allow: bool) -> Self {
+        self.allow_credentials = allow;
+        self
+    }
+
+    /// Lets browsers cache preflight responses for `max_age`
+    pub fn max_age(mut self, max_age: std::time::Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Restricts the policy to the service `service` (e.g. `user.v1.UserService`)
+    pub fn for_service(mut self, service: impl Into<String>) -> Self {
+        self.services
+            .push(service.into().trim_start_matches('.').to_string());
+        self
+    }
+
+    // Origins and headers the generated layer can't send, and credentials allowed with `*`,
+    // which browsers refuse for credentialed requests
+    fn check(&self) -> Result<(), String> {
+        let invalid_origin = self
+            .origins
+            .iter()
+            .find(|origin| !origin.bytes().all(|b| b.is_ascii_graphic()));
+        if let Some(origin) = invalid_origin {
+            return Err(format!(
+                "CORS origin `{origin}` is not a valid header value"
+            ));
+        }
+        let invalid_header = self
+            .headers
+            .iter()
+            .find(|header| *header != "*" && !is_header_name(header));
+        if let Some(header) = invalid_header {
+            return Err(format!("CORS header `{header}` is not a valid header name"));
+        }
+        let any_value = self
+            .origins
+            .iter()
+            .chain(&self.headers)
+            .any(|value| value == "*");
+        if self.allow_credentials && any_value {
+            return Err(
+                "CORS policies allowing credentials need explicit origins and headers instead of `*`"
+                    .to_string(),
+            );
+        }
+        Ok(())
+    }
+
+    // Whether the policy applies to `service_path`
+    fn applies_to(&self, service_path: &str) -> bool {
+        self.services.is_empty() || self.services.iter().any(|service| service == service_path)
+    }
+
+    // Tokens of the `PreflightPolicy` answering preflight requests with the policy's headers
+    fn preflight_policy_tokens(&self) -> proc_macro2::TokenStream {
+        let any_origin = self.origins.iter().any(|origin| origin == "*");
+        let origins = &self.origins;
+        let any_header = self.headers.iter().any(|header| header == "*");
+        let headers = self.headers.join(", ");
+        let allow_credentials = self.allow_credentials;
+        let max_age = match self.max_age {
+            Some(max_age) => {
+                let secs = max_age.as_secs();
+                quote! { Some(#secs) }
+            }
+            None => quote! { None },
+        };
+
+        quote! {
+            PreflightPolicy {
+                origins: &[#(#origins),*],
+                any_origin: #any_origin,
+                headers: #headers,
+                any_header: #any_header,
+                allow_credentials: #allow_credentials,
+                max_age: #max_age,
+            }
+        }
+    }
+
+    // Tokens building the `tower_http` layer of the policy for routes served with `verbs`
+    fn layer_tokens(&self, verbs: &[String]) -> proc_macro2::TokenStream {
+        let allow_origin = if self.origins.iter().any(|origin| origin == "*") {
+            quote! { ::tower_http::cors::AllowOrigin::any() }
+        } else {
+            let origins = &self.origins;
+            quote! { ::tower_http::cors::AllowOrigin::list([#(::http::HeaderValue::from_static(#origins)),*]) }
+        };
+        let allow_headers = if self.headers.iter().any(|header| header == "*") {
+            quote! { ::tower_http::cors::AllowHeaders::any() }
+        } else {
+            let headers = &self.headers;
+            quote! { ::tower_http::cors::AllowHeaders::list([#(::http::header::HeaderName::from_static(#headers)),*]) }
+        };
+        let mut verbs = verbs.to_vec();
+        verbs.sort();
+        verbs.dedup();
+        let methods = verbs.iter().map(|verb| quote::format_ident!("{}", verb));
+        let allow_credentials = self.allow_credentials;
+        let max_age = self.max_age.map(|max_age| {
+            let secs = max_age.as_secs();
+            quote! { .max_age(::std::time::Duration::from_secs(#secs)) }
+        });
+
+        quote! {
+            ::tower_http::cors::CorsLayer::new()
+                .allow_origin(#allow_origin)
+                .allow_methods([#(::http::Method::#methods),*])
+                .allow_headers(#allow_headers)
+                .allow_credentials(#allow_credentials)
+                #max_age
+        }
+    }
+}
+
+/// Naming convention of JSON object keys in an API version dialect.
+///
+/// See [`BridgeGenerator::with_api_version`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JsonFieldCase {
+    /// `field_name`, as generated by prost
+    SnakeCase,
+    /// `fieldName`
+    CamelCase,
+}
+
+/// Service mesh whose tracing and routing headers always cross the HTTP/gRPC hop.
+///
+/// See [`BridgeGenerator::with_mesh_preset`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MeshPreset {
+    /// Istio request id, B3, W3C, OpenTracing and Cloud Trace headers
+    Istio,
+    /// Linkerd context, override and sampling headers, plus B3 and W3C trace headers
+    Linkerd,
+    /// Envoy request id, tracing, timeout and retry headers
+    Envoy,
+}
+
+impl MeshPreset {
+    /// Header names propagated by this preset (lowercase)
+    pub fn headers(self) -> &'static [&'static str] {
+        match self {
+            MeshPreset::Istio => &[
+                "x-request-id",
+                "x-b3-traceid",
+                "x-b3-spanid",
+                "x-b3-parentspanid",
+                "x-b3-sampled",
+                "x-b3-flags",
+                "b3",
+                "x-ot-span-context",
+                "x-cloud-trace-context",
+                "traceparent",
+                "tracestate",
+                "grpc-trace-bin",
+            ],
+            MeshPreset::Linkerd => &[
+                "l5d-dst-override",
+                "l5d-ctx-trace",
+                "l5d-ctx-deadline",
+                "l5d-ctx-dtab",
+                "l5d-dtab",
+                "l5d-sample",
+                "x-b3-traceid",
+                "x-b3-spanid",
+                "x-b3-parentspanid",
+                "x-b3-sampled",
+                "x-b3-flags",
+                "b3",
+                "traceparent",
+                "tracestate",
+            ],
+            MeshPreset::Envoy => &[
+                "x-request-id",
+                "x-client-trace-id",
+                "x-envoy-force-trace",
+                "x-b3-traceid",
+                "x-b3-spanid",
+                "x-b3-parentspanid",
+                "x-b3-sampled",
+                "x-b3-flags",
+                "b3",
+                "x-ot-span-context",
+                "traceparent",
+                "tracestate",
+                "x-envoy-expected-rq-timeout-ms",
+                "x-envoy-upstream-rq-timeout-ms",
+                "x-envoy-retry-on",
+                "x-envoy-max-retries",
+                "x-envoy-attempt-count",
+            ],
+        }
+    }
+}
+
+///
+/// Include path providing `g2h/options.proto`, for use with
+/// [`BridgeGenerator::with_route_options`].
+///
+pub fn options_proto_include() -> std::path::PathBuf {
+    std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("proto")
+}
+
+/// Configuration helper for building prost config with automatic enum detection
+pub struct EnumConfig {
+    generator: BridgeGenerator,
+}
+impl EnumConfig {
+    /// Create a new EnumConfig from a BridgeGenerator
+    pub fn new(generator: BridgeGenerator) -> Self {
+        Self { generator }
+    }
+
+    /// Build prost config with automatic enum field detection and deserializers
+    pub fn build_prost_config_with_descriptors(
+        self,
+        file_descriptor_set: &FileDescriptorSet,
+    ) -> prost_build::Config {
+        let enable_string_enums = self.generator.enable_string_enums;
+        let max_function_id_len = self.generator.max_function_id_len;
+        let file_descriptor_set = &self.generator.local_descriptors(file_descriptor_set);
+        let mut config = self.generator.build_prost_config();
+
+        if enable_string_enums {
+            config = Self::add_enum_string_support_static(
+                config,
+                file_descriptor_set,
+                max_function_id_len,
+            );
+        }
+
+        // Add skip nulls support by default
+        config = Self::add_skip_nulls_support_static(config, file_descriptor_set);
+
+        config
+    }
+
+    /// Add enum string support by detecting enum fields automatically (static version)
+    fn add_enum_string_support_static(
+        mut config: prost_build::Config,
+        file_descriptor_set: &FileDescriptorSet,
+        max_function_id_len: Option<usize>,
+    ) -> prost_build::Config {
+        for file in &file_descriptor_set.file {
+            config = Self::process_file_descriptor_static(config, file, max_function_id_len);
+        }
+        config
+    }
+
+    fn process_file_descriptor_static(
+        mut config: prost_build::Config,
+        file: &FileDescriptorProto,
+        max_function_id_len: Option<usize>,
+    ) -> prost_build::Config {
+        // Process all message types in the file
+        for message in &file.message_type {
+            let package = file.package();
+            config = Self::process_message_descriptor_static(
+                config,
+                message,
+                package,
+                max_function_id_len,
+            );
+        }
+        config
+    }
+
+    fn process_message_descriptor_static(
+        config: prost_build::Config,
+        message: &DescriptorProto,
+        package: &str,
+        max_function_id_len: Option<usize>,
+    ) -> prost_build::Config {
+        Self::process_message_descriptor_with_path_static(
+            config,
+            message,
+            package,
+            "",
+            max_function_id_len,
+        )
+    }
+
+    fn process_message_descriptor_with_path_static(
+        mut config: prost_build::Config,
+        message: &DescriptorProto,
+        package: &str,
+        message_path: &str,
+        max_function_id_len: Option<usize>,
+    ) -> prost_build::Config {
+        let message_name = message.name();
+        let current_path = if message_path.is_empty() {
+            message_name.to_snake_case()
+        } else {
+            format!("{}_{}", message_path, message_name.to_snake_case())
+        };
+
+        // Determine if this is a nested message (has a parent message path)
+        let is_nested = !message_path.is_empty();
+
+        // Process all fields in the message
+        for field in &message.field {
+            if Self::is_enum_field_static(field) {
+                config = Self::add_enum_deserializer_with_path_static(
+                    config,
+                    &current_path,
+                    message_name,
+                    field,
+                    package,
+                    is_nested,
+                    max_function_id_len,
+                );
+            }
+        }
+
+        // Recursively process nested message types
+        for nested_message in &message.nested_type {
+            config = Self::process_message_descriptor_with_path_static(
+                config,
+                nested_message,
+                package,
+                &current_path,
+                max_function_id_len,
+            );
+        }
+
+        config
+    }
+
+    fn is_enum_field_static(field: &FieldDescriptorProto) -> bool {
+        // Check if the field type is an enum
+        field.r#type() == Type::Enum
+    }
+
+    fn add_enum_deserializer_with_path_static(
+        mut config: prost_build::Config,
+        message_path: &str,
+        message_name: &str,
+        field: &FieldDescriptorProto,
+        _package: &str,
+        is_nested: bool,
+        max_function_id_len: Option<usize>,
+    ) -> prost_build::Config {
+        // Use the actual message name for the field_path (what prost expects)
+        let field_path = format!("{}.{}", message_name, field.name());
+
+        // Create field-specific serializer function names using the full path
+        let field_id = abbreviate_function_id(
+            &format!("{}_{}", message_path, field.name().to_snake_case()),
+            max_function_id_len,
+        );
+
+        // Use the correct module path based on whether this message is nested
+        let enum_deserializer_path = if is_nested {
+            "super::enum_deserializer"
+        } else {
+            "enum_deserializer"
+        };
+
+        let serde_attribute = match Self::get_field_label_static(field) {
+            FieldLabel::Optional => {
+                // For optional fields, check if prost would generate Option<T> or just T with default
+                if field.proto3_optional() {
+                    format!("#[serde(serialize_with = \"{enum_deserializer_path}::serialize_option_{field_id}_as_string\", deserialize_with = \"{enum_deserializer_path}::deserialize_option_{field_id}_from_string\", default)]")
+                } else {
+                    // In proto3, scalar types have implicit defaults, so use regular deserializer
+                    format!("#[serde(serialize_with = \"{enum_deserializer_path}::serialize_{field_id}_as_string\", deserialize_with = \"{enum_deserializer_path}::deserialize_{field_id}_from_string\", default)]")
+                }
+            },
+            FieldLabel::Required => format!("#[serde(serialize_with = \"{enum_deserializer_path}::serialize_{field_id}_as_string\", deserialize_with = \"{enum_deserializer_path}::deserialize_{field_id}_from_string\")]"),
+            FieldLabel::Repeated => format!("#[serde(serialize_with = \"{enum_deserializer_path}::serialize_repeated_{field_id}_as_string\", deserialize_with = \"{enum_deserializer_path}::deserialize_repeated_{field_id}_from_string\", default)]"),
+        };
+
+        config.field_attribute(&field_path, &serde_attribute);
+        config
+    }
+
+    fn get_field_label_static(field: &FieldDescriptorProto) -> FieldLabel {
+        match field.label() {
+            Label::Optional => FieldLabel::Optional,
+            Label::Required => FieldLabel::Required,
+            Label::Repeated => FieldLabel::Repeated,
+        }
+    }
+
+    /// Add skip nulls support by detecting field types and adding appropriate skip_serializing_if attributes
+    fn add_skip_nulls_support_static(
+        mut config: prost_build::Config,
+        file_descriptor_set: &FileDescriptorSet,
+    ) -> prost_build::Config {
+        for file in &file_descriptor_set.file {
+            for message in &file.message_type {
+                config = Self::process_message_skip_nulls_recursive(config, message);
+            }
+        }
+        config
+    }
+
+    fn process_message_skip_nulls_recursive(
+        mut config: prost_build::Config,
+        message: &DescriptorProto,
+    ) -> prost_build::Config {
+        let message_name = message.name();
+
+        // Process all fields in the message
+        for field in &message.field {
+            config = Self::add_skip_null_attribute_static(config, message_name, field);
+        }
+
+        // Recursively process nested message types
+        for nested_message in &message.nested_type {
+            config = Self::process_message_skip_nulls_recursive(config, nested_message);
+        }
+
+        config
+    }
+
+    fn add_skip_null_attribute_static(
+        mut config: prost_build::Config,
+        message_name: &str,
+        field: &FieldDescriptorProto,
+    ) -> prost_build::Config {
+        const SKIP_NONE: &str = "#[serde(skip_serializing_if = \"Option::is_none\")]";
+        const SKIP_EMPTY: &str = "#[serde(skip_serializing_if = \"String::is_empty\")]";
+        let field_path = format!("{}.{}", message_name, field.name());
+        let skip_attribute = if field.proto3_optional()
+            || (field.label() == Label::Optional && field.r#type() == Type::Message)
+        {
+            Some(SKIP_NONE)
+        } else if field.r#type() == Type::String && field.label() != Label::Repeated {
+            Some(SKIP_EMPTY)
+        } else {
+            None
+        };
+
+        if let Some(attribute) = skip_attribute {
+            config.field_attribute(&field_path, attribute);
+        }
+
+        config
+    }
+
+    /// Generate enum deserializer code that can be included in the generated crate
+    pub fn generate_enum_deserializer_code(
+        &self,
+        file_descriptor_set: &FileDescriptorSet,
+    ) -> String {
+        Self::generate_enum_deserializer_code_static(file_descriptor_set)
+    }
+
+    /// Static version for generating enum deserializer code
+    fn generate_enum_deserializer_code_static(file_descriptor_set: &FileDescriptorSet) -> String {
+        let enum_types = Self::extract_all_enum_types_static(file_descriptor_set);
+
+        let enum_list_macro = Self::generate_enum_list_macro_static(&enum_types);
+        let enum_serializer_macro = Self::generate_enum_serializer_macro_static(&enum_types);
+        let single_deserializer = Self::generate_single_enum_deserializer_static();
+        let option_deserializer = Self::generate_option_enum_deserializer_static();
+        let repeated_deserializer = Self::generate_repeated_enum_deserializer_static();
+        let single_serializer = Self::generate_single_enum_serializer_static();
+        let option_serializer = Self::generate_option_enum_serializer_static();
+        let repeated_serializer = Self::generate_repeated_enum_serializer_static();
+
+        // Parse the generated strings as token streams for quote
+        let enum_list_tokens: proc_macro2::TokenStream = enum_list_macro.parse().unwrap();
+        let enum_serializer_tokens: proc_macro2::TokenStream =
+            enum_serializer_macro.parse().unwrap();
+        let single_deserializer_tokens: proc_macro2::TokenStream =
+            single_deserializer.parse().unwrap();
+        let option_deserializer_tokens: proc_macro2::TokenStream =
+            option_deserializer.parse().unwrap();
+        let repeated_deserializer_tokens: proc_macro2::TokenStream =
+            repeated_deserializer.parse().unwrap();
+        let single_serializer_tokens: proc_macro2::TokenStream = single_serializer.parse().unwrap();
+        let option_serializer_tokens: proc_macro2::TokenStream = option_serializer.parse().unwrap();
+        let repeated_serializer_tokens: proc_macro2::TokenStream =
+            repeated_serializer.parse().unwrap();
+
+        quote! {
+            // Auto-generated enum deserializer module
+            // This file contains utilities for serializing and deserializing protobuf enums from string values in JSON
+
+            pub mod enum_deserializer {
+                use super::*;
+
+                #enum_list_tokens
+
+                #enum_serializer_tokens
+
+                #single_deserializer_tokens
+
+                #option_deserializer_tokens
+
+                #repeated_deserializer_tokens
+
+                #single_serializer_tokens
+
+                #option_serializer_tokens
+
+                #repeated_serializer_tokens
+            }
+        }
+        .to_string()
+    }
+
+    fn extract_all_enum_types_static(file_descriptor_set: &FileDescriptorSet) -> Vec<String> {
+        let mut enum_types = Vec::new();
+
+        for file in &file_descriptor_set.file {
+            // Top-level enums
+            for enum_desc in &file.enum_type {
+                let enum_name = enum_desc.name();
+                enum_types.push(enum_name.to_string());
+            }
+
+            // Enums in messages (recursive)
+            for message in &file.message_type {
+                enum_types.extend(Self::extract_nested_enums_static(message, ""));
+            }
+        }
+
+        enum_types
+    }
+
+    fn extract_nested_enums_static(message: &DescriptorProto, module_path: &str) -> Vec<String> {
+        let mut enum_types = Vec::new();
+        let message_name = message.name();
+
+        // Convert message name to snake_case for module path (prost convention)
+        let message_module = message_name.to_snake_case();
+
+        // Enums directly in this message
+        for enum_desc in &message.enum_type {
+            let enum_name = enum_desc.name();
+            enum_types.push(format!("{module_path}{message_module}::{enum_name}"));
+        }
+
+        // Recursively check nested messages
+        for nested_message in &message.nested_type {
+            let nested_path = format!("{module_path}{message_module}::");
+            enum_types.extend(Self::extract_nested_enums_static(
+                nested_message,
+                &nested_path,
+            ));
+        }
+
+        enum_types
+    }
+
+    fn generate_enum_list_macro_static(enum_types: &[String]) -> String {
+        // Convert enum type strings to identifiers for quote
+        let enum_idents: Vec<proc_macro2::TokenStream> = enum_types
+            .iter()
+            .map(|enum_type| {
+                // Parse the enum type path as tokens (e.g., "MyEnum" or "module::MyEnum")
+                enum_type
+                    .parse()
+                    .unwrap_or_else(|e| panic!("Invalid enum type path '{enum_type}': {e}"))
+            })
+            .collect();
+
+        quote! {
+            macro_rules! try_parse_all_enums {
+                ($s:expr) => {
+                    {
+                        // Try each enum type
+                        #(
+                            if let Some(val) = #enum_idents::from_str_name($s) {
+                                return Some(val as i32);
+                            }
+                        )*
+
+                        None
+                    }
+                };
+            }
+        }
+        .to_string()
+    }
+
+    fn generate_enum_serializer_macro_static(enum_types: &[String]) -> String {
+        // Convert enum type strings to identifiers for quote
+        let enum_idents: Vec<proc_macro2::TokenStream> = enum_types
+            .iter()
+            .map(|enum_type| {
+                // Parse the enum type path as tokens (e.g., "MyEnum" or "module::MyEnum")
+                enum_type
+                    .parse()
+                    .unwrap_or_else(|e| panic!("Invalid enum type path '{enum_type}': {e}"))
+            })
+            .collect();
+
+        quote! {
+            macro_rules! try_serialize_all_enums {
+                ($value:expr) => {
+                    {
+                        // Try each enum type
+                        #(
+                            if let Ok(enum_val) = #enum_idents::try_from($value) {
+                                return Some(enum_val.as_str_name());
+                            }
+                        )*
+
+                        None
+                    }
+                };
+            }
+        }
+        .to_string()
+    }
+
+    fn generate_single_enum_deserializer_static() -> String {
+        quote! {
+            #[allow(dead_code)]
+            pub fn deserialize_enum_from_string<'de, D>(deserializer: D) -> Result<i32, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                use serde::Deserialize;
+
+                #[derive(Deserialize)]
+                #[serde(untagged)]
+                #[allow(dead_code)]
+                enum EnumOrString {
+                    String(String),
+                    Int(i32),
+                }
+
+                match EnumOrString::deserialize(deserializer)? {
+                    EnumOrString::String(s) => {
+                        fn try_parse_enum(s: &str) -> Option<i32> {
+                            try_parse_all_enums!(s)
+                        }
+                        try_parse_enum(&s).ok_or_else(|| {
+                            serde::de::Error::custom(format!("Unknown enum value: {}", s))
+                        })
+                    }
+                    EnumOrString::Int(i) => Ok(i),
+                }
+            }
+        }
+        .to_string()
+    }
+
+    fn generate_option_enum_deserializer_static() -> String {
+        quote! {
+            #[allow(dead_code)]
+            pub fn deserialize_option_enum_from_string<'de, D>(deserializer: D) -> Result<Option<i32>, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                use serde::Deserialize;
+                #[derive(Deserialize)]
+                #[serde(untagged)]
+                #[allow(dead_code)]
+                enum OptionalEnumOrString {
+                    String(String),
+                    Int(i32),
+                    None,
+                }
+                match Option::<OptionalEnumOrString>::deserialize(deserializer)? {
+                    Some(OptionalEnumOrString::String(s)) => {
+                        fn try_parse_enum(s: &str) -> Option<i32> {
+                            try_parse_all_enums!(s)
+                        }
+                        try_parse_enum(&s)
+                            .map(Some)
+                            .ok_or_else(|| serde::de::Error::custom(format!("Unknown enum value: {}", s)))
+                    }
+                    Some(OptionalEnumOrString::Int(i)) => Ok(Some(i)),
+                    Some(OptionalEnumOrString::None) | None => Ok(None),
+                }
+            }
+        }.to_string()
+    }
+
+    fn generate_repeated_enum_deserializer_static() -> String {
+        quote! {
+            #[allow(dead_code)]
+            pub fn deserialize_repeated_enum_from_string<'de, D>(deserializer: D) -> Result<Vec<i32>, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                use serde::Deserialize;
+                #[derive(Deserialize)]
+                #[serde(untagged)]
+                #[allow(dead_code)]
+                enum EnumOrStringItem {
+                    String(String),
+                    Int(i32),
+                }
+                let items: Vec<EnumOrStringItem> = Vec::deserialize(deserializer)?;
+                let mut result = Vec::with_capacity(items.len());
+
+                for item in items {
+                    match item {
+                        EnumOrStringItem::String(s) => {
+                            fn try_parse_enum(s: &str) -> Option<i32> {
+                                try_parse_all_enums!(s)
+                            }
+                            if let Some(enum_val) = try_parse_enum(&s) {
+                                result.push(enum_val);
+                            } else {
+                                return Err(serde::de::Error::custom(format!("Unknown enum value: {}", s)));
+                            }
+                        }
+                        EnumOrStringItem::Int(i) => {
+                            result.push(i);
+                        }
+                    }
+                }
+
+                Ok(result)
+            }
+        }.to_string()
+    }
+
+    fn generate_single_enum_serializer_static() -> String {
+        quote! {
+            #[allow(dead_code)]
+            pub fn serialize_enum_as_string<S>(value: &i32, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: serde::Serializer,
+            {
+                use serde::Serialize;
+                fn try_enum_to_string(value: i32) -> Option<&'static str> {
+                    try_serialize_all_enums!(value)
+                }
+                if let Some(enum_str) = try_enum_to_string(*value) {
+                    enum_str.serialize(serializer)
+                } else {
+                    value.serialize(serializer)
+                }
+            }
+        }.to_string()
+    }
+
+    fn generate_option_enum_serializer_static() -> String {
+        quote! {
+            #[allow(dead_code)]
+            pub fn serialize_option_enum_as_string<S>(value: &Option<i32>, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: serde::Serializer,
+            {
+                use serde::Serialize;
+                fn try_enum_to_string(value: i32) -> Option<&'static str> {
+                    try_serialize_all_enums!(value)
+                }
+                match value {
+                    Some(val) => {
+                        if let Some(enum_str) = try_enum_to_string(*val) {
+                            Some(enum_str).serialize(serializer)
+                        } else {
+                            Some(*val).serialize(serializer)
+                        }
+                    }
+                    None => None::<&str>.serialize(serializer),
+                }
+            }
+        }.to_string()
+    }
+
+    fn generate_repeated_enum_serializer_static() -> String {
+        quote! {
+            #[allow(dead_code)]
+            pub fn serialize_repeated_enum_as_string<S>(values: &[i32], serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: serde::Serializer,
+            {
+                use serde::Serialize;
+                fn try_enum_to_string(value: i32) -> Option<&'static str> {
+                    try_serialize_all_enums!(value)
+                }
+                let string_values: Vec<_> = values.iter().map(|val| {
+                    if let Some(enum_str) = try_enum_to_string(*val) {
+                        enum_str.to_string()
+                    } else {
+                        val.to_string()
+                    }
+                }).collect();
+                string_values.serialize(serializer)
+            }
+        }.to_string()
+    }
+}
+
+/// Tokens of a Rust type generated by prost, such as `HelloRequest`,
+/// `super::common::Money` or `()` for `google.protobuf.Empty`
+fn rust_type_tokens(rust_type: &str) -> proc_macro2::TokenStream {
+    rust_type
+        .trim_matches('"')
+        .parse()
+        .unwrap_or_else(|e| panic!("Invalid Rust type '{rust_type}': {e}"))
+}
+
+/// Messages with fields matching `is_annotated`, e.g. `g2h.sensitive` ones, directly or
+/// through nested messages, along with the matching fields and the fields holding such
+/// nested messages
+fn annotated_messages(
+    messages: &std::collections::HashMap<String, Vec<annotations::FieldAnnotations>>,
+    is_annotated: impl Fn(&annotations::FieldAnnotations) -> bool,
+) -> std::collections::BTreeMap<String, Vec<annotations::FieldAnnotations>> {
+    let mut annotated = std::collections::BTreeMap::new();
+    loop {
+        let mut changed = false;
+        for (name, fields) in messages {
+            let annotated_fields = fields
+                .iter()
+                .filter(|field| {
+                    is_annotated(field)
+                        || field
+                            .type_name
+                            .as_ref()
+                            .is_some_and(|type_name| annotated.contains_key(type_name))
+                })
+                .cloned()
+                .collect::<Vec<_>>();
+            let known = annotated.get(name).map_or(0, Vec::len);
+            if annotated_fields.len() > known {
+                annotated.insert(name.clone(), annotated_fields);
+                changed = true;
+            }
+        }
+        if !changed {
+            return annotated;
+        }
+    }
+}
+
+/// Messages with `buf.validate` constraints on themselves, their oneofs or their fields,
+/// directly or through nested messages
+fn protovalidate_messages(
+    annotations: &annotations::Annotations,
+) -> std::collections::BTreeSet<String> {
+    let mut constrained = annotations
+        .messages
+        .iter()
+        .filter(|(name, fields)| {
+            annotations.constrained_messages.contains(*name)
+                || fields.iter().any(|field| field.constrained)
+        })
+        .map(|(name, _)| name.clone())
+        .collect::<std::collections::BTreeSet<_>>();
+    loop {
+        let mut changed = false;
+        for (name, fields) in &annotations.messages {
+            let nested = fields.iter().any(|field| {
+                field
+                    .type_name
+                    .as_ref()
+                    .is_some_and(|type_name| constrained.contains(type_name))
+            });
+            if nested && constrained.insert(name.clone()) {
+                changed = true;
+            }
+        }
+        if !changed {
+            return constrained;
+        }
+    }
+}
+
+/// Prefix every top-level item of `code` with `#[cfg(predicate)]`
+///
+/// Items end at a top-level `;` or at a top-level brace group, including a `;` right
+/// after it as in `const X: T = T { .. };`. Attributes stay attached to their item.
+fn gate_items(code: &str, predicate: &str) -> String {
+    use proc_macro2::{Delimiter, TokenTree};
+
+    let predicate: proc_macro2::TokenStream = predicate
+        .parse()
+        .expect("the cfg predicate is checked with the options");
+    let tokens: proc_macro2::TokenStream = code
+        .parse()
+        .unwrap_or_else(|e| panic!("Generated HTTP code should be valid Rust syntax: {e}"));
+    let mut items = Vec::new();
+    let mut item = Vec::new();
+    let mut tokens = tokens.into_iter().peekable();
+    while let Some(token) = tokens.next() {
+        let ends_item = match &token {
+            TokenTree::Punct(punct) => punct.as_char() == ';',
+            TokenTree::Group(group) => group.delimiter() == Delimiter::Brace,
+            _ => false,
+        };
+        item.push(token);
+        if ends_item {
+            if let Some(TokenTree::Punct(punct)) = tokens.peek() {
+                if punct.as_char() == ';' {
+                    item.extend(tokens.next());
+                }
+            }
+            items.push(item.drain(..).collect::<proc_macro2::TokenStream>());
+        }
+    }
+    if !item.is_empty() {
+        items.push(item.into_iter().collect());
+    }
+
+    quote! {
+        #(
+            #[cfg(#predicate)]
+            #items
+        )*
+    }
+    .to_string()
+}
+
+/// `path` relative to `dir` when it lies inside it, both relative to the current directory
+fn path_within(path: &std::path::Path, dir: &std::path::Path) -> Option<std::path::PathBuf> {
+    let current_dir = std::env::current_dir().ok()?;
+    current_dir
+        .join(path)
+        .strip_prefix(current_dir.join(dir))
+        .ok()
+        .map(std::path::Path::to_path_buf)
+}
+
+/// Whether `value` can be sent as an HTTP header value, i.e. visible ASCII and spaces
+fn is_header_value(value: &str) -> bool {
+    value.bytes().all(|b| b == b' ' || b.is_ascii_graphic())
+}
+
+/// Whether `name` is a valid HTTP header name, i.e. a non-empty token
+fn is_header_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b))
+}
+
+/// Whether the selector `entry` names `method` of `service_path`, as `pkg.Svc/Method` or `pkg.Svc.Method`
+fn names_method(entry: &str, service_path: &str, method: &str) -> bool {
+    entry
+        .strip_prefix(service_path)
+        .and_then(|rest| rest.strip_prefix(['/', '.']))
+        == Some(method)
+}
+
+/// Whether the selector `entry` names `method` of `service_path` or the whole service
+fn selects_method(entry: &str, service_path: &str, method: &str) -> bool {
+    entry == service_path || names_method(entry, service_path, method)
+}
+
+/// Value of the last entry whose selector names `method` of `service_path`
+fn method_entry<'a, V>(
+    entries: &'a [(String, V)],
+    service_path: &str,
+    method: &str,
+) -> Option<&'a V> {
+    entries
+        .iter()
+        .rev()
+        .find(|(entry, _)| names_method(entry, service_path, method))
+        .map(|(_, value)| value)
+}
+
+/// Value of the last entry whose selector names the service `service_path` itself
+fn service_entry<'a, V>(entries: &'a [(String, V)], service_path: &str) -> Option<&'a V> {
+    entries
+        .iter()
+        .rev()
+        .find(|(entry, _)| entry == service_path)
+        .map(|(_, value)| value)
+}
+
+/// Whether `method` takes `google.protobuf.Empty`, which prost maps to `()`
+fn is_empty_request(method: &prost_build::Method) -> bool {
+    method.input_proto_type == ".google.protobuf.Empty"
+}
+
+/// Shortest abbreviated function id, a letter and the 8 digits of the hash
+const MIN_FUNCTION_ID_LEN: usize = 9;
+
+/// Shorten `id` to at most `max_len` characters when a limit is configured
+///
+/// Abbreviated identifiers keep a readable prefix and end in an 8-digit FNV-1a hash of
+/// the full identifier, so the result is stable across builds and toolchains.
+fn abbreviate_function_id(id: &str, max_len: Option<usize>) -> String {
+    let Some(max_len) = max_len else {
+        return id.to_string();
+    };
+    if id.len() <= max_len {
+        return id.to_string();
+    }
+
+    let hash = id.bytes().fold(0x811c_9dc5_u32, |hash, byte| {
+        (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193)
+    });
+    let prefix = id[..max_len.saturating_sub(MIN_FUNCTION_ID_LEN)].trim_end_matches('_');
+
+    if prefix.is_empty() {
+        format!("h{hash:08x}")
+    } else {
+        format!("{prefix}_{hash:08x}")
+    }
+}
+
+/// An enum-typed message field discovered while scanning a package
+#[derive(Debug)]
+struct EnumField {
+    /// Snake-case identifier used to name the field-specific functions
+    field_id: String,
+    /// `field_id` before abbreviation
+    full_field_id: String,
+    /// Snake-case identifier used to name the shared functions of the enum type
+    enum_fn_id: String,
+    /// Rust path of the enum type relative to the package module
+    enum_path: String,
+    /// Fully-qualified protobuf name of the enum type, without the leading dot
+    enum_type: String,
+    /// One of `Single`, `Option` or `Repeated`
+    field_label: &'static str,
+}
+
+#[derive(Debug)]
+enum FieldLabel {
+    Optional,
+    Required,
+    Repeated,
+}
+
+impl prost_build::ServiceGenerator for BridgeGenerator {
+    fn generate(&mut self, service: prost_build::Service, buf: &mut String) {
+        self.inner.generate(service.clone(), buf);
+        for generator in &mut self.chained {
+            generator.generate(service.clone(), buf);
+        }
+        let http_start = buf.len();
+
+        // Without a compile method, e.g. with a custom `prost_build::Config`, the options are
+        // checked here, where a panic is the only way to fail the build
+        if let Err(err) = self.check_options() {
+            panic!("{err}");
+        }
+
+        let service_path = if service.package.is_empty() {
+            service.proto_name.clone()
+        } else {
+            format!("{}.{}", service.package, service.proto_name)
+        };
+
+        // Server streaming methods give the service trait associated types, so no trait objects
+        let object_safe = !service.methods.iter().any(|method| method.server_streaming);
+
+        // Mocks implement the whole service trait, including the methods the bridge skips
+        if self.enable_mocks {
+            let router = self.backend == Backend::Axum;
+            let mock = mock::service_code(&service, router);
+            buf.push_str(&mock.to_string());
+        }
+
+        // Only the HTTP bridge skips filtered methods, the gRPC service keeps all of them
+        let mut service = service;
+        service
+            .methods
+            .retain(|method| self.is_method_exposed(&service_path, &method.proto_name));
+
+        let package = &service.package;
+        let name = &service.proto_name;
+        let func_name = service.name.to_string();
+        let ident_func_name = quote::format_ident!("{}", func_name);
+        let branch_names = service
+            .methods
+            .iter()
+            .map(|method| format!("/{package}.{name}/{}", method.proto_name))
+            .collect::<Vec<_>>();
+
+        // `g2h.route` options of each method, when enabled
+        let route_options = branch_names
+            .iter()
+            .map(|branch_name| {
+                self.method_annotations
+                    .get(branch_name)
+                    .and_then(|annotations| annotations.route.as_ref())
+                    .filter(|_| self.enable_route_options)
+            })
+            .collect::<Vec<_>>();
+        let route_paths = service
+            .methods
+            .iter()
+            .zip(&route_options)
+            .map(|(method, route)| match route {
+                Some(route) if !route.path.is_empty() => route.path.clone(),
+                _ => self.route_path(&service, method),
+            })
+            .collect::<Vec<_>>();
+        if self.enable_version_prefix {
+            for (route_path, branch_name) in route_paths.iter().zip(&branch_names) {
+                let previous = self
+                    .versioned_paths
+                    .insert(route_path.clone(), branch_name.clone());
+                if let Some(previous) = previous.filter(|previous| previous != branch_name) {
+                    panic!("`{previous}` and `{branch_name}` are both served on `{route_path}`");
+                }
+            }
+        }
+        let route_verbs = service
+            .methods
+            .iter()
+            .zip(&route_options)
+            .map(|(method, route)| {
+                route
+                    .map(|route| route.verb.to_ascii_uppercase())
+                    .filter(|verb| !verb.is_empty())
+                    .or_else(|| {
+                        method_entry(&self.method_verbs, &service_path, &method.proto_name)
+                            .map(|verb| verb.as_str().to_string())
+                    })
+                    .or_else(|| self.inferred_verb(method).map(str::to_string))
+                    .unwrap_or_else(|| "POST".to_string())
+            })
+            .collect::<Vec<_>>();
+        self.package_has_get_routes |= route_verbs.iter().any(|verb| verb == "GET");
+        let route_method_filters = route_verbs
+            .iter()
+            .map(|verb| quote::format_ident!("{}", verb))
+            .collect::<Vec<_>>();
+        let success_statuses = route_options
+            .iter()
+            .map(|route| match route {
+                Some(route) if route.success_status != 0 => {
+                    // A 2xx status, checked with the options
+                    let status = route.success_status as u16;
+                    quote! { ::http::StatusCode::from_u16(#status).unwrap_or(::http::StatusCode::OK) }
+                }
+                _ => quote! { ::http::StatusCode::OK },
+            })
+            .collect::<Vec<_>>();
+        let sunset_header = self.deprecation_sunset.as_ref().map(|sunset| {
+            quote! {
+                response.headers_mut().insert("sunset", ::http::HeaderValue::from_static(#sunset));
+            }
+        });
+        let deprecation_headers = route_options
+            .iter()
+            .zip(&service.methods)
+            .map(|(route, method)| {
+                let deprecated = route.is_some_and(|route| route.deprecated)
+                    || method.options.deprecated()
+                    || service.options.deprecated();
+                if deprecated {
+                    quote! {
+                        let mut response = response;
+                        response.headers_mut().insert("deprecation", ::http::HeaderValue::from_static("true"));
+                        #sunset_header
+                    }
+                } else {
+                    quote! {}
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let method_infos = service
+            .methods
+            .iter()
+            .map(|method| MethodInfo {
+                name: method.proto_name.clone(),
+                input_type: method.input_proto_type.trim_start_matches('.').to_string(),
+                output_type: method.output_proto_type.trim_start_matches('.').to_string(),
+            })
+            .collect::<Vec<_>>();
+        let before_hooks = method_infos
+            .iter()
+            .map(|method| {
+                let hooks = self.hooks.iter().map(|hook| hook.before_call(method));
+                quote! { #(#hooks)* }
+            })
+            .collect::<Vec<_>>();
+        let after_hooks = method_infos
+            .iter()
+            .map(|method| {
+                let hooks = self.hooks.iter().map(|hook| hook.after_call(method));
+                quote! { #(#hooks)* }
+            })
+            .collect::<Vec<_>>();
+
+        // Idempotency keys are read before the headers become metadata
+        let (idempotency_captures, idempotency_replays, idempotency_records) = if self
+            .enable_idempotency
+        {
+            (
+                    quote! {
+                        let idempotency_key = headers
+                            .get("idempotency-key")
+                            .and_then(|key| key.to_str().ok())
+                            .filter(|key| !key.is_empty())
+                            .map(str::to_string);
+                        let idempotency_storage = extension.get::<IdempotencyStorage>().cloned();
+                    },
+                    route_paths
+                        .iter()
+                        .map(|route_path| {
+                            quote! {
+                                let idempotent_request = match (idempotency_storage, idempotency_key) {
+                                    (Some(storage), Some(key)) => {
+                                        let fingerprint = request_fingerprint(&body);
+                                        match storage.0.reserve(#route_path, &key, &metadata_map).await {
+                                            IdempotencyReservation::Stored(stored) if stored.request_fingerprint != fingerprint => {
+                                                return idempotency_conflict_response();
+                                            }
+                                            IdempotencyReservation::Stored(stored) => return replay_stored_response(stored),
+                                            IdempotencyReservation::Pending => return idempotency_pending_response(),
+                                            IdempotencyReservation::Reserved => Some(ReservedIdempotencyKey {
+                                                storage,
+                                                path: #route_path,
+                                                key,
+                                                metadata: metadata_map.clone(),
+                                                fingerprint,
+                                                stored: false,
+                                            }),
+                                        }
+                                    }
+                                    _ => None,
+                                };
+                            }
+                        })
+                        .collect::<Vec<_>>(),
+                    vec![
+                        quote! {
+                            // Dropping the reservation of other responses releases the key
+                            let response = match idempotent_request {
+                                Some(reservation) if !response.status().is_server_error() => {
+                                    store_response(reservation, response).await
+                                }
+                                _ => response,
+                            };
+                        };
+                        service.methods.len()
+                    ],
+                )
+        } else {
+            (
+                quote! {},
+                vec![quote! {}; service.methods.len()],
+                vec![quote! {}; service.methods.len()],
+            )
+        };
+
+        // Whether the client accepts trailers is read before the headers become metadata
+        let (trailer_captures, status_trailers, trailer_applies) = if self.enable_trailers {
+            (
+                quote! {
+                    let trailers_accepted = headers
+                        .get_all(::http::header::TE)
+                        .iter()
+                        .filter_map(|te| te.to_str().ok())
+                        .flat_map(|te| te.split(','))
+                        .any(|te| te.trim().eq_ignore_ascii_case("trailers"));
+                },
+                quote! {
+                    let mut response = response;
+                    response.extensions_mut().insert(TrailingMetadata(status.metadata().clone()));
+                },
+                quote! {
+                    let response = apply_trailing_metadata(response, trailers_accepted).await;
+                },
+            )
+        } else {
+            (quote! {}, quote! {}, quote! {})
+        };
+
+        // Hooks are read before the request extensions move into the `tonic::Request`
+        let (message_hook_captures, message_before_hooks, message_after_hooks) = if self
+            .enable_message_hooks
+        {
+            (
+                    quote! {
+                        let message_hooks = extension.get::<MessageHooking>().cloned();
+                    },
+                    branch_names
+                        .iter()
+                        .map(|branch_name| {
+                            quote! {
+                                let body = match &message_hooks {
+                                    Some(hooks) => match hook_request_message(hooks, #branch_name, body).await {
+                                        Ok(body) => body,
+                                        Err(err) => {
+                                            let error_body = ErrorResponse {
+                                                error: ErrorDetails::new(error_code(::tonic::Code::InvalidArgument), format!("Request transformed into an invalid message: {}", err))
+                                            };
+                                            return (::http::StatusCode::BAD_REQUEST, ::axum::Json(error_body)).into_response();
+                                        }
+                                    },
+                                    None => body,
+                                };
+                            }
+                        })
+                        .collect::<Vec<_>>(),
+                    branch_names
+                        .iter()
+                        .map(|branch_name| {
+                            quote! {
+                                let body = match &message_hooks {
+                                    Some(hooks) => hook_response_message(hooks, #branch_name, body).await,
+                                    None => ::serde_json::to_value(&body),
+                                };
+                                let body = match body {
+                                    Ok(body) => body,
+                                    Err(err) => {
+                                        let error_body = ErrorResponse {
+                                            error: ErrorDetails::new(error_code(::tonic::Code::Internal), format!("Failed to serialize the response: {}", err))
+                                        };
+                                        return (::http::StatusCode::INTERNAL_SERVER_ERROR, ::axum::Json(error_body)).into_response();
+                                    }
+                                };
+                            }
+                        })
+                        .collect::<Vec<_>>(),
+                )
+        } else {
+            (
+                quote! {},
+                vec![quote! {}; service.methods.len()],
+                vec![quote! {}; service.methods.len()],
+            )
+        };
+
+        let message_hooks_method = if self.enable_message_hooks {
+            quote! {
+                /// Runs `hooks` on the request and response messages of every method
+                pub fn message_hooks(self, hooks: impl MessageHooks) -> Self {
+                    let hooking = MessageHooking::new(hooks);
+                    self.map_routes(move |route| route.layer(::axum::Extension(hooking.clone())))
+                }
+            }
+        } else {
+            quote! {}
+        };
+
+        let cache_controls = service
+            .methods
+            .iter()
+            .zip(&route_options)
+            .map(|(method, route)| {
+                let policy = route
+                    .map(|route| route.cache_control.clone())
+                    .filter(|policy| !policy.is_empty())
+                    .or_else(|| {
+                        method_entry(&self.cache_policies, &service_path, &method.proto_name)
+                            .cloned()
+                    })
+                    .or_else(|| service_entry(&self.cache_policies, &service_path).cloned());
+                match policy {
+                    Some(policy) => {
+                        quote! {
+                            let mut response = response;
+                            if response.status().is_success() {
+                                response.headers_mut().insert(
+                                    ::http::header::CACHE_CONTROL,
+                                    ::http::HeaderValue::from_static(#policy),
+                                );
+                            }
+                        }
+                    }
+                    None => quote! {},
+                }
+            })
+            .collect::<Vec<_>>();
+
+        // GET routes answer with an ETag and honor `If-None-Match`
+        let (etag_captures, etag_checks): (Vec<_>, Vec<_>) = route_verbs
+            .iter()
+            .map(|verb| {
+                if verb == "GET" {
+                    (
+                        quote! {
+                            let if_none_match = headers.get(::http::header::IF_NONE_MATCH).cloned();
+                        },
+                        quote! {
+                            let response = conditional_get_response(response, if_none_match).await;
+                        },
+                    )
+                } else {
+                    (quote! {}, quote! {})
+                }
+            })
+            .unzip();
+
+        let func_names = service
+            .methods
+            .iter()
+            .map(|method| quote::format_ident!("{}", method.name))
+            .collect::<Vec<_>>();
+
+        let branch_request = service
+            .methods
+            .iter()
+            .map(|method| rust_type_tokens(&method.input_type))
+            .collect::<Vec<_>>();
+
+        #[cfg(feature = "doc")]
+        let branch_response = service
+            .methods
+            .iter()
+            .map(|method| rust_type_tokens(&method.output_type))
+            .collect::<Vec<_>>();
+
+        let snake_case_name = func_name.to_snake_case();
+        let service_name = quote::format_ident!("{}_handler", snake_case_name);
+        let service_name_with = quote::format_ident!("{}_handler_with", snake_case_name);
+        let router_parts = quote::format_ident!("{}_router_parts", snake_case_name);
+        let service_name_arc = quote::format_ident!("{}_handler_arc", snake_case_name);
+        let service_name_interceptor =
+            quote::format_ident!("{}_handler_with_interceptor", snake_case_name);
+        let handler_fns = service
+            .methods
+            .iter()
+            .map(|method| quote::format_ident!("{}_{}_http_handler", snake_case_name, method.name))
+            .collect::<Vec<_>>();
+        #[cfg_attr(not(feature = "doc"), allow(unused_mut))]
+        let mut handler_fn_docs = branch_names
+            .iter()
+            .map(|branch_name| {
+                format!("HTTP handler of `{branch_name}`, as routed by [`{service_name}`].")
+            })
+            .collect::<Vec<_>>();
+        // Example bodies need the message descriptors, loaded by the compile methods
+        #[cfg(feature = "doc")]
+        if let Some(ref file_descriptor_set) = self.file_descriptor_set {
+            let descriptors = doc_examples::Descriptors::new(file_descriptor_set);
+            for (((doc, method), path), verb) in handler_fn_docs
+                .iter_mut()
+                .zip(&service.methods)
+                .zip(&route_paths)
+                .zip(&route_verbs)
+            {
+                let request =
+                    descriptors.example_json(&method.input_proto_type, self.enable_string_enums);
+                let response =
+                    descriptors.example_json(&method.output_proto_type, self.enable_string_enums);
+                let query =
+                    descriptors.example_query(&method.input_proto_type, self.enable_string_enums);
+                let curl = doc_examples::curl_command(verb, path, &request, &query);
+                doc.push_str(&format!(
+                    "\n\nExample request:\n\n```json\n{request}\n```\n\n\
+                     Example response:\n\n```json\n{response}\n```\n\n\
+                     ```sh\n{curl}\n```"
+                ));
+            }
+        }
+        let router_builder = quote::format_ident!("{}RouterBuilder", func_name);
+        let server_module = quote::format_ident!("{}_server", snake_case_name);
+
+        #[cfg(feature = "doc")]
+        let docs = quote! {
+            #[doc = "Axum Router for handling the gRPC service. This router is generated with the [`prost-build`] crate. This builds a web router on top of the gRPC service."]
+            #[doc = ""]
+            #[doc = ::std::concat!("Package: `", stringify!(#package), "`")]
+            #[doc = ""]
+            #[doc = ::std::concat!("Name: `", stringify!(#name), "`")]
+            #[doc = ""]
+            #[doc = "Routes:"]
+            #(
+                #[doc = ::std::concat!("- `", stringify!(#func_names), "` `::` [`", stringify!(#branch_request), "`]` -> `[`", stringify!(#branch_response), "`]")]
+            )*
+        };
+        #[cfg(not(feature = "doc"))]
+        let docs = quote! {};
+
+        if self.enable_not_found_fallback && self.list_known_paths {
+            self.package_route_paths.extend(route_paths.iter().cloned());
+        }
+
+        let routes_const = quote::format_ident!("{}_ROUTES", func_name.to_shouty_snake_case());
+        let method_proto_names = service.methods.iter().map(|method| &method.proto_name);
+        let request_proto_types = service
+            .methods
+            .iter()
+            .map(|method| method.input_proto_type.trim_start_matches('.'));
+        let response_proto_types = service
+            .methods
+            .iter()
+            .map(|method| method.output_proto_type.trim_start_matches('.'));
+        let routes_doc = format!(
+            "HTTP routes served by [`{service_name}`] as `(path, method, request type, response type)`, \
+             with protobuf type names."
+        );
+
+        if self.route_manifest_path.is_some() {
+            for ((method, route_path), verb) in
+                service.methods.iter().zip(&route_paths).zip(&route_verbs)
+            {
+                self.manifest_routes.push(manifest::ManifestRoute {
+                    path: route_path.clone(),
+                    http_method: verb.clone(),
+                    service: service_path.clone(),
+                    method: method.proto_name.clone(),
+                    request_type: method.input_proto_type.trim_start_matches('.').to_string(),
+                    response_type: method.output_proto_type.trim_start_matches('.').to_string(),
+                    client_streaming: method.client_streaming,
+                    server_streaming: method.server_streaming,
+                });
+            }
+        }
+
+        if self.enable_graphql {
+            buf.push_str(&graphql::service_code(&service, &route_verbs).to_string());
+        }
+
+        if self.backend == Backend::Actix {
+            let routes = service
+                .methods
+                .iter()
+                .zip(&route_paths)
+                .zip(&route_verbs)
+                .zip(&route_options)
+                .map(|(((method, path), verb), route)| actix::ActixRoute {
+                    method,
+                    path,
+                    verb,
+                    success_status: route
+                        .and_then(|route| u16::try_from(route.success_status).ok())
+                        .filter(|status| (200..300).contains(status))
+                        .unwrap_or(200),
+                    masked: self
+                        .masked_messages
+                        .contains_key(method.input_proto_type.trim_start_matches('.')),
+                })
+                .collect::<Vec<_>>();
+            buf.push_str(&actix::service_code(&service, &routes).to_string());
+            self.gate_http_code(buf, http_start);
+            return;
+        }
+
+        if self.enable_test_clients {
+            let routes = route_paths
+                .iter()
+                .cloned()
+                .zip(route_verbs.iter().cloned())
+                .collect::<Vec<_>>();
+            buf.push_str(&test_client::service_code(&service, &routes).to_string());
+        }
+
+        let routes_fn = quote::format_ident!("{}_routes", snake_case_name);
+        let route_info_doc = format!("Describes every HTTP route served by [`{service_name}`].");
+        let route_streaming = service.methods.iter().map(|method| {
+            match (method.client_streaming, method.server_streaming) {
+                (false, false) => quote! { RouteStreaming::Unary },
+                (true, false) => quote! { RouteStreaming::ClientStreaming },
+                (false, true) => quote! { RouteStreaming::ServerStreaming },
+                (true, true) => quote! { RouteStreaming::BidiStreaming },
+            }
+        });
+        let route_info_paths = &route_paths;
+        let route_info_names = service.methods.iter().map(|method| &method.proto_name);
+        let route_info_requests = service
+            .methods
+            .iter()
+            .map(|method| method.input_proto_type.trim_start_matches('.'));
+        let route_info_responses = service
+            .methods
+            .iter()
+            .map(|method| method.output_proto_type.trim_start_matches('.'));
+
+        let paths_mod = quote::format_ident!("{}_paths", snake_case_name);
+        let paths_doc = format!("Names and paths of the `{service_path}` service and its methods.");
+        let method_path_consts = service
+            .methods
+            .iter()
+            .map(|method| quote::format_ident!("{}", method.proto_name.to_shouty_snake_case()));
+        let method_route_consts = service.methods.iter().map(|method| {
+            quote::format_ident!("{}_ROUTE", method.proto_name.to_shouty_snake_case())
+        });
+        let method_path_docs = branch_names
+            .iter()
+            .map(|branch_name| format!("gRPC path of `{branch_name}`."));
+        let method_route_docs = branch_names
+            .iter()
+            .map(|branch_name| format!("HTTP route serving `{branch_name}`."));
+
+        let route_metadata = quote! {
+            #[doc = #paths_doc]
+            #[allow(dead_code)]
+            pub mod #paths_mod {
+                /// Fully qualified name of the service.
+                pub const SERVICE_NAME: &str = #service_path;
+
+                #(
+                    #[doc = #method_path_docs]
+                    pub const #method_path_consts: &str = #branch_names;
+
+                    #[doc = #method_route_docs]
+                    pub const #method_route_consts: &str = #route_paths;
+                )*
+            }
+
+            #[doc = #route_info_doc]
+            #[allow(dead_code)]
+            pub fn #routes_fn() -> Vec<RouteInfo> {
+                vec![
+                    #(RouteInfo {
+                        path: #route_info_paths,
+                        http_method: #route_verbs,
+                        method_name: #route_info_names,
+                        request_type: #route_info_requests,
+                        response_type: #route_info_responses,
+                        streaming: #route_streaming,
+                    }),*
+                ]
+            }
+
+            #[doc = #routes_doc]
+            #[allow(dead_code)]
+            pub const #routes_const: &[(&str, &str, &str, &str)] = &[
+                #((#route_paths, #method_proto_names, #request_proto_types, #response_proto_types)),*
+            ];
+
+            // Fail compilation if two methods map to the same HTTP path
+            const _: () = {
+                const fn str_eq(a: &str, b: &str) -> bool {
+                    let (a, b) = (a.as_bytes(), b.as_bytes());
+                    if a.len() != b.len() {
+                        return false;
+                    }
+                    let mut i = 0;
+                    while i < a.len() {
+                        if a[i] != b[i] {
+                            return false;
+                        }
+                        i += 1;
+                    }
+                    true
+                }
+
+                let mut i = 0;
+                while i < #routes_const.len() {
+                    let mut j = i + 1;
+                    while j < #routes_const.len() {
+                        assert!(!str_eq(#routes_const[i].0, #routes_const[j].0), "duplicate HTTP route path");
+                        j += 1;
+                    }
+                    i += 1;
+                }
+            };
+        };
+
+        let validation_checks = service
+            .methods
+            .iter()
+            .map(|method| {
+                let proto_type = method.input_proto_type.trim_start_matches('.');
+                if self.enable_request_validation
+                    && !method.client_streaming
+                    && self.validated_messages.contains_key(proto_type)
+                {
+                    quote! {
+                        if let Some(response) = request_validation_error(#proto_type, &body) {
+                            return response;
+                        }
+                    }
+                } else {
+                    quote! {}
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let protovalidate_checks = service
+            .methods
+            .iter()
+            .map(|method| {
+                let proto_type = method.input_proto_type.trim_start_matches('.');
+                if self.enable_protovalidate
+                    && !method.client_streaming
+                    && self.protovalidate_messages.contains(proto_type)
+                {
+                    quote! {
+                        let Some(validator) = extension.get::<RequestValidator>() else {
+                            let error_body = ErrorResponse {
+                                error: ErrorDetails::new(error_code(::tonic::Code::Internal), "No `RequestValidator` is installed to check the request constraints")
+                            };
+
+                            return (::http::StatusCode::INTERNAL_SERVER_ERROR, ::axum::Json(error_body)).into_response();
+                        };
+                        let violations = validator.violations(#proto_type, &body);
+                        if !violations.is_empty() {
+                            return field_violations_response(violations);
+                        }
+                    }
+                } else {
+                    quote! {}
+                }
+            })
+            .collect::<Vec<_>>();
+
+        // API key headers of the `g2h.route` and `g2h.service` options, captured before the
+        // headers become metadata
+        let service_api_key_header = self
+            .service_annotations
+            .get(&service_path)
+            .filter(|_| self.enable_route_options)
+            .map(|options| options.api_key_header.as_str())
+            .filter(|header| !header.is_empty());
+        let api_key_headers = route_options
+            .iter()
+            .map(|route| {
+                route
+                    .map(|route| route.api_key_header.as_str())
+                    .filter(|header| !header.is_empty())
+                    .or(service_api_key_header)
+                    .map(str::to_ascii_lowercase)
+            })
+            .collect::<Vec<_>>();
+        let api_key_captures = api_key_headers
+            .iter()
+            .map(|header| match header {
+                Some(header) => quote! {
+                    let api_key = headers
+                        .get(#header)
+                        .and_then(|api_key| api_key.to_str().ok())
+                        .map(str::trim)
+                        .filter(|api_key| !api_key.is_empty())
+                        .and_then(|api_key| ::tonic::metadata::AsciiMetadataValue::try_from(api_key).ok());
+                },
+                None => quote! {},
+            })
+            .collect::<Vec<_>>();
+        let api_key_checks = api_key_headers
+            .iter()
+            .map(|header| match header {
+                Some(header) => {
+                    let message = format!("Missing `{header}` API key header");
+                    quote! {
+                        let Some(api_key) = api_key else {
+                            let error_body = ErrorResponse {
+                                error: ErrorDetails::new(error_code(::tonic::Code::Unauthenticated), #message)
+                            };
+
+                            return (::http::StatusCode::UNAUTHORIZED, ::axum::Json(error_body)).into_response();
+                        };
+                        let mut metadata_map = metadata_map;
+                        metadata_map.insert("api-key", api_key);
+                    }
+                }
+                None => quote! {},
+            })
+            .collect::<Vec<_>>();
+
+        // Access levels checked by the `Authorizer`, from the options, then the builder
+        let service_access = self
+            .service_annotations
+            .get(&service_path)
+            .filter(|_| self.enable_route_options)
+            .map(|options| options.access.as_str())
+            .filter(|access| !access.is_empty());
+        let authorizer_checks = service
+            .methods
+            .iter()
+            .zip(&route_options)
+            .zip(&branch_names)
+            .map(|((method, route), branch_name)| {
+                let option_level = route
+                    .map(|route| route.access.as_str())
+                    .filter(|access| !access.is_empty())
+                    .or(service_access)
+                    .and_then(AccessLevel::from_option);
+                let level = option_level
+                    .or_else(|| method_entry(&self.access_levels, &service_path, &method.proto_name).copied())
+                    .or_else(|| service_entry(&self.access_levels, &service_path).copied());
+                let level = match level {
+                    None | Some(AccessLevel::Public) => return quote! {},
+                    Some(AccessLevel::Authenticated) => quote! { AccessLevel::Authenticated },
+                    Some(AccessLevel::Admin) => quote! { AccessLevel::Admin },
+                };
+                quote! {
+                    let Some(authorization) = extension.get::<Authorization>().cloned() else {
+                        let error_body = ErrorResponse {
+                            error: ErrorDetails::new(error_code(::tonic::Code::Internal), "No `Authorizer` is installed to check the access level")
+                        };
+
+                        return (::http::StatusCode::INTERNAL_SERVER_ERROR, ::axum::Json(error_body)).into_response();
+                    };
+                    if let Err(err) = authorization.0.authorize(#branch_name, #level, &metadata_map).await {
+                        return authorization_error_response(err);
+                    }
+                }
+            })
+            .collect::<Vec<_>>();
+
+        // Rate limiting after authentication, when a `RateLimiting` extension is installed
+        let rate_limit_checks = branch_names
+            .iter()
+            .map(|branch_name| {
+                if self.enable_rate_limiting {
+                    quote! {
+                        if let Some(rate_limiting) = extension.get::<RateLimiting>().cloned() {
+                            if let Err(limited) = rate_limiting.0.check(#branch_name, &metadata_map).await {
+                                return rate_limited_response(limited);
+                            }
+                        }
+                    }
+                } else {
+                    quote! {}
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let auth_checks = service
+            .methods
+            .iter()
+            .zip(&route_options)
+            .map(|(method, route)| {
+                let required = route.is_some_and(|route| route.auth_required)
+                    || self
+                        .bearer_token_required
+                        .iter()
+                        .any(|entry| selects_method(entry, &service_path, &method.proto_name));
+                if required {
+                    quote! {
+                        if metadata_map.get("token").is_none() {
+                            let error_body = ErrorResponse {
+                                error: ErrorDetails::new(error_code(::tonic::Code::Unauthenticated), "Missing `Authorization: Bearer` header")
+                            };
+
+                            return (::http::StatusCode::UNAUTHORIZED, ::axum::Json(error_body)).into_response();
+                        }
+                    }
+                } else {
+                    quote! {}
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let default_timeout = self.default_timeout.map(|timeout| {
+            let nanos = u64::try_from(timeout.as_nanos()).unwrap_or(u64::MAX);
+            quote! { ::std::time::Duration::from_nanos(#nanos) }
+        });
+        let deadline = match (self.enable_timeout_headers, default_timeout) {
+            (true, Some(default_timeout)) => Some(quote! {
+                let deadline = Some(request_deadline(&headers).map_or(#default_timeout, |deadline| deadline.min(#default_timeout)));
+            }),
+            (true, None) => Some(quote! {
+                let deadline = request_deadline(&headers);
+            }),
+            (false, Some(default_timeout)) => Some(quote! {
+                let deadline = Some(#default_timeout);
+            }),
+            (false, None) => None,
+        };
+        // Spans around every handler, when the `tracing` feature is enabled
+        #[cfg(feature = "tracing")]
+        let (tracing_starts, tracing_instrument, tracing_code, tracing_latency) = (
+            service
+                .methods
+                .iter()
+                .map(|method| {
+                    let method_name = &method.proto_name;
+                    quote! {
+                        let tracing_started = ::std::time::Instant::now();
+                        let mut tracing_grpc_code = None;
+                        let tracing_span = ::tracing::info_span!(
+                            "g2h.handler",
+                            service = #service_path,
+                            method = #method_name,
+                            grpc.code = ::tracing::field::Empty,
+                            latency_ms = ::tracing::field::Empty,
+                        );
+                    }
+                })
+                .collect::<Vec<_>>(),
+            quote! {
+                let call = ::tracing::Instrument::instrument(call, tracing_span.clone());
+            },
+            quote! {
+                tracing_grpc_code = Some(match &output {
+                    Ok(_) => ::tonic::Code::Ok,
+                    Err(status) => status.code(),
+                });
+            },
+            quote! {
+                // Requests rejected before the service is called have no status of their own
+                let grpc_code = tracing_grpc_code.unwrap_or_else(|| grpc_code_of(response.status()));
+                tracing_span.record("grpc.code", grpc_code as i32);
+                tracing_span.record("latency_ms", tracing_started.elapsed().as_secs_f64() * 1000.0);
+            },
+        );
+        #[cfg(not(feature = "tracing"))]
+        let (tracing_starts, tracing_instrument, tracing_code, tracing_latency) = (
+            vec![quote! {}; service.methods.len()],
+            quote! {},
+            quote! {},
+            quote! {},
+        );
+
+        // RED metrics of every handler, when the `metrics` feature is enabled
+        #[cfg(feature = "metrics")]
+        let (metrics_starts, metrics_records) = (
+            quote! {
+                let metrics_started = ::std::time::Instant::now();
+            },
+            service
+                .methods
+                .iter()
+                .map(|method| {
+                    let method_name = &method.proto_name;
+                    quote! {
+                        let metrics_labels = [
+                            ("service", #service_path.to_string()),
+                            ("method", #method_name.to_string()),
+                            ("status", response.status().as_u16().to_string()),
+                        ];
+                        ::metrics::counter!("g2h_http_requests_total", &metrics_labels[..]).increment(1);
+                        ::metrics::histogram!("g2h_http_request_duration_seconds", &metrics_labels[..])
+                            .record(metrics_started.elapsed().as_secs_f64());
+                    }
+                })
+                .collect::<Vec<_>>(),
+        );
+        #[cfg(not(feature = "metrics"))]
+        let (metrics_starts, metrics_records) = (quote! {}, vec![quote! {}; service.methods.len()]);
+
+        // wasm32 has no clock to time spans and requests with
+        let (tracing_starts, tracing_instrument, tracing_code, tracing_latency) =
+            if self.wasm_target {
+                (
+                    vec![quote! {}; service.methods.len()],
+                    quote! {},
+                    quote! {},
+                    quote! {},
+                )
+            } else {
+                (
+                    tracing_starts,
+                    tracing_instrument,
+                    tracing_code,
+                    tracing_latency,
+                )
+            };
+        let (metrics_starts, metrics_records) = if self.wasm_target {
+            (quote! {}, vec![quote! {}; service.methods.len()])
+        } else {
+            (metrics_starts, metrics_records)
+        };
+
+        // Access log reporting, when an `AccessLogger` extension is installed
+        let (
+            access_log_start,
+            access_log_request,
+            access_log_response,
+            access_log_error,
+            access_log_records,
+        ) = if self.enable_access_log {
+            (
+                quote! {
+                    let access_log_started = ::std::time::Instant::now();
+                    let access_logger = extension.get::<AccessLogger>().cloned();
+                    let mut access_log_request = None;
+                    let mut access_log_response = None;
+                },
+                service
+                    .methods
+                    .iter()
+                    .map(|method| {
+                        let mask = self.mask_tokens(&method.input_proto_type);
+                        quote! {
+                            access_log_request = access_logger
+                                .as_ref()
+                                .filter(|logger| logger.bodies)
+                                .map(|_| {
+                                    let mut value = ::serde_json::to_value(&body).unwrap_or_default();
+                                    #mask
+                                    value
+                                });
+                        }
+                    })
+                    .collect::<Vec<_>>(),
+                service
+                    .methods
+                    .iter()
+                    .map(|method| {
+                        let mask = self.mask_tokens(&method.output_proto_type);
+                        quote! {
+                            if access_logger.as_ref().is_some_and(|logger| logger.bodies) {
+                                let mut value = ::serde_json::to_value(&body).unwrap_or_default();
+                                #mask
+                                access_log_response = Some(value);
+                            }
+                        }
+                    })
+                    .collect::<Vec<_>>(),
+                quote! {
+                    if access_logger.as_ref().is_some_and(|logger| logger.bodies) {
+                        access_log_response = Some(::serde_json::to_value(&error_body).unwrap_or_default());
+                    }
+                },
+                route_paths
+                    .iter()
+                    .zip(&service.methods)
+                    .map(|(route_path, method)| {
+                        let method_name = &method.proto_name;
+                        quote! {
+                            if let Some(logger) = access_logger {
+                                logger.log(AccessLogEntry {
+                                    path: #route_path,
+                                    method: #method_name,
+                                    status: response.status(),
+                                    latency: access_log_started.elapsed(),
+                                    request: access_log_request,
+                                    response: access_log_response,
+                                });
+                            }
+                        }
+                    })
+                    .collect::<Vec<_>>(),
+            )
+        } else {
+            (
+                quote! {},
+                vec![quote! {}; service.methods.len()],
+                vec![quote! {}; service.methods.len()],
+                quote! {},
+                vec![quote! {}; service.methods.len()],
+            )
+        };
+
+        let concurrency_layers = service
+            .methods
+            .iter()
+            .map(
+                |method| match self.concurrency_limit_of(&service_path, &method.proto_name) {
+                    Some(max) => quote! {
+                        .layer::<_, ::std::convert::Infallible>(::axum::middleware::from_fn({
+                            let concurrency_limit = ::std::sync::Arc::new(::tokio::sync::Semaphore::new(#max));
+                            move |request: ::axum::extract::Request, next: ::axum::middleware::Next| {
+                                let concurrency_limit = concurrency_limit.clone();
+                                async move {
+                                    match concurrency_limit.try_acquire() {
+                                        Ok(_permit) => next.run(request).await,
+                                        Err(_) => concurrency_limit_exceeded(),
+                                    }
+                                }
+                            }
+                        }))
+                    },
+                    None => quote! {},
+                },
+            )
+            .collect::<Vec<_>>();
+
+        let body_limit = match self.max_body_size {
+            Some(bytes) => quote! {
+                .layer::<_, ::std::convert::Infallible>(::axum::middleware::map_response(payload_too_large_as_json))
+                .layer::<_, ::std::convert::Infallible>(::axum::extract::DefaultBodyLimit::max(#bytes))
+            },
+            None => quote! {},
+        };
+
+        // Only routes reading a JSON body, GET routes take their request from the query
+        let content_type_checks = route_verbs
+            .iter()
+            .map(|verb| {
+                if self.enable_content_type_check && verb != "GET" {
+                    quote! { .layer::<_, ::std::convert::Infallible>(::axum::middleware::map_request(check_json_content_type)) }
+                } else {
+                    quote! {}
+                }
+            })
+            .collect::<Vec<_>>();
+        let request_decompression = if self.enable_gzip_requests {
+            quote! { .layer::<_, ::std::convert::Infallible>(::axum::middleware::map_request(decompress_gzip_request)) }
+        } else {
+            quote! {}
+        };
+
+        // wasm32 aborts on panics, so there is nothing for `CatchPanic` to catch
+        let call_wrapper = if self.wasm_target {
+            quote! { ::std::convert::identity }
+        } else {
+            quote! { CatchPanic }
+        };
+
+        let (deadline_capture, await_call) = if let Some(deadline) = deadline {
+            (
+                deadline,
+                quote! {
+                    match deadline {
+                        Some(deadline) => ::tokio::time::timeout(deadline, call)
+                            .await
+                            .unwrap_or_else(|_| Err(::tonic::Status::deadline_exceeded("Request deadline exceeded"))),
+                        None => call.await,
+                    }
+                },
+            )
+        } else {
+            (quote! {}, quote! { call.await })
+        };
+
+        let api_versions = service
+            .methods
+            .iter()
+            .map(|method| self.api_versions_of(&service_path, &method.proto_name))
+            .collect::<Vec<_>>();
+        let request_body_types = branch_request
+            .iter()
+            .zip(&api_versions)
+            .map(|(request_type, versions)| {
+                if !versions.is_empty() {
+                    quote! { ::serde_json::Value }
+                } else {
+                    quote! { #request_type }
+                }
+            })
+            .collect::<Vec<_>>();
+        // Parse errors of requests with `g2h.sensitive` fields could echo their values
+        let request_masked = service
+            .methods
+            .iter()
+            .map(|method| {
+                self.masked_messages
+                    .contains_key(method.input_proto_type.trim_start_matches('.'))
+            })
+            .collect::<Vec<_>>();
+        // With the `simd-json` feature, JSON bodies are read as bytes and parsed with simd-json
+        let simd_json = cfg!(feature = "simd-json");
+        let rejection_responses = request_masked
+            .iter()
+            .map(|masked| {
+                if simd_json && *masked {
+                    quote! { withheld_body_error(simd_json_status(&rejection)) }
+                } else if simd_json {
+                    quote! { simd_json_rejection(rejection) }
+                } else if *masked {
+                    quote! {
+                        match rejection {
+                            ::axum::extract::rejection::JsonRejection::JsonDataError(_) => {
+                                withheld_body_error(::http::StatusCode::UNPROCESSABLE_ENTITY)
+                            }
+                            ::axum::extract::rejection::JsonRejection::JsonSyntaxError(_) => {
+                                withheld_body_error(::http::StatusCode::BAD_REQUEST)
+                            }
+                            rejection => rejection.into_response(),
+                        }
+                    }
+                } else {
+                    quote! { rejection.into_response() }
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let decode_bodies = branch_request
+            .iter()
+            .zip(&route_verbs)
+            .zip(&service.methods)
+            .zip(&request_masked)
+            .zip(&api_versions)
+            .map(|((((request_type, verb), method), masked), versions)| {
+                if !versions.is_empty() && (verb != "GET" || !is_empty_request(method)) {
+                    let message = method.input_proto_type.trim_start_matches('.');
+                    // The status axum answers invalid fields with, for queries or JSON bodies
+                    let status = if verb == "GET" {
+                        quote! { ::http::StatusCode::BAD_REQUEST }
+                    } else {
+                        quote! { ::http::StatusCode::UNPROCESSABLE_ENTITY }
+                    };
+                    let decode_error = if *masked {
+                        quote! { Err(_) => return withheld_body_error(#status), }
+                    } else {
+                        quote! { Err(response) => return response, }
+                    };
+                    quote! {
+                        let body: #request_type = match decode_versioned_body(body, #message, api_version, #status) {
+                            Ok(body) => body,
+                            #decode_error
+                        };
+                    }
+                } else {
+                    quote! {}
+                }
+            })
+            .collect::<Vec<_>>();
+        let (api_version_capture, encode_body): (Vec<_>, Vec<_>) = api_versions
+            .iter()
+            .zip(&service.methods)
+            .map(|(versions, method)| {
+                if versions.is_empty() {
+                    return (quote! {}, quote! { ::axum::Json(body) });
+                }
+                let version_entries = versions.iter().map(|(version, field_case)| {
+                    let field_case = match field_case {
+                        JsonFieldCase::SnakeCase => quote! { ApiFieldCase::Snake },
+                        JsonFieldCase::CamelCase => quote! { ApiFieldCase::Camel },
+                    };
+                    quote! { (#version, #field_case) }
+                });
+                let message = method.output_proto_type.trim_start_matches('.');
+                (
+                    quote! {
+                        let api_version = match request_api_version(&headers, &[#(#version_entries),*]) {
+                            Ok(api_version) => api_version,
+                            Err(response) => return response,
+                        };
+                    },
+                    quote! {
+                        match encode_versioned_body(&body, #message, api_version) {
+                            Ok(body) => ::axum::Json(body),
+                            Err(response) => return response,
+                        }
+                    },
+                )
+            })
+            .unzip();
+
+        let (routing_captures, routing_inserts): (Vec<_>, Vec<_>) = branch_names
+            .iter()
+            .map(|branch_name| {
+                match self.method_annotations.get(branch_name).map(|annotations| &annotations.routing_parameters) {
+                    Some(parameters) if self.enable_routing_headers && !parameters.is_empty() => {
+                        let fields = parameters.iter().map(|parameter| &parameter.field);
+                        let templates = parameters.iter().map(|parameter| &parameter.path_template);
+                        (
+                            quote! {
+                                let routing_params = request_routing_params(&body, &[#((#fields, #templates)),*]);
+                            },
+                            quote! {
+                                let mut metadata_map = metadata_map;
+                                if let Some(routing_params) = routing_params {
+                                    metadata_map.insert("x-goog-request-params", routing_params);
+                                }
+                            },
+                        )
+                    }
+                    _ => (quote! {}, quote! {}),
+                }
+            })
+            .unzip();
+
+        let (cancellation_setup, cancellation_disarm) = if self.enable_cancellation {
+            (
+                quote! {
+                    let mut extension = extension;
+                    let cancellation = HttpCancellation::default();
+                    extension.insert(cancellation.clone());
+                    let cancellation_guard = HttpCancellationGuard(Some(cancellation));
+                },
+                quote! {
+                    cancellation_guard.disarm();
+                },
+            )
+        } else {
+            (quote! {}, quote! {})
+        };
+
+        let http_transport = if self.enable_http_transport {
+            let transport_name = quote::format_ident!("{}HttpTransport", func_name);
+            let transport_doc = format!(
+                "Transport for [`{snake_case_name}_client::{func_name}Client`] calling a g2h bridge of \
+                 `{service_path}` over HTTP/JSON through the inner HTTP client service."
+            );
+            let branch_response = service
+                .methods
+                .iter()
+                .map(|method| rust_type_tokens(&method.output_type));
+            quote! {
+                #[doc = #transport_doc]
+                #[allow(dead_code)]
+                #[derive(Debug, Clone)]
+                pub struct #transport_name<S> {
+                    inner: S,
+                    base_uri: ::http::Uri,
+                }
+
+                #[allow(dead_code)]
+                impl<S> #transport_name<S> {
+                    /// Creates a transport sending calls through `inner` to the bridge at `base_uri`
+                    pub fn new(inner: S, base_uri: ::http::Uri) -> Self {
+                        Self { inner, base_uri }
+                    }
+                }
+
+                impl<S, RB> ::tonic::codegen::Service<::http::Request<::tonic::body::Body>> for #transport_name<S>
+                where
+                    S: ::tonic::codegen::Service<::http::Request<::axum::body::Body>, Response = ::http::Response<RB>> + Clone + Send + 'static,
+                    S::Future: Send,
+                    S::Error: Into<::tonic::codegen::StdError>,
+                    RB: ::tonic::codegen::Body<Data = ::tonic::codegen::Bytes> + Send + 'static,
+                    RB::Error: Into<::tonic::codegen::StdError>,
+                {
+                    type Response = ::http::Response<::axum::body::Body>;
+                    type Error = ::tonic::codegen::StdError;
+                    type Future = ::tonic::codegen::BoxFuture<Self::Response, Self::Error>;
+
+                    fn poll_ready(&mut self, cx: &mut ::std::task::Context<'_>) -> ::std::task::Poll<Result<(), Self::Error>> {
+                        self.inner.poll_ready(cx).map_err(Into::into)
+                    }
+
+                    fn call(&mut self, request: ::http::Request<::tonic::body::Body>) -> Self::Future {
+                        let clone = self.inner.clone();
+                        let inner = ::std::mem::replace(&mut self.inner, clone);
+                        let base_uri = self.base_uri.clone();
+
+                        let path = request.uri().path().to_string();
+                        match path.as_str() {
+                            #(#branch_names => Box::pin(http_transport_unary::<#branch_request, #branch_response, S, RB>(inner, base_uri, #route_paths, ::http::Method::#route_method_filters, request)),)*
+                            _ => {
+                                let status = ::tonic::Status::unimplemented(format!("`{}` is not served by the HTTP transport", path));
+                                Box::pin(async move { Ok(status.into_http()) })
+                            }
+                        }
+                    }
+                }
+            }
+        } else {
+            quote! {}
+        };
+
+        let route_method_names = service
+            .methods
+            .iter()
+            .map(|method| &method.proto_name)
+            .collect::<Vec<_>>();
+        let handler_with_doc =
+            format!(
+            "Like [`{service_name}`], but passes every route through `layer` together with the \
+             protobuf method name (e.g. `{}`), to attach tower layers to individual methods.",
+            route_method_names.first().map_or("Method", |name| name.as_str())
+        );
+
+        let builder_doc = format!(
+            "Builder for a customized [`{service_name}`] router: routes can be moved to other paths, \
+             disabled, or wrapped in middleware before the router is built."
+        );
+
+        // Services are shared between requests through an `Arc`, or by cloning them
+        let (state_type, state_value, clone_bound) = if self.enable_clone_state {
+            (quote! { T }, quote! { server }, quote! { + Clone })
+        } else {
+            (
+                quote! { ::std::sync::Arc<T> },
+                quote! { ::std::sync::Arc::new(server) },
+                quote! {},
+            )
+        };
+        let service_name_dyn = quote::format_ident!("{}_handler_dyn", snake_case_name);
+        let unsized_bound = if self.enable_clone_state {
+            quote! {}
+        } else {
+            quote! { ?Sized + }
+        };
+        let handler_dyn = if self.enable_clone_state || !object_safe {
+            quote! {}
+        } else {
+            let handler_dyn_doc = format!(
+                "Like [`{service_name}`], but dispatches to the service through a trait object, so \
+                 the router is compiled once for all implementations of `{func_name}`."
+            );
+            quote! {
+                #[allow(dead_code)]
+                #[doc = #handler_dyn_doc]
+                pub fn #service_name_dyn(server: ::std::sync::Arc<dyn #server_module::#ident_func_name>) -> ::axum::Router {
+                    #router_parts(server, |_, path, route| Some((path.to_string(), route)))
+                }
+            }
+        };
+        let handler_interceptor_doc = format!(
+            "Like [`{service_name}`], but runs `interceptor` on every request before the checks of \
+             the handler and the service, as a tonic server created with `{func_name}Server::with_interceptor` would. \
+             Requests it rejects are answered with the JSON error of the returned status."
+        );
+        let server_bound = quote! { #server_module::#ident_func_name #clone_bound };
+
+        let handler_arc = if self.enable_clone_state {
+            quote! {}
+        } else {
+            let handler_arc_doc = format!(
+                "Like [`{service_name}`], but serves an already shared service, e.g. the same \
+                 instance as a tonic server created with `{}Server::from_arc`.",
+                func_name
+            );
+            quote! {
+                #[allow(dead_code)]
+                #[doc = #handler_arc_doc]
+                pub fn #service_name_arc<T: #server_bound>(server: ::std::sync::Arc<T>) -> ::axum::Router {
+                    #router_parts(server, |_, path, route| Some((path.to_string(), route)))
+                }
+            }
+        };
+
+        // REST mapping of the standard `grpc.health.v1.Health` service, as load balancers expect
+        let serves_health_check = service_path == "grpc.health.v1.Health"
+            && service
+                .methods
+                .iter()
+                .any(|method| method.proto_name == "Check");
+        let grpc_health_routes = if serves_health_check {
+            quote! {
+                async fn rest_health_check<T: #unsized_bound #server_module::#ident_func_name>(
+                    state: #state_type,
+                    extension: ::http::Extensions,
+                    headers: ::http::header::HeaderMap,
+                    service: String,
+                ) -> ::axum::response::Response {
+                    #[derive(serde::Serialize)]
+                    struct ServingStatusBody {
+                        status: &'static str,
+                    }
+
+                    let metadata_map = match request_metadata_from_headers(headers, &extension) {
+                        Ok(metadata_map) => metadata_map,
+                        Err(response) => return response,
+                    };
+                    let request = ::tonic::Request::from_parts(metadata_map, extension, HealthCheckRequest { service });
+
+                    match <T as #server_module::#ident_func_name>::check(&state, request).await {
+                        Ok(response) => {
+                            let status = health_check_response::ServingStatus::try_from(response.get_ref().status)
+                                .unwrap_or(health_check_response::ServingStatus::Unknown);
+                            let code = if status == health_check_response::ServingStatus::Serving {
+                                ::http::StatusCode::OK
+                            } else {
+                                ::http::StatusCode::SERVICE_UNAVAILABLE
+                            };
+                            (code, ::axum::Json(ServingStatusBody { status: status.as_str_name() })).into_response()
+                        }
+                        Err(status) => {
+                            let code = if status.code() == ::tonic::Code::NotFound {
+                                ::http::StatusCode::NOT_FOUND
+                            } else {
+                                ::http::StatusCode::SERVICE_UNAVAILABLE
+                            };
+                            let error_body = ErrorResponse {
+                                error: ErrorDetails::new(error_code(status.code()), status.message())
+                            };
+                            (code, ::axum::Json(error_body)).into_response()
+                        }
+                    }
+                }
+
+                let router = router
+                    .route("/health", ::axum::routing::get(|::axum::extract::State(state): ::axum::extract::State<#state_type>, extension: ::http::Extensions, headers: ::http::header::HeaderMap| {
+                        rest_health_check(state, extension, headers, String::new())
+                    }))
+                    .route("/health/{service}", ::axum::routing::get(|::axum::extract::State(state): ::axum::extract::State<#state_type>, ::axum::extract::Path(service): ::axum::extract::Path<String>, extension: ::http::Extensions, headers: ::http::header::HeaderMap| {
+                        rest_health_check(state, extension, headers, service)
+                    }));
+            }
+        } else {
+            quote! {}
+        };
+
+        let trailing_slash_route = if self.enable_trailing_slash {
+            quote! {
+                let router = if path.ends_with('/') || path.contains("{*") {
+                    router
+                } else {
+                    router.route(&format!("{}/", path), route.clone())
+                };
+            }
+        } else {
+            quote! {}
+        };
+
+        let method_not_allowed_code = &self.method_not_allowed_code;
+        let cors_spec = self
+            .cors_specs
+            .iter()
+            .find(|spec| spec.applies_to(&service_path));
+        let preflight_routes = route_verbs
+            .iter()
+            .map(|verb| {
+                if self.enable_preflight_responses {
+                    let policy = match cors_spec {
+                        Some(spec) => {
+                            let policy = spec.preflight_policy_tokens();
+                            quote! { Some(&#policy) }
+                        }
+                        None => quote! { None },
+                    };
+                    quote! {
+                        .options(|headers: ::http::header::HeaderMap| async move {
+                            preflight_response(&headers, #verb, #policy)
+                        })
+                    }
+                } else {
+                    quote! {}
+                }
+            })
+            .collect::<Vec<_>>();
+        let cors = cors_spec.map(|spec| {
+            let layer = spec.layer_tokens(&route_verbs);
+            quote! {
+                let router = router.layer(#layer);
+            }
+        });
+
+        // Outside of the fallback, so routing errors carry the request ID too
+        let request_ids = if self.enable_request_ids {
+            quote! {
+                let router = router.layer(::axum::middleware::from_fn(ensure_request_id));
+            }
+        } else {
+            quote! {}
+        };
+
+        // Outside of the request IDs, so they are added before errors are wrapped
+        let response_envelope = if self.enable_response_envelope {
+            quote! {
+                let router = router.layer(::axum::middleware::from_fn(envelope_response));
+            }
+        } else {
+            quote! {}
+        };
+
+        let empty_bodies = route_verbs
+            .iter()
+            .zip(&service.methods)
+            .map(|(verb, method)| {
+                if verb == "GET" && is_empty_request(method) {
+                    quote! { let body = (); }
+                } else {
+                    quote! {}
+                }
+            })
+            .collect::<Vec<_>>();
+        // Bodies are extracted as results, so that malformed requests go through the reports
+        // of the handler instead of being rejected by axum before it
+        let query_parses = route_verbs
+            .iter()
+            .zip(&service.methods)
+            .map(|(verb, method)| {
+                if verb == "GET" && !is_empty_request(method) {
+                    quote! {
+                        let ::axum::extract::Query(body) = match body {
+                            Ok(body) => body,
+                            Err(rejection) => return rejection.into_response(),
+                        };
+                    }
+                } else {
+                    quote! {}
+                }
+            })
+            .collect::<Vec<_>>();
+        let (body_extractors, profile_parses, profile_call_start, profile_call_end, profile_report) =
+            if self.is_profiling_slow_requests() {
+                // Routes with a content type check layer have it checked before the handler
+                let content_type_check = if self.enable_content_type_check {
+                    quote! {}
+                } else {
+                    quote! {
+                        if let Err(response) = check_profiled_content_type(&headers) {
+                            return response;
+                        }
+                    }
+                };
+                (
+                    request_body_types
+                        .iter()
+                        .zip(&route_verbs)
+                        .zip(&service.methods)
+                        .map(|((request_type, verb), method)| {
+                            if verb == "GET" && is_empty_request(method) {
+                                quote! {}
+                            } else if verb == "GET" {
+                                quote! { body: ::std::result::Result<::axum::extract::Query<#request_type>, ::axum::extract::rejection::QueryRejection> }
+                            } else {
+                                quote! { body: ::axum::body::Bytes }
+                            }
+                        })
+                        .collect::<Vec<_>>(),
+                    request_body_types
+                        .iter()
+                        .zip(&route_verbs)
+                        .zip(&rejection_responses)
+                        .zip(&query_parses)
+                        .map(|(((request_type, verb), rejection_response), query_parse)| {
+                            let json_parse = if simd_json {
+                                quote! { parse_json_body::<#request_type>(body).map(::axum::Json) }
+                            } else {
+                                quote! { ::axum::Json::<#request_type>::from_bytes(&body) }
+                            };
+                            if verb == "GET" {
+                                return quote! {
+                                    #query_parse
+                                    let request_started = ::std::time::Instant::now();
+                                    let request_size = 0;
+                                    let parse_time = ::std::time::Duration::ZERO;
+                                    let slow_request_observer = extension.get::<SlowRequestObserver>().cloned();
+                                };
+                            }
+                            quote! {
+                                #content_type_check
+                                let request_started = ::std::time::Instant::now();
+                                let request_size = body.len();
+                                let ::axum::Json(body) = match #json_parse {
+                                    Ok(body) => body,
+                                    Err(rejection) => return #rejection_response,
+                                };
+                                let parse_time = request_started.elapsed();
+                                let slow_request_observer = extension.get::<SlowRequestObserver>().cloned();
+                            }
+                        })
+                        .collect::<Vec<_>>(),
+                    quote! {
+                        let handler_started = ::std::time::Instant::now();
+                    },
+                    quote! {
+                        let handler_time = handler_started.elapsed();
+                        let serialize_started = ::std::time::Instant::now();
+                    },
+                    route_paths
+                        .iter()
+                        .zip(&service.methods)
+                        .map(|(branch_name, method)| {
+                            // Methods without a threshold are never reported
+                            let threshold_nanos = self
+                                .slow_request_threshold_of(&service_path, &method.proto_name)
+                                .map_or(u64::MAX, |threshold| {
+                                    u64::try_from(threshold.as_nanos()).unwrap_or(u64::MAX)
+                                });
+                            quote! {
+                                if let Some(observer) = slow_request_observer {
+                                    let total_time = request_started.elapsed();
+                                    if total_time >= ::std::time::Duration::from_nanos(#threshold_nanos) {
+                                        observer.observe(&SlowRequestProfile {
+                                            method: #branch_name,
+                                            parse_time,
+                                            handler_time,
+                                            serialize_time: serialize_started.elapsed(),
+                                            total_time,
+                                            request_size,
+                                            response_size: ::axum::body::HttpBody::size_hint(response.body())
+                                                .exact()
+                                                .map_or(0, |size| size as usize),
+                                        });
+                                    }
+                                }
+                            }
+                        })
+                        .collect::<Vec<_>>(),
+                )
+            } else {
+                (
+                    request_body_types
+                        .iter()
+                        .zip(&route_verbs)
+                        .zip(&service.methods)
+                        .map(|((request_type, verb), method)| {
+                            if verb == "GET" && is_empty_request(method) {
+                                quote! {}
+                            } else if verb == "GET" {
+                                quote! { body: ::std::result::Result<::axum::extract::Query<#request_type>, ::axum::extract::rejection::QueryRejection> }
+                            } else if simd_json {
+                                quote! { body: ::axum::body::Bytes }
+                            } else {
+                                quote! { body: ::std::result::Result<::axum::Json<#request_type>, ::axum::extract::rejection::JsonRejection> }
+                            }
+                        })
+                        .collect::<Vec<_>>(),
+                    route_verbs
+                        .iter()
+                        .zip(&query_parses)
+                        .zip(&rejection_responses)
+                        .zip(&request_body_types)
+                        .map(|(((verb, query_parse), rejection_response), request_type)| {
+                            if verb == "GET" {
+                                query_parse.clone()
+                            } else if simd_json {
+                                quote! {
+                                    let body = match parse_json_body::<#request_type>(body) {
+                                        Ok(body) => body,
+                                        Err(rejection) => return #rejection_response,
+                                    };
+                                }
+                            } else {
+                                quote! {
+                                    let ::axum::Json(body) = match body {
+                                        Ok(body) => body,
+                                        Err(rejection) => return #rejection_response,
+                                    };
+                                }
+                            }
+                        })
+                        .collect::<Vec<_>>(),
+                    quote! {},
+                    quote! {},
+                    branch_names.iter().map(|_| quote! {}).collect::<Vec<_>>(),
+                )
+            };
+
+        // Templated handlers take the routed name, the built-in ones stay under another name
+        let custom_handlers = service
+            .methods
+            .iter()
+            .zip(&method_infos)
+            .zip(route_paths.iter().zip(&route_verbs))
+            .zip(&handler_fns)
+            .map(|(((method, method_info), (path, verb)), handler_fn)| {
+                let template = self.handler_template.as_ref()?;
+                template(&HandlerContext {
+                    service: ServiceInfo {
+                        package: service.package.clone(),
+                        name: service.proto_name.clone(),
+                    },
+                    method: method_info.clone(),
+                    path: path.clone(),
+                    verb: verb.clone(),
+                    handler_name: handler_fn.to_string(),
+                    builtin_handler_name: format!("{handler_fn}_builtin"),
+                    request_type: method.input_type.clone(),
+                    response_type: method.output_type.clone(),
+                    server_bound: quote!(#unsized_bound #server_bound).to_string(),
+                    state_type: state_type.to_string(),
+                })
+            })
+            .collect::<Vec<_>>();
+        let builtin_handler_fns = handler_fns
+            .iter()
+            .zip(&custom_handlers)
+            .map(|(handler_fn, custom)| match custom {
+                Some(_) => quote::format_ident!("{}_builtin", handler_fn),
+                None => handler_fn.clone(),
+            })
+            .collect::<Vec<_>>();
+        let custom_handlers = custom_handlers.into_iter().flatten();
+
+        // With single dispatch, static paths right under the service path go into a map
+        // served by one route, and the others keep their own routes
+        let (dispatch_setup, route_registration, dispatch_route) = if self.enable_single_dispatch {
+            let service_prefix = format!("/{package}.{name}/");
+            let dispatch_path = format!("{service_prefix}{{method}}");
+            let not_found = if self.enable_not_found_fallback {
+                quote! { not_found(request.uri().clone()).await }
+            } else {
+                quote! { ::http::StatusCode::NOT_FOUND.into_response() }
+            };
+            (
+                quote! {
+                    let mut dispatched: ::std::collections::HashMap<String, ::axum::routing::MethodRouter<#state_type>> = ::std::collections::HashMap::new();
+                },
+                quote! {
+                    let dispatches = path
+                        .strip_prefix(#service_prefix)
+                        .is_some_and(|method| !method.is_empty() && !method.contains(['/', '{', ':', '*']));
+                    if dispatches {
+                        dispatched.insert(path, route);
+                        router
+                    } else {
+                        router.route(&path, route)
+                    }
+                },
+                quote! {
+                    let router = if dispatched.is_empty() {
+                        router
+                    } else {
+                        let dispatched = ::std::sync::Arc::new(dispatched);
+                        router.route(#dispatch_path, ::axum::routing::any(move |::axum::extract::State(state): ::axum::extract::State<#state_type>, request: ::axum::extract::Request| {
+                            let route = dispatched.get(request.uri().path()).cloned();
+                            async move {
+                                match route {
+                                    Some(route) => <::axum::routing::MethodRouter<#state_type> as ::axum::handler::Handler<(), #state_type>>::call(route, request, state).await,
+                                    None => #not_found,
+                                }
+                            }
+                        }))
+                    };
+                },
+            )
+        } else {
+            (quote! {}, quote! { router.route(&path, route) }, quote! {})
+        };
+
+        let output = quote! {
+            #route_metadata
+
+            #[allow(dead_code)]
+            #docs
+            pub fn #service_name<T: #server_bound>(server: T) -> ::axum::Router {
+                #service_name_with(server, |_, route| route)
+            }
+
+            #[allow(dead_code, unused_mut)]
+            #[doc = #handler_with_doc]
+            pub fn #service_name_with<T, F>(server: T, mut layer: F) -> ::axum::Router
+            where
+                T: #server_bound,
+                F: FnMut(&str, ::axum::routing::MethodRouter<#state_type>) -> ::axum::routing::MethodRouter<#state_type>,
+            {
+                #router_parts(#state_value, |method_name, path, route| Some((path.to_string(), layer(method_name, route))))
+            }
+
+            #handler_arc
+
+            #handler_dyn
+
+            #[allow(dead_code)]
+            #[doc = #handler_interceptor_doc]
+            pub fn #service_name_interceptor<T, I>(server: T, interceptor: I) -> ::axum::Router
+            where
+                T: #server_bound,
+                I: ::tonic::service::Interceptor + Clone + Send + Sync + 'static,
+            {
+                #service_name(server).layer(::axum::Extension(HttpInterceptor::new(interceptor)))
+            }
+
+            #(
+                #[allow(dead_code, unused_mut)]
+                #[doc = #handler_fn_docs]
+                pub async fn #builtin_handler_fns<T>(
+                    ::axum::extract::State(state): ::axum::extract::State<#state_type>,
+                    extension: ::http::Extensions,
+                    headers: ::http::header::HeaderMap,
+                    #body_extractors
+                ) -> ::axum::response::Response
+                where
+                    T: #unsized_bound #server_bound,
+                {
+                    use ::axum::response::IntoResponse;
+
+                    #tracing_starts
+                    #metrics_starts
+                    #access_log_start
+                    // Every exit of the handler, early rejections included, goes through the reports below
+                    let response: ::axum::response::Response = async {
+                        #etag_captures
+                        #trailer_captures
+                        #idempotency_captures
+                        #api_key_captures
+                        #message_hook_captures
+                        #empty_bodies
+                        #profile_parses
+                        #api_version_capture
+                        #decode_bodies
+                        #routing_captures
+                        #deadline_capture
+                        let metadata_map = match request_metadata_from_headers(headers, &extension) {
+                            Ok(metadata_map) => metadata_map,
+                            Err(response) => return response,
+                        };
+                        let (metadata_map, extension) = match intercept_http_request(metadata_map, extension) {
+                            Ok(parts) => parts,
+                            Err(status) => return status_error_response(&status),
+                        };
+                        #routing_inserts
+                        #auth_checks
+                        #api_key_checks
+                        #authorizer_checks
+                        #rate_limit_checks
+                        #message_before_hooks
+                        #validation_checks
+                        #protovalidate_checks
+                        #idempotency_replays
+                        #before_hooks
+                        #cancellation_setup
+                        #access_log_request
+                        let request = ::tonic::Request::from_parts(metadata_map, extension, body);
+
+                        #profile_call_start
+                        let call = #call_wrapper(Box::pin(async {
+                            <T as #server_module::#ident_func_name>::#func_names(&state, request).await
+                        }));
+                        #tracing_instrument
+                        let output = #await_call;
+                        #tracing_code
+                        #cancellation_disarm
+                        #profile_call_end
+
+                        let response = match output {
+                            Ok(response) => {
+                                let (metadata_map, body, extension) = response.into_parts();
+                                let headers = response_headers_from_metadata(metadata_map);
+                                #message_after_hooks
+                                #access_log_response
+                                let body = #encode_body;
+
+                                (#success_statuses, headers, extension, body).into_response()
+                            },
+                            Err(status) => {
+                                let code = http_status_of(status.code());
+
+                                // Create JSON error response
+                                let error_body = ErrorResponse {
+                                    error: ErrorDetails::new(error_code(status.code()), status.message())
+                                };
+
+                                #access_log_error
+                                let body = ::axum::Json(error_body);
+
+                                let response = (code, body).into_response();
+                                #status_trailers
+                                response
+                            }
+                        };
+                        #after_hooks
+                        #cache_controls
+                        #etag_checks
+                        #deprecation_headers
+                        #profile_report
+                        #idempotency_records
+                        #trailer_applies
+
+                        response
+                    }
+                    .await;
+                    #tracing_latency
+                    #metrics_records
+                    #access_log_records
+
+                    response
+                }
+            )*
+
+            // Builds the router, letting `route_for` relocate, wrap or drop (`None`) every route
+            #[allow(dead_code, unused_mut)]
+            fn #router_parts<T, F>(state: #state_type, mut route_for: F) -> ::axum::Router
+            where
+                T: #unsized_bound #server_bound,
+                F: FnMut(&str, &str, ::axum::routing::MethodRouter<#state_type>) -> Option<(String, ::axum::routing::MethodRouter<#state_type>)>,
+            {
+                use ::axum::response::IntoResponse;
+
+                async fn method_not_allowed(allow: &'static str) -> ::axum::response::Response {
+                    let error_body = ErrorResponse {
+                        error: ErrorDetails::new(#method_not_allowed_code, format!("gRPC methods are served over HTTP {} only", allow))
+                    };
+
+                    (::http::StatusCode::METHOD_NOT_ALLOWED, [(::http::header::ALLOW, allow)], ::axum::Json(error_body)).into_response()
+                }
+
+                let router = ::axum::Router::new();
+                // The generated paths are checked at compile time, these include `route_for`'s
+                let mut served_paths: Vec<(String, &'static str)> = Vec::new();
+                #dispatch_setup
+
+                #(
+                    let router = match route_for(#route_method_names, #route_paths, ::axum::routing::on(::axum::routing::MethodFilter::#route_method_filters, #handler_fns::<T>).fallback(|| method_not_allowed(#route_verbs)) #preflight_routes #body_limit #content_type_checks #request_decompression #concurrency_layers) {
+                        Some((path, route)) => {
+                            if let Some((_, previous)) = served_paths.iter().find(|(served, _)| *served == path) {
+                                panic!("`{}` and `{}` are both served on `{}`", previous, #route_method_names, path);
+                            }
+                            served_paths.push((path.clone(), #route_method_names));
+                            #trailing_slash_route
+                            #route_registration
+                        }
+                        None => router,
+                    };
+                )*
+
+                #grpc_health_routes
+                #dispatch_route
+                #request_ids
+                #response_envelope
+                #cors
+
+                router.with_state(state)
+            }
+
+            #[doc = #builder_doc]
+            #[allow(dead_code)]
+            pub struct #router_builder<T: #server_bound> {
+                server: T,
+                paths: Vec<(&'static str, String)>,
+                disabled: Vec<&'static str>,
+                route_maps: Vec<(&'static str, Box<dyn FnOnce(::axum::routing::MethodRouter<#state_type>) -> ::axum::routing::MethodRouter<#state_type>>)>,
+                all_route_maps: Vec<Box<dyn Fn(::axum::routing::MethodRouter<#state_type>) -> ::axum::routing::MethodRouter<#state_type>>>,
+            }
+
+            #[allow(dead_code)]
+            impl<T: #server_bound> #router_builder<T> {
+                const METHODS: &'static [&'static str] = &[#(#route_method_names),*];
+
+                /// Starts a router serving every method of `server` on its default path
+                pub fn new(server: T) -> Self {
+                    Self {
+                        server,
+                        paths: Vec::new(),
+                        disabled: Vec::new(),
+                        route_maps: Vec::new(),
+                        all_route_maps: Vec::new(),
+                    }
+                }
+
+                // Panics on names that are not protobuf methods of the service
+                fn method(method_name: &str) -> &'static str {
+                    Self::METHODS
+                        .iter()
+                        .find(|method| **method == method_name)
+                        .copied()
+                        .unwrap_or_else(|| panic!("`{}` is not a method of the service, expected one of {:?}", method_name, Self::METHODS))
+                }
+
+                /// Serves the method `method_name` (e.g. `SayHello`) on `path` instead of its default path
+                pub fn path(mut self, method_name: &str, path: impl Into<String>) -> Self {
+                    self.paths.push((Self::method(method_name), path.into()));
+                    self
+                }
+
+                /// Leaves the method `method_name` out of the router
+                pub fn disable(mut self, method_name: &str) -> Self {
+                    self.disabled.push(Self::method(method_name));
+                    self
+                }
+
+                /// Transforms the route of `method_name`, e.g. to attach a tower layer with `MethodRouter::layer`
+                pub fn map_route(
+                    mut self,
+                    method_name: &str,
+                    map: impl FnOnce(::axum::routing::MethodRouter<#state_type>) -> ::axum::routing::MethodRouter<#state_type> + 'static,
+                ) -> Self {
+                    self.route_maps.push((Self::method(method_name), Box::new(map)));
+                    self
+                }
+
+                /// Transforms the route of every method, after the per-method transformations
+                pub fn map_routes(
+                    mut self,
+                    map: impl Fn(::axum::routing::MethodRouter<#state_type>) -> ::axum::routing::MethodRouter<#state_type> + 'static,
+                ) -> Self {
+                    self.all_route_maps.push(Box::new(map));
+                    self
+                }
+
+                #message_hooks_method
+
+                /// Builds the router, panicking if two methods are served on the same path
+                pub fn build(self) -> ::axum::Router {
+                    let Self { server, paths, disabled, mut route_maps, all_route_maps } = self;
+
+                    #router_parts(#state_value, |method_name, path, route| {
+                        if disabled.iter().any(|method| *method == method_name) {
+                            return None;
+                        }
+                        let path = paths
+                            .iter()
+                            .rev()
+                            .find(|(method, _)| *method == method_name)
+                            .map_or_else(|| path.to_string(), |(_, path)| path.clone());
+
+                        let (matching, remaining) = ::std::mem::take(&mut route_maps)
+                            .into_iter()
+                            .partition::<Vec<_>, _>(|(method, _)| *method == method_name);
+                        route_maps = remaining;
+
+                        let route = matching.into_iter().fold(route, |route, (_, map)| map(route));
+                        let route = all_route_maps.iter().fold(route, |route, map| map(route));
+                        Some((path, route))
+                    })
+                }
+            }
+
+            #http_transport
+
+            #(#custom_handlers)*
+        };
+
+        buf.push_str(&output.to_string());
+        self.gate_http_code(buf, http_start);
+    }
+    fn finalize(&mut self, buf: &mut String) {
+        self.inner.finalize(buf);
+        for generator in &mut self.chained {
+            generator.finalize(buf);
+        }
+        // Without a compile method, e.g. with a custom `prost_build::Config`, the check
+        // happens here, where a panic is the only way to fail the build
+        if let Err(err) = self.check_dependencies() {
+            panic!("{err}");
+        }
+    }
+
+    fn finalize_package(&mut self, package: &str, buf: &mut String) {
+        self.inner.finalize_package(package, buf);
+        for generator in &mut self.chained {
+            generator.finalize_package(package, buf);
+        }
+        let http_start = buf.len();
+
+        // `ErrorDetails` only gets the optional fields of the enabled features, so the
+        // `ErrorDetails { code, message }` literals of existing users keep compiling
+        let mut detail_names = Vec::new();
+        let mut detail_types = Vec::new();
+        if self.enable_not_found_fallback && self.list_known_paths {
+            detail_names.push(quote::format_ident!("known_paths"));
+            detail_types.push(quote! { Vec<String> });
+        }
+        let validates_fields =
+            self.enable_request_validation && !self.validated_messages.is_empty();
+        let protovalidates = self.enable_protovalidate && !self.protovalidate_messages.is_empty();
+        if validates_fields || protovalidates {
+            detail_names.push(quote::format_ident!("field_violations"));
+            detail_types.push(quote! { Vec<FieldViolation> });
+        }
+        if self.enable_request_ids {
+            detail_names.push(quote::format_ident!("request_id"));
+            detail_types.push(quote! { String });
+        }
+
+        let error_code = if self.enable_grpc_code_names {
+            quote! {
+                // Name of a gRPC code as in the gRPC spec, e.g. `NOT_FOUND`, the `code` of JSON errors
+                #[allow(dead_code)]
+                fn error_code(code: ::tonic::Code) -> &'static str {
+                    match code {
+                        ::tonic::Code::Ok => "OK",
+                        ::tonic::Code::Cancelled => "CANCELLED",
+                        ::tonic::Code::Unknown => "UNKNOWN",
+                        ::tonic::Code::InvalidArgument => "INVALID_ARGUMENT",
+                        ::tonic::Code::DeadlineExceeded => "DEADLINE_EXCEEDED",
+                        ::tonic::Code::NotFound => "NOT_FOUND",
+                        ::tonic::Code::AlreadyExists => "ALREADY_EXISTS",
+                        ::tonic::Code::PermissionDenied => "PERMISSION_DENIED",
+                        ::tonic::Code::ResourceExhausted => "RESOURCE_EXHAUSTED",
+                        ::tonic::Code::FailedPrecondition => "FAILED_PRECONDITION",
+                        ::tonic::Code::Aborted => "ABORTED",
+                        ::tonic::Code::OutOfRange => "OUT_OF_RANGE",
+                        ::tonic::Code::Unimplemented => "UNIMPLEMENTED",
+                        ::tonic::Code::Internal => "INTERNAL",
+                        ::tonic::Code::Unavailable => "UNAVAILABLE",
+                        ::tonic::Code::DataLoss => "DATA_LOSS",
+                        ::tonic::Code::Unauthenticated => "UNAUTHENTICATED",
+                    }
+                }
+            }
+        } else {
+            quote! {
+                // Description of a gRPC code, e.g. `Some requested entity was not found`, the
+                // `code` of JSON errors
+                #[allow(dead_code)]
+                fn error_code(code: ::tonic::Code) -> &'static str {
+                    code.description()
+                }
+            }
+        };
+
+        // Add error response structures once per package
+        let error_structs = quote! {
+            // Error response structures for HTTP endpoints
+            #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+            pub struct ErrorResponse {
+                pub error: ErrorDetails,
+            }
+
+            #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+            pub struct ErrorDetails {
+                pub code: String,
+                pub message: String,
+                #(
+                    #[serde(default, skip_serializing_if = "Option::is_none")]
+                    pub #detail_names: Option<#detail_types>,
+                )*
+            }
+
+            impl ErrorDetails {
+                /// Error with the gRPC `code` and `message`, without optional details
+                pub fn new(code: impl Into<String>, message: impl Into<String>) -> Self {
+                    Self {
+                        code: code.into(),
+                        message: message.into(),
+                        #(#detail_names: None,)*
+                    }
+                }
+            }
+
+            #error_code
+
+            // A request field failing validation, as in `google.rpc.BadRequest`
+            #[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+            #[allow(dead_code)]
+            pub struct FieldViolation {
+                pub field: String,
+                pub constraint: String,
+                pub description: String,
+            }
+        };
+
+        buf.push('\n');
+        buf.push_str(&error_structs.to_string());
+
+        // Turn panics of service calls into `INTERNAL` errors instead of dropped connections
+        let catch_panic = quote! {
+            #[allow(dead_code)]
+            struct CatchPanic<F>(::std::pin::Pin<Box<F>>);
+
+            impl<T, F> ::std::future::Future for CatchPanic<F>
+            where
+                F: ::std::future::Future<Output = Result<T, ::tonic::Status>>,
+            {
+                type Output = Result<T, ::tonic::Status>;
+
+                fn poll(mut self: ::std::pin::Pin<&mut Self>, cx: &mut ::std::task::Context<'_>) -> ::std::task::Poll<Self::Output> {
+                    let call = self.0.as_mut();
+                    match ::std::panic::catch_unwind(::std::panic::AssertUnwindSafe(|| call.poll(cx))) {
+                        Ok(poll) => poll,
+                        Err(_) => ::std::task::Poll::Ready(Err(::tonic::Status::internal("The service panicked while handling the request"))),
+                    }
+                }
+            }
+        };
+
+        if !self.wasm_target {
+            buf.push('\n');
+            buf.push_str(&catch_panic.to_string());
+        }
+
+        buf.push('\n');
+        buf.push_str(&self.metadata_helpers().to_string());
+
+        match self.backend {
+            Backend::Axum => self.axum_package_helpers(buf),
+            Backend::Actix => {
+                buf.push('\n');
+                buf.push_str(&actix::package_helpers().to_string());
+            }
+        }
+        self.package_has_get_routes = false;
+        self.package_route_paths.clear();
+        self.gate_http_code(buf, http_start);
+
+        // The descriptor set and reflection server belong to the gRPC side
+        if let Some(ref path) = self.descriptor_set_path {
+            // `include_bytes!` resolves relative paths against the generated file, which
+            // lives in the output directory when one is set
+            let path = match self
+                .out_dir
+                .as_deref()
+                .and_then(|dir| path_within(path, dir))
+            {
+                Some(relative) => relative,
+                None if path.is_relative() => std::env::current_dir()
+                    .map(|dir| dir.join(path))
+                    .unwrap_or_else(|_| path.clone()),
+                None => path.clone(),
+            };
+            let path = path.to_string_lossy().into_owned();
+            let reflection_service = if self.enable_reflection_service {
+                quote! {
+                    /// Builds a gRPC reflection server describing the services of the descriptor set
+                    #[allow(dead_code)]
+                    pub fn reflection_service() -> Result<
+                        ::tonic_reflection::server::v1::ServerReflectionServer<impl ::tonic_reflection::server::v1::ServerReflection>,
+                        ::tonic_reflection::server::Error,
+                    > {
+                        ::tonic_reflection::server::Builder::configure()
+                            .register_encoded_file_descriptor_set(FILE_DESCRIPTOR_SET)
+                            .build_v1()
+                    }
+                }
+            } else {
+                quote! {}
+            };
+            let descriptor_set = quote! {
+                /// Encoded file descriptor set of the compiled protos
+                #[allow(dead_code)]
+                pub const FILE_DESCRIPTOR_SET: &[u8] = include_bytes!(#path);
+
+                #reflection_service
+            };
+
+            buf.push('\n');
+            buf.push_str(&descriptor_set.to_string());
+        }
+        let http_start = buf.len();
+
+        if self.backend == Backend::Axum {
+            self.axum_package_extras(buf);
+        }
+        self.gate_http_code(buf, http_start);
+
+        // Rewrite the route manifest, so the last package leaves it complete
+        if let (Some(path), Some(file_descriptor_set)) =
+            (&self.route_manifest_path, &self.file_descriptor_set)
+        {
+            let contents = manifest::render(&self.manifest_routes, file_descriptor_set);
+            if let Err(err) = std::fs::write(path, contents) {
+                panic!("failed to write route manifest `{}`: {err}", path.display());
+            }
+        }
+
+        // If string enums are enabled, add the enum deserializer module at the end of each package
+        if self.enable_string_enums {
+            if let Some(ref file_descriptor_set) = self.file_descriptor_set {
+                // Generate enum deserializer code only for enums in this specific package
+                let enum_deserializer_code = Self::generate_package_specific_enum_deserializer_code(
+                    &self.local_descriptors(file_descriptor_set),
+                    package,
+                    self.enable_enum_tables,
+                    self.max_function_id_len,
+                );
+                if !enum_deserializer_code.trim().is_empty() {
+                    buf.push('\n');
+                    buf.push_str(&enum_deserializer_code);
+                }
+            }
+        }
+    }
+}
+
+// Package-level code of the axum backend
+impl BridgeGenerator {
+    // Header <-> metadata conversion and status mapping shared by the handlers of a package,
+    // whatever their backend
+    fn metadata_helpers(&self) -> proc_macro2::TokenStream {
+        let passthrough_headers = &self.passthrough_headers;
+        let (request_prefix_filter, response_prefix) = match self.metadata_header_prefix {
+            Some(ref prefix) => (
+                quote! {
+                    const PASSTHROUGH_HEADERS: &[&str] = &[#(#passthrough_headers),*];
+
+                    // Values are moved, only the names of kept headers are rebuilt
+                    let mut prefixed_headers = ::http::header::HeaderMap::with_capacity(headers.len());
+                    let mut current_name = None;
+                    for (name, value) in headers {
+                        if let Some(name) = name {
+                            current_name = if PASSTHROUGH_HEADERS.contains(&name.as_str()) {
+                                Some(name)
+                            } else {
+                                name.as_str()
+                                    .strip_prefix(#prefix)
+                                    .and_then(|name| ::http::header::HeaderName::from_bytes(name.as_bytes()).ok())
+                            };
+                        }
+                        if let Some(ref name) = current_name {
+                            prefixed_headers.append(name.clone(), value);
+                        }
+                    }
+                    let headers = prefixed_headers;
+                },
+                quote! {
+                    const PASSTHROUGH_HEADERS: &[&str] = &[#(#passthrough_headers),*];
+
+                    let mut prefixed_headers = ::http::header::HeaderMap::with_capacity(headers.len());
+                    let mut current_name = None;
+                    for (name, value) in headers {
+                        if let Some(name) = name {
+                            current_name = if PASSTHROUGH_HEADERS.contains(&name.as_str()) {
+                                Some(name)
+                            } else {
+                                ::http::header::HeaderName::from_bytes(format!("{}{}", #prefix, name).as_bytes()).ok()
+                            };
+                        }
+                        if let Some(ref name) = current_name {
+                            prefixed_headers.append(name.clone(), value);
+                        }
+                    }
+                    let headers = prefixed_headers;
+                },
+            ),
+            None => (quote! {}, quote! {}),
+        };
+
+        let bearer_token = if self.enable_bearer_token {
+            quote! {
+                metadata_map.remove("token");
+                if let Some(authorization) = authorization {
+                    if let Some(value) = authorization.to_str().ok().and_then(|value| value.parse().ok()) {
+                        metadata_map.insert("authorization", value);
+                    }
+                    let token = authorization
+                        .to_str()
+                        .ok()
+                        .and_then(|value| value.split_once(' '))
+                        .filter(|(scheme, _)| scheme.eq_ignore_ascii_case("bearer"))
+                        .map(|(_, token)| token.trim())
+                        .filter(|token| !token.is_empty());
+                    if let Some(value) = token.and_then(|token| token.parse().ok()) {
+                        metadata_map.insert("token", value);
+                    }
+                }
+            }
+        } else {
+            quote! {}
+        };
+        let capture_authorization = if self.enable_bearer_token {
+            quote! {
+                let authorization = headers.get(::http::header::AUTHORIZATION).cloned();
+            }
+        } else {
+            quote! {}
+        };
+
+        let merge_duplicates = match self.duplicate_header_policy {
+            DuplicateHeaderPolicy::KeepAll => quote! {},
+            DuplicateHeaderPolicy::KeepFirst => quote! {
+                // Only the first value of every name comes with the name
+                let mut merged_headers = ::http::header::HeaderMap::with_capacity(headers.keys_len());
+                for (name, value) in headers {
+                    if let Some(name) = name {
+                        merged_headers.insert(name, value);
+                    }
+                }
+                let headers = merged_headers;
+            },
+            DuplicateHeaderPolicy::Join => quote! {
+                let mut merged_headers = ::http::header::HeaderMap::with_capacity(headers.keys_len());
+                for name in headers.keys() {
+                    if name.as_str().ends_with("-bin") {
+                        for value in headers.get_all(name) {
+                            merged_headers.append(name.clone(), value.clone());
+                        }
+                        continue;
+                    }
+                    let joined = headers
+                        .get_all(name)
+                        .iter()
+                        .map(|value| value.as_bytes())
+                        .collect::<Vec<_>>()
+                        .join(&b", "[..]);
+                    if let Ok(value) = ::http::header::HeaderValue::from_bytes(&joined) {
+                        merged_headers.insert(name.clone(), value);
+                    }
+                }
+                let headers = merged_headers;
+            },
+        };
+
+        let (capture_client_ip, client_ip) = match self.client_ip_metadata_key {
+            Some(ref key) => (
+                match self.trusted_proxy_hops {
+                    0 => quote! {
+                        let client_ip = peer_ip;
+                    },
+                    hops => {
+                        let skipped_hops = hops - 1;
+                        quote! {
+                            // Entries left of the ones the trusted proxies appended may come from
+                            // the client itself
+                            let client_ip = headers
+                                .get_all("x-forwarded-for")
+                                .iter()
+                                .filter_map(|value| value.to_str().ok())
+                                .flat_map(|value| value.split(','))
+                                .collect::<Vec<_>>()
+                                .into_iter()
+                                .rev()
+                                .nth(#skipped_hops)
+                                .and_then(|value| value.trim().parse::<::std::net::IpAddr>().ok());
+                        }
+                    }
+                },
+                quote! {
+                    metadata_map.remove(#key);
+                    if let Some(value) = client_ip.and_then(|client_ip| client_ip.to_string().parse().ok()) {
+                        metadata_map.insert(#key, value);
+                    }
+                },
+            ),
+            None => (quote! {}, quote! {}),
+        };
+
+        let metadata_limits = if self.max_metadata_size.is_some()
+            || self.max_header_value_len.is_some()
+        {
+            let max_metadata_size = match self.max_metadata_size {
+                Some(max_size) => quote! { Some(#max_size) },
+                None => quote! { None::<usize> },
+            };
+            let max_header_value_len = match self.max_header_value_len {
+                Some(max_len) => quote! { Some(#max_len) },
+                None => quote! { None::<usize> },
+            };
+            quote! {
+                let max_metadata_size: Option<usize> = #max_metadata_size;
+                let max_header_value_len: Option<usize> = #max_header_value_len;
+                let mut metadata_size = 0;
+                let mut violation = None;
+                for (name, value) in headers.iter() {
+                    metadata_size += name.as_str().len() + value.len();
+                    if max_header_value_len.is_some_and(|max_len| value.len() > max_len) {
+                        violation = Some(format!("Header `{}` exceeds the maximum value length", name));
+                        break;
+                    }
+                }
+                if violation.is_none() && max_metadata_size.is_some_and(|max_size| metadata_size > max_size) {
+                    violation = Some("Request metadata exceeds the maximum total size".to_string());
+                }
+                if let Some(message) = violation {
+                    let error_body = ErrorResponse {
+                        error: ErrorDetails::new(error_code(::tonic::Code::ResourceExhausted), message)
+                    };
+
+                    return Err((::http::StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE, error_body));
+                }
+            }
+        } else {
+            quote! {}
+        };
+
         quote! {
-            // Auto-generated enum deserializer module for package: #target_package
-            // This file contains field-specific utilities for serializing and deserializing protobuf enums from string values in JSON
+            // Connection-level headers that describe the HTTP transport rather than the call
+            #[allow(dead_code)]
+            const TRANSPORT_HEADERS: &[&str] = &[
+                "host",
+                "connection",
+                "content-length",
+                "transfer-encoding",
+                "keep-alive",
+                "proxy-connection",
+                "upgrade",
+                "te",
+                "trailer",
+            ];
+
+            // Headers owned by the HTTP bridge itself or by the gRPC protocol, which response
+            // metadata must not override
+            #[allow(dead_code)]
+            const RESERVED_RESPONSE_HEADERS: &[&str] = &[
+                "content-type",
+                "content-encoding",
+                "grpc-status",
+                "grpc-message",
+                "grpc-status-details-bin",
+                "grpc-encoding",
+                "grpc-accept-encoding",
+            ];
 
-            pub mod enum_deserializer {
-                use super::*;
+            #[allow(dead_code)]
+            fn strip_transport_headers(headers: &mut ::http::header::HeaderMap) {
+                for name in TRANSPORT_HEADERS {
+                    headers.remove(*name);
+                }
+            }
 
-                #field_functions_tokens
+            // Re-encode `-bin` metadata, which travels as base64 in HTTP headers, and
+            // return the keys whose values could not be decoded (those are dropped)
+            #[allow(dead_code)]
+            fn normalize_binary_metadata(metadata_map: &mut ::tonic::metadata::MetadataMap) -> Vec<String> {
+                let mut binary_entries = Vec::new();
+                for entry in metadata_map.iter() {
+                    if let ::tonic::metadata::KeyAndValueRef::Binary(key, value) = entry {
+                        binary_entries.push((key.clone(), value.to_bytes()));
+                    }
+                }
+
+                for (key, _) in &binary_entries {
+                    metadata_map.remove_bin(key);
+                }
+
+                let mut invalid_keys = Vec::new();
+                for (key, bytes) in binary_entries {
+                    match bytes {
+                        Ok(bytes) => {
+                            metadata_map.append_bin(key, ::tonic::metadata::MetadataValue::from_bytes(&bytes));
+                        }
+                        Err(_) => invalid_keys.push(key.to_string()),
+                    }
+                }
+
+                invalid_keys
             }
-        }
-        .to_string()
-    }
 
-    /// Extract enum fields with their types from a specific package
-    fn extract_package_enum_fields_static(
-        file_descriptor_set: &FileDescriptorSet,
-        target_package: &str,
-    ) -> Vec<(String, String, String)> {
-        // (field_id, enum_type, field_label)
-        let mut enum_fields = Vec::new();
+            // Turns request headers into metadata, or into the HTTP status and JSON error of
+            // the rejected headers. `peer_ip` is the client address of the connection.
+            #[allow(dead_code, unused_variables)]
+            fn metadata_from_headers(
+                mut headers: ::http::header::HeaderMap,
+                peer_ip: Option<::std::net::IpAddr>,
+            ) -> Result<::tonic::metadata::MetadataMap, (::http::StatusCode, ErrorResponse)> {
+                strip_transport_headers(&mut headers);
+                #capture_authorization
+                #capture_client_ip
+                #merge_duplicates
+                #request_prefix_filter
+                #metadata_limits
+                let mut metadata_map = ::tonic::metadata::MetadataMap::from_headers(headers);
+
+                let invalid_keys = normalize_binary_metadata(&mut metadata_map);
+                if !invalid_keys.is_empty() {
+                    let error_body = ErrorResponse {
+                        error: ErrorDetails::new(error_code(::tonic::Code::InvalidArgument), format!("Binary metadata must be base64 encoded: {}", invalid_keys.join(", ")))
+                    };
+
+                    return Err((::http::StatusCode::BAD_REQUEST, error_body));
+                }
+                #bearer_token
+                #client_ip
 
-        for file in &file_descriptor_set.file {
-            let package = file.package();
+                Ok(metadata_map)
+            }
 
-            // Only process files that match the target package
-            if package != target_package {
-                continue;
+            #[allow(dead_code)]
+            fn response_headers_from_metadata(mut metadata_map: ::tonic::metadata::MetadataMap) -> ::http::header::HeaderMap {
+                normalize_binary_metadata(&mut metadata_map);
+
+                // Use dashes instead of underscores, which many proxies drop, and skip
+                // hop-by-hop and reserved names. Values are moved, not copied.
+                let metadata_headers = metadata_map.into_headers();
+                let mut headers = ::http::header::HeaderMap::with_capacity(metadata_headers.len());
+                let mut current_name = None;
+                for (name, value) in metadata_headers {
+                    if let Some(name) = name {
+                        let name = if name.as_str().contains('_') {
+                            ::http::header::HeaderName::from_bytes(name.as_str().replace('_', "-").as_bytes()).ok()
+                        } else {
+                            Some(name)
+                        };
+                        current_name = name.filter(|name| {
+                            !TRANSPORT_HEADERS.contains(&name.as_str())
+                                && !RESERVED_RESPONSE_HEADERS.contains(&name.as_str())
+                        });
+                    }
+                    if let Some(ref name) = current_name {
+                        headers.append(name.clone(), value);
+                    }
+                }
+                #merge_duplicates
+                #response_prefix
+                headers
             }
 
-            // Process all message types in the file
-            for message in &file.message_type {
-                Self::extract_enum_fields_from_message_static(message, &mut enum_fields);
+            // HTTP status answering the gRPC `code`
+            #[allow(dead_code)]
+            fn http_status_of(code: ::tonic::Code) -> ::http::StatusCode {
+                match code {
+                    ::tonic::Code::Ok => ::http::StatusCode::OK,
+                    ::tonic::Code::InvalidArgument => ::http::StatusCode::BAD_REQUEST,
+                    ::tonic::Code::NotFound => ::http::StatusCode::NOT_FOUND,
+                    ::tonic::Code::AlreadyExists | ::tonic::Code::Aborted => ::http::StatusCode::CONFLICT,
+                    ::tonic::Code::PermissionDenied => ::http::StatusCode::FORBIDDEN,
+                    ::tonic::Code::Unauthenticated => ::http::StatusCode::UNAUTHORIZED,
+                    ::tonic::Code::ResourceExhausted => ::http::StatusCode::TOO_MANY_REQUESTS,
+                    ::tonic::Code::FailedPrecondition => ::http::StatusCode::PRECONDITION_FAILED,
+                    ::tonic::Code::Unimplemented => ::http::StatusCode::NOT_IMPLEMENTED,
+                    ::tonic::Code::Unavailable => ::http::StatusCode::SERVICE_UNAVAILABLE,
+                    ::tonic::Code::DeadlineExceeded | ::tonic::Code::Cancelled => ::http::StatusCode::REQUEST_TIMEOUT,
+                    ::tonic::Code::OutOfRange => ::http::StatusCode::RANGE_NOT_SATISFIABLE,
+                    _ => ::http::StatusCode::INTERNAL_SERVER_ERROR,
+                }
             }
         }
-
-        enum_fields
     }
 
-    /// Recursively extract enum fields from a message
-    fn extract_enum_fields_from_message_static(
-        message: &DescriptorProto,
-        enum_fields: &mut Vec<(String, String, String)>,
-    ) {
-        Self::extract_enum_fields_from_message_with_path_static(message, enum_fields, "");
-    }
+    // Error handling and header <-> metadata helpers used by the generated handlers
+    fn axum_package_helpers(&self, buf: &mut String) {
+        if let Some(bytes) = self.max_body_size {
+            let body_limit = quote! {
+                // Replaces axum's plain text rejection of oversized bodies with the JSON error shape
+                #[allow(dead_code)]
+                async fn payload_too_large_as_json(response: ::axum::response::Response) -> ::axum::response::Response {
+                    use ::axum::response::IntoResponse;
+
+                    let is_json = response
+                        .headers()
+                        .get(::http::header::CONTENT_TYPE)
+                        .is_some_and(|content_type| content_type.as_bytes().starts_with(b"application/json"));
+                    if response.status() != ::http::StatusCode::PAYLOAD_TOO_LARGE || is_json {
+                        return response;
+                    }
 
-    /// Helper function to extract enum fields with full message path tracking
-    fn extract_enum_fields_from_message_with_path_static(
-        message: &DescriptorProto,
-        enum_fields: &mut Vec<(String, String, String)>,
-        message_path: &str,
-    ) {
-        let message_name = message.name();
-        let current_path = if message_path.is_empty() {
-            message_name.to_snake_case()
+                    let error_body = ErrorResponse {
+                        error: ErrorDetails::new(error_code(::tonic::Code::ResourceExhausted), format!("Request body exceeds the limit of {} bytes", #bytes))
+                    };
+
+                    (::http::StatusCode::PAYLOAD_TOO_LARGE, ::axum::Json(error_body)).into_response()
+                }
+            };
+
+            buf.push('\n');
+            buf.push_str(&body_limit.to_string());
+        }
+
+        if self.enable_not_found_fallback {
+            let error_details = quote! {
+                ErrorDetails::new("NOT_FOUND", format!("No method is mapped to `{}`", uri.path()))
+            };
+            let error_details = if self.list_known_paths {
+                let known_paths = &self.package_route_paths;
+                quote! {
+                    ErrorDetails {
+                        known_paths: Some(vec![#(#known_paths.to_string()),*]),
+                        ..#error_details
+                    }
+                }
+            } else {
+                error_details
+            };
+            // The layers the generated routers apply to their own routing errors
+            let request_ids = self.enable_request_ids.then(|| {
+                quote! { let route = route.layer(::axum::middleware::from_fn(ensure_request_id)); }
+            });
+            let response_envelope = self.enable_response_envelope.then(|| {
+                quote! { let route = route.layer(::axum::middleware::from_fn(envelope_response)); }
+            });
+            let not_found = quote! {
+                // Answers requests for unknown paths with the JSON error shape
+                #[allow(dead_code)]
+                async fn not_found(uri: ::http::Uri) -> ::axum::response::Response {
+                    use ::axum::response::IntoResponse;
+
+                    let error_body = ErrorResponse {
+                        error: #error_details
+                    };
+
+                    (::http::StatusCode::NOT_FOUND, ::axum::Json(error_body)).into_response()
+                }
+
+                /// JSON `404 Not Found` fallback for the routers of this package. Attach it
+                /// once to the router combining them, e.g.
+                /// `app.fallback_service(not_found_fallback())`.
+                #[allow(dead_code)]
+                pub fn not_found_fallback() -> ::axum::routing::MethodRouter {
+                    let route = ::axum::routing::any(not_found);
+                    #request_ids
+                    #response_envelope
+                    route
+                }
+            };
+
+            buf.push('\n');
+            buf.push_str(&not_found.to_string());
+        }
+
+        // Only packages with GET routes answer with ETags
+        if self.package_has_get_routes {
+            let conditional_get = quote! {
+                // Adds a strong ETag over the body of a successful GET response, answering
+                // `304 Not Modified` when it matches `If-None-Match`
+                #[allow(dead_code)]
+                async fn conditional_get_response(
+                    response: ::axum::response::Response,
+                    if_none_match: Option<::http::HeaderValue>,
+                ) -> ::axum::response::Response {
+                    if !response.status().is_success() {
+                        return response;
+                    }
+
+                    let (mut parts, body) = response.into_parts();
+                    let body = match ::axum::body::to_bytes(body, usize::MAX).await {
+                        Ok(body) => body,
+                        Err(_) => {
+                            parts.status = ::http::StatusCode::INTERNAL_SERVER_ERROR;
+                            return ::axum::response::Response::from_parts(parts, ::axum::body::Body::empty());
+                        }
+                    };
+
+                    // 64-bit FNV-1a, stable across builds and replicas
+                    let hash = body.iter().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+                        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+                    });
+                    let etag = format!("\"{:016x}\"", hash);
+
+                    let matches = if_none_match
+                        .as_ref()
+                        .and_then(|value| value.to_str().ok())
+                        .is_some_and(|value| {
+                            value.split(',').map(str::trim).any(|candidate| {
+                                candidate == "*" || candidate.trim_start_matches("W/") == etag
+                            })
+                        });
+                    if let Ok(etag) = ::http::HeaderValue::from_str(&etag) {
+                        parts.headers.insert(::http::header::ETAG, etag);
+                    }
+                    if matches {
+                        parts.status = ::http::StatusCode::NOT_MODIFIED;
+                        parts.headers.remove(::http::header::CONTENT_TYPE);
+                        parts.headers.remove(::http::header::CONTENT_LENGTH);
+                        return ::axum::response::Response::from_parts(parts, ::axum::body::Body::empty());
+                    }
+
+                    ::axum::response::Response::from_parts(parts, ::axum::body::Body::from(body))
+                }
+            };
+
+            buf.push('\n');
+            buf.push_str(&conditional_get.to_string());
+        }
+
+        if self.enable_gzip_requests {
+            let max_body_size = self.max_body_size.unwrap_or(2 * 1024 * 1024);
+            let decompression = quote! {
+                // Decompresses `Content-Encoding: gzip` request bodies for the JSON extractors
+                #[allow(dead_code)]
+                async fn decompress_gzip_request(
+                    request: ::axum::extract::Request,
+                ) -> Result<::axum::extract::Request, ::axum::response::Response> {
+                    use ::axum::response::IntoResponse;
+                    use ::std::io::Read;
+
+                    const MAX_BODY_SIZE: usize = #max_body_size;
+
+                    let is_gzip = request
+                        .headers()
+                        .get(::http::header::CONTENT_ENCODING)
+                        .is_some_and(|encoding| encoding.as_bytes().eq_ignore_ascii_case(b"gzip"));
+                    if !is_gzip {
+                        return Ok(request);
+                    }
+
+                    let error = |status: ::http::StatusCode, code: ::tonic::Code, message: String| {
+                        let error_body = ErrorResponse {
+                            error: ErrorDetails::new(error_code(code), message)
+                        };
+                        (status, ::axum::Json(error_body)).into_response()
+                    };
+                    let too_large = || {
+                        error(
+                            ::http::StatusCode::PAYLOAD_TOO_LARGE,
+                            ::tonic::Code::ResourceExhausted,
+                            format!("Request body exceeds the limit of {} bytes", MAX_BODY_SIZE),
+                        )
+                    };
+
+                    let (mut parts, body) = request.into_parts();
+                    let compressed = ::axum::body::to_bytes(body, MAX_BODY_SIZE)
+                        .await
+                        .map_err(|_| too_large())?;
+
+                    let mut decompressed = Vec::new();
+                    ::flate2::read::GzDecoder::new(compressed.as_ref())
+                        .take(MAX_BODY_SIZE as u64 + 1)
+                        .read_to_end(&mut decompressed)
+                        .map_err(|err| {
+                            error(
+                                ::http::StatusCode::BAD_REQUEST,
+                                ::tonic::Code::InvalidArgument,
+                                format!("Failed to decompress the gzip request body: {}", err),
+                            )
+                        })?;
+                    if decompressed.len() > MAX_BODY_SIZE {
+                        return Err(too_large());
+                    }
+
+                    parts.headers.remove(::http::header::CONTENT_ENCODING);
+                    parts.headers.remove(::http::header::CONTENT_LENGTH);
+                    Ok(::axum::extract::Request::from_parts(parts, ::axum::body::Body::from(decompressed)))
+                }
+            };
+
+            buf.push('\n');
+            buf.push_str(&decompression.to_string());
+        }
+
+        if self.concurrency_limit.is_some() || !self.method_concurrency_limits.is_empty() {
+            let concurrency_limit_exceeded = quote! {
+                // Response of requests shed by a saturated concurrency limit
+                #[allow(dead_code)]
+                fn concurrency_limit_exceeded() -> ::axum::response::Response {
+                    use ::axum::response::IntoResponse;
+
+                    let error_body = ErrorResponse {
+                        error: ErrorDetails::new(error_code(::tonic::Code::Unavailable), "Too many concurrent requests, try again later")
+                    };
+
+                    (::http::StatusCode::SERVICE_UNAVAILABLE, ::axum::Json(error_body)).into_response()
+                }
+            };
+
+            buf.push('\n');
+            buf.push_str(&concurrency_limit_exceeded.to_string());
+        }
+
+        #[cfg(feature = "simd-json")]
+        {
+            let simd_json_parse = quote! {
+                // Parses a JSON body with simd-json, in the buffer of the body when nothing else
+                // holds it, and in a copy otherwise
+                #[allow(dead_code)]
+                fn parse_json_body<B: serde::de::DeserializeOwned>(
+                    body: ::axum::body::Bytes,
+                ) -> Result<B, ::simd_json::Error> {
+                    let mut buffer = Vec::from(body);
+                    ::simd_json::serde::from_slice::<B>(&mut buffer)
+                }
+
+                // Status of a body simd-json rejected, `422` when serde rejected its values as
+                // with axum's `JsonDataError`, and `400` otherwise
+                #[allow(dead_code)]
+                fn simd_json_status(err: &::simd_json::Error) -> ::http::StatusCode {
+                    match err.error() {
+                        ::simd_json::ErrorType::Serde(_) => ::http::StatusCode::UNPROCESSABLE_ENTITY,
+                        _ => ::http::StatusCode::BAD_REQUEST,
+                    }
+                }
+
+                // Response to a body simd-json rejected, with the messages of axum's rejections
+                #[allow(dead_code)]
+                fn simd_json_rejection(err: ::simd_json::Error) -> ::axum::response::Response {
+                    use ::axum::response::IntoResponse;
+
+                    let status = simd_json_status(&err);
+                    let message = if status == ::http::StatusCode::UNPROCESSABLE_ENTITY {
+                        format!("Failed to deserialize the JSON body into the target type: {}", err)
+                    } else {
+                        format!("Failed to parse the request body as JSON: {}", err)
+                    };
+                    (status, message).into_response()
+                }
+            };
+
+            buf.push('\n');
+            buf.push_str(&simd_json_parse.to_string());
+        }
+
+        // Add route introspection types returned by the `*_routes()` functions
+        let route_info = quote! {
+            /// How a route's RPC streams messages.
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+            #[allow(dead_code)]
+            pub enum RouteStreaming {
+                Unary,
+                ClientStreaming,
+                ServerStreaming,
+                BidiStreaming,
+            }
+
+            /// An HTTP route and the RPC it forwards to, with protobuf type names.
+            #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+            #[allow(dead_code)]
+            pub struct RouteInfo {
+                pub path: &'static str,
+                pub http_method: &'static str,
+                pub method_name: &'static str,
+                pub request_type: &'static str,
+                pub response_type: &'static str,
+                pub streaming: RouteStreaming,
+            }
+        };
+
+        buf.push('\n');
+        buf.push_str(&route_info.to_string());
+
+        let peer_ip = if self.client_ip_metadata_key.is_some() {
+            quote! {
+                extensions
+                    .get::<::axum::extract::ConnectInfo<::std::net::SocketAddr>>()
+                    .map(|connect_info| connect_info.0.ip())
+            }
         } else {
-            format!("{}_{}", message_path, message_name.to_snake_case())
+            quote! { None }
         };
+        let request_metadata = quote! {
+            // Turns the request headers into metadata, answering rejected headers with their
+            // JSON error
+            #[allow(dead_code, unused_variables)]
+            fn request_metadata_from_headers(headers: ::http::header::HeaderMap, extensions: &::http::Extensions) -> Result<::tonic::metadata::MetadataMap, ::axum::response::Response> {
+                use ::axum::response::IntoResponse;
 
-        // Process all fields in the message
-        for field in &message.field {
-            if field.r#type() == Type::Enum {
-                let field_id = format!("{}_{}", current_path, field.name().to_snake_case());
-                let enum_type = field.type_name().trim_start_matches('.');
+                metadata_from_headers(headers, #peer_ip)
+                    .map_err(|(status, error_body)| (status, ::axum::Json(error_body)).into_response())
+            }
+        };
 
-                let enum_path = Self::resolve_enum_path(enum_type);
+        buf.push('\n');
+        buf.push_str(&request_metadata.to_string());
+
+        // Interceptors see the request without its body, like in `tonic::service::InterceptedService`
+        let interceptor_helpers = quote! {
+            /// A `tonic::service::Interceptor` installed as a request extension, run before
+            /// the checks of the handler and the service
+            #[derive(Clone)]
+            pub struct HttpInterceptor(
+                ::std::sync::Arc<dyn Fn(::tonic::Request<()>) -> Result<::tonic::Request<()>, ::tonic::Status> + Send + Sync>,
+            );
 
-                let field_label = match field.label() {
-                    Label::Optional => {
-                        if field.proto3_optional() {
-                            "Option"
-                        } else {
-                            "Single"
+            impl HttpInterceptor {
+                /// Wraps `interceptor` to install it as a request extension
+                pub fn new<I>(interceptor: I) -> Self
+                where
+                    I: ::tonic::service::Interceptor + Clone + Send + Sync + 'static,
+                {
+                    Self(::std::sync::Arc::new(move |request| {
+                        ::tonic::service::Interceptor::call(&mut interceptor.clone(), request)
+                    }))
+                }
+            }
+
+            impl ::std::fmt::Debug for HttpInterceptor {
+                fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                    f.debug_struct("HttpInterceptor").finish_non_exhaustive()
+                }
+            }
+
+            // Runs the installed interceptor on the request metadata, before any check of the handler
+            #[allow(dead_code)]
+            fn intercept_http_request(
+                metadata: ::tonic::metadata::MetadataMap,
+                extensions: ::http::Extensions,
+            ) -> Result<(::tonic::metadata::MetadataMap, ::http::Extensions), ::tonic::Status> {
+                let Some(interceptor) = extensions.get::<HttpInterceptor>().cloned() else {
+                    return Ok((metadata, extensions));
+                };
+                let (metadata, extensions, ()) = (interceptor.0)(::tonic::Request::from_parts(metadata, extensions, ()))?.into_parts();
+                Ok((metadata, extensions))
+            }
+
+            // gRPC code of an HTTP `status`, for responses not built from a `tonic::Status`
+            #[allow(dead_code)]
+            fn grpc_code_of(status: ::http::StatusCode) -> ::tonic::Code {
+                match status {
+                    status if status.is_success() || status.is_redirection() => ::tonic::Code::Ok,
+                    ::http::StatusCode::UNAUTHORIZED => ::tonic::Code::Unauthenticated,
+                    ::http::StatusCode::FORBIDDEN => ::tonic::Code::PermissionDenied,
+                    ::http::StatusCode::NOT_FOUND => ::tonic::Code::NotFound,
+                    ::http::StatusCode::CONFLICT => ::tonic::Code::Aborted,
+                    ::http::StatusCode::PRECONDITION_FAILED => ::tonic::Code::FailedPrecondition,
+                    ::http::StatusCode::TOO_MANY_REQUESTS => ::tonic::Code::ResourceExhausted,
+                    ::http::StatusCode::REQUEST_TIMEOUT => ::tonic::Code::DeadlineExceeded,
+                    ::http::StatusCode::RANGE_NOT_SATISFIABLE => ::tonic::Code::OutOfRange,
+                    ::http::StatusCode::NOT_IMPLEMENTED => ::tonic::Code::Unimplemented,
+                    ::http::StatusCode::SERVICE_UNAVAILABLE => ::tonic::Code::Unavailable,
+                    status if status.is_client_error() => ::tonic::Code::InvalidArgument,
+                    _ => ::tonic::Code::Internal,
+                }
+            }
+
+            // Answers with the JSON error of a `status` returned before the service is called
+            #[allow(dead_code)]
+            fn status_error_response(status: &::tonic::Status) -> ::axum::response::Response {
+                use ::axum::response::IntoResponse;
+
+                let error_body = ErrorResponse {
+                    error: ErrorDetails::new(error_code(status.code()), status.message())
+                };
+
+                (http_status_of(status.code()), ::axum::Json(error_body)).into_response()
+            }
+        };
+
+        buf.push('\n');
+        buf.push_str(&interceptor_helpers.to_string());
+
+        if self.enable_message_hooks {
+            let message_hook_types = quote! {
+                /// Transforms the JSON of request and response messages
+                #[::tonic::async_trait]
+                pub trait MessageHooks: Send + Sync + 'static {
+                    /// Modifies the `request` message of the method at the gRPC `path` before the service is called
+                    async fn before_request(&self, path: &str, request: &mut ::serde_json::Value) {
+                        let _ = (path, request);
+                    }
+
+                    /// Modifies the `response` message of the method at the gRPC `path` before it is encoded
+                    async fn after_response(&self, path: &str, response: &mut ::serde_json::Value) {
+                        let _ = (path, response);
+                    }
+                }
+
+                /// `MessageHooks` installed as a request extension
+                #[derive(Clone)]
+                pub struct MessageHooking(pub ::std::sync::Arc<dyn MessageHooks>);
+
+                impl MessageHooking {
+                    /// Wraps `hooks` to install them as a request extension
+                    pub fn new(hooks: impl MessageHooks) -> Self {
+                        Self(::std::sync::Arc::new(hooks))
+                    }
+                }
+
+                impl ::std::fmt::Debug for MessageHooking {
+                    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                        f.debug_struct("MessageHooking").finish_non_exhaustive()
+                    }
+                }
+
+                #[allow(dead_code)]
+                async fn hook_request_message<M>(hooks: &MessageHooking, path: &str, message: M) -> Result<M, ::serde_json::Error>
+                where
+                    M: serde::Serialize + serde::de::DeserializeOwned,
+                {
+                    let mut value = ::serde_json::to_value(&message)?;
+                    hooks.0.before_request(path, &mut value).await;
+                    ::serde_json::from_value(value)
+                }
+
+                // The hooked JSON is sent as is, keeping the fields hooks add to the message
+                #[allow(dead_code)]
+                async fn hook_response_message<M: serde::Serialize>(hooks: &MessageHooking, path: &str, message: M) -> Result<::serde_json::Value, ::serde_json::Error> {
+                    let mut value = ::serde_json::to_value(&message)?;
+                    hooks.0.after_response(path, &mut value).await;
+                    Ok(value)
+                }
+            };
+
+            buf.push('\n');
+            buf.push_str(&message_hook_types.to_string());
+        }
+
+        if self.enable_rate_limiting {
+            let rate_limit_types = quote! {
+                /// Rejection of a request by a `RateLimiter`
+                #[derive(Debug, Clone, Default)]
+                pub struct RateLimited {
+                    /// Delay after which the request may be retried, sent as `Retry-After`
+                    pub retry_after: Option<::std::time::Duration>,
+                    /// Message of the JSON error
+                    pub message: Option<String>,
+                }
+
+                #[allow(dead_code)]
+                impl RateLimited {
+                    /// A rejection to retry after `delay`
+                    pub fn retry_after(delay: ::std::time::Duration) -> Self {
+                        Self {
+                            retry_after: Some(delay),
+                            message: None,
                         }
                     }
-                    Label::Required => "Single",
-                    Label::Repeated => "Repeated",
-                };
+                }
+
+                /// Decides whether requests exceed their quota
+                #[::tonic::async_trait]
+                pub trait RateLimiter: Send + Sync + 'static {
+                    /// Checks the request `metadata` against the quota of the method at the gRPC `path`
+                    async fn check(&self, path: &str, metadata: &::tonic::metadata::MetadataMap) -> Result<(), RateLimited>;
+                }
+
+                /// A `RateLimiter` installed as a request extension
+                #[derive(Clone)]
+                pub struct RateLimiting(pub ::std::sync::Arc<dyn RateLimiter>);
+
+                #[allow(dead_code)]
+                impl RateLimiting {
+                    /// Wraps `limiter` to install it as a request extension
+                    pub fn new(limiter: impl RateLimiter) -> Self {
+                        Self(::std::sync::Arc::new(limiter))
+                    }
+                }
+
+                impl ::std::fmt::Debug for RateLimiting {
+                    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                        f.debug_struct("RateLimiting").finish_non_exhaustive()
+                    }
+                }
+
+                // Answers with `429 Too Many Requests`, rounding the retry delay up to seconds
+                #[allow(dead_code)]
+                fn rate_limited_response(limited: RateLimited) -> ::axum::response::Response {
+                    use ::axum::response::IntoResponse;
+
+                    let error_body = ErrorResponse {
+                        error: ErrorDetails::new(error_code(::tonic::Code::ResourceExhausted), limited.message.unwrap_or_else(|| "Rate limit exceeded".to_string()))
+                    };
+                    let mut response = (::http::StatusCode::TOO_MANY_REQUESTS, ::axum::Json(error_body)).into_response();
+                    if let Some(delay) = limited.retry_after {
+                        let seconds = delay.as_secs() + u64::from(delay.subsec_nanos() > 0);
+                        response.headers_mut().insert(::http::header::RETRY_AFTER, ::http::HeaderValue::from(seconds));
+                    }
+                    response
+                }
+            };
 
-                enum_fields.push((field_id, enum_path, field_label.to_string()));
-            }
+            buf.push('\n');
+            buf.push_str(&rate_limit_types.to_string());
         }
 
-        // Recursively process nested message types
-        for nested_message in &message.nested_type {
-            Self::extract_enum_fields_from_message_with_path_static(
-                nested_message,
-                enum_fields,
-                &current_path,
-            );
-        }
-    }
+        if self.enable_authorizer {
+            let authorizer_types = quote! {
+                /// Access level required by a method
+                #[allow(dead_code)]
+                #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+                pub enum AccessLevel {
+                    /// Callable by authenticated callers
+                    Authenticated,
+                    /// Callable by administrators
+                    Admin,
+                }
 
-    /// Resolve the correct Rust path for an enum type from its protobuf type name
-    fn resolve_enum_path(enum_type: &str) -> String {
-        if !enum_type.contains('.') {
-            return enum_type.to_string();
-        }
+                /// Rejection of a request by an `Authorizer`
+                #[allow(dead_code)]
+                #[derive(Debug, Clone)]
+                pub enum AuthorizationError {
+                    /// The caller is unknown, answered with `401 Unauthorized`
+                    Unauthenticated(String),
+                    /// The caller lacks the access level, answered with `403 Forbidden`
+                    Forbidden(String),
+                }
 
-        let parts: Vec<&str> = enum_type.split('.').collect();
+                /// Decides whether requests may call methods requiring an access level
+                #[allow(dead_code)]
+                #[::tonic::async_trait]
+                pub trait Authorizer: Send + Sync + 'static {
+                    /// Checks the request `metadata` against the `level` of the method at the gRPC `path`
+                    async fn authorize(&self, path: &str, level: AccessLevel, metadata: &::tonic::metadata::MetadataMap) -> Result<(), AuthorizationError>;
+                }
 
-        match parts.len() {
-            0 | 1 => parts.last().unwrap_or(&"UnknownEnum").to_string(),
-            2 => {
-                // Package-level enum like "package.EnumName"
-                // Use just the enum name since it's in the same crate
-                parts[1].to_string()
-            }
-            _ => {
-                // Three or more parts - need to determine the structure
-                let enum_name = parts[parts.len() - 1];
+                /// An `Authorizer` installed as a request extension
+                #[allow(dead_code)]
+                #[derive(Clone)]
+                pub struct Authorization(pub ::std::sync::Arc<dyn Authorizer>);
 
-                // Look for message parts (PascalCase) vs package parts (lowercase/version)
-                let mut message_parts = Vec::new();
-                let start_idx = 1; // Skip the package name
+                #[allow(dead_code)]
+                impl Authorization {
+                    /// Wraps `authorizer` to install it as a request extension
+                    pub fn new(authorizer: impl Authorizer) -> Self {
+                        Self(::std::sync::Arc::new(authorizer))
+                    }
+                }
 
-                for &part in &parts[start_idx..parts.len() - 1] {
-                    if Self::is_message_name(part) {
-                        message_parts.push(part.to_snake_case());
+                impl ::std::fmt::Debug for Authorization {
+                    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                        f.debug_struct("Authorization").finish_non_exhaustive()
                     }
                 }
 
-                if message_parts.is_empty() {
-                    // No message parts found, treat as package-level enum
-                    enum_name.to_string()
-                } else {
-                    // Build the nested module path
-                    format!("{}::{}", message_parts.join("::"), enum_name)
+                // Answers with the status and JSON error of an authorization failure
+                #[allow(dead_code)]
+                fn authorization_error_response(err: AuthorizationError) -> ::axum::response::Response {
+                    use ::axum::response::IntoResponse;
+
+                    let (status, code, message) = match err {
+                        AuthorizationError::Unauthenticated(message) => (::http::StatusCode::UNAUTHORIZED, ::tonic::Code::Unauthenticated, message),
+                        AuthorizationError::Forbidden(message) => (::http::StatusCode::FORBIDDEN, ::tonic::Code::PermissionDenied, message),
+                    };
+                    let error_body = ErrorResponse {
+                        error: ErrorDetails::new(error_code(code), message)
+                    };
+
+                    (status, ::axum::Json(error_body)).into_response()
                 }
-            }
+            };
+
+            buf.push('\n');
+            buf.push_str(&authorizer_types.to_string());
         }
-    }
 
-    /// Check if a name looks like a protobuf message name (PascalCase)
-    fn is_message_name(name: &str) -> bool {
-        name.chars().next().is_some_and(|c| c.is_uppercase())
-    }
+        if self.enable_request_ids {
+            let (read_body, rebuild_body) = self.response_body_tokens();
+            let request_id_middleware = quote! {
+                // Ensures the request has an `x-request-id`, echoing it on the response and in
+                // JSON error bodies
+                #[allow(dead_code)]
+                async fn ensure_request_id(mut request: ::axum::extract::Request, next: ::axum::middleware::Next) -> ::axum::response::Response {
+                    let request_id = match request.headers().get("x-request-id") {
+                        Some(request_id) if !request_id.is_empty() => request_id.clone(),
+                        _ => {
+                            let request_id = ::http::HeaderValue::from_str(&::uuid::Uuid::new_v4().to_string())
+                                .expect("UUIDs are valid header values");
+                            request.headers_mut().insert("x-request-id", request_id.clone());
+                            request_id
+                        }
+                    };
 
-    /// Generate field-specific enum serialization/deserialization functions
-    fn generate_field_specific_enum_functions_static(
-        enum_fields: &[(String, String, String)],
-    ) -> String {
-        let mut functions = String::new();
+                    let mut response = next.run(request).await;
+                    response.headers_mut().entry("x-request-id").or_insert(request_id.clone());
 
-        for (field_id, enum_name, field_label) in enum_fields {
-            let enum_ident: proc_macro2::TokenStream = enum_name
-                .parse()
-                .unwrap_or_else(|e| panic!("Invalid enum type path '{enum_name}': {e}"));
+                    let is_json = response
+                        .headers()
+                        .get(::http::header::CONTENT_TYPE)
+                        .is_some_and(|content_type| content_type.as_bytes().starts_with(b"application/json"));
+                    if !(response.status().is_client_error() || response.status().is_server_error()) || !is_json {
+                        return response;
+                    }
 
-            let function_code = match field_label.as_str() {
-                "Single" => Self::generate_single_enum_functions(field_id, &enum_ident),
-                "Option" => Self::generate_option_enum_functions(field_id, &enum_ident),
-                "Repeated" => Self::generate_repeated_enum_functions(field_id, &enum_ident),
-                _ => String::new(),
+                    let (mut parts, body) = response.into_parts();
+                    #read_body
+                    let body = match ::serde_json::from_slice::<ErrorResponse>(&body) {
+                        Ok(mut error_body) => {
+                            error_body.error.request_id = request_id.to_str().ok().map(str::to_string);
+                            parts.headers.remove(::http::header::CONTENT_LENGTH);
+                            ::serde_json::to_vec(&error_body).map(::axum::body::Bytes::from).unwrap_or(body)
+                        }
+                        Err(_) => body,
+                    };
+                    ::axum::response::Response::from_parts(parts, #rebuild_body)
+                }
             };
 
-            functions.push_str(&function_code);
+            buf.push('\n');
+            buf.push_str(&request_id_middleware.to_string());
         }
 
-        functions
-    }
+        if self.enable_content_type_check {
+            let missing_content_type = if self.allow_missing_content_type {
+                quote! {
+                    let mut request = request;
+                    request.headers_mut().insert(::http::header::CONTENT_TYPE, ::http::HeaderValue::from_static("application/json"));
+                    return Ok(request);
+                }
+            } else {
+                quote! {
+                    return Err(unsupported("Expected request with `Content-Type: application/json`".to_string()));
+                }
+            };
+            let content_type_check = quote! {
+                // Accepts JSON bodies in UTF-8, with any other media type parameters, answering
+                // other content types with a JSON 415. Requests without a body are left to the
+                // method router, which answers them with a 405 or a preflight response
+                #[allow(dead_code)]
+                async fn check_json_content_type(request: ::axum::extract::Request) -> Result<::axum::extract::Request, ::axum::response::Response> {
+                    use ::axum::response::IntoResponse;
 
-    /// Generate serializer/deserializer functions for a single enum field
-    fn generate_single_enum_functions(
-        field_id: &str,
-        enum_ident: &proc_macro2::TokenStream,
-    ) -> String {
-        let serialize_fn = quote::format_ident!("serialize_{}_as_string", field_id);
-        let deserialize_fn = quote::format_ident!("deserialize_{}_from_string", field_id);
+                    if matches!(*request.method(), ::http::Method::GET | ::http::Method::HEAD | ::http::Method::OPTIONS) {
+                        return Ok(request);
+                    }
 
-        quote! {
-            #[allow(dead_code)]
-            pub fn #serialize_fn<S>(value: &i32, serializer: S) -> Result<S::Ok, S::Error>
-            where
-                S: serde::Serializer,
-            {
-                use serde::Serialize;
-                if let Ok(enum_val) = #enum_ident::try_from(*value) {
-                    enum_val.as_str_name().serialize(serializer)
-                } else {
-                    value.serialize(serializer)
+                    let unsupported = |message: String| {
+                        let error_body = ErrorResponse {
+                            error: ErrorDetails::new(error_code(::tonic::Code::InvalidArgument), message)
+                        };
+                        (::http::StatusCode::UNSUPPORTED_MEDIA_TYPE, ::axum::Json(error_body)).into_response()
+                    };
+
+                    let Some(content_type) = request.headers().get(::http::header::CONTENT_TYPE) else {
+                        #missing_content_type
+                    };
+                    let Ok(content_type) = content_type.to_str() else {
+                        return Err(unsupported("Invalid `Content-Type` header".to_string()));
+                    };
+
+                    let mut parameters = content_type.split(';');
+                    let essence = parameters.next().unwrap_or_default().trim().to_ascii_lowercase();
+                    let is_json = essence == "application/json"
+                        || (essence.starts_with("application/") && essence.ends_with("+json"));
+                    if !is_json {
+                        return Err(unsupported(format!("Expected `Content-Type: application/json`, got `{}`", content_type)));
+                    }
+                    for parameter in parameters {
+                        let Some((name, value)) = parameter.split_once('=') else {
+                            continue;
+                        };
+                        let charset = value.trim().trim_matches('"');
+                        if name.trim().eq_ignore_ascii_case("charset") && !charset.eq_ignore_ascii_case("utf-8") {
+                            return Err(unsupported(format!("Unsupported charset `{}`, request bodies must be UTF-8", charset)));
+                        }
+                    }
+                    Ok(request)
                 }
-            }
+            };
 
-            #[allow(dead_code)]
-            pub fn #deserialize_fn<'de, D>(deserializer: D) -> Result<i32, D::Error>
-            where
-                D: serde::Deserializer<'de>,
-            {
-                use serde::Deserialize;
+            buf.push('\n');
+            buf.push_str(&content_type_check.to_string());
+        }
 
-                #[derive(Deserialize)]
-                #[serde(untagged)]
+        if self.route_path_case == RoutePathCase::Lowercase {
+            let lowercase_shim = quote! {
+                /// Lowercases the path of `request`, leaving the query as is, so mixed-case
+                /// requests reach the lowercase routes. Routing happens before the layers of
+                /// a router run, so wrap the router itself, e.g. with `tower::util::MapRequestLayer`.
                 #[allow(dead_code)]
-                enum EnumOrString {
-                    String(String),
-                    Int(i32),
+                pub fn lowercase_request_path<B>(mut request: ::http::Request<B>) -> ::http::Request<B> {
+                    let Some(path_and_query) = request.uri().path_and_query() else {
+                        return request;
+                    };
+                    let path_and_query = match path_and_query.query() {
+                        Some(query) => format!("{}?{}", path_and_query.path().to_ascii_lowercase(), query),
+                        None => path_and_query.path().to_ascii_lowercase(),
+                    };
+
+                    let mut parts = request.uri().clone().into_parts();
+                    parts.path_and_query = path_and_query.parse().ok();
+                    if let Ok(uri) = ::http::Uri::from_parts(parts) {
+                        *request.uri_mut() = uri;
+                    }
+                    request
                 }
+            };
 
-                match EnumOrString::deserialize(deserializer)? {
-                    EnumOrString::String(s) => {
-                        if let Some(enum_val) = #enum_ident::from_str_name(&s) {
-                            Ok(enum_val as i32)
-                        } else {
-                            Err(serde::de::Error::custom(format!("Unknown enum value for {}: {}", stringify!(#enum_ident), s)))
-                        }
-                    }
-                    EnumOrString::Int(i) => Ok(i),
+            buf.push('\n');
+            buf.push_str(&lowercase_shim.to_string());
+        }
+
+        if self.enable_preflight_responses {
+            let preflight_helpers = quote! {
+                // CORS headers of preflight responses, from the `CorsSpec` of the service
+                #[allow(dead_code)]
+                struct PreflightPolicy {
+                    origins: &'static [&'static str],
+                    any_origin: bool,
+                    headers: &'static str,
+                    any_header: bool,
+                    allow_credentials: bool,
+                    max_age: Option<u64>,
                 }
-            }
-        }.to_string()
-    }
 
-    /// Generate serializer/deserializer functions for an optional enum field
-    fn generate_option_enum_functions(
-        field_id: &str,
-        enum_ident: &proc_macro2::TokenStream,
-    ) -> String {
-        let serialize_fn = quote::format_ident!("serialize_option_{}_as_string", field_id);
-        let deserialize_fn = quote::format_ident!("deserialize_option_{}_from_string", field_id);
+                // Answers `OPTIONS` requests on a route served with `allow`, adding the CORS
+                // headers of `policy` to preflights from allowed origins
+                #[allow(dead_code)]
+                fn preflight_response(headers: &::http::header::HeaderMap, allow: &'static str, policy: Option<&PreflightPolicy>) -> ::axum::response::Response {
+                    use ::axum::response::IntoResponse;
+
+                    let allow = ::http::HeaderValue::from_str(&format!("{}, OPTIONS", allow))
+                        .expect("HTTP methods are valid header values");
+                    let mut response = (::http::StatusCode::NO_CONTENT, [(::http::header::ALLOW, allow.clone())]).into_response();
+
+                    let (Some(policy), Some(origin)) = (policy, headers.get(::http::header::ORIGIN)) else {
+                        return response;
+                    };
+                    let allowed = policy.any_origin || policy.origins.iter().any(|allowed| allowed.as_bytes() == origin.as_bytes());
+                    if !allowed || !headers.contains_key(::http::header::ACCESS_CONTROL_REQUEST_METHOD) {
+                        return response;
+                    }
 
-        quote! {
-            #[allow(dead_code)]
-            pub fn #serialize_fn<S>(value: &Option<i32>, serializer: S) -> Result<S::Ok, S::Error>
-            where
-                S: serde::Serializer,
-            {
-                use serde::Serialize;
-                match value {
-                    Some(val) => {
-                        if let Ok(enum_val) = #enum_ident::try_from(*val) {
-                            Some(enum_val.as_str_name()).serialize(serializer)
-                        } else {
-                            Some(*val).serialize(serializer)
+                    let response_headers = response.headers_mut();
+                    let allow_origin = if policy.any_origin && !policy.allow_credentials {
+                        ::http::HeaderValue::from_static("*")
+                    } else {
+                        origin.clone()
+                    };
+                    response_headers.insert(::http::header::ACCESS_CONTROL_ALLOW_ORIGIN, allow_origin);
+                    response_headers.insert(::http::header::ACCESS_CONTROL_ALLOW_METHODS, allow);
+                    if policy.any_header {
+                        if let Some(requested) = headers.get(::http::header::ACCESS_CONTROL_REQUEST_HEADERS) {
+                            response_headers.insert(::http::header::ACCESS_CONTROL_ALLOW_HEADERS, requested.clone());
                         }
+                    } else if !policy.headers.is_empty() {
+                        response_headers.insert(::http::header::ACCESS_CONTROL_ALLOW_HEADERS, ::http::HeaderValue::from_static(policy.headers));
                     }
-                    None => None::<&str>.serialize(serializer),
+                    if policy.allow_credentials {
+                        response_headers.insert(::http::header::ACCESS_CONTROL_ALLOW_CREDENTIALS, ::http::HeaderValue::from_static("true"));
+                    }
+                    if let Some(max_age) = policy.max_age {
+                        response_headers.insert(::http::header::ACCESS_CONTROL_MAX_AGE, ::http::HeaderValue::from(max_age));
+                    }
+                    response_headers.insert(
+                        ::http::header::VARY,
+                        ::http::HeaderValue::from_static("origin, access-control-request-method, access-control-request-headers"),
+                    );
+                    response
                 }
-            }
+            };
 
-            #[allow(dead_code)]
-            pub fn #deserialize_fn<'de, D>(deserializer: D) -> Result<Option<i32>, D::Error>
-            where
-                D: serde::Deserializer<'de>,
-            {
-                use serde::Deserialize;
-                #[derive(Deserialize)]
-                #[serde(untagged)]
-                #[allow(dead_code)]
-                enum OptionalEnumOrString {
-                    String(String),
-                    Int(i32),
-                    None,
+            buf.push('\n');
+            buf.push_str(&preflight_helpers.to_string());
+        }
+
+        if self.enable_response_envelope {
+            let (read_body, rebuild_body) = self.response_body_tokens();
+            let envelope_middleware = quote! {
+                /// Envelope of every JSON response, with either `data` or `error` set
+                #[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+                pub struct ResponseEnvelope<T = ::serde_json::Value> {
+                    /// Message of a successful response
+                    pub data: Option<T>,
+                    /// Error of a failed response
+                    pub error: Option<ErrorDetails>,
                 }
-                match Option::<OptionalEnumOrString>::deserialize(deserializer)? {
-                    Some(OptionalEnumOrString::String(s)) => {
-                        if let Some(enum_val) = #enum_ident::from_str_name(&s) {
-                            Ok(Some(enum_val as i32))
-                        } else {
-                            Err(serde::de::Error::custom(format!("Unknown enum value for {}: {}", stringify!(#enum_ident), s)))
-                        }
+
+                // Wraps JSON responses in a `ResponseEnvelope`, answering errors with HTTP 200
+                #[allow(dead_code)]
+                async fn envelope_response(request: ::axum::extract::Request, next: ::axum::middleware::Next) -> ::axum::response::Response {
+                    let response = next.run(request).await;
+                    let is_json = response
+                        .headers()
+                        .get(::http::header::CONTENT_TYPE)
+                        .is_some_and(|content_type| content_type.as_bytes().starts_with(b"application/json"));
+                    if !is_json {
+                        return response;
                     }
-                    Some(OptionalEnumOrString::Int(i)) => Ok(Some(i)),
-                    Some(OptionalEnumOrString::None) | None => Ok(None),
+
+                    let (mut parts, body) = response.into_parts();
+                    #read_body
+                    let envelope = if parts.status.is_client_error() || parts.status.is_server_error() {
+                        match ::serde_json::from_slice::<ErrorResponse>(&body) {
+                            Ok(error_body) => {
+                                parts.status = ::http::StatusCode::OK;
+                                ResponseEnvelope { data: None, error: Some(error_body.error) }
+                            }
+                            Err(_) => return ::axum::response::Response::from_parts(parts, #rebuild_body),
+                        }
+                    } else {
+                        match ::serde_json::from_slice::<::serde_json::Value>(&body) {
+                            Ok(data) => ResponseEnvelope { data: Some(data), error: None },
+                            Err(_) => return ::axum::response::Response::from_parts(parts, #rebuild_body),
+                        }
+                    };
+
+                    parts.headers.remove(::http::header::CONTENT_LENGTH);
+                    let body = ::serde_json::to_vec(&envelope).map(::axum::body::Bytes::from).unwrap_or(body);
+                    ::axum::response::Response::from_parts(parts, #rebuild_body)
                 }
-            }
-        }.to_string()
-    }
+            };
 
-    /// Generate serializer/deserializer functions for a repeated enum field
-    fn generate_repeated_enum_functions(
-        field_id: &str,
-        enum_ident: &proc_macro2::TokenStream,
-    ) -> String {
-        let serialize_fn = quote::format_ident!("serialize_repeated_{}_as_string", field_id);
-        let deserialize_fn = quote::format_ident!("deserialize_repeated_{}_from_string", field_id);
+            buf.push('\n');
+            buf.push_str(&envelope_middleware.to_string());
+        }
 
-        quote! {
-            #[allow(dead_code)]
-            pub fn #serialize_fn<S>(values: &[i32], serializer: S) -> Result<S::Ok, S::Error>
-            where
-                S: serde::Serializer,
-            {
-                use serde::Serialize;
-                let string_values: Vec<_> = values.iter().map(|val| {
-                    if let Ok(enum_val) = #enum_ident::try_from(*val) {
-                        enum_val.as_str_name().to_string()
-                    } else {
-                        val.to_string()
+        if self.enable_idempotency {
+            let idempotency_helpers = quote! {
+                /// A response stored for an idempotency key
+                #[derive(Debug, Clone)]
+                pub struct StoredResponse {
+                    /// HTTP status of the response
+                    pub status: ::http::StatusCode,
+                    /// Headers of the response
+                    pub headers: ::http::header::HeaderMap,
+                    /// Encoded body of the response
+                    pub body: ::axum::body::Bytes,
+                    /// Fingerprint of the request message the response answers
+                    pub request_fingerprint: u64,
+                }
+
+                /// Outcome of reserving an idempotency key
+                #[derive(Debug, Clone)]
+                pub enum IdempotencyReservation {
+                    /// The key was free and is now held by the request, until its response is
+                    /// stored or the key released
+                    Reserved,
+                    /// Another request with the key is in progress
+                    Pending,
+                    /// The response of an earlier request with the key
+                    Stored(StoredResponse),
+                }
+
+                /// Storage of responses by method path and idempotency key
+                ///
+                /// `metadata` is the metadata of the caller, to scope keys to a tenant or user.
+                #[::tonic::async_trait]
+                pub trait IdempotencyStore: Send + Sync + 'static {
+                    /// Returns the response stored for `key` on the method at `path`, or reserves
+                    /// the key when it is free, in one atomic step
+                    async fn reserve(&self, path: &str, key: &str, metadata: &::tonic::metadata::MetadataMap) -> IdempotencyReservation;
+                    /// Stores the response of the request holding the reservation of `key`
+                    async fn put(&self, path: &str, key: &str, metadata: &::tonic::metadata::MetadataMap, response: StoredResponse);
+                    /// Releases the reservation of `key` when its request ends without a stored
+                    /// response, with a 5xx error or dropped by the client. It runs in `Drop`
+                    /// and must not block; stores which can't release keys right away should
+                    /// let reservations expire instead.
+                    fn release(&self, path: &str, key: &str, metadata: &::tonic::metadata::MetadataMap);
+                }
+
+                /// An `IdempotencyStore` installed as a request extension
+                #[derive(Clone)]
+                pub struct IdempotencyStorage(pub ::std::sync::Arc<dyn IdempotencyStore>);
+
+                #[allow(dead_code)]
+                impl IdempotencyStorage {
+                    /// Wraps `store` to install it as a request extension
+                    pub fn new(store: impl IdempotencyStore) -> Self {
+                        Self(::std::sync::Arc::new(store))
                     }
-                }).collect();
-                string_values.serialize(serializer)
-            }
+                }
 
-            #[allow(dead_code)]
-            pub fn #deserialize_fn<'de, D>(deserializer: D) -> Result<Vec<i32>, D::Error>
-            where
-                D: serde::Deserializer<'de>,
-            {
-                use serde::Deserialize;
-                #[derive(Deserialize)]
-                #[serde(untagged)]
+                impl ::std::fmt::Debug for IdempotencyStorage {
+                    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                        f.debug_struct("IdempotencyStorage").finish_non_exhaustive()
+                    }
+                }
+
+                /// An `IdempotencyStore` keeping responses in memory, without expiry
                 #[allow(dead_code)]
-                enum EnumOrStringItem {
-                    String(String),
-                    Int(i32),
+                #[derive(Debug, Default)]
+                pub struct InMemoryIdempotencyStore {
+                    // Reserved keys have no response yet
+                    responses: ::std::sync::Mutex<::std::collections::HashMap<(String, String), Option<StoredResponse>>>,
                 }
-                let items: Vec<EnumOrStringItem> = Vec::deserialize(deserializer)?;
-                let mut result = Vec::with_capacity(items.len());
 
-                for item in items {
-                    match item {
-                        EnumOrStringItem::String(s) => {
-                            if let Some(enum_val) = #enum_ident::from_str_name(&s) {
-                                result.push(enum_val as i32);
-                            } else {
-                                return Err(serde::de::Error::custom(format!("Unknown enum value for {}: {}", stringify!(#enum_ident), s)));
+                #[::tonic::async_trait]
+                impl IdempotencyStore for InMemoryIdempotencyStore {
+                    async fn reserve(&self, path: &str, key: &str, _metadata: &::tonic::metadata::MetadataMap) -> IdempotencyReservation {
+                        let mut responses = self.responses.lock().unwrap_or_else(|err| err.into_inner());
+                        match responses.entry((path.to_string(), key.to_string())) {
+                            ::std::collections::hash_map::Entry::Occupied(entry) => match entry.get() {
+                                Some(stored) => IdempotencyReservation::Stored(stored.clone()),
+                                None => IdempotencyReservation::Pending,
+                            },
+                            ::std::collections::hash_map::Entry::Vacant(entry) => {
+                                entry.insert(None);
+                                IdempotencyReservation::Reserved
                             }
                         }
-                        EnumOrStringItem::Int(i) => {
-                            result.push(i);
+                    }
+
+                    async fn put(&self, path: &str, key: &str, _metadata: &::tonic::metadata::MetadataMap, response: StoredResponse) {
+                        let mut responses = self.responses.lock().unwrap_or_else(|err| err.into_inner());
+                        let stored = responses.entry((path.to_string(), key.to_string())).or_default();
+                        stored.get_or_insert(response);
+                    }
+
+                    fn release(&self, path: &str, key: &str, _metadata: &::tonic::metadata::MetadataMap) {
+                        let mut responses = self.responses.lock().unwrap_or_else(|err| err.into_inner());
+                        let key = (path.to_string(), key.to_string());
+                        if responses.get(&key).is_some_and(Option::is_none) {
+                            responses.remove(&key);
                         }
                     }
                 }
 
-                Ok(result)
-            }
-        }.to_string()
-    }
-}
-
-/// Configuration helper for building prost config with automatic enum detection
-pub struct EnumConfig {
-    generator: BridgeGenerator,
-}
-impl EnumConfig {
-    /// Create a new EnumConfig from a BridgeGenerator
-    pub fn new(generator: BridgeGenerator) -> Self {
-        Self { generator }
-    }
+                // An idempotency key reserved by a request, released on drop unless its response
+                // was stored
+                #[allow(dead_code)]
+                struct ReservedIdempotencyKey {
+                    storage: IdempotencyStorage,
+                    path: &'static str,
+                    key: String,
+                    metadata: ::tonic::metadata::MetadataMap,
+                    fingerprint: u64,
+                    stored: bool,
+                }
 
-    /// Build prost config with automatic enum field detection and deserializers
-    pub fn build_prost_config_with_descriptors(
-        self,
-        file_descriptor_set: &FileDescriptorSet,
-    ) -> prost_build::Config {
-        let enable_string_enums = self.generator.enable_string_enums;
-        let mut config = self.generator.build_prost_config();
+                impl Drop for ReservedIdempotencyKey {
+                    fn drop(&mut self) {
+                        if !self.stored {
+                            self.storage.0.release(self.path, &self.key, &self.metadata);
+                        }
+                    }
+                }
 
-        if enable_string_enums {
-            config = Self::add_enum_string_support_static(config, file_descriptor_set);
-        }
+                // Answers a repeated request with the stored response
+                #[allow(dead_code)]
+                fn replay_stored_response(stored: StoredResponse) -> ::axum::response::Response {
+                    let mut response = ::axum::response::Response::new(::axum::body::Body::from(stored.body));
+                    *response.status_mut() = stored.status;
+                    *response.headers_mut() = stored.headers;
+                    response.headers_mut().insert("idempotent-replayed", ::http::HeaderValue::from_static("true"));
+                    response
+                }
 
-        // Add skip nulls support by default
-        config = Self::add_skip_nulls_support_static(config, file_descriptor_set);
+                // Answers a key reused with another request message
+                #[allow(dead_code)]
+                fn idempotency_conflict_response() -> ::axum::response::Response {
+                    use ::axum::response::IntoResponse;
 
-        config
-    }
+                    let error_body = ErrorResponse {
+                        error: ErrorDetails::new(error_code(::tonic::Code::InvalidArgument), "The idempotency key was already used with another request")
+                    };
 
-    /// Add enum string support by detecting enum fields automatically (static version)
-    fn add_enum_string_support_static(
-        mut config: prost_build::Config,
-        file_descriptor_set: &FileDescriptorSet,
-    ) -> prost_build::Config {
-        for file in &file_descriptor_set.file {
-            config = Self::process_file_descriptor_static(config, file);
-        }
-        config
-    }
+                    (::http::StatusCode::UNPROCESSABLE_ENTITY, ::axum::Json(error_body)).into_response()
+                }
 
-    fn process_file_descriptor_static(
-        mut config: prost_build::Config,
-        file: &FileDescriptorProto,
-    ) -> prost_build::Config {
-        // Process all message types in the file
-        for message in &file.message_type {
-            let package = file.package();
-            config = Self::process_message_descriptor_static(config, message, package);
-        }
-        config
-    }
+                // Answers a request whose key is reserved by another one in progress
+                #[allow(dead_code)]
+                fn idempotency_pending_response() -> ::axum::response::Response {
+                    use ::axum::response::IntoResponse;
 
-    fn process_message_descriptor_static(
-        config: prost_build::Config,
-        message: &DescriptorProto,
-        package: &str,
-    ) -> prost_build::Config {
-        Self::process_message_descriptor_with_path_static(config, message, package, "")
-    }
+                    let error_body = ErrorResponse {
+                        error: ErrorDetails::new(error_code(::tonic::Code::Aborted), "A request with the idempotency key is still in progress")
+                    };
 
-    fn process_message_descriptor_with_path_static(
-        mut config: prost_build::Config,
-        message: &DescriptorProto,
-        package: &str,
-        message_path: &str,
-    ) -> prost_build::Config {
-        let message_name = message.name();
-        let current_path = if message_path.is_empty() {
-            message_name.to_snake_case()
-        } else {
-            format!("{}_{}", message_path, message_name.to_snake_case())
-        };
+                    (::http::StatusCode::CONFLICT, ::axum::Json(error_body)).into_response()
+                }
 
-        // Determine if this is a nested message (has a parent message path)
-        let is_nested = !message_path.is_empty();
+                // FNV-1a hash of the JSON of a request message, stable across processes and builds.
+                // Going through `serde_json::Value` sorts the keys of map fields.
+                #[allow(dead_code)]
+                fn request_fingerprint(body: &impl ::serde::Serialize) -> u64 {
+                    let json = ::serde_json::to_value(body)
+                        .and_then(|value| ::serde_json::to_vec(&value))
+                        .unwrap_or_default();
+                    json.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+                        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+                    })
+                }
 
-        // Process all fields in the message
-        for field in &message.field {
-            if Self::is_enum_field_static(field) {
-                config = Self::add_enum_deserializer_with_path_static(
-                    config,
-                    &current_path,
-                    message_name,
-                    field,
-                    package,
-                    is_nested,
-                );
-            }
-        }
+                // Stores the response of the request holding the reservation of its key
+                #[allow(dead_code)]
+                async fn store_response(
+                    mut reservation: ReservedIdempotencyKey,
+                    response: ::axum::response::Response,
+                ) -> ::axum::response::Response {
+                    let (parts, body) = response.into_parts();
+                    let body = ::axum::body::to_bytes(body, usize::MAX).await.unwrap_or_default();
+                    let stored = StoredResponse {
+                        status: parts.status,
+                        headers: parts.headers.clone(),
+                        body: body.clone(),
+                        request_fingerprint: reservation.fingerprint,
+                    };
+                    reservation
+                        .storage
+                        .0
+                        .put(reservation.path, &reservation.key, &reservation.metadata, stored)
+                        .await;
+                    reservation.stored = true;
+
+                    ::axum::response::Response::from_parts(parts, ::axum::body::Body::from(body))
+                }
+            };
 
-        // Recursively process nested message types
-        for nested_message in &message.nested_type {
-            config = Self::process_message_descriptor_with_path_static(
-                config,
-                nested_message,
-                package,
-                &current_path,
-            );
+            buf.push('\n');
+            buf.push_str(&idempotency_helpers.to_string());
         }
 
-        config
-    }
-
-    fn is_enum_field_static(field: &FieldDescriptorProto) -> bool {
-        // Check if the field type is an enum
-        field.r#type() == Type::Enum
-    }
+        if self.enable_trailers {
+            let trailer_helpers = quote! {
+                /// Trailing metadata of a successful response, sent as HTTP trailers when
+                /// inserted into the extensions of a `tonic::Response`
+                #[derive(Debug, Clone, Default)]
+                pub struct TrailingMetadata(pub ::tonic::metadata::MetadataMap);
+
+                // Body sending the in-memory response body, then the trailers
+                struct TrailingMetadataBody {
+                    data: Option<::axum::body::Bytes>,
+                    trailers: Option<::http::header::HeaderMap>,
+                }
 
-    fn add_enum_deserializer_with_path_static(
-        mut config: prost_build::Config,
-        message_path: &str,
-        message_name: &str,
-        field: &FieldDescriptorProto,
-        _package: &str,
-        is_nested: bool,
-    ) -> prost_build::Config {
-        // Use the actual message name for the field_path (what prost expects)
-        let field_path = format!("{}.{}", message_name, field.name());
+                impl ::http_body::Body for TrailingMetadataBody {
+                    type Data = ::axum::body::Bytes;
+                    type Error = ::std::convert::Infallible;
 
-        // Create field-specific serializer function names using the full path
-        let field_id = format!("{}_{}", message_path, field.name().to_snake_case());
+                    fn poll_frame(
+                        mut self: ::std::pin::Pin<&mut Self>,
+                        _cx: &mut ::std::task::Context<'_>,
+                    ) -> ::std::task::Poll<Option<Result<::http_body::Frame<Self::Data>, Self::Error>>> {
+                        if let Some(data) = self.data.take() {
+                            return ::std::task::Poll::Ready(Some(Ok(::http_body::Frame::data(data))));
+                        }
+                        ::std::task::Poll::Ready(self.trailers.take().map(|trailers| Ok(::http_body::Frame::trailers(trailers))))
+                    }
 
-        // Use the correct module path based on whether this message is nested
-        let enum_deserializer_path = if is_nested {
-            "super::enum_deserializer"
-        } else {
-            "enum_deserializer"
-        };
+                    fn is_end_stream(&self) -> bool {
+                        self.data.is_none() && self.trailers.is_none()
+                    }
+                }
 
-        let serde_attribute = match Self::get_field_label_static(field) {
-            FieldLabel::Optional => {
-                // For optional fields, check if prost would generate Option<T> or just T with default
-                if field.proto3_optional() {
-                    format!("#[serde(serialize_with = \"{enum_deserializer_path}::serialize_option_{field_id}_as_string\", deserialize_with = \"{enum_deserializer_path}::deserialize_option_{field_id}_from_string\", default)]")
-                } else {
-                    // In proto3, scalar types have implicit defaults, so use regular deserializer
-                    format!("#[serde(serialize_with = \"{enum_deserializer_path}::serialize_{field_id}_as_string\", deserialize_with = \"{enum_deserializer_path}::deserialize_{field_id}_from_string\", default)]")
+                // Reads a whole response body, keeping its trailers
+                #[allow(dead_code)]
+                async fn collect_body(mut body: ::axum::body::Body) -> (::axum::body::Bytes, Option<::http::header::HeaderMap>) {
+                    let mut data = Vec::new();
+                    let mut trailers = None;
+                    while let Some(Ok(frame)) = ::std::future::poll_fn(|cx| ::http_body::Body::poll_frame(::std::pin::Pin::new(&mut body), cx)).await {
+                        match frame.into_data() {
+                            Ok(chunk) => data.extend_from_slice(&chunk),
+                            Err(frame) => trailers = frame.into_trailers().ok(),
+                        }
+                    }
+                    (data.into(), trailers)
                 }
-            },
-            FieldLabel::Required => format!("#[serde(serialize_with = \"{enum_deserializer_path}::serialize_{field_id}_as_string\", deserialize_with = \"{enum_deserializer_path}::deserialize_{field_id}_from_string\")]"),
-            FieldLabel::Repeated => format!("#[serde(serialize_with = \"{enum_deserializer_path}::serialize_repeated_{field_id}_as_string\", deserialize_with = \"{enum_deserializer_path}::deserialize_repeated_{field_id}_from_string\", default)]"),
-        };
 
-        config.field_attribute(&field_path, &serde_attribute);
-        config
-    }
+                // Body of in-memory `data`, followed by `trailers` when there are any
+                #[allow(dead_code)]
+                fn body_with_trailers(data: ::axum::body::Bytes, trailers: Option<::http::header::HeaderMap>) -> ::axum::body::Body {
+                    match trailers {
+                        Some(trailers) => ::axum::body::Body::new(TrailingMetadataBody {
+                            data: Some(data),
+                            trailers: Some(trailers),
+                        }),
+                        None => ::axum::body::Body::from(data),
+                    }
+                }
 
-    fn get_field_label_static(field: &FieldDescriptorProto) -> FieldLabel {
-        match field.label() {
-            Label::Optional => FieldLabel::Optional,
-            Label::Required => FieldLabel::Required,
-            Label::Repeated => FieldLabel::Repeated,
-        }
-    }
+                // Sends the `TrailingMetadata` of a response as trailers, or merged into the
+                // headers when the client doesn't accept trailers
+                #[allow(dead_code)]
+                async fn apply_trailing_metadata(mut response: ::axum::response::Response, trailers_accepted: bool) -> ::axum::response::Response {
+                    let Some(TrailingMetadata(metadata_map)) = response.extensions_mut().remove::<TrailingMetadata>() else {
+                        return response;
+                    };
+                    let trailers = response_headers_from_metadata(metadata_map);
+                    if trailers.is_empty() {
+                        return response;
+                    }
 
-    /// Add skip nulls support by detecting field types and adding appropriate skip_serializing_if attributes
-    fn add_skip_nulls_support_static(
-        mut config: prost_build::Config,
-        file_descriptor_set: &FileDescriptorSet,
-    ) -> prost_build::Config {
-        for file in &file_descriptor_set.file {
-            for message in &file.message_type {
-                config = Self::process_message_skip_nulls_recursive(config, message);
-            }
-        }
-        config
-    }
+                    if !trailers_accepted {
+                        for (name, value) in &trailers {
+                            response.headers_mut().append(name.clone(), value.clone());
+                        }
+                        return response;
+                    }
 
-    fn process_message_skip_nulls_recursive(
-        mut config: prost_build::Config,
-        message: &DescriptorProto,
-    ) -> prost_build::Config {
-        let message_name = message.name();
+                    let names = trailers
+                        .keys()
+                        .map(|name| name.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    let (mut parts, body) = response.into_parts();
+                    let data = ::axum::body::to_bytes(body, usize::MAX).await.unwrap_or_default();
+
+                    // Trailers need a chunked response
+                    parts.headers.remove(::http::header::CONTENT_LENGTH);
+                    if let Ok(names) = ::http::HeaderValue::from_str(&names) {
+                        parts.headers.insert(::http::header::TRAILER, names);
+                    }
+                    ::axum::response::Response::from_parts(parts, body_with_trailers(data, Some(trailers)))
+                }
+            };
 
-        // Process all fields in the message
-        for field in &message.field {
-            config = Self::add_skip_null_attribute_static(config, message_name, field);
+            buf.push('\n');
+            buf.push_str(&trailer_helpers.to_string());
         }
 
-        // Recursively process nested message types
-        for nested_message in &message.nested_type {
-            config = Self::process_message_skip_nulls_recursive(config, nested_message);
+        if self.enable_test_clients {
+            buf.push('\n');
+            buf.push_str(&test_client::package_helpers().to_string());
         }
 
-        config
-    }
-
-    fn add_skip_null_attribute_static(
-        mut config: prost_build::Config,
-        message_name: &str,
-        field: &FieldDescriptorProto,
-    ) -> prost_build::Config {
-        const SKIP_NONE: &str = "#[serde(skip_serializing_if = \"Option::is_none\")]";
-        const SKIP_EMPTY: &str = "#[serde(skip_serializing_if = \"String::is_empty\")]";
-        let field_path = format!("{}.{}", message_name, field.name());
-        let skip_attribute = if field.proto3_optional()
-            || (field.label() == Label::Optional && field.r#type() == Type::Message)
-        {
-            Some(SKIP_NONE)
-        } else if field.r#type() == Type::String && field.label() != Label::Repeated {
-            Some(SKIP_EMPTY)
-        } else {
-            None
-        };
+        // Shared by the query strings of GET requests and the routing header values
+        if self.enable_http_transport || self.enable_test_clients || self.enable_routing_headers {
+            let percent_encoding = quote! {
+                // Percent-encodes every byte of `value` outside of the unreserved characters of RFC 3986
+                #[allow(dead_code)]
+                fn percent_encode(value: &str) -> String {
+                    let mut encoded = String::with_capacity(value.len());
+                    for byte in value.bytes() {
+                        match byte {
+                            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => encoded.push(byte as char),
+                            _ => encoded.push_str(&format!("%{:02X}", byte)),
+                        }
+                    }
+                    encoded
+                }
+            };
 
-        if let Some(attribute) = skip_attribute {
-            config.field_attribute(&field_path, attribute);
+            buf.push('\n');
+            buf.push_str(&percent_encoding.to_string());
         }
-
-        config
     }
 
-    /// Generate enum deserializer code that can be included in the generated crate
-    pub fn generate_enum_deserializer_code(
-        &self,
-        file_descriptor_set: &FileDescriptorSet,
-    ) -> String {
-        Self::generate_enum_deserializer_code_static(file_descriptor_set)
-    }
+    // Types and helpers of optional features, after the gRPC descriptor set
+    fn axum_package_extras(&self, buf: &mut String) {
+        if self.enable_health_endpoints {
+            let health_types = quote! {
+                /// Readiness reported by the `/readyz` route of [`health_router`]
+                #[::tonic::async_trait]
+                pub trait HttpReadiness: Send + Sync + 'static {
+                    /// Whether the service can take traffic, `true` unless overridden
+                    async fn is_ready(&self) -> bool {
+                        true
+                    }
+                }
 
-    /// Static version for generating enum deserializer code
-    fn generate_enum_deserializer_code_static(file_descriptor_set: &FileDescriptorSet) -> String {
-        let enum_types = Self::extract_all_enum_types_static(file_descriptor_set);
+                /// Always ready, for routers only probing liveness
+                impl HttpReadiness for () {}
 
-        let enum_list_macro = Self::generate_enum_list_macro_static(&enum_types);
-        let enum_serializer_macro = Self::generate_enum_serializer_macro_static(&enum_types);
-        let single_deserializer = Self::generate_single_enum_deserializer_static();
-        let option_deserializer = Self::generate_option_enum_deserializer_static();
-        let repeated_deserializer = Self::generate_repeated_enum_deserializer_static();
-        let single_serializer = Self::generate_single_enum_serializer_static();
-        let option_serializer = Self::generate_option_enum_serializer_static();
-        let repeated_serializer = Self::generate_repeated_enum_serializer_static();
+                // Body of the `/healthz` and `/readyz` responses
+                #[derive(Debug, Clone, serde::Serialize)]
+                struct HealthStatus {
+                    status: &'static str,
+                }
 
-        // Parse the generated strings as token streams for quote
-        let enum_list_tokens: proc_macro2::TokenStream = enum_list_macro.parse().unwrap();
-        let enum_serializer_tokens: proc_macro2::TokenStream =
-            enum_serializer_macro.parse().unwrap();
-        let single_deserializer_tokens: proc_macro2::TokenStream =
-            single_deserializer.parse().unwrap();
-        let option_deserializer_tokens: proc_macro2::TokenStream =
-            option_deserializer.parse().unwrap();
-        let repeated_deserializer_tokens: proc_macro2::TokenStream =
-            repeated_deserializer.parse().unwrap();
-        let single_serializer_tokens: proc_macro2::TokenStream = single_serializer.parse().unwrap();
-        let option_serializer_tokens: proc_macro2::TokenStream = option_serializer.parse().unwrap();
-        let repeated_serializer_tokens: proc_macro2::TokenStream =
-            repeated_serializer.parse().unwrap();
+                /// Router serving the `/healthz` and `/readyz` probes, asking `readiness` for the latter
+                #[allow(dead_code)]
+                pub fn health_router(readiness: impl HttpReadiness) -> ::axum::Router {
+                    let readiness: ::std::sync::Arc<dyn HttpReadiness> = ::std::sync::Arc::new(readiness);
+                    ::axum::Router::new()
+                        .route("/healthz", ::axum::routing::get(|| async {
+                            ::axum::Json(HealthStatus { status: "ok" })
+                        }))
+                        .route("/readyz", ::axum::routing::get(move || async move {
+                            if readiness.is_ready().await {
+                                (::http::StatusCode::OK, ::axum::Json(HealthStatus { status: "ready" }))
+                            } else {
+                                (::http::StatusCode::SERVICE_UNAVAILABLE, ::axum::Json(HealthStatus { status: "not_ready" }))
+                            }
+                        }))
+                }
+            };
 
-        quote! {
-            // Auto-generated enum deserializer module
-            // This file contains utilities for serializing and deserializing protobuf enums from string values in JSON
+            buf.push('\n');
+            buf.push_str(&health_types.to_string());
+        }
 
-            pub mod enum_deserializer {
-                use super::*;
+        if self.is_profiling_slow_requests() {
+            let profiler_types = quote! {
+                /// Timing breakdown of a bridged request slower than the configured threshold
+                #[allow(dead_code)]
+                #[derive(Debug, Clone)]
+                pub struct SlowRequestProfile {
+                    /// HTTP path of the method
+                    pub method: &'static str,
+                    /// Time spent parsing the JSON request body
+                    pub parse_time: ::std::time::Duration,
+                    /// Time spent in the service implementation
+                    pub handler_time: ::std::time::Duration,
+                    /// Time spent building the HTTP response
+                    pub serialize_time: ::std::time::Duration,
+                    /// Total time spent in the handler
+                    pub total_time: ::std::time::Duration,
+                    /// Size of the request body in bytes
+                    pub request_size: usize,
+                    /// Size of the response body in bytes
+                    pub response_size: usize,
+                }
+
+                /// Receives the profiles of slow requests, installed as a request extension
+                #[derive(Clone)]
+                pub struct SlowRequestObserver(::std::sync::Arc<dyn Fn(&SlowRequestProfile) + Send + Sync>);
+
+                #[allow(dead_code)]
+                impl SlowRequestObserver {
+                    /// Creates an observer calling `observe` for every slow request
+                    pub fn new(observe: impl Fn(&SlowRequestProfile) + Send + Sync + 'static) -> Self {
+                        Self(::std::sync::Arc::new(observe))
+                    }
 
-                #enum_list_tokens
+                    fn observe(&self, profile: &SlowRequestProfile) {
+                        (self.0)(profile)
+                    }
+                }
 
-                #enum_serializer_tokens
+                impl ::std::fmt::Debug for SlowRequestObserver {
+                    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                        f.debug_struct("SlowRequestObserver").finish_non_exhaustive()
+                    }
+                }
 
-                #single_deserializer_tokens
+                // Profiled bodies are read as bytes, so they check the `Content-Type` that
+                // `axum::Json` would, answering other content types with a JSON 415
+                #[allow(dead_code)]
+                fn check_profiled_content_type(headers: &::http::header::HeaderMap) -> Result<(), ::axum::response::Response> {
+                    use ::axum::response::IntoResponse;
+
+                    let essence = headers
+                        .get(::http::header::CONTENT_TYPE)
+                        .and_then(|content_type| content_type.to_str().ok())
+                        .and_then(|content_type| content_type.split(';').next())
+                        .map(|essence| essence.trim().to_ascii_lowercase());
+                    match essence {
+                        Some(essence) if essence == "application/json" || (essence.starts_with("application/") && essence.ends_with("+json")) => Ok(()),
+                        _ => {
+                            let error_body = ErrorResponse {
+                                error: ErrorDetails::new(error_code(::tonic::Code::InvalidArgument), "Expected request with `Content-Type: application/json`")
+                            };
+                            Err((::http::StatusCode::UNSUPPORTED_MEDIA_TYPE, ::axum::Json(error_body)).into_response())
+                        }
+                    }
+                }
+            };
 
-                #option_deserializer_tokens
+            buf.push('\n');
+            buf.push_str(&profiler_types.to_string());
+        }
 
-                #repeated_deserializer_tokens
+        if !self.masked_messages.is_empty() {
+            let withheld_body_error = quote! {
+                // Parse error of a request with `g2h.sensitive` fields, without serde's details
+                #[allow(dead_code)]
+                fn withheld_body_error(status: ::http::StatusCode) -> ::axum::response::Response {
+                    use ::axum::response::IntoResponse;
 
-                #single_serializer_tokens
+                    let error_body = ErrorResponse {
+                        error: ErrorDetails::new(error_code(::tonic::Code::InvalidArgument), "Failed to deserialize the JSON body, details are withheld as the message has sensitive fields")
+                    };
 
-                #option_serializer_tokens
+                    (status, ::axum::Json(error_body)).into_response()
+                }
+            };
 
-                #repeated_serializer_tokens
-            }
+            buf.push('\n');
+            buf.push_str(&withheld_body_error.to_string());
         }
-        .to_string()
-    }
 
-    fn extract_all_enum_types_static(file_descriptor_set: &FileDescriptorSet) -> Vec<String> {
-        let mut enum_types = Vec::new();
+        let validates_fields =
+            self.enable_request_validation && !self.validated_messages.is_empty();
+        let protovalidates = self.enable_protovalidate && !self.protovalidate_messages.is_empty();
+        if validates_fields || protovalidates {
+            let violations_response = quote! {
+                // Answers a request failing validation with a 400 listing the violations
+                #[allow(dead_code)]
+                fn field_violations_response(violations: Vec<FieldViolation>) -> ::axum::response::Response {
+                    use ::axum::response::IntoResponse;
 
-        for file in &file_descriptor_set.file {
-            // Top-level enums
-            for enum_desc in &file.enum_type {
-                let enum_name = enum_desc.name();
-                enum_types.push(enum_name.to_string());
-            }
+                    let error_body = ErrorResponse {
+                        error: ErrorDetails {
+                            field_violations: Some(violations),
+                            ..ErrorDetails::new(error_code(::tonic::Code::InvalidArgument), "Request validation failed")
+                        }
+                    };
+                    (::http::StatusCode::BAD_REQUEST, ::axum::Json(error_body)).into_response()
+                }
+            };
 
-            // Enums in messages (recursive)
-            for message in &file.message_type {
-                enum_types.extend(Self::extract_nested_enums_static(message, ""));
-            }
+            buf.push('\n');
+            buf.push_str(&violations_response.to_string());
         }
 
-        enum_types
-    }
+        if protovalidates {
+            let request_validator = quote! {
+                /// Checks requests against their `buf.validate` constraints, installed as a request
+                /// extension of the generated routers
+                ///
+                /// The closure receives the fully qualified message type and the protobuf encoded
+                /// message, e.g. for a protovalidate runtime using `FILE_DESCRIPTOR_SET`, and returns
+                /// the violations.
+                #[derive(Clone)]
+                pub struct RequestValidator {
+                    validate: ::std::sync::Arc<dyn Fn(&str, &[u8]) -> Vec<FieldViolation> + Send + Sync>,
+                }
 
-    fn extract_nested_enums_static(message: &DescriptorProto, module_path: &str) -> Vec<String> {
-        let mut enum_types = Vec::new();
-        let message_name = message.name();
+                impl ::std::fmt::Debug for RequestValidator {
+                    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                        f.debug_struct("RequestValidator").finish_non_exhaustive()
+                    }
+                }
 
-        // Convert message name to snake_case for module path (prost convention)
-        let message_module = message_name.to_snake_case();
+                #[allow(dead_code)]
+                impl RequestValidator {
+                    /// Creates a validator from a closure returning the violations of a message
+                    pub fn new(validate: impl Fn(&str, &[u8]) -> Vec<FieldViolation> + Send + Sync + 'static) -> Self {
+                        Self {
+                            validate: ::std::sync::Arc::new(validate),
+                        }
+                    }
 
-        // Enums directly in this message
-        for enum_desc in &message.enum_type {
-            let enum_name = enum_desc.name();
-            enum_types.push(format!("{module_path}{message_module}::{enum_name}"));
-        }
+                    /// Violations of `message`, of the fully qualified type `message_type`
+                    pub fn violations<M: ::prost::Message>(&self, message_type: &str, message: &M) -> Vec<FieldViolation> {
+                        (self.validate)(message_type, &message.encode_to_vec())
+                    }
 
-        // Recursively check nested messages
-        for nested_message in &message.nested_type {
-            let nested_path = format!("{module_path}{message_module}::");
-            enum_types.extend(Self::extract_nested_enums_static(
-                nested_message,
-                &nested_path,
-            ));
+                    /// Checks `message` in a gRPC service, failing with `INVALID_ARGUMENT` on violations
+                    pub fn check<M: ::prost::Message>(&self, message_type: &str, message: &M) -> Result<(), ::tonic::Status> {
+                        let violations = self.violations(message_type, message);
+                        if violations.is_empty() {
+                            return Ok(());
+                        }
+                        let details = violations
+                            .iter()
+                            .map(|violation| format!("{}: {}", violation.field, violation.description))
+                            .collect::<Vec<_>>()
+                            .join("; ");
+                        Err(::tonic::Status::invalid_argument(format!("Request validation failed: {details}")))
+                    }
+                }
+            };
+
+            buf.push('\n');
+            buf.push_str(&request_validator.to_string());
         }
 
-        enum_types
-    }
+        if validates_fields {
+            let mut required_fields = Vec::new();
+            let mut nested_fields = Vec::new();
+            for (message, fields) in &self.validated_messages {
+                for field in fields {
+                    let name = field.name.to_snake_case();
+                    if field.required {
+                        required_fields.push(quote! { (#message, #name) });
+                    }
+                    match field.type_name {
+                        Some(ref type_name)
+                            if !field.map && self.validated_messages.contains_key(type_name) =>
+                        {
+                            nested_fields.push(quote! { (#message, #name, #type_name) });
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            let validation = quote! {
+                // Collects the violations of the field constraints of a JSON `message`, nested ones included
+                #[allow(dead_code)]
+                fn collect_field_violations(
+                    message: &str,
+                    value: &::serde_json::Value,
+                    prefix: &str,
+                    violations: &mut Vec<FieldViolation>,
+                ) {
+                    const REQUIRED_FIELDS: &[(&str, &str)] = &[#(#required_fields),*];
+                    const NESTED_FIELDS: &[(&str, &str, &str)] = &[#(#nested_fields),*];
+
+                    let ::serde_json::Value::Object(object) = value else {
+                        return;
+                    };
+                    for (_, field) in REQUIRED_FIELDS.iter().filter(|(owner, _)| *owner == message) {
+                        let is_set = match object.get(*field) {
+                            None | Some(::serde_json::Value::Null) => false,
+                            Some(::serde_json::Value::Bool(value)) => *value,
+                            Some(::serde_json::Value::Number(value)) => value.as_f64() != Some(0.0),
+                            Some(::serde_json::Value::String(value)) => !value.is_empty(),
+                            Some(::serde_json::Value::Array(values)) => !values.is_empty(),
+                            Some(::serde_json::Value::Object(values)) => !values.is_empty(),
+                        };
+                        if !is_set {
+                            violations.push(FieldViolation {
+                                field: format!("{prefix}{field}"),
+                                constraint: "required".to_string(),
+                                description: "Field is required".to_string(),
+                            });
+                        }
+                    }
+                    for (_, field, nested) in NESTED_FIELDS.iter().filter(|(owner, _, _)| *owner == message) {
+                        match object.get(*field) {
+                            Some(::serde_json::Value::Array(items)) => {
+                                for (index, item) in items.iter().enumerate() {
+                                    collect_field_violations(nested, item, &format!("{prefix}{field}[{index}]."), violations);
+                                }
+                            }
+                            Some(value) => collect_field_violations(nested, value, &format!("{prefix}{field}."), violations),
+                            None => {}
+                        }
+                    }
+                }
 
-    fn generate_enum_list_macro_static(enum_types: &[String]) -> String {
-        // Convert enum type strings to identifiers for quote
-        let enum_idents: Vec<proc_macro2::TokenStream> = enum_types
-            .iter()
-            .map(|enum_type| {
-                // Parse the enum type path as tokens (e.g., "MyEnum" or "module::MyEnum")
-                enum_type
-                    .parse()
-                    .unwrap_or_else(|e| panic!("Invalid enum type path '{enum_type}': {e}"))
-            })
-            .collect();
+                // Answers a request whose JSON `message` violates its field constraints with a 400 listing the violations
+                #[allow(dead_code)]
+                fn request_validation_error<B: serde::Serialize>(message: &str, body: &B) -> Option<::axum::response::Response> {
+                    let value = ::serde_json::to_value(body).ok()?;
+                    let mut violations = Vec::new();
+                    collect_field_violations(message, &value, "", &mut violations);
+                    if violations.is_empty() {
+                        return None;
+                    }
+                    Some(field_violations_response(violations))
+                }
+            };
 
-        quote! {
-            macro_rules! try_parse_all_enums {
-                ($s:expr) => {
-                    {
-                        // Try each enum type
-                        #(
-                            if let Some(val) = #enum_idents::from_str_name($s) {
-                                return Some(val as i32);
-                            }
-                        )*
+            buf.push('\n');
+            buf.push_str(&validation.to_string());
+        }
 
-                        None
+        if self.enable_access_log && !self.masked_messages.is_empty() {
+            let mut sensitive_fields = Vec::new();
+            let mut nested_fields = Vec::new();
+            for (message, fields) in &self.masked_messages {
+                for field in fields {
+                    let name = field.name.to_snake_case();
+                    let map = field.map;
+                    match field.type_name {
+                        Some(ref type_name) if !field.sensitive => {
+                            nested_fields.push(quote! { (#message, #name, #type_name, #map) });
+                        }
+                        _ => sensitive_fields.push(quote! { (#message, #name) }),
                     }
-                };
+                }
             }
+            let masking = quote! {
+                // Replaces the `g2h.sensitive` fields of a JSON `message`, nested ones included, with "****"
+                #[allow(dead_code)]
+                fn mask_sensitive_fields(message: &str, value: &mut ::serde_json::Value) {
+                    const SENSITIVE_FIELDS: &[(&str, &str)] = &[#(#sensitive_fields),*];
+                    // (message, field, nested message, whether the field is a map of nested messages)
+                    const NESTED_FIELDS: &[(&str, &str, &str, bool)] = &[#(#nested_fields),*];
+
+                    let ::serde_json::Value::Object(object) = value else {
+                        return;
+                    };
+                    for (_, field) in SENSITIVE_FIELDS.iter().filter(|(owner, _)| *owner == message) {
+                        if let Some(value) = object.get_mut(*field).filter(|value| !value.is_null()) {
+                            *value = ::serde_json::Value::from("****");
+                        }
+                    }
+                    for (_, field, nested, map) in NESTED_FIELDS.iter().filter(|(owner, _, _, _)| *owner == message) {
+                        match object.get_mut(*field) {
+                            Some(::serde_json::Value::Object(entries)) if *map => {
+                                for entry in entries.values_mut() {
+                                    mask_sensitive_fields(nested, entry);
+                                }
+                            }
+                            Some(::serde_json::Value::Array(items)) => {
+                                for item in items {
+                                    mask_sensitive_fields(nested, item);
+                                }
+                            }
+                            Some(value) => mask_sensitive_fields(nested, value),
+                            None => {}
+                        }
+                    }
+                }
+            };
+
+            buf.push('\n');
+            buf.push_str(&masking.to_string());
         }
-        .to_string()
-    }
 
-    fn generate_enum_serializer_macro_static(enum_types: &[String]) -> String {
-        // Convert enum type strings to identifiers for quote
-        let enum_idents: Vec<proc_macro2::TokenStream> = enum_types
-            .iter()
-            .map(|enum_type| {
-                // Parse the enum type path as tokens (e.g., "MyEnum" or "module::MyEnum")
-                enum_type
-                    .parse()
-                    .unwrap_or_else(|e| panic!("Invalid enum type path '{enum_type}': {e}"))
-            })
-            .collect();
+        if self.enable_access_log {
+            let access_log_types = quote! {
+                /// A bridged request, as reported to the `AccessLogger`
+                #[allow(dead_code)]
+                #[derive(Debug, Clone)]
+                pub struct AccessLogEntry {
+                    /// HTTP path of the method
+                    pub path: &'static str,
+                    /// Name of the method in the proto service, e.g. `SayHello`
+                    pub method: &'static str,
+                    /// Status of the HTTP response
+                    pub status: ::http::StatusCode,
+                    /// Total time spent in the handler
+                    pub latency: ::std::time::Duration,
+                    /// Redacted JSON request, when the logger captures bodies
+                    pub request: Option<::serde_json::Value>,
+                    /// Redacted JSON response or error, when the logger captures bodies
+                    pub response: Option<::serde_json::Value>,
+                }
 
-        quote! {
-            macro_rules! try_serialize_all_enums {
-                ($value:expr) => {
-                    {
-                        // Try each enum type
-                        #(
-                            if let Ok(enum_val) = #enum_idents::try_from($value) {
-                                return Some(enum_val.as_str_name());
-                            }
-                        )*
+                type AccessLogRedaction = ::std::sync::Arc<dyn Fn(&str, &mut ::serde_json::Value) + Send + Sync>;
 
-                        None
+                /// Receives an entry for every bridged request, installed as a request extension
+                #[derive(Clone)]
+                pub struct AccessLogger {
+                    log: ::std::sync::Arc<dyn Fn(&AccessLogEntry) + Send + Sync>,
+                    bodies: bool,
+                    redactions: Vec<AccessLogRedaction>,
+                }
+
+                #[allow(dead_code)]
+                impl AccessLogger {
+                    /// Creates a logger calling `log` for every request, without bodies
+                    pub fn new(log: impl Fn(&AccessLogEntry) + Send + Sync + 'static) -> Self {
+                        Self {
+                            log: ::std::sync::Arc::new(log),
+                            bodies: false,
+                            redactions: Vec::new(),
+                        }
                     }
-                };
-            }
-        }
-        .to_string()
-    }
 
-    fn generate_single_enum_deserializer_static() -> String {
-        quote! {
-            #[allow(dead_code)]
-            pub fn deserialize_enum_from_string<'de, D>(deserializer: D) -> Result<i32, D::Error>
-            where
-                D: serde::Deserializer<'de>,
-            {
-                use serde::Deserialize;
+                    /// Includes the JSON request and response in the entries
+                    pub fn with_bodies(mut self) -> Self {
+                        self.bodies = true;
+                        self
+                    }
 
-                #[derive(Deserialize)]
-                #[serde(untagged)]
-                #[allow(dead_code)]
-                enum EnumOrString {
-                    String(String),
-                    Int(i32),
-                }
+                    /// Adds a closure rewriting captured bodies, called with the method's HTTP path
+                    pub fn redact(mut self, redaction: impl Fn(&str, &mut ::serde_json::Value) + Send + Sync + 'static) -> Self {
+                        self.redactions.push(::std::sync::Arc::new(redaction));
+                        self
+                    }
 
-                match EnumOrString::deserialize(deserializer)? {
-                    EnumOrString::String(s) => {
-                        fn try_parse_enum(s: &str) -> Option<i32> {
-                            try_parse_all_enums!(s)
+                    fn log(&self, mut entry: AccessLogEntry) {
+                        for redaction in &self.redactions {
+                            for body in [&mut entry.request, &mut entry.response].into_iter().flatten() {
+                                redaction(entry.path, body);
+                            }
                         }
-                        try_parse_enum(&s).ok_or_else(|| {
-                            serde::de::Error::custom(format!("Unknown enum value: {}", s))
-                        })
+                        (self.log)(&entry)
                     }
-                    EnumOrString::Int(i) => Ok(i),
                 }
-            }
+
+                impl ::std::fmt::Debug for AccessLogger {
+                    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                        f.debug_struct("AccessLogger")
+                            .field("bodies", &self.bodies)
+                            .finish_non_exhaustive()
+                    }
+                }
+            };
+
+            buf.push('\n');
+            buf.push_str(&access_log_types.to_string());
         }
-        .to_string()
-    }
 
-    fn generate_option_enum_deserializer_static() -> String {
-        quote! {
-            #[allow(dead_code)]
-            pub fn deserialize_option_enum_from_string<'de, D>(deserializer: D) -> Result<Option<i32>, D::Error>
-            where
-                D: serde::Deserializer<'de>,
-            {
-                use serde::Deserialize;
-                #[derive(Deserialize)]
-                #[serde(untagged)]
+        if self.enable_http_transport {
+            let transport_helpers = quote! {
+                // Request headers of tonic clients that only make sense on a gRPC connection
                 #[allow(dead_code)]
-                enum OptionalEnumOrString {
-                    String(String),
-                    Int(i32),
-                    None,
-                }
-                match Option::<OptionalEnumOrString>::deserialize(deserializer)? {
-                    Some(OptionalEnumOrString::String(s)) => {
-                        fn try_parse_enum(s: &str) -> Option<i32> {
-                            try_parse_all_enums!(s)
+                const GRPC_CLIENT_HEADERS: &[&str] = &[
+                    "te",
+                    "content-type",
+                    "content-length",
+                    "grpc-encoding",
+                    "grpc-accept-encoding",
+                ];
+
+                // Response headers of the bridge that describe the HTTP/JSON body
+                #[allow(dead_code)]
+                const HTTP_BODY_HEADERS: &[&str] = &[
+                    "content-type",
+                    "content-length",
+                    "transfer-encoding",
+                    "connection",
+                ];
+
+                // Performs one unary call of a tonic client against a g2h bridge
+                #[allow(dead_code)]
+                async fn http_transport_unary<Req, Resp, S, RB>(
+                    mut inner: S,
+                    base_uri: ::http::Uri,
+                    route_path: &'static str,
+                    verb: ::http::Method,
+                    request: ::http::Request<::tonic::body::Body>,
+                ) -> Result<::http::Response<::axum::body::Body>, ::tonic::codegen::StdError>
+                where
+                    Req: ::prost::Message + Default + serde::Serialize,
+                    Resp: ::prost::Message + serde::de::DeserializeOwned,
+                    S: ::tonic::codegen::Service<::http::Request<::axum::body::Body>, Response = ::http::Response<RB>>,
+                    S::Error: Into<::tonic::codegen::StdError>,
+                    RB: ::tonic::codegen::Body<Data = ::tonic::codegen::Bytes> + Send + 'static,
+                    RB::Error: Into<::tonic::codegen::StdError>,
+                {
+                    let (parts, body) = request.into_parts();
+                    let frame = ::axum::body::to_bytes(::axum::body::Body::new(body), usize::MAX).await?;
+                    let message = match decode_grpc_frame::<Req>(&frame) {
+                        Ok(message) => message,
+                        Err(status) => return Ok(status.into_http()),
+                    };
+
+                    let mut uri = format!("{}{}", base_uri.to_string().trim_end_matches('/'), route_path);
+                    let body = if verb == ::http::Method::GET {
+                        // GET routes take the scalar request fields from the query string
+                        let fields = match ::serde_json::to_value(&message)? {
+                            ::serde_json::Value::Object(fields) => fields,
+                            _ => Default::default(),
+                        };
+                        let query = fields
+                            .iter()
+                            .filter_map(|(name, value)| {
+                                let value = match value {
+                                    ::serde_json::Value::String(value) => value.clone(),
+                                    ::serde_json::Value::Number(value) => value.to_string(),
+                                    ::serde_json::Value::Bool(value) => value.to_string(),
+                                    _ => return None,
+                                };
+                                Some(format!("{}={}", percent_encode(name), percent_encode(&value)))
+                            })
+                            .collect::<Vec<_>>()
+                            .join("&");
+                        if !query.is_empty() {
+                            uri = format!("{}?{}", uri, query);
+                        }
+                        ::axum::body::Body::empty()
+                    } else {
+                        ::axum::body::Body::from(::serde_json::to_vec(&message)?)
+                    };
+                    let mut http_request = ::http::Request::builder().method(verb).uri(uri).body(body)?;
+                    for (name, value) in parts.headers.iter() {
+                        if !GRPC_CLIENT_HEADERS.contains(&name.as_str()) {
+                            http_request.headers_mut().append(name.clone(), value.clone());
                         }
-                        try_parse_enum(&s)
-                            .map(Some)
-                            .ok_or_else(|| serde::de::Error::custom(format!("Unknown enum value: {}", s)))
                     }
-                    Some(OptionalEnumOrString::Int(i)) => Ok(Some(i)),
-                    Some(OptionalEnumOrString::None) | None => Ok(None),
+                    http_request.headers_mut().insert(
+                        ::http::header::CONTENT_TYPE,
+                        ::http::HeaderValue::from_static("application/json"),
+                    );
+
+                    let response = inner.call(http_request).await.map_err(Into::into)?;
+                    let (parts, body) = response.into_parts();
+                    let body = ::axum::body::to_bytes(::axum::body::Body::new(body), usize::MAX).await?;
+
+                    if !parts.status.is_success() {
+                        return Ok(status_from_http_error(parts.status, &body).into_http());
+                    }
+                    let message: Resp = match ::serde_json::from_slice(&body) {
+                        Ok(message) => message,
+                        Err(err) => {
+                            let status = ::tonic::Status::internal(format!("Failed to deserialize the JSON response: {}", err));
+                            return Ok(status.into_http());
+                        }
+                    };
+
+                    let encoded = message.encode_to_vec();
+                    let mut frame = Vec::with_capacity(encoded.len() + 5);
+                    frame.push(0);
+                    frame.extend_from_slice(&(encoded.len() as u32).to_be_bytes());
+                    frame.extend_from_slice(&encoded);
+
+                    let mut grpc_response = ::http::Response::new(::axum::body::Body::from(frame));
+                    for (name, value) in parts.headers.iter() {
+                        if !HTTP_BODY_HEADERS.contains(&name.as_str()) {
+                            grpc_response.headers_mut().append(name.clone(), value.clone());
+                        }
+                    }
+                    grpc_response.headers_mut().insert(
+                        ::http::header::CONTENT_TYPE,
+                        ::http::HeaderValue::from_static("application/grpc"),
+                    );
+                    grpc_response.headers_mut().insert("grpc-status", ::http::HeaderValue::from_static("0"));
+
+                    Ok(grpc_response)
                 }
-            }
-        }.to_string()
-    }
 
-    fn generate_repeated_enum_deserializer_static() -> String {
-        quote! {
-            #[allow(dead_code)]
-            pub fn deserialize_repeated_enum_from_string<'de, D>(deserializer: D) -> Result<Vec<i32>, D::Error>
-            where
-                D: serde::Deserializer<'de>,
-            {
-                use serde::Deserialize;
-                #[derive(Deserialize)]
-                #[serde(untagged)]
                 #[allow(dead_code)]
-                enum EnumOrStringItem {
-                    String(String),
-                    Int(i32),
+                fn decode_grpc_frame<M: ::prost::Message + Default>(frame: &[u8]) -> Result<M, ::tonic::Status> {
+                    let Some((&compressed, rest)) = frame.split_first() else {
+                        return Err(::tonic::Status::internal("Missing gRPC request message"));
+                    };
+                    if compressed != 0 {
+                        return Err(::tonic::Status::unimplemented("Compressed messages are not supported by the HTTP transport"));
+                    }
+                    let payload = rest
+                        .get(..4)
+                        .map(|len| u32::from_be_bytes([len[0], len[1], len[2], len[3]]) as usize)
+                        .and_then(|len| rest.get(4..4 + len))
+                        .ok_or_else(|| ::tonic::Status::internal("Malformed gRPC message frame"))?;
+
+                    M::decode(payload).map_err(|err| ::tonic::Status::internal(format!("Failed to decode the gRPC message: {}", err)))
+                }
+
+                // Recovers the `tonic::Status` behind a JSON error answered by a g2h bridge
+                #[allow(dead_code)]
+                fn status_from_http_error(status: ::http::StatusCode, body: &[u8]) -> ::tonic::Status {
+                    let error = ::serde_json::from_slice::<ErrorResponse>(body).ok().map(|response| response.error);
+                    let code = error.as_ref().and_then(|error| {
+                        (0..=16)
+                            .map(::tonic::Code::from_i32)
+                            .find(|code| error_code(*code) == error.code)
+                    });
+                    let code = code.unwrap_or(match status {
+                        ::http::StatusCode::BAD_REQUEST | ::http::StatusCode::UNPROCESSABLE_ENTITY => ::tonic::Code::InvalidArgument,
+                        ::http::StatusCode::UNAUTHORIZED => ::tonic::Code::Unauthenticated,
+                        ::http::StatusCode::FORBIDDEN => ::tonic::Code::PermissionDenied,
+                        ::http::StatusCode::NOT_FOUND => ::tonic::Code::NotFound,
+                        ::http::StatusCode::CONFLICT => ::tonic::Code::Aborted,
+                        ::http::StatusCode::TOO_MANY_REQUESTS | ::http::StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE => ::tonic::Code::ResourceExhausted,
+                        ::http::StatusCode::PRECONDITION_FAILED => ::tonic::Code::FailedPrecondition,
+                        ::http::StatusCode::METHOD_NOT_ALLOWED | ::http::StatusCode::NOT_IMPLEMENTED => ::tonic::Code::Unimplemented,
+                        ::http::StatusCode::SERVICE_UNAVAILABLE => ::tonic::Code::Unavailable,
+                        ::http::StatusCode::REQUEST_TIMEOUT | ::http::StatusCode::GATEWAY_TIMEOUT => ::tonic::Code::DeadlineExceeded,
+                        ::http::StatusCode::RANGE_NOT_SATISFIABLE => ::tonic::Code::OutOfRange,
+                        ::http::StatusCode::INTERNAL_SERVER_ERROR => ::tonic::Code::Internal,
+                        _ => ::tonic::Code::Unknown,
+                    });
+                    let message = match error {
+                        Some(error) => error.message,
+                        None => status.canonical_reason().unwrap_or("HTTP error").to_string(),
+                    };
+
+                    ::tonic::Status::new(code, message)
+                }
+            };
+
+            buf.push('\n');
+            buf.push_str(&transport_helpers.to_string());
+        }
+
+        if self.enable_routing_headers {
+            let routing_helpers = quote! {
+                // Builds `x-goog-request-params` from the `google.api.routing` parameters of a method
+                #[allow(dead_code)]
+                fn request_routing_params<B: serde::Serialize>(body: &B, parameters: &[(&str, &str)]) -> Option<::tonic::metadata::MetadataValue<::tonic::metadata::Ascii>> {
+                    let body = ::serde_json::to_value(body).ok()?;
+                    let mut params: Vec<(String, String)> = Vec::new();
+                    for (field, path_template) in parameters {
+                        let value = field
+                            .split('.')
+                            .try_fold(&body, |value, segment| value.get(segment))
+                            .and_then(|value| value.as_str());
+                        let Some(value) = value else { continue };
+                        let Some((key, value)) = match_routing_template(field, path_template, value) else { continue };
+                        match params.iter_mut().find(|(existing, _)| *existing == key) {
+                            Some(param) => param.1 = value,
+                            None => params.push((key, value)),
+                        }
+                    }
+                    if params.is_empty() {
+                        return None;
+                    }
+
+                    let encoded = params
+                        .iter()
+                        .map(|(key, value)| format!("{}={}", key, percent_encode(value)))
+                        .collect::<Vec<_>>()
+                        .join("&");
+                    encoded.parse().ok()
                 }
-                let items: Vec<EnumOrStringItem> = Vec::deserialize(deserializer)?;
-                let mut result = Vec::with_capacity(items.len());
 
-                for item in items {
-                    match item {
-                        EnumOrStringItem::String(s) => {
-                            fn try_parse_enum(s: &str) -> Option<i32> {
-                                try_parse_all_enums!(s)
-                            }
-                            if let Some(enum_val) = try_parse_enum(&s) {
-                                result.push(enum_val);
-                            } else {
-                                return Err(serde::de::Error::custom(format!("Unknown enum value: {}", s)));
-                            }
+                // Extracts `(key, value)` from a field value with a `prefix/{key=pattern}/suffix` template
+                #[allow(dead_code)]
+                fn match_routing_template(field: &str, path_template: &str, value: &str) -> Option<(String, String)> {
+                    if value.is_empty() {
+                        return None;
+                    }
+                    if path_template.is_empty() {
+                        return Some((field.to_string(), value.to_string()));
+                    }
+
+                    let open = path_template.find('{')?;
+                    let close = path_template[open..].find('}')? + open;
+                    let variable = &path_template[open + 1..close];
+                    let (key, pattern) = variable.split_once('=').unwrap_or((variable, "*"));
+
+                    let segments = |template: &str| -> Vec<String> {
+                        template.split('/').filter(|segment| !segment.is_empty()).map(str::to_string).collect()
+                    };
+                    let prefix = segments(&path_template[..open]);
+                    let pattern = segments(pattern);
+                    let suffix = segments(&path_template[close + 1..]);
+                    let value_segments = value.split('/').collect::<Vec<_>>();
+
+                    fn glob(pattern: &[String], value: &[&str]) -> bool {
+                        match pattern.split_first() {
+                            None => value.is_empty(),
+                            Some((head, rest)) if head == "**" => (0..=value.len()).any(|skip| glob(rest, &value[skip..])),
+                            Some((head, rest)) => match value.split_first() {
+                                Some((segment, remaining)) => {
+                                    ((head == "*" && !segment.is_empty()) || head == segment) && glob(rest, remaining)
+                                }
+                                None => false,
+                            },
                         }
-                        EnumOrStringItem::Int(i) => {
-                            result.push(i);
+                    }
+
+                    let len = value_segments.len();
+                    for start in 0..=len {
+                        if !glob(&prefix, &value_segments[..start]) {
+                            continue;
+                        }
+                        for end in start + 1..=len {
+                            if glob(&pattern, &value_segments[start..end]) && glob(&suffix, &value_segments[end..]) {
+                                return Some((key.to_string(), value_segments[start..end].join("/")));
+                            }
                         }
                     }
+                    None
                 }
+            };
 
-                Ok(result)
+            buf.push('\n');
+            buf.push_str(&routing_helpers.to_string());
+        }
+
+        if self.is_versioned() {
+            let mut map_paths = Vec::new();
+            for (message, fields) in &self.map_messages {
+                for field in fields {
+                    let name = field.name.to_snake_case();
+                    let nested = field.type_name.as_deref().unwrap_or_default();
+                    let map = field.map;
+                    map_paths.push(quote! { (#message, #name, #nested, #map) });
+                }
             }
-        }.to_string()
-    }
 
-    fn generate_single_enum_serializer_static() -> String {
-        quote! {
-            #[allow(dead_code)]
-            pub fn serialize_enum_as_string<S>(value: &i32, serializer: S) -> Result<S::Ok, S::Error>
-            where
-                S: serde::Serializer,
-            {
-                use serde::Serialize;
-                fn try_enum_to_string(value: i32) -> Option<&'static str> {
-                    try_serialize_all_enums!(value)
+            let version_helpers = quote! {
+                // JSON key convention selected by the `x-api-version` header
+                #[allow(dead_code)]
+                #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+                enum ApiFieldCase {
+                    Snake,
+                    Camel,
                 }
-                if let Some(enum_str) = try_enum_to_string(*value) {
-                    enum_str.serialize(serializer)
-                } else {
-                    value.serialize(serializer)
+
+                // The dialect of the request among the `versions` of its method
+                #[allow(dead_code)]
+                fn request_api_version(headers: &::http::header::HeaderMap, versions: &[(&str, ApiFieldCase)]) -> Result<ApiFieldCase, ::axum::response::Response> {
+                    use ::axum::response::IntoResponse;
+
+                    let Some(version) = headers.get("x-api-version") else {
+                        return Ok(ApiFieldCase::Snake);
+                    };
+                    let version = version.to_str().unwrap_or_default();
+                    match versions.iter().find(|(known, _)| *known == version) {
+                        Some((_, field_case)) => Ok(*field_case),
+                        None => {
+                            let supported_versions = versions.iter().map(|(known, _)| *known).collect::<Vec<_>>().join(", ");
+                            let error_body = ErrorResponse {
+                                error: ErrorDetails::new(error_code(::tonic::Code::InvalidArgument), format!("Unsupported API version `{}`, expected one of: {}", version, supported_versions))
+                            };
+
+                            Err((::http::StatusCode::BAD_REQUEST, ::axum::Json(error_body)).into_response())
+                        }
+                    }
                 }
-            }
-        }.to_string()
-    }
 
-    fn generate_option_enum_serializer_static() -> String {
-        quote! {
-            #[allow(dead_code)]
-            pub fn serialize_option_enum_as_string<S>(value: &Option<i32>, serializer: S) -> Result<S::Ok, S::Error>
-            where
-                S: serde::Serializer,
-            {
-                use serde::Serialize;
-                fn try_enum_to_string(value: i32) -> Option<&'static str> {
-                    try_serialize_all_enums!(value)
+                // Converts the keys of a JSON `message` with `convert`, except the keys of its
+                // map fields, which are data rather than field names
+                #[allow(dead_code)]
+                fn convert_json_keys(message: &str, value: ::serde_json::Value, convert: &dyn Fn(&str) -> String) -> ::serde_json::Value {
+                    // (message, field, nested message, whether the field is a map) of the fields
+                    // leading to map fields
+                    const MAP_PATHS: &[(&str, &str, &str, bool)] = &[#(#map_paths),*];
+
+                    match value {
+                        ::serde_json::Value::Object(object) => ::serde_json::Value::Object(
+                            object
+                                .into_iter()
+                                .map(|(key, value)| {
+                                    let converted = convert(&key);
+                                    // Fields are snake_case before requests and after responses are converted
+                                    let path = MAP_PATHS.iter().find(|(owner, field, _, _)| {
+                                        *owner == message && (*field == key || *field == converted)
+                                    });
+                                    let value = match (path, value) {
+                                        (Some((_, _, nested, true)), ::serde_json::Value::Object(entries)) => ::serde_json::Value::Object(
+                                            entries
+                                                .into_iter()
+                                                .map(|(entry_key, entry)| (entry_key, convert_json_keys(nested, entry, convert)))
+                                                .collect(),
+                                        ),
+                                        (Some((_, _, nested, _)), value) => convert_json_keys(nested, value, convert),
+                                        (None, value) => convert_json_keys("", value, convert),
+                                    };
+                                    (converted, value)
+                                })
+                                .collect(),
+                        ),
+                        ::serde_json::Value::Array(items) => ::serde_json::Value::Array(
+                            items.into_iter().map(|item| convert_json_keys(message, item, convert)).collect(),
+                        ),
+                        other => other,
+                    }
                 }
-                match value {
-                    Some(val) => {
-                        if let Some(enum_str) = try_enum_to_string(*val) {
-                            Some(enum_str).serialize(serializer)
+
+                #[allow(dead_code)]
+                fn camel_to_snake_case(key: &str) -> String {
+                    let mut converted = String::with_capacity(key.len() + 4);
+                    for c in key.chars() {
+                        if c.is_ascii_uppercase() {
+                            converted.push('_');
+                            converted.push(c.to_ascii_lowercase());
                         } else {
-                            Some(*val).serialize(serializer)
+                            converted.push(c);
                         }
                     }
-                    None => None::<&str>.serialize(serializer),
+                    converted
                 }
-            }
-        }.to_string()
-    }
 
-    fn generate_repeated_enum_serializer_static() -> String {
-        quote! {
-            #[allow(dead_code)]
-            pub fn serialize_repeated_enum_as_string<S>(values: &[i32], serializer: S) -> Result<S::Ok, S::Error>
-            where
-                S: serde::Serializer,
-            {
-                use serde::Serialize;
-                fn try_enum_to_string(value: i32) -> Option<&'static str> {
-                    try_serialize_all_enums!(value)
-                }
-                let string_values: Vec<_> = values.iter().map(|val| {
-                    if let Some(enum_str) = try_enum_to_string(*val) {
-                        enum_str.to_string()
-                    } else {
-                        val.to_string()
+                #[allow(dead_code)]
+                fn snake_to_camel_case(key: &str) -> String {
+                    let mut converted = String::with_capacity(key.len());
+                    let mut uppercase_next = false;
+                    for c in key.chars() {
+                        if c == '_' {
+                            uppercase_next = true;
+                        } else if uppercase_next {
+                            converted.push(c.to_ascii_uppercase());
+                            uppercase_next = false;
+                        } else {
+                            converted.push(c);
+                        }
                     }
-                }).collect();
-                string_values.serialize(serializer)
-            }
-        }.to_string()
-    }
-}
+                    converted
+                }
 
-#[derive(Debug)]
-enum FieldLabel {
-    Optional,
-    Required,
-    Repeated,
-}
+                // Invalid fields are answered with `status`, as the unversioned extractors would
+                #[allow(dead_code)]
+                fn decode_versioned_body<B: serde::de::DeserializeOwned>(body: ::serde_json::Value, message: &str, field_case: ApiFieldCase, status: ::http::StatusCode) -> Result<B, ::axum::response::Response> {
+                    use ::axum::response::IntoResponse;
+
+                    let body = match field_case {
+                        ApiFieldCase::Snake => body,
+                        ApiFieldCase::Camel => convert_json_keys(message, body, &camel_to_snake_case),
+                    };
+                    ::serde_json::from_value(body).map_err(|err| {
+                        let error_body = ErrorResponse {
+                            error: ErrorDetails::new(error_code(::tonic::Code::InvalidArgument), format!("Failed to deserialize the JSON body: {}", err))
+                        };
+
+                        (status, ::axum::Json(error_body)).into_response()
+                    })
+                }
 
-impl prost_build::ServiceGenerator for BridgeGenerator {
-    fn generate(&mut self, service: prost_build::Service, buf: &mut String) {
-        self.inner.generate(service.clone(), buf);
+                #[allow(dead_code)]
+                fn encode_versioned_body<B: serde::Serialize>(body: &B, message: &str, field_case: ApiFieldCase) -> Result<::serde_json::Value, ::axum::response::Response> {
+                    use ::axum::response::IntoResponse;
+
+                    let body = ::serde_json::to_value(body).map_err(|err| {
+                        let error_body = ErrorResponse {
+                            error: ErrorDetails::new(error_code(::tonic::Code::Internal), format!("Failed to serialize the response: {}", err))
+                        };
+
+                        (::http::StatusCode::INTERNAL_SERVER_ERROR, ::axum::Json(error_body)).into_response()
+                    })?;
+                    Ok(match field_case {
+                        ApiFieldCase::Snake => body,
+                        ApiFieldCase::Camel => convert_json_keys(message, body, &snake_to_camel_case),
+                    })
+                }
+            };
 
-        let package = &service.package;
-        let name = &service.proto_name;
-        let func_name = service.name.to_string();
-        let ident_func_name = quote::format_ident!("{}", func_name);
-        let branch_names = service
-            .methods
-            .iter()
-            .map(|method| format!("/{package}.{name}/{}", method.proto_name))
-            .collect::<Vec<_>>();
+            buf.push('\n');
+            buf.push_str(&version_helpers.to_string());
+        }
 
-        let func_names = service
-            .methods
-            .iter()
-            .map(|method| quote::format_ident!("{}", method.name))
-            .collect::<Vec<_>>();
+        if self.enable_timeout_headers {
+            let deadline_helpers = quote! {
+                // Parse a timeout in gRPC wire format, e.g. `250m` or `5S`
+                #[allow(dead_code)]
+                fn parse_grpc_timeout(value: &str) -> Option<::std::time::Duration> {
+                    let value = value.trim();
+                    if value.len() < 2 || !value.is_ascii() {
+                        return None;
+                    }
+                    let (amount, unit) = value.split_at(value.len() - 1);
+                    let amount: u64 = amount.parse().ok()?;
+                    match unit {
+                        "H" => Some(::std::time::Duration::from_secs(amount.saturating_mul(3600))),
+                        "M" => Some(::std::time::Duration::from_secs(amount.saturating_mul(60))),
+                        "S" => Some(::std::time::Duration::from_secs(amount)),
+                        "m" => Some(::std::time::Duration::from_millis(amount)),
+                        "u" => Some(::std::time::Duration::from_micros(amount)),
+                        "n" => Some(::std::time::Duration::from_nanos(amount)),
+                        _ => None,
+                    }
+                }
 
-        let branch_request = service
-            .methods
-            .iter()
-            .map(|method| quote::format_ident!("{}", method.input_type.trim_matches('"')))
-            .collect::<Vec<_>>();
+                #[allow(dead_code)]
+                fn request_deadline(headers: &::http::header::HeaderMap) -> Option<::std::time::Duration> {
+                    let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+
+                    header("grpc-timeout")
+                        .and_then(parse_grpc_timeout)
+                        .or_else(|| {
+                            header("x-request-timeout").and_then(|value| {
+                                value
+                                    .trim()
+                                    .parse()
+                                    .ok()
+                                    .map(::std::time::Duration::from_millis)
+                                    .or_else(|| parse_grpc_timeout(value))
+                            })
+                        })
+                }
+            };
 
-        #[cfg(feature = "doc")]
-        let branch_response = service
-            .methods
-            .iter()
-            .map(|method| quote::format_ident!("{}", method.output_type.trim_matches('"')))
-            .collect::<Vec<_>>();
+            buf.push('\n');
+            buf.push_str(&deadline_helpers.to_string());
+        }
 
-        let snake_case_name = func_name.to_snake_case();
-        let service_name = quote::format_ident!("{}_handler", snake_case_name);
-        let server_module = quote::format_ident!("{}_server", snake_case_name);
+        if self.enable_cancellation {
+            let cancellation = quote! {
+                /// Cancellation signal inserted into the request extensions of HTTP calls.
+                ///
+                /// It fires when the HTTP request is abandoned (e.g. the client disconnected)
+                /// before the service call completed.
+                #[derive(Debug, Clone, Default)]
+                pub struct HttpCancellation {
+                    state: ::std::sync::Arc<(::std::sync::atomic::AtomicBool, ::tokio::sync::Notify)>,
+                }
 
-        #[cfg(feature = "doc")]
-        let docs = quote! {
-            #[doc = "Axum Router for handling the gRPC service. This router is generated with the [`prost-build`] crate. This builds a web router on top of the gRPC service."]
-            #[doc = ""]
-            #[doc = ::std::concat!("Package: `", stringify!(#package), "`")]
-            #[doc = ""]
-            #[doc = ::std::concat!("Name: `", stringify!(#name), "`")]
-            #[doc = ""]
-            #[doc = "Routes:"]
-            #(
-                #[doc = ::std::concat!("- `", stringify!(#func_names), "` `::` [`", stringify!(#branch_request), "`]` -> `[`", stringify!(#branch_response), "`]")]
-            )*
-        };
-        #[cfg(not(feature = "doc"))]
-        let docs = quote! {};
+                #[allow(dead_code)]
+                impl HttpCancellation {
+                    /// Whether the HTTP request has been abandoned
+                    pub fn is_cancelled(&self) -> bool {
+                        self.state.0.load(::std::sync::atomic::Ordering::SeqCst)
+                    }
 
-        let output = quote! {
-            #[allow(dead_code)]
-            #docs
-            pub fn #service_name<T: #server_module::#ident_func_name>(server: T) -> ::axum::Router {
-                use ::axum::extract::State;
-                use ::axum::response::IntoResponse;
-                use std::sync::Arc;
-                let router = ::axum::Router::new();
+                    /// Wait until the HTTP request is abandoned
+                    pub async fn cancelled(&self) {
+                        loop {
+                            let notified = self.state.1.notified();
+                            if self.is_cancelled() {
+                                return;
+                            }
+                            notified.await;
+                        }
+                    }
 
-                #(
-                    let router = router.route(#branch_names, ::axum::routing::post(|State(state): State<Arc<T>>, extension: ::http::Extensions, headers: ::http::header::HeaderMap, ::axum::Json(body): ::axum::Json<#branch_request>| async move {
+                    fn cancel(&self) {
+                        self.state.0.store(true, ::std::sync::atomic::Ordering::SeqCst);
+                        self.state.1.notify_waiters();
+                    }
+                }
 
-                        let metadata_map = ::tonic::metadata::MetadataMap::from_headers(headers);
-                        let request = ::tonic::Request::from_parts(metadata_map, extension, body);
+                // Cancels the wrapped signal when dropped without being disarmed
+                #[allow(dead_code)]
+                struct HttpCancellationGuard(Option<HttpCancellation>);
+
+                #[allow(dead_code)]
+                impl HttpCancellationGuard {
+                    fn disarm(mut self) {
+                        self.0 = None;
+                    }
+                }
 
-                        let output = <T as #server_module::#ident_func_name>::#func_names(&state, request).await;
+                impl Drop for HttpCancellationGuard {
+                    fn drop(&mut self) {
+                        if let Some(cancellation) = self.0.take() {
+                            cancellation.cancel();
+                        }
+                    }
+                }
+            };
 
-                        match output {
-                            Ok(response) => {
-                                let (metadata_map, body, extension) = response.into_parts();
-                                let headers = metadata_map.into_headers();
-                                let body = ::axum::Json(body);
+            buf.push('\n');
+            buf.push_str(&cancellation.to_string());
+        }
+    }
+}
 
-                                (headers, extension, body).into_response()
-                            },
-                            Err(status) => {
-                                let code = match status.code() {
-                                    ::tonic::Code::Ok => ::http::StatusCode::OK,
-                                    ::tonic::Code::InvalidArgument => ::http::StatusCode::BAD_REQUEST,
-                                    ::tonic::Code::NotFound => ::http::StatusCode::NOT_FOUND,
-                                    ::tonic::Code::AlreadyExists | ::tonic::Code::Aborted => ::http::StatusCode::CONFLICT,
-                                    ::tonic::Code::PermissionDenied => ::http::StatusCode::FORBIDDEN,
-                                    ::tonic::Code::Unauthenticated => ::http::StatusCode::UNAUTHORIZED,
-                                    ::tonic::Code::ResourceExhausted => ::http::StatusCode::TOO_MANY_REQUESTS,
-                                    ::tonic::Code::FailedPrecondition => ::http::StatusCode::PRECONDITION_FAILED,
-                                    ::tonic::Code::Unimplemented => ::http::StatusCode::NOT_IMPLEMENTED,
-                                    ::tonic::Code::Unavailable => ::http::StatusCode::SERVICE_UNAVAILABLE,
-                                    ::tonic::Code::DeadlineExceeded | ::tonic::Code::Cancelled => ::http::StatusCode::REQUEST_TIMEOUT,
-                                    ::tonic::Code::OutOfRange => ::http::StatusCode::RANGE_NOT_SATISFIABLE,
-                                    _ => ::http::StatusCode::INTERNAL_SERVER_ERROR,
-                                };
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-                                // Create JSON error response
-                                let error_body = ErrorResponse {
-                                    error: ErrorDetails {
-                                        code: status.code().to_string(),
-                                        message: status.message().to_string(),
-                                    }
-                                };
+    // `code` as printed by proc_macro2, to compare token streams whatever their spacing
+    fn tokens(code: &str) -> String {
+        code.parse::<proc_macro2::TokenStream>()
+            .unwrap()
+            .to_string()
+    }
 
-                                let body = ::axum::Json(error_body);
+    #[test]
+    fn gate_items_gates_every_top_level_item() {
+        let gated = gate_items("fn a() {} struct B; use c::D;", "test");
 
-                                (code, body).into_response()
-                            }
-                        }
+        assert_eq!(
+            gated,
+            tokens("#[cfg(test)] fn a() {} #[cfg(test)] struct B; #[cfg(test)] use c::D;")
+        );
+    }
 
-                    }));
-                )*
+    #[test]
+    fn gate_items_keeps_attributes_with_their_item() {
+        let gated = gate_items(
+            "#[derive(Debug)] #[allow(dead_code)] struct A { x: u8 }",
+            r#"feature = "http""#,
+        );
+
+        assert_eq!(
+            gated,
+            tokens(
+                r#"#[cfg(feature = "http")] #[derive(Debug)] #[allow(dead_code)] struct A { x: u8 }"#
+            )
+        );
+    }
 
-                router.with_state(Arc::new(server))
-            }
-        };
+    #[test]
+    fn gate_items_keeps_the_semicolon_after_a_brace_group() {
+        let gated = gate_items("const X: A = A { a: 1 }; impl A { fn f() {} }", "unix");
 
-        buf.push_str(&output.to_string());
+        assert_eq!(
+            gated,
+            tokens("#[cfg(unix)] const X: A = A { a: 1 }; #[cfg(unix)] impl A { fn f() {} }")
+        );
     }
-    fn finalize(&mut self, buf: &mut String) {
-        self.inner.finalize(buf);
+
+    #[test]
+    fn gate_items_gates_trailing_tokens_as_one_item() {
+        assert_eq!(
+            gate_items("struct A; m!(x)", "unix"),
+            tokens("#[cfg(unix)] struct A; #[cfg(unix)] m!(x)")
+        );
+        assert_eq!(gate_items("", "unix"), "");
     }
 
-    fn finalize_package(&mut self, package: &str, buf: &mut String) {
-        self.inner.finalize_package(package, buf);
+    #[test]
+    fn abbreviate_function_id_keeps_short_ids() {
+        let id = "deserialize_payment_status_from_string";
 
-        // Add error response structures once per package
-        let error_structs = quote! {
-            // Error response structures for HTTP endpoints
-            #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
-            pub struct ErrorResponse {
-                pub error: ErrorDetails,
-            }
+        assert_eq!(abbreviate_function_id(id, None), id);
+        assert_eq!(abbreviate_function_id(id, Some(id.len())), id);
+    }
 
-            #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
-            pub struct ErrorDetails {
-                pub code: String,
-                pub message: String,
-            }
-        };
+    #[test]
+    fn abbreviate_function_id_appends_a_stable_hash() {
+        let id = "deserialize_option_outer_inner_deeply_nested_field_from_string";
+        let abbreviated = abbreviate_function_id(id, Some(24));
 
-        buf.push('\n');
-        buf.push_str(&error_structs.to_string());
+        assert_eq!(abbreviated.len(), 24);
+        assert!(abbreviated.starts_with("deserialize_opt_"));
+        let hash = &abbreviated[abbreviated.len() - 8..];
+        assert!(hash.bytes().all(|b| b.is_ascii_hexdigit()));
+        assert_eq!(abbreviate_function_id(id, Some(24)), abbreviated);
 
-        // If string enums are enabled, add the enum deserializer module at the end of each package
-        if self.enable_string_enums {
-            if let Some(ref file_descriptor_set) = self.file_descriptor_set {
-                // Generate enum deserializer code only for enums in this specific package
-                let enum_deserializer_code = Self::generate_package_specific_enum_deserializer_code(
-                    file_descriptor_set,
-                    package,
-                );
-                if !enum_deserializer_code.trim().is_empty() {
-                    buf.push('\n');
-                    buf.push_str(&enum_deserializer_code);
-                }
-            }
-        }
+        // Ids sharing the prefix get different hashes
+        let other = "deserialize_option_outer_inner_deeply_nested_other_from_string";
+        assert_ne!(abbreviate_function_id(other, Some(24)), abbreviated);
+    }
+
+    #[test]
+    fn abbreviate_function_id_trims_the_prefix() {
+        // The prefix `abc_` loses its underscore before the hash is appended
+        let abbreviated = abbreviate_function_id("abc_defghijklmnop", Some(13));
+        assert!(abbreviated.starts_with("abc_"));
+        assert_eq!(abbreviated.len(), 12);
+        assert!(!abbreviated.contains("__"));
+
+        // Without room for a prefix, the id is the hash alone
+        let abbreviated = abbreviate_function_id("abc_defghijklmnop", Some(MIN_FUNCTION_ID_LEN));
+        assert_eq!(abbreviated.len(), MIN_FUNCTION_ID_LEN);
+        assert!(abbreviated.starts_with('h'));
     }
 }
@@ -0,0 +1,134 @@
+//!
+//! Build-time route manifest: a JSON description of every generated route and the
+//! messages it exchanges, written for API gateway configuration tooling.
+//!
+use std::collections::{BTreeMap, VecDeque};
+
+use prost_types::field_descriptor_proto::{Label, Type};
+use prost_types::{DescriptorProto, FileDescriptorSet};
+
+/// A generated HTTP route, as recorded in the manifest.
+pub(crate) struct ManifestRoute {
+    pub(crate) path: String,
+    pub(crate) http_method: String,
+    pub(crate) service: String,
+    pub(crate) method: String,
+    pub(crate) request_type: String,
+    pub(crate) response_type: String,
+    pub(crate) client_streaming: bool,
+    pub(crate) server_streaming: bool,
+}
+
+/// Renders `routes` and the schemas of the messages they reference, transitively.
+pub(crate) fn render(routes: &[ManifestRoute], file_descriptor_set: &FileDescriptorSet) -> String {
+    let mut descriptors = BTreeMap::new();
+    for file in &file_descriptor_set.file {
+        let package = file.package();
+        for message in &file.message_type {
+            collect_descriptors(package, message, &mut descriptors);
+        }
+    }
+
+    let mut messages = BTreeMap::new();
+    let mut pending = routes
+        .iter()
+        .flat_map(|route| [route.request_type.clone(), route.response_type.clone()])
+        .collect::<VecDeque<_>>();
+    while let Some(name) = pending.pop_front() {
+        if messages.contains_key(&name) {
+            continue;
+        }
+        let Some(descriptor) = descriptors.get(&name) else {
+            continue;
+        };
+        pending.extend(
+            descriptor
+                .field
+                .iter()
+                .filter(|field| field.r#type() == Type::Message)
+                .map(|field| field.type_name().trim_start_matches('.').to_string()),
+        );
+        messages.insert(name, *descriptor);
+    }
+
+    // Routes are collected in the order prost hands over services, which follows the
+    // order of the input files; sort them so the manifest diffs cleanly
+    let mut routes = routes.iter().collect::<Vec<_>>();
+    routes.sort_by(|a, b| (&a.path, &a.http_method).cmp(&(&b.path, &b.http_method)));
+
+    let mut out = String::from("{\n  \"routes\": [");
+    for (index, route) in routes.iter().enumerate() {
+        out.push_str(if index == 0 { "\n" } else { ",\n" });
+        out.push_str(&format!(
+            "    {{\"path\": {}, \"http_method\": {}, \"service\": {}, \"method\": {}, \
+             \"request_type\": {}, \"response_type\": {}, \"client_streaming\": {}, \
+             \"server_streaming\": {}}}",
+            json_string(&route.path),
+            json_string(&route.http_method),
+            json_string(&route.service),
+            json_string(&route.method),
+            json_string(&route.request_type),
+            json_string(&route.response_type),
+            route.client_streaming,
+            route.server_streaming,
+        ));
+    }
+    out.push_str("\n  ],\n  \"messages\": {");
+    for (index, (name, descriptor)) in messages.iter().enumerate() {
+        out.push_str(if index == 0 { "\n" } else { ",\n" });
+        out.push_str(&format!("    {}: {{\"fields\": [", json_string(name)));
+        for (index, field) in descriptor.field.iter().enumerate() {
+            if index > 0 {
+                out.push_str(", ");
+            }
+            let field_type = match field.r#type() {
+                Type::Message | Type::Enum => field.type_name().trim_start_matches('.').to_string(),
+                scalar => scalar
+                    .as_str_name()
+                    .trim_start_matches("TYPE_")
+                    .to_ascii_lowercase(),
+            };
+            out.push_str(&format!(
+                "{{\"name\": {}, \"number\": {}, \"type\": {}, \"repeated\": {}}}",
+                json_string(field.name()),
+                field.number(),
+                json_string(&field_type),
+                field.label() == Label::Repeated,
+            ));
+        }
+        out.push_str("]}");
+    }
+    out.push_str("\n  }\n}\n");
+    out
+}
+
+fn collect_descriptors<'a>(
+    scope: &str,
+    message: &'a DescriptorProto,
+    descriptors: &mut BTreeMap<String, &'a DescriptorProto>,
+) {
+    let name = if scope.is_empty() {
+        message.name().to_string()
+    } else {
+        format!("{scope}.{}", message.name())
+    };
+    for nested in &message.nested_type {
+        collect_descriptors(&name, nested, descriptors);
+    }
+    descriptors.insert(name, message);
+}
+
+pub(crate) fn json_string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
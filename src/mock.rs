@@ -0,0 +1,155 @@
+//!
+//! Mock services: `{Service}Mock` types implementing the tonic service trait with
+//! programmable responses, and routers serving them like the real services.
+//!
+use heck::ToSnakeCase;
+use quote::quote;
+
+use crate::rust_type_tokens;
+
+/// Generates the mock of `service`, and its router when `router` is set.
+///
+/// Only services with unary methods get a mock, since streaming methods would need
+/// streams of programmed messages.
+pub(crate) fn service_code(
+    service: &prost_build::Service,
+    router: bool,
+) -> proc_macro2::TokenStream {
+    if service
+        .methods
+        .iter()
+        .any(|method| method.client_streaming || method.server_streaming)
+    {
+        return quote! {};
+    }
+
+    let snake_case_name = service.name.to_snake_case();
+    let trait_name = quote::format_ident!("{}", service.name);
+    let server_module = quote::format_ident!("{}_server", snake_case_name);
+    let mock_name = quote::format_ident!("{}Mock", service.name);
+    let mock_doc = format!(
+        "Mock of `{}.{}` answering every method with the response programmed with its \
+         `on_*` methods, and `UNIMPLEMENTED` otherwise.",
+        service.package, service.proto_name
+    );
+
+    let method_fns = service
+        .methods
+        .iter()
+        .map(|method| quote::format_ident!("{}", method.name))
+        .collect::<Vec<_>>();
+    let setters = service
+        .methods
+        .iter()
+        .map(|method| quote::format_ident!("on_{}", method.name))
+        .collect::<Vec<_>>();
+    let json_setters = service
+        .methods
+        .iter()
+        .map(|method| quote::format_ident!("on_{}_json", method.name))
+        .collect::<Vec<_>>();
+    let setter_docs = service
+        .methods
+        .iter()
+        .map(|method| {
+            format!(
+                "Answers `{}` with the result of `respond`.",
+                method.proto_name
+            )
+        })
+        .collect::<Vec<_>>();
+    let json_setter_docs = service
+        .methods
+        .iter()
+        .map(|method| {
+            format!(
+                "Answers `{}` with the JSON `fixture`, failing with `INTERNAL` if it doesn't parse.",
+                method.proto_name
+            )
+        })
+        .collect::<Vec<_>>();
+    let proto_names = service.methods.iter().map(|method| &method.proto_name);
+    let request_types = service
+        .methods
+        .iter()
+        .map(|method| rust_type_tokens(&method.input_type))
+        .collect::<Vec<_>>();
+    let response_types = service
+        .methods
+        .iter()
+        .map(|method| rust_type_tokens(&method.output_type))
+        .collect::<Vec<_>>();
+
+    let mock_router = if router {
+        let service_name = quote::format_ident!("{}_handler", snake_case_name);
+        let mock_handler = quote::format_ident!("{}_mock_handler", snake_case_name);
+        let mock_handler_doc =
+            format!("Serves `mock` like [`{service_name}`] serves the real service.");
+        quote! {
+            #[allow(dead_code)]
+            #[doc = #mock_handler_doc]
+            pub fn #mock_handler(mock: #mock_name) -> ::axum::Router {
+                #service_name(mock)
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    quote! {
+        #[allow(dead_code)]
+        #[doc = #mock_doc]
+        #[derive(Clone, Default)]
+        pub struct #mock_name {
+            #(
+                #method_fns: Option<::std::sync::Arc<dyn Fn(#request_types) -> Result<#response_types, ::tonic::Status> + Send + Sync>>,
+            )*
+        }
+
+        #[allow(dead_code)]
+        impl #mock_name {
+            /// Creates a mock without programmed responses
+            pub fn new() -> Self {
+                Self::default()
+            }
+
+            #(
+                #[doc = #setter_docs]
+                pub fn #setters(mut self, respond: impl Fn(#request_types) -> Result<#response_types, ::tonic::Status> + Send + Sync + 'static) -> Self {
+                    self.#method_fns = Some(::std::sync::Arc::new(respond));
+                    self
+                }
+
+                #[doc = #json_setter_docs]
+                pub fn #json_setters(self, fixture: impl Into<String>) -> Self {
+                    let fixture = fixture.into();
+                    self.#setters(move |_| {
+                        ::serde_json::from_str(&fixture)
+                            .map_err(|err| ::tonic::Status::internal(format!("Invalid mock fixture: {}", err)))
+                    })
+                }
+            )*
+        }
+
+        impl ::std::fmt::Debug for #mock_name {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                f.debug_struct(stringify!(#mock_name)).finish_non_exhaustive()
+            }
+        }
+
+        #[::tonic::async_trait]
+        impl #server_module::#trait_name for #mock_name {
+            #(
+                async fn #method_fns(&self, request: ::tonic::Request<#request_types>) -> Result<::tonic::Response<#response_types>, ::tonic::Status> {
+                    match &self.#method_fns {
+                        Some(respond) => respond(request.into_inner()).map(::tonic::Response::new),
+                        None => Err(::tonic::Status::unimplemented(concat!("No mock response is programmed for `", #proto_names, "`"))),
+                    }
+                }
+            )*
+        }
+
+
+        #mock_router
+    }
+}
@@ -0,0 +1,444 @@
+//!
+//! Builder options by name, shared by the `g2h` CLI and the protoc plugin.
+//!
+//! Every option maps to the `BridgeGenerator` method of the same name: `string_enums` to
+//! `with_string_enums()`, `max_body_size=1048576` to `with_max_body_size(1048576)`. Options
+//! taking a method and a value separate them with `=`, as in
+//! `cache_control=shop.Catalog/GetItem=max-age=60`. Route naming callbacks and CORS can only
+//! be configured from a build script.
+//!
+use std::time::Duration;
+
+use crate::{
+    AccessLevel, Backend, BridgeGenerator, DuplicateHeaderPolicy, HttpVerb, JsonFieldCase,
+    MeshPreset, RoutePathCase,
+};
+
+/// Names and descriptions of every option, for help output
+pub(crate) const OPTIONS: &[(&str, &str)] = &[
+    ("string_enums", "accept and emit enums as strings"),
+    ("enum_tables", "table-driven string enum serializers"),
+    (
+        "max_function_id_len=<len>",
+        "abbreviate long serializer names",
+    ),
+    (
+        "not_found_fallback[=list_known_paths]",
+        "structured 404 responses",
+    ),
+    (
+        "method_not_allowed_code=<code>",
+        "error code of 405 responses",
+    ),
+    (
+        "grpc_code_names",
+        "gRPC names as error codes, e.g. NOT_FOUND",
+    ),
+    (
+        "cancellation_signal",
+        "cancel services when clients disconnect",
+    ),
+    (
+        "route_path_case=<dotted|kebab_case|snake_case|lowercase>",
+        "route path naming",
+    ),
+    ("version_prefix", "prefix routes with the package version"),
+    ("verb_inference", "infer HTTP methods from method names"),
+    ("empty_request_get", "serve Empty requests as GET"),
+    (
+        "metadata_header_prefix=<prefix>",
+        "only forward prefixed headers",
+    ),
+    (
+        "mesh_preset=<istio|linkerd|envoy>",
+        "always forward mesh headers, needs metadata_header_prefix",
+    ),
+    (
+        "bearer_token_extraction",
+        "extract bearer tokens into metadata",
+    ),
+    (
+        "require_bearer_token=<method>",
+        "require a bearer token, repeatable",
+    ),
+    ("exclude_methods=<method>", "skip a method, repeatable"),
+    (
+        "include_only=<service or method>",
+        "only bridge these, repeatable",
+    ),
+    (
+        "duplicate_header_policy=<keep_all|keep_first|join>",
+        "duplicate headers",
+    ),
+    ("client_ip_metadata=<key>", "forward the client address"),
+    (
+        "trusted_proxy_hops=<n>",
+        "take the client address from X-Forwarded-For",
+    ),
+    ("max_metadata_size=<bytes>", "limit the total metadata size"),
+    ("max_header_value_len=<len>", "limit header value lengths"),
+    (
+        "timeout_headers",
+        "honor grpc-timeout and X-Request-Timeout",
+    ),
+    (
+        "default_timeout_ms=<ms>",
+        "deadline of requests without timeout",
+    ),
+    ("max_body_size=<bytes>", "limit request body sizes"),
+    ("gzip_requests", "accept gzip-encoded request bodies"),
+    (
+        "concurrency_limit=<max>",
+        "shed load above this many requests",
+    ),
+    (
+        "method_concurrency_limit=<method>=<max>",
+        "per-method limit, repeatable",
+    ),
+    ("health_endpoints", "generate /healthz and /readyz"),
+    ("slow_request_profiler_ms=<ms>", "report slower requests"),
+    (
+        "method_slow_request_threshold_ms=<method>=<ms>",
+        "per-method threshold, repeatable",
+    ),
+    ("access_log", "generate an access logger"),
+    (
+        "request_validation",
+        "reject requests missing required fields",
+    ),
+    ("protovalidate", "check buf.validate constraints"),
+    ("strict_validation", "fail on incompatible dependencies"),
+    ("expect_axum=<version_req>", "expected axum versions"),
+    ("expect_tonic=<version_req>", "expected tonic versions"),
+    ("expect_http=<version_req>", "expected http versions"),
+    ("backend=<axum|actix>", "HTTP framework of the handlers"),
+    ("wasm_target", "generate code for wasm32 targets"),
+    ("mocks", "generate mock services"),
+    ("test_clients", "generate in-process test clients"),
+    ("graphql", "generate GraphQL schemas (experimental)"),
+    ("trailers", "send trailing metadata as HTTP trailers"),
+    (
+        "idempotency_keys",
+        "replay responses of repeated Idempotency-Keys",
+    ),
+    ("request_ids", "ensure and echo X-Request-Id"),
+    ("response_envelope", "wrap responses in {data, error}"),
+    ("preflight_responses", "answer OPTIONS on every route"),
+    ("trailing_slash", "also serve routes with a trailing slash"),
+    (
+        "content_type_check[=allow_missing]",
+        "check JSON Content-Type and charset",
+    ),
+    ("rate_limiting", "consult a RateLimiter before calls"),
+    ("message_hooks", "run installed MessageHooks on messages"),
+    (
+        "api_version=<version>[=<snake_case|camel_case>]",
+        "versioned routes, repeatable",
+    ),
+    (
+        "method_api_version=<method>=<version>[=<snake_case|camel_case>]",
+        "versioned routes of a method, repeatable",
+    ),
+    ("routing_headers", "read google.api.routing annotations"),
+    ("route_options", "read g2h.route annotations"),
+    (
+        "deprecation_sunset=<date>",
+        "Sunset header of deprecated routes",
+    ),
+    (
+        "cache_control=<method>=<policy>",
+        "Cache-Control of a method, repeatable",
+    ),
+    (
+        "access_level=<method>=<public|authenticated|admin>",
+        "access level of a method, repeatable",
+    ),
+    (
+        "method_verb=<method>=<get|post|put|patch|delete>",
+        "HTTP verb of a method, repeatable",
+    ),
+    (
+        "http_transport",
+        "generate HTTP transports for tonic clients",
+    ),
+    ("clone_state", "clone services instead of sharing an Arc"),
+    ("single_dispatch", "serve each service with one route"),
+    (
+        "feature_gate[=<name>]",
+        "gate the bridge behind a cargo feature",
+    ),
+    (
+        "cfg_gate=<predicate>",
+        "gate the bridge behind a cfg predicate",
+    ),
+    (
+        "file_descriptor_set_path=<path>",
+        "write the descriptor set",
+    ),
+    ("reflection_service", "generate a reflection server helper"),
+    ("route_manifest_path=<path>", "write a JSON route manifest"),
+    ("out_dir=<dir>", "directory of the generated code"),
+    ("protoc_arg=<arg>", "extra protoc argument, repeatable"),
+    ("btree_map=<path>", "generate maps as BTreeMap, repeatable"),
+    ("bytes=<path>", "generate bytes as bytes::Bytes, repeatable"),
+    ("boxed=<path>", "box a message field, repeatable"),
+    (
+        "extern_path=<proto path>=<rust path>",
+        "use types of another crate, repeatable",
+    ),
+    (
+        "compile_well_known_types",
+        "generate the google.protobuf types",
+    ),
+];
+
+/// Applies the option `key`, with its optional `value`, to `generator`
+pub(crate) fn apply(
+    generator: BridgeGenerator,
+    key: &str,
+    value: Option<&str>,
+) -> Result<BridgeGenerator, String> {
+    let generator = match (key, value) {
+        ("string_enums", None) => generator.with_string_enums(),
+        ("enum_tables", None) => generator.with_enum_tables(),
+        ("max_function_id_len", Some(len)) => generator.with_max_function_id_len(parse(key, len)?),
+        ("not_found_fallback", None) => generator.with_not_found_fallback(),
+        ("not_found_fallback", Some("list_known_paths")) => generator.with_not_found_known_paths(),
+        ("method_not_allowed_code", Some(code)) => generator.with_method_not_allowed_code(code),
+        ("grpc_code_names", None) => generator.with_grpc_code_names(),
+        ("cancellation_signal", None) => generator.with_cancellation_signal(),
+        ("route_path_case", Some(case)) => generator.with_route_path_case(match case {
+            "dotted" => RoutePathCase::Dotted,
+            "kebab_case" => RoutePathCase::KebabCase,
+            "snake_case" => RoutePathCase::SnakeCase,
+            "lowercase" => RoutePathCase::Lowercase,
+            _ => return Err(invalid(key, case)),
+        }),
+        ("version_prefix", None) => generator.with_version_prefix(),
+        ("verb_inference", None) => generator.with_verb_inference(),
+        ("empty_request_get", None) => generator.with_empty_request_get(),
+        ("metadata_header_prefix", Some(prefix)) => generator.with_metadata_header_prefix(prefix),
+        ("mesh_preset", Some(preset)) => generator.with_mesh_preset(match preset {
+            "istio" => MeshPreset::Istio,
+            "linkerd" => MeshPreset::Linkerd,
+            "envoy" => MeshPreset::Envoy,
+            _ => return Err(invalid(key, preset)),
+        }),
+        ("bearer_token_extraction", None) => generator.with_bearer_token_extraction(),
+        ("require_bearer_token", Some(method)) => generator.require_bearer_token([method]),
+        ("exclude_methods", Some(method)) => generator.exclude_methods([method]),
+        ("include_only", Some(entry)) => generator.include_only([entry]),
+        ("duplicate_header_policy", Some(policy)) => {
+            generator.with_duplicate_header_policy(match policy {
+                "keep_all" => DuplicateHeaderPolicy::KeepAll,
+                "keep_first" => DuplicateHeaderPolicy::KeepFirst,
+                "join" => DuplicateHeaderPolicy::Join,
+                _ => return Err(invalid(key, policy)),
+            })
+        }
+        ("client_ip_metadata", Some(metadata_key)) => {
+            generator.with_client_ip_metadata(metadata_key)
+        }
+        ("trusted_proxy_hops", Some(hops)) => generator.with_trusted_proxy_hops(parse(key, hops)?),
+        ("max_metadata_size", Some(size)) => generator.with_max_metadata_size(parse(key, size)?),
+        ("max_header_value_len", Some(len)) => {
+            generator.with_max_header_value_len(parse(key, len)?)
+        }
+        ("timeout_headers", None) => generator.with_timeout_headers(),
+        ("default_timeout_ms", Some(ms)) => {
+            generator.with_default_timeout(Duration::from_millis(parse(key, ms)?))
+        }
+        ("max_body_size", Some(bytes)) => generator.with_max_body_size(parse(key, bytes)?),
+        ("gzip_requests", None) => generator.with_gzip_requests(),
+        ("concurrency_limit", Some(max)) => generator.with_concurrency_limit(parse(key, max)?),
+        ("method_concurrency_limit", Some(limit)) => {
+            let (method, max) = limit.split_once('=').ok_or_else(|| invalid(key, limit))?;
+            generator.with_method_concurrency_limit(method, parse(key, max)?)
+        }
+        ("health_endpoints", None) => generator.with_health_endpoints(),
+        ("slow_request_profiler_ms", Some(ms)) => {
+            generator.with_slow_request_profiler(Duration::from_millis(parse(key, ms)?))
+        }
+        ("method_slow_request_threshold_ms", Some(threshold)) => {
+            let (method, ms) = threshold
+                .split_once('=')
+                .ok_or_else(|| invalid(key, threshold))?;
+            generator
+                .with_method_slow_request_threshold(method, Duration::from_millis(parse(key, ms)?))
+        }
+        ("access_log", None) => generator.with_access_log(),
+        ("request_validation", None) => generator.with_request_validation(),
+        ("protovalidate", None) => generator.with_protovalidate(),
+        ("strict_validation", None) => generator.with_strict_validation(),
+        ("expect_axum", Some(version_req)) => generator.expect_axum(version_req),
+        ("expect_tonic", Some(version_req)) => generator.expect_tonic(version_req),
+        ("expect_http", Some(version_req)) => generator.expect_http(version_req),
+        ("backend", Some(backend)) => generator.with_backend(match backend {
+            "axum" => Backend::Axum,
+            "actix" => Backend::Actix,
+            _ => return Err(invalid(key, backend)),
+        }),
+        ("wasm_target", None) => generator.with_wasm_target(),
+        ("mocks", None) => generator.with_mocks(),
+        ("test_clients", None) => generator.with_test_clients(),
+        ("graphql", None) => generator.with_graphql(),
+        ("trailers", None) => generator.with_trailers(),
+        ("idempotency_keys", None) => generator.with_idempotency_keys(),
+        ("request_ids", None) => generator.with_request_ids(),
+        ("response_envelope", None) => generator.with_response_envelope(),
+        ("preflight_responses", None) => generator.with_preflight_responses(),
+        ("trailing_slash", None) => generator.with_trailing_slash(),
+        ("content_type_check", None) => generator.with_content_type_check(false),
+        ("content_type_check", Some("allow_missing")) => generator.with_content_type_check(true),
+        ("rate_limiting", None) => generator.with_rate_limiting(),
+        ("message_hooks", None) => generator.with_message_hooks(),
+        ("api_version", Some(version)) => {
+            let (version, field_case) = parse_api_version(key, version)?;
+            generator.with_api_version(version, field_case)
+        }
+        ("method_api_version", Some(entry)) => {
+            let (method, version) = entry.split_once('=').ok_or_else(|| invalid(key, entry))?;
+            let (version, field_case) = parse_api_version(key, version)?;
+            generator.with_method_api_version(method, version, field_case)
+        }
+        ("routing_headers", None) => generator.with_routing_headers(),
+        ("route_options", None) => generator.with_route_options(),
+        ("deprecation_sunset", Some(sunset)) => generator.with_deprecation_sunset(sunset),
+        ("cache_control", Some(control)) => {
+            let (method, policy) = control
+                .split_once('=')
+                .ok_or_else(|| invalid(key, control))?;
+            generator.with_cache_control(method, policy)
+        }
+        ("access_level", Some(access)) => {
+            let (method, level) = access.split_once('=').ok_or_else(|| invalid(key, access))?;
+            generator.with_access_level(
+                method,
+                match level {
+                    "public" => AccessLevel::Public,
+                    "authenticated" => AccessLevel::Authenticated,
+                    "admin" => AccessLevel::Admin,
+                    _ => return Err(invalid(key, access)),
+                },
+            )
+        }
+        ("method_verb", Some(verb)) => {
+            let (method, verb) = verb.split_once('=').ok_or_else(|| invalid(key, verb))?;
+            generator.with_method_verb(
+                method,
+                match verb {
+                    "get" => HttpVerb::Get,
+                    "post" => HttpVerb::Post,
+                    "put" => HttpVerb::Put,
+                    "patch" => HttpVerb::Patch,
+                    "delete" => HttpVerb::Delete,
+                    _ => return Err(invalid(key, verb)),
+                },
+            )
+        }
+        ("http_transport", None) => generator.with_http_transport(),
+        ("clone_state", None) => generator.with_clone_state(),
+        ("single_dispatch", None) => generator.with_single_dispatch(),
+        ("feature_gate", None) => generator.with_feature_gate(),
+        ("feature_gate", Some(feature)) => generator.with_feature_gate_named(feature),
+        ("cfg_gate", Some(predicate)) => generator.with_cfg_gate(predicate),
+        ("file_descriptor_set_path", Some(path)) => generator.file_descriptor_set_path(path),
+        ("reflection_service", None) => generator.with_reflection_service(),
+        ("route_manifest_path", Some(path)) => generator.route_manifest_path(path),
+        ("out_dir", Some(dir)) => generator.out_dir(dir),
+        ("protoc_arg", Some(arg)) => generator.protoc_arg(arg),
+        ("btree_map", Some(path)) => generator.btree_map([path]),
+        ("bytes", Some(path)) => generator.bytes([path]),
+        ("boxed", Some(path)) => generator.boxed(path),
+        ("extern_path", Some(mapping)) => {
+            let (proto_path, rust_path) = mapping
+                .split_once('=')
+                .ok_or_else(|| invalid(key, mapping))?;
+            generator.extern_path(proto_path, rust_path)
+        }
+        ("compile_well_known_types", None) => generator.compile_well_known_types(),
+        (_, Some(value)) => return Err(format!("unknown option `{key}={value}`")),
+        (_, None) => return Err(format!("unknown option `{key}`")),
+    };
+    Ok(generator)
+}
+
+// Splits `<version>[=<snake_case|camel_case>]`
+fn parse_api_version<'a>(key: &str, value: &'a str) -> Result<(&'a str, JsonFieldCase), String> {
+    match value.split_once('=') {
+        None => Ok((value, JsonFieldCase::SnakeCase)),
+        Some((version, "snake_case")) => Ok((version, JsonFieldCase::SnakeCase)),
+        Some((version, "camel_case")) => Ok((version, JsonFieldCase::CamelCase)),
+        Some(_) => Err(invalid(key, value)),
+    }
+}
+
+fn parse<T: std::str::FromStr>(key: &str, value: &str) -> Result<T, String> {
+    value.parse().map_err(|_| invalid(key, value))
+}
+
+fn invalid(key: &str, value: &str) -> String {
+    format!("invalid value `{value}` for option `{key}`")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply_option(key: &str, value: Option<&str>) -> Result<BridgeGenerator, String> {
+        apply(BridgeGenerator::with_tonic_build(), key, value)
+    }
+
+    #[test]
+    fn apply_maps_options_to_builder_methods() {
+        let generator = apply_option("string_enums", None).unwrap();
+        assert!(generator.enable_string_enums);
+
+        let generator = apply_option("max_body_size", Some("1024")).unwrap();
+        assert_eq!(generator.max_body_size, Some(1024));
+
+        let generator = apply_option("backend", Some("actix")).unwrap();
+        assert!(generator.backend == Backend::Actix);
+    }
+
+    #[test]
+    fn apply_splits_method_entries_on_the_first_equals_sign() {
+        let generator =
+            apply_option("cache_control", Some("shop.Catalog/GetItem=max-age=60")).unwrap();
+
+        assert_eq!(
+            generator.cache_policies,
+            [("shop.Catalog/GetItem".to_string(), "max-age=60".to_string())]
+        );
+    }
+
+    #[test]
+    fn apply_rejects_invalid_values() {
+        for (key, value) in [
+            ("max_body_size", "1kb"),
+            ("backend", "rocket"),
+            ("access_level", "shop.Catalog"),
+            ("method_verb", "shop.Catalog/GetItem=head"),
+        ] {
+            let error = apply_option(key, Some(value)).err().unwrap_or_default();
+            assert!(
+                error.starts_with("invalid value `")
+                    && error.ends_with(&format!("for option `{key}`")),
+                "unexpected error `{error}`"
+            );
+        }
+    }
+
+    #[test]
+    fn apply_rejects_unknown_options() {
+        assert_eq!(
+            apply_option("string_enum", None).err().as_deref(),
+            Some("unknown option `string_enum`")
+        );
+        // Flags don't take values
+        assert_eq!(
+            apply_option("string_enums", Some("true")).err().as_deref(),
+            Some("unknown option `string_enums=true`")
+        );
+    }
+}
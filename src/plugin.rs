@@ -0,0 +1,145 @@
+//!
+//! The protoc plugin protocol, used by the `protoc-gen-g2h` binary to run g2h from
+//! `buf generate` or `protoc --g2h_out=...` instead of a Cargo build script.
+//!
+//! Builder options are passed as the comma-separated plugin parameter, e.g.
+//! `--g2h_opt=string_enums,access_log,max_body_size=1048576`, and named like the builder
+//! methods; `g2h generate --help` lists all of them. Commas within a value are escaped
+//! as `\,`, as in `cache_control=shop.Catalog=public\, max-age=60`.
+//!
+use prost::Message;
+use prost_types::compiler::{code_generator_response, CodeGeneratorResponse};
+
+use crate::{options, BridgeGenerator};
+
+/// `CodeGeneratorRequest` keeping the files encoded, so their custom options survive
+#[derive(Clone, PartialEq, prost::Message)]
+struct RawCodeGeneratorRequest {
+    #[prost(string, repeated, tag = "1")]
+    file_to_generate: Vec<String>,
+    #[prost(string, optional, tag = "2")]
+    parameter: Option<String>,
+    #[prost(bytes = "vec", repeated, tag = "15")]
+    proto_file: Vec<Vec<u8>>,
+}
+
+/// `FileDescriptorSet` made of already encoded files
+#[derive(Clone, PartialEq, prost::Message)]
+struct RawFileDescriptorSet {
+    #[prost(bytes = "vec", repeated, tag = "1")]
+    file: Vec<Vec<u8>>,
+}
+
+///
+/// Runs the plugin on an encoded `CodeGeneratorRequest` and returns the encoded
+/// `CodeGeneratorResponse`. Failures are reported through the `error` of the response,
+/// as the protocol expects.
+///
+pub fn run(request: &[u8]) -> Vec<u8> {
+    let response = match generate(request) {
+        Ok(file) => CodeGeneratorResponse {
+            file,
+            supported_features: Some(code_generator_response::Feature::Proto3Optional as u64),
+            ..Default::default()
+        },
+        Err(err) => CodeGeneratorResponse {
+            error: Some(format!("g2h: {err}")),
+            ..Default::default()
+        },
+    };
+    response.encode_to_vec()
+}
+
+fn generate(
+    request: &[u8],
+) -> Result<Vec<code_generator_response::File>, Box<dyn std::error::Error>> {
+    let request = RawCodeGeneratorRequest::decode(request)?;
+    let generator = configure(
+        BridgeGenerator::with_tonic_build(),
+        request.parameter.as_deref().unwrap_or_default(),
+    )?;
+
+    let file_descriptor_set = RawFileDescriptorSet {
+        file: request.proto_file,
+    }
+    .encode_to_vec();
+    let files = generator.generate_files(&file_descriptor_set, Some(&request.file_to_generate))?;
+
+    Ok(files
+        .into_iter()
+        .map(|(name, content)| code_generator_response::File {
+            name: Some(name),
+            content: Some(content),
+            ..Default::default()
+        })
+        .collect())
+}
+
+fn configure(mut generator: BridgeGenerator, parameter: &str) -> Result<BridgeGenerator, String> {
+    for option in split_parameter(parameter)
+        .iter()
+        .map(|o| o.trim())
+        .filter(|o| !o.is_empty())
+    {
+        generator = match option.split_once('=') {
+            Some((key, value)) => options::apply(generator, key, Some(value))?,
+            None => options::apply(generator, option, None)?,
+        };
+    }
+    Ok(generator)
+}
+
+// Splits the parameter on the commas not escaped as `\,`, unescaping `\,` and `\\`
+fn split_parameter(parameter: &str) -> Vec<String> {
+    let mut options = vec![String::new()];
+    let mut chars = parameter.chars();
+    while let Some(c) = chars.next() {
+        let option = options.last_mut().expect("options is never empty");
+        match c {
+            '\\' => match chars.next() {
+                Some(escaped @ (',' | '\\')) => option.push(escaped),
+                Some(other) => {
+                    option.push(c);
+                    option.push(other);
+                }
+                None => option.push(c),
+            },
+            ',' => options.push(String::new()),
+            _ => option.push(c),
+        }
+    }
+    options
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_parameter_splits_on_commas() {
+        assert_eq!(
+            split_parameter("string_enums,max_body_size=1024"),
+            ["string_enums", "max_body_size=1024"]
+        );
+        assert_eq!(split_parameter(""), [""]);
+        assert_eq!(split_parameter("a,,b"), ["a", "", "b"]);
+    }
+
+    #[test]
+    fn split_parameter_unescapes_commas_and_backslashes() {
+        assert_eq!(
+            split_parameter(r"cache_control=shop.Catalog=public\, max-age=60,access_log"),
+            [
+                "cache_control=shop.Catalog=public, max-age=60",
+                "access_log"
+            ]
+        );
+        assert_eq!(split_parameter(r"out_dir=C:\\,a"), [r"out_dir=C:\", "a"]);
+    }
+
+    #[test]
+    fn split_parameter_keeps_other_backslashes() {
+        assert_eq!(split_parameter(r"a=x\ny"), [r"a=x\ny"]);
+        assert_eq!(split_parameter(r"a=x\"), [r"a=x\"]);
+    }
+}
@@ -0,0 +1,165 @@
+//!
+//! Test clients: `{Service}TestClient` types calling a generated router in-process with
+//! `tower::ServiceExt::oneshot`, with one typed method per route.
+//!
+use heck::ToSnakeCase;
+use quote::quote;
+
+use crate::rust_type_tokens;
+
+/// Generates the test client of `service`, whose methods are served on `routes` as
+/// `(path, verb)`.
+pub(crate) fn service_code(
+    service: &prost_build::Service,
+    routes: &[(String, String)],
+) -> proc_macro2::TokenStream {
+    let snake_case_name = service.name.to_snake_case();
+    let client_name = quote::format_ident!("{}TestClient", service.name);
+    let client_fn = quote::format_ident!("{}_test_client", snake_case_name);
+    let client_doc = format!(
+        "Calls the routes of `{}.{}` on a router in-process, for tests.",
+        service.package, service.proto_name
+    );
+    let client_fn_doc = format!("Creates a [`{client_name}`] sending requests to `router`.");
+
+    // Streaming methods have no single JSON response to decode
+    let methods = service
+        .methods
+        .iter()
+        .zip(routes)
+        .filter(|(method, _)| !method.client_streaming && !method.server_streaming)
+        .collect::<Vec<_>>();
+    let method_fns = methods
+        .iter()
+        .map(|(method, _)| quote::format_ident!("{}", method.name));
+    let method_docs = methods.iter().map(|(method, (path, verb))| {
+        format!("Calls `{}` with `{verb} {path}`.", method.proto_name)
+    });
+    let request_types = methods
+        .iter()
+        .map(|(method, _)| rust_type_tokens(&method.input_type));
+    let response_types = methods
+        .iter()
+        .map(|(method, _)| rust_type_tokens(&method.output_type));
+    let paths = methods.iter().map(|(_, (path, _))| path);
+    let verbs = methods
+        .iter()
+        .map(|(_, (_, verb))| quote::format_ident!("{}", verb));
+
+    quote! {
+        #[allow(dead_code)]
+        #[doc = #client_doc]
+        #[derive(Debug, Clone)]
+        pub struct #client_name {
+            router: ::axum::Router,
+            headers: ::http::header::HeaderMap,
+        }
+
+        #[allow(dead_code)]
+        #[doc = #client_fn_doc]
+        pub fn #client_fn(router: ::axum::Router) -> #client_name {
+            #client_name {
+                router,
+                headers: ::http::header::HeaderMap::new(),
+            }
+        }
+
+        #[allow(dead_code)]
+        impl #client_name {
+            /// Sends `name: value` with every request, e.g. as request metadata
+            pub fn header(mut self, name: ::http::header::HeaderName, value: ::http::HeaderValue) -> Self {
+                self.headers.append(name, value);
+                self
+            }
+
+            #(
+                #[doc = #method_docs]
+                pub async fn #method_fns(&self, request: #request_types) -> Result<#response_types, ErrorResponse> {
+                    test_client_call(&self.router, &self.headers, ::http::Method::#verbs, #paths, &request).await
+                }
+            )*
+        }
+    }
+}
+
+/// Generates the request helper shared by the test clients of a package.
+pub(crate) fn package_helpers() -> proc_macro2::TokenStream {
+    quote! {
+        // Sends `request` to `router` as JSON (or as a query string for GET routes),
+        // decoding the JSON response or error body
+        #[allow(dead_code)]
+        async fn test_client_call<Req, Resp>(
+            router: &::axum::Router,
+            headers: &::http::header::HeaderMap,
+            verb: ::http::Method,
+            path: &str,
+            request: &Req,
+        ) -> Result<Resp, ErrorResponse>
+        where
+            Req: serde::Serialize,
+            Resp: serde::de::DeserializeOwned,
+        {
+            use ::tower::ServiceExt;
+
+            let client_error = |message: String| ErrorResponse {
+                error: ErrorDetails::new("TEST_CLIENT", message)
+            };
+
+            let mut uri = path.to_string();
+            let body = if verb == ::http::Method::GET {
+                let fields = match ::serde_json::to_value(request) {
+                    Ok(::serde_json::Value::Object(fields)) => fields,
+                    _ => Default::default(),
+                };
+                let query = fields
+                    .iter()
+                    .filter_map(|(name, value)| {
+                        let value = match value {
+                            ::serde_json::Value::String(value) => value.clone(),
+                            ::serde_json::Value::Number(value) => value.to_string(),
+                            ::serde_json::Value::Bool(value) => value.to_string(),
+                            _ => return None,
+                        };
+                        Some(format!("{}={}", percent_encode(name), percent_encode(&value)))
+                    })
+                    .collect::<Vec<_>>()
+                    .join("&");
+                if !query.is_empty() {
+                    uri = format!("{}?{}", uri, query);
+                }
+                ::axum::body::Body::empty()
+            } else {
+                let body = ::serde_json::to_vec(request).map_err(|err| client_error(err.to_string()))?;
+                ::axum::body::Body::from(body)
+            };
+
+            let mut http_request = ::http::Request::builder()
+                .method(verb)
+                .uri(uri)
+                .header(::http::header::CONTENT_TYPE, "application/json")
+                .body(body)
+                .map_err(|err| client_error(err.to_string()))?;
+            for (name, value) in headers {
+                http_request.headers_mut().append(name.clone(), value.clone());
+            }
+
+            let response = match router.clone().oneshot(http_request).await {
+                Ok(response) => response,
+                Err(never) => match never {},
+            };
+            let status = response.status();
+            let body = ::axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .map_err(|err| client_error(err.to_string()))?;
+
+            if status.is_success() {
+                let body = if body.is_empty() { &b"{}"[..] } else { &body[..] };
+                ::serde_json::from_slice(body).map_err(|err| client_error(err.to_string()))
+            } else {
+                Err(::serde_json::from_slice::<ErrorResponse>(&body).unwrap_or_else(|_| ErrorResponse {
+                    error: ErrorDetails::new(status.as_str(), String::from_utf8_lossy(&body).into_owned())
+                }))
+            }
+        }
+    }
+}
@@ -1,24 +1,34 @@
-use cargo_metadata::semver::Version;
+use cargo_metadata::{semver::VersionReq, DependencyKind, Metadata, Node, Package};
 
 ///
 /// [`Deps`] this is used to check the dependencies of the project. `g2h` is a build-dependency.
 ///
 /// We can preemptively recognize the version of the following dependencies:
-/// - `axum`
+/// - `axum`, or `actix-web` with the actix backend
 /// - `tonic`
 /// - `http`
 ///
+/// Each of them is checked against a range of compatible versions (e.g. `>=0.8.3, <0.9`),
+/// so patch and minor releases within the range don't raise warnings. The generated code
+/// also needs the dependencies of [`REQUIRED_DEPS`], with their features.
+///
 pub struct Deps {
-    axum_version: Version,
-    tonic_version: Version,
-    http_version: Version,
+    versions: Vec<(&'static str, VersionReq)>,
 }
 
+/// Dependencies the generated code needs regardless of their version, with the features
+/// it relies on
+const REQUIRED_DEPS: &[(&str, &[&str])] = &[("serde", &["derive"]), ("serde_json", &[])];
+
 #[derive(Debug, thiserror::Error)]
 pub enum DepError {
     #[error("Dependency `{name}` is absent")]
     DependencyAbsent { name: String },
-    #[error("Incompatible dependency `{name}`: expected `{expected}`, found `{actual}`")]
+    #[error("Failed to find the package being built in Cargo metadata")]
+    PackageAbsent,
+    #[error("Dependency `{name}` is missing the `{feature}` feature")]
+    DependencyFeatureMissing { name: String, feature: String },
+    #[error("Incompatible dependency `{name}`: expected a version matching `{expected}`, found `{actual}`")]
     DependencyVersionMismatch {
         name: String,
         expected: String,
@@ -31,46 +41,121 @@ pub enum DepError {
 }
 
 impl Deps {
-    pub fn new(
-        axum_version: &str,
-        tonic_version: &str,
-        http_version: &str,
-    ) -> Result<Self, DepError> {
-        Ok(Self {
-            axum_version: Version::parse(axum_version)?,
-            tonic_version: Version::parse(tonic_version)?,
-            http_version: Version::parse(http_version)?,
-        })
+    /// Expects each dependency of `versions` to match its version requirement
+    pub fn new(versions: &[(&'static str, &str)]) -> Result<Self, DepError> {
+        let versions = versions
+            .iter()
+            .map(|&(name, version_req)| Ok((name, VersionReq::parse(version_req)?)))
+            .collect::<Result<_, DepError>>()?;
+        Ok(Self { versions })
     }
 
     pub fn validate(self) -> Result<(), DepError> {
-        let deps = [
-            ("axum", self.axum_version),
-            ("tonic", self.tonic_version),
-            ("http", self.http_version),
-        ];
-
-        let metadata = cargo_metadata::MetadataCommand::new().exec()?;
+        // Build scripts run in the directory of their manifest, but other callers may not
+        let manifest_dir = std::env::var_os("CARGO_MANIFEST_DIR").map(std::path::PathBuf::from);
+        let mut command = cargo_metadata::MetadataCommand::new();
+        if let Some(dir) = &manifest_dir {
+            command.manifest_path(dir.join("Cargo.toml"));
+        }
+        let metadata = command.exec()?;
+        let current = current_node(&metadata, manifest_dir.as_deref())?;
 
-        for (name, expected_version) in deps {
-            let actual_version = &metadata
-                .packages
-                .iter()
-                .find(|pkg| pkg.name == name)
-                .ok_or_else(|| DepError::DependencyAbsent {
-                    name: name.to_string(),
-                })?
-                .version;
+        for (name, version_req) in self.versions {
+            let (package, _) = dependency(&metadata, current, name)?;
+            let actual_version = &package.version;
 
-            if expected_version.cmp_precedence(actual_version) != std::cmp::Ordering::Equal {
+            if !version_req.matches(actual_version) {
                 return Err(DepError::DependencyVersionMismatch {
                     name: name.to_string(),
-                    expected: expected_version.to_string(),
+                    expected: version_req.to_string(),
                     actual: actual_version.to_string(),
                 });
             }
         }
 
+        for (name, features) in REQUIRED_DEPS {
+            let (_, node) = dependency(&metadata, current, name)?;
+
+            if let Some(feature) = features.iter().find(|feature| {
+                !node
+                    .features
+                    .iter()
+                    .any(|enabled| enabled.as_str() == **feature)
+            }) {
+                return Err(DepError::DependencyFeatureMissing {
+                    name: name.to_string(),
+                    feature: feature.to_string(),
+                });
+            }
+        }
+
         Ok(())
     }
 }
+
+/// Resolve node of the package being built, the one of the build script running g2h
+fn current_node<'a>(
+    metadata: &'a Metadata,
+    manifest_dir: Option<&std::path::Path>,
+) -> Result<&'a Node, DepError> {
+    let resolve = metadata.resolve.as_ref().ok_or(DepError::PackageAbsent)?;
+    let id = match manifest_dir {
+        Some(dir) => metadata
+            .packages
+            .iter()
+            .find(|pkg| {
+                pkg.manifest_path
+                    .parent()
+                    .is_some_and(|path| path.as_std_path() == dir)
+            })
+            .map(|pkg| &pkg.id),
+        None => resolve.root.as_ref(),
+    }
+    .ok_or(DepError::PackageAbsent)?;
+
+    resolve
+        .nodes
+        .iter()
+        .find(|node| node.id == *id)
+        .ok_or(DepError::PackageAbsent)
+}
+
+/// Resolves the normal dependency `name` of `node` through its dependency edges
+///
+/// `name` is the crate name the generated code uses, so renamed dependencies (`package =
+/// "..."`) and forks are found under the name they are imported with, i.e. with dashes as
+/// underscores.
+fn dependency<'a>(
+    metadata: &'a Metadata,
+    node: &Node,
+    name: &str,
+) -> Result<(&'a Package, &'a Node), DepError> {
+    let absent = || DepError::DependencyAbsent {
+        name: name.to_string(),
+    };
+    let lib_name = name.replace('-', "_");
+    let id = &node
+        .deps
+        .iter()
+        .find(|dep| {
+            dep.name == lib_name
+                && dep
+                    .dep_kinds
+                    .iter()
+                    .any(|info| info.kind == DependencyKind::Normal)
+        })
+        .ok_or_else(absent)?
+        .pkg;
+
+    let package = metadata
+        .packages
+        .iter()
+        .find(|pkg| pkg.id == *id)
+        .ok_or_else(absent)?;
+    let node = metadata
+        .resolve
+        .as_ref()
+        .and_then(|resolve| resolve.nodes.iter().find(|node| node.id == *id))
+        .ok_or_else(absent)?;
+    Ok((package, node))
+}